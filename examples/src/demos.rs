@@ -0,0 +1,43 @@
+use pollster::FutureExt;
+use skie_draw::{
+    demos::{self, DemoRunner, DEMOS},
+    GpuContext,
+};
+
+/// `demo <name|all> [width] [height]`, renders registered demo scenes into `output/`
+pub fn run(args: &[String]) {
+    let Some(name) = args.first() else {
+        eprintln!("Usage: skie_examples demo <name|all> [width] [height]");
+        eprintln!();
+        eprintln!("Demos\n-----");
+        for demo in DEMOS {
+            eprintln!("- {}: {}", demo.name, demo.description);
+        }
+        return;
+    };
+
+    let size = |i: usize| {
+        args.get(i)
+            .map(|arg| arg.parse::<u32>().expect("size must be a number"))
+    };
+    let width = size(1).unwrap_or(512);
+    let height = size(2).unwrap_or(width);
+
+    let gpu = GpuContext::new()
+        .block_on()
+        .expect("Error creating gpu context");
+    let runner = DemoRunner::new().size(width, height);
+
+    let written = if name == "all" {
+        runner.write_all(&gpu)
+    } else if let Some(demo) = demos::find(name) {
+        runner.write(&gpu, demo).map(|path| vec![path])
+    } else {
+        eprintln!("Demo not found: {name}");
+        return;
+    };
+
+    for path in written.expect("Error rendering demo") {
+        println!("Saved to {}", path.to_string_lossy());
+    }
+}
@@ -1,5 +1,6 @@
 use std::fmt::Write;
 
+mod demos;
 mod skie_draw_app;
 mod skie_draw_canvas;
 
@@ -20,10 +21,13 @@ static EXAMPLES: &[ExampleDescriptor] = &[
 ];
 
 fn main() {
-    let example_name = std::env::args().nth(1);
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let example_name = args.first();
 
     if let Some(example_name) = example_name {
-        if let Some(example) = EXAMPLES.iter().find(|example| example.name == example_name) {
+        if example_name == "demo" {
+            demos::run(&args[1..]);
+        } else if let Some(example) = EXAMPLES.iter().find(|example| example.name == example_name) {
             println!("Running `{}`", example.name);
             (example.runner)();
         } else {
@@ -32,6 +36,11 @@ fn main() {
     } else {
         let mut error = String::new();
         writeln!(&mut error, "Usage: skie_examples <example_name>").unwrap();
+        writeln!(
+            &mut error,
+            "       skie_examples demo <name|all> [width] [height]"
+        )
+        .unwrap();
         writeln!(&mut error).unwrap();
         writeln!(&mut error, "Examples\n---------").unwrap();
         for example in EXAMPLES {
@@ -52,8 +52,8 @@ impl MovingSquare {
     }
 
     fn update(&mut self, keystate: &KeyState, window: &app::Window) {
-        let size = window.inner_size();
-        let screen = Rect::xywh(0., 0., size.width as f32, size.height as f32);
+        let size = window.inner_size().to_logical::<f32>(window.scale_factor());
+        let screen = Rect::xywh(0., 0., size.width, size.height);
 
         let old_pos = self.rect.origin;
 
@@ -89,23 +89,17 @@ impl SkieAppHandle for App {
     }
 
     fn on_create_window(&mut self, window: &app::Window) {
-        let size = window.inner_size();
-
-        self.square.rect = Rect::xywh(
-            size.width.half() as f32,
-            size.height.half() as f32,
-            201.0,
-            201.0,
-        )
-        .centered();
+        let size = window.inner_size().to_logical::<f32>(window.scale_factor());
+
+        self.square.rect =
+            Rect::xywh(size.width.half(), size.height.half(), 201.0, 201.0).centered();
     }
 
     fn update(&mut self, window: &app::Window) {
         self.square.update(&self.keystate, window);
     }
 
-    fn draw(&mut self, cx: &mut Canvas, window: &app::Window) {
-        let scale_factor = window.scale_factor();
+    fn draw(&mut self, cx: &mut Canvas, _window: &app::Window) {
         cx.clear_color(Color::THAMAR_BLACK);
 
         self.square.draw(cx, &self.keystate);
@@ -114,7 +108,7 @@ impl SkieAppHandle for App {
             .pos(101.0, 10.0)
             .font_weight(FontWeight::BOLD)
             .font_style(FontStyle::Italic)
-            .size_px(33.0 * scale_factor as f32);
+            .size_px(33.0);
 
         cx.fill_text(&text, Color::WHITE);
 
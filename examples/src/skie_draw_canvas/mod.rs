@@ -38,7 +38,7 @@ pub fn run() {
     canvas.fill_text(&text, Color::BLACK);
 
     canvas.clear_color(Color::THAMAR_BLACK);
-    canvas.render(&mut surface).expect("error painting");
+    canvas.finish(&mut surface).expect("error painting");
 
     let snapshot = canvas
         .snapshot_sync(&surface)
@@ -1,10 +1,10 @@
 use skie_draw::{
     app::{self, LogicalSize, SkieAppHandle, WindowAttributes},
     paint::PathBrush,
-    vec2, Canvas, Color, Corners, Half, LineCap, Path, Size,
+    vec2, Canvas, Color, Corners, Half, LineCap, Path,
 };
 
-use skie_draw::{Brush, Rect};
+use skie_draw::{Antialias, Brush, Rect};
 
 #[derive(Default)]
 struct SandboxApp;
@@ -18,18 +18,13 @@ impl SkieAppHandle for SandboxApp {
 
     fn update(&mut self, _window: &app::Window) {}
 
-    fn draw(&mut self, cx: &mut Canvas, window: &app::Window) {
-        let scale_factor = window.scale_factor() as f32;
-        let size = window.inner_size();
-        let size = Size::new(size.width as f32, size.height as f32).scale(1.0 / scale_factor);
+    fn draw(&mut self, cx: &mut Canvas, _window: &app::Window) {
+        let size = cx.logical_size();
 
         let mut shadow_color = Color::from_rgb(0x939496);
         shadow_color.a = 100;
         cx.clear_color(Color::THAMAR_BLACK);
 
-        cx.save();
-        cx.scale(scale_factor, scale_factor);
-
         let rect = Rect::xywh(0.0, 0.0, 200.0, 200.0);
 
         cx.draw_rect(
@@ -44,7 +39,7 @@ impl SkieAppHandle for SandboxApp {
             center.x,
             center.y,
             10.0,
-            Brush::filled(Color::BLUE).feathering(2.0),
+            Brush::filled(Color::BLUE).antialiasing(Antialias::Feather(2.0)),
         );
 
         // draw rotated square
@@ -56,12 +51,12 @@ impl SkieAppHandle for SandboxApp {
         // shadow
         cx.draw_round_rect(
             &Rect::xywh(0.0, 0.0, 200.0, 200.0),
-            Corners::with_all(8.0),
-            Brush::filled(shadow_color).feathering(15.0),
+            &Corners::with_all(8.0),
+            Brush::filled(shadow_color).antialiasing(Antialias::Feather(15.0)),
         );
         cx.draw_rect(
             &Rect::xywh(0.0, 0.0, 200.0, 200.0),
-            Brush::filled(Color::WHITE).feathering(5.0),
+            Brush::filled(Color::WHITE).antialiasing(Antialias::Feather(5.0)),
         );
         cx.restore();
 
@@ -72,8 +67,8 @@ impl SkieAppHandle for SandboxApp {
 
         cx.draw_round_rect(
             &Rect::xywh(0.0, 0.0, 210.0, 210.0).centered(),
-            Corners::with_all(8.0),
-            Brush::filled(shadow_color).feathering(15.0),
+            &Corners::with_all(8.0),
+            Brush::filled(shadow_color).antialiasing(Antialias::Feather(15.0)),
         );
         cx.draw_rect(
             &Rect::xywh(0.0, 0.0, 200.0, 200.0).centered(),
@@ -83,8 +78,6 @@ impl SkieAppHandle for SandboxApp {
         cx.restore();
 
         Man::draw(cx);
-
-        cx.restore();
     }
 }
 
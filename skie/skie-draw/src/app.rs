@@ -67,6 +67,7 @@ impl<'a> ApplicationHandler for App<'a> {
             let size = window.inner_size();
 
             self.canvas.resize(size.width, size.height);
+            self.canvas.set_scale_factor(window.scale_factor() as f32);
 
             let surface = self
                 .canvas
@@ -140,6 +141,9 @@ impl<'a> ApplicationHandler for App<'a> {
             WindowEvent::Resized(size) => {
                 self.canvas.resize(size.width, size.height);
             }
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                self.canvas.set_scale_factor(scale_factor as f32);
+            }
             _ => {}
         }
     }
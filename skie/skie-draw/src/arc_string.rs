@@ -22,6 +22,12 @@ impl From<&'static str> for ArcString {
     }
 }
 
+impl From<String> for ArcString {
+    fn from(value: String) -> Self {
+        Self(ArcCow::Owned(Arc::from(value)))
+    }
+}
+
 impl Deref for ArcString {
     type Target = str;
 
@@ -1,39 +1,149 @@
-use std::{borrow::Cow, sync::Arc};
+use std::{
+    borrow::Cow,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use crate::{
+    arc_string::ArcString,
     circle,
+    paint::{arc, ellipse, pie, points, ring},
     paint::{
         AtlasKey, Brush, GpuTextureView, GraphicsInstruction, GraphicsInstructionBatcher,
-        PathBrush, Primitive, SkieAtlas, SkieAtlasTextureInfoMap, TextureKind,
+        PathBrush, PointShape, Primitive, SkieAtlas, SkieAtlasTextureInfoMap, DEFAULT_FEATHERING,
+        DEFAULT_TOLERANCE,
     },
     path::Path,
     quad,
-    renderer::Renderable,
-    AtlasTextureInfo, Color, DrawList, GlyphImage, IsZero, Rect, Renderer2D, Size, Text,
-    TextSystem, TextureId, TextureOptions,
+    renderer::{stencil_attachment, DebugView, Renderable},
+    AtlasTextureInfo, Borders, Color, DrawList, Font, FontProvider, GlyphRun, IsZero, Rect,
+    Renderer2D, Size, Text, TextMeasure, TextOverflow, TextSystem, TextureId, TextureInfo,
+    TextureOptions,
 };
 use ahash::HashSet;
 use anyhow::Result;
-use cosmic_text::{Attrs, Buffer, Metrics, Shaping};
-use skie_math::{vec2, Corners, Mat3, Vec2};
+use parking_lot::Mutex;
+use skie_math::{vec2, Corners, Mat3, Vec2, Viewport};
 use surface::{CanvasSurface, CanvasSurfaceConfig};
 use wgpu::FilterMode;
 
 pub mod backend_target;
+pub mod batches;
+mod blur;
 pub mod builder;
+mod damage;
+mod frame_ops;
+mod layer;
+mod layer_cache;
 pub mod offscreen_target;
+pub mod picture;
+mod pixel_snap;
 pub mod render_list;
 pub mod snapshot;
+pub mod stats;
 pub mod surface;
 
+use batches::{BatchBreak, BatchInfo};
+use damage::{DamageKey, DamageTracker, Footprint};
+use frame_ops::{FrameOp, FrameOpPlacer, FrameStep};
+use layer_cache::LayerCache;
 use render_list::RenderList;
+use stats::RenderStats;
+
+const MIN_TESSELLATION_TOLERANCE: f32 = 0.01;
+
+/// Where the last line of a [`TextOverflow::Fade`] text fades out, in physical pixels
+struct TextFade {
+    /// baseline of the last line, as laid out by [`FontProvider::layout`]
+    line_y: i32,
+    start: f32,
+    end: f32,
+}
+
+/// The fade of `text` when it doesn't fit, over the last two ems of its box or last line
+fn text_fade(provider: &mut dyn FontProvider, text: &Text, bounds: Size<f32>) -> Option<TextFade> {
+    let measure = provider.measure(text, bounds);
+    if !measure.truncated {
+        return None;
+    }
+
+    let line_y = provider.layout(text, bounds).iter().map(|g| g.y).max()?;
+    let width = text
+        .max_width
+        .unwrap_or_else(|| measure.line_advances.last().copied().unwrap_or_default());
+    let end = text.pos.x + width;
+    Some(TextFade {
+        line_y,
+        start: end - (text.size * 2.0).min(width / 2.0),
+        end,
+    })
+}
+
+/// `text` scaled to physical pixels, glyphs are shaped and rasterized there to stay crisp
+fn physical_text(text: &Text, scale: f32) -> Cow<'_, Text> {
+    if scale == 1.0 {
+        return Cow::Borrowed(text);
+    }
+
+    let mut scaled = text.clone();
+    scaled.size *= scale;
+    scaled.pos = scaled.pos * scale;
+    scaled.word_spacing *= scale;
+    scaled.max_width = scaled.max_width.map(|width| width * scale);
+    scaled.max_height = scaled.max_height.map(|height| height * scale);
+    Cow::Owned(scaled)
+}
+
+pub use builder::{CanvasBuildError, CanvasBuilder};
+
+/// Corners of `rect` after `transform`, clockwise from the top left
+fn transformed_corners(rect: &Rect<f32>, transform: &Mat3) -> [Vec2<f32>; 4] {
+    [
+        rect.top_left(),
+        rect.top_right(),
+        rect.bottom_right(),
+        rect.bottom_left(),
+    ]
+    .map(|corner| *transform * corner)
+}
+
+/// Bounding box of `rect` after `transform`
+fn map_rect(rect: &Rect<f32>, transform: &Mat3) -> Rect<f32> {
+    if *rect == Rect::EVERYTHING || transform.is_identity() {
+        return rect.clone();
+    }
 
-pub use builder::CanvasBuilder;
+    let corners = transformed_corners(rect, transform);
+    let min = corners.iter().fold(corners[0], |min, p| min.min(p));
+    let max = corners.iter().fold(corners[0], |max, p| max.max(p));
+    Rect::from_corners(min, max)
+}
+
+fn quad_contains_point(quad: &[Vec2<f32>; 4], point: Vec2<f32>) -> bool {
+    let sides = [0, 1, 2, 3].map(|i| {
+        let (a, b) = (quad[i], quad[(i + 1) % 4]);
+        (b - a).cross(&(point - a))
+    });
+    sides.iter().all(|side| *side >= 0.0) || sides.iter().all(|side| *side <= 0.0)
+}
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct CanvasState {
     pub transform: Mat3,
+    /// Scissor rect in canvas space, the bounding box of rotated clips
     pub clip_rect: Rect<f32>,
+    /// Clips that dont line up with the canvas axes, masked with the stencil buffer
+    pub clip_quads: Vec<[Vec2<f32>; 4]>,
+    /// Whether brushes that dont set [`Brush::antialias`] get feathered edges
+    pub antialias: bool,
+    /// Multiplies the alpha of everything drawn, see [`Canvas::set_global_alpha`]
+    pub global_alpha: f32,
+    /// Whether quads and strokes are snapped to device pixels, see [`Canvas::set_pixel_snapping`]
+    pub pixel_snap: bool,
+    /// Used by draws without a brush, see [`Canvas::set_fill_brush`]
+    pub fill_brush: Brush,
+    /// see [`Canvas::set_stroke_brush`]
+    pub stroke_brush: Brush,
 }
 
 impl Default for CanvasState {
@@ -41,7 +151,105 @@ impl Default for CanvasState {
         Self {
             transform: Mat3::identity(),
             clip_rect: Rect::EVERYTHING,
+            clip_quads: Vec::new(),
+            antialias: false,
+            global_alpha: 1.0,
+            pixel_snap: false,
+            fill_brush: Brush::filled(Color::BLACK),
+            stroke_brush: Brush::default().stroke_color(Color::BLACK),
+        }
+    }
+}
+
+// the default brushes only pick the brush of later draws, states differing in them render
+// the same and keep their draws batched together
+impl PartialEq for CanvasState {
+    fn eq(&self, other: &Self) -> bool {
+        self.transform == other.transform
+            && self.clip_rect == other.clip_rect
+            && self.clip_quads == other.clip_quads
+            && self.antialias == other.antialias
+            && self.global_alpha == other.global_alpha
+            && self.pixel_snap == other.pixel_snap
+    }
+}
+
+/// How the render target is cleared before a frame is drawn
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClearMode {
+    Color(Color),
+    /// Draw on top of the previous frame, for apps that paint incrementally.
+    /// Swapchain targets only keep their contents between frames when multisampled
+    Preserve,
+}
+
+impl Default for ClearMode {
+    fn default() -> Self {
+        Self::Color(Color::WHITE)
+    }
+}
+
+impl From<Color> for ClearMode {
+    fn from(color: Color) -> Self {
+        Self::Color(color)
+    }
+}
+
+/// How an image is scaled into an area, see [`Canvas::clear_with_texture`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ImageFit {
+    /// Stretched over the area
+    #[default]
+    Fill,
+    /// Scaled to fit inside the area, the rest is left as it is
+    Contain,
+    /// Scaled to cover the area, what goes past it is cut off
+    Cover,
+}
+
+impl ImageFit {
+    /// Where an image of `size` is drawn in `area`, centered in it
+    pub fn rect(&self, size: Size<f32>, area: &Rect<f32>) -> Rect<f32> {
+        let scale_x = area.size.width / size.width;
+        let scale_y = area.size.height / size.height;
+        let scale = match self {
+            ImageFit::Fill => return area.clone(),
+            ImageFit::Contain => scale_x.min(scale_y),
+            ImageFit::Cover => scale_x.max(scale_y),
+        };
+        if !scale.is_finite() {
+            return area.clone();
         }
+
+        let center = area.center();
+        Rect::xywh(center.x, center.y, size.width * scale, size.height * scale).centered()
+    }
+}
+
+/// Id given to drawing with [`Canvas::with_hit_id`]
+pub type HitId = u64;
+
+/// A primitive drawn under [`Canvas::with_hit_id`]
+#[derive(Debug, Clone)]
+struct HitRegion {
+    id: HitId,
+    primitive: Primitive,
+    /// maps canvas points into the primitive's space
+    inverse_transform: Mat3,
+    clip_rect: Rect<f32>,
+    clip_quads: Vec<[Vec2<f32>; 4]>,
+}
+
+impl HitRegion {
+    fn contains_point(&self, point: Vec2<f32>) -> bool {
+        self.clip_rect.contains_point(&point)
+            && self
+                .clip_quads
+                .iter()
+                .all(|quad| quad_contains_point(quad, point))
+            && self
+                .primitive
+                .contains_point(&(self.inverse_transform * point))
     }
 }
 
@@ -63,10 +271,46 @@ pub struct Canvas {
     current_state: CanvasState,
 
     cached_renderables: Vec<Renderable>,
+    /// number of instructions `cached_renderables` was built from
+    prepared_instructions: Option<usize>,
+    /// kept across frames with the meshes of the previous frame to reuse their buffers
+    drawlist: DrawList,
 
     white_texture_uv: Vec2<f32>,
 
-    clear_color: Color,
+    clear_mode: ClearMode,
+    /// see [`Canvas::clear_with_texture`]
+    clear_texture: Option<(TextureId, ImageFit)>,
+    tessellation_tolerance: f32,
+    /// see [`Canvas::image_filter`]
+    image_filter: FilterMode,
+
+    hit_id: Option<HitId>,
+    hit_regions: Vec<HitRegion>,
+    /// the hit regions of the finished frame, picked until the next frame draws anything
+    last_hit_regions: Vec<HitRegion>,
+
+    /// the draw calls of the prepared frame, see [`Canvas::debug_batches`]
+    batches: Vec<BatchInfo>,
+
+    /// blurs and layers with the number of instructions drawn before them, in drawing order
+    frame_ops: Vec<(usize, FrameOp)>,
+    /// the frame ops placed after the renderables drawn before them, built by [`Canvas::prepare`]
+    frame_steps: Vec<(usize, FrameStep)>,
+    /// `state_stack` depth of each open layer
+    layer_stack: Vec<usize>,
+    /// see [`Canvas::cache_layer`]
+    layer_cache: LayerCache,
+
+    /// warn about unbalanced saves and leftover transforms when a frame is prepared
+    validate_state: bool,
+
+    /// submit to completion time of the last finished frame
+    gpu_time: Arc<Mutex<Option<Duration>>>,
+    /// see [`Canvas::render_stats`]
+    stats: RenderStats,
+    /// see [`Canvas::set_damage_tracking`]
+    damage: DamageTracker,
     // TODO msaa
 }
 
@@ -93,7 +337,10 @@ impl Canvas {
 
             state_stack: Default::default(),
 
-            clear_color: Color::WHITE,
+            clear_mode: ClearMode::default(),
+            clear_texture: None,
+            tessellation_tolerance: DEFAULT_TOLERANCE,
+            image_filter: FilterMode::Linear,
             current_state: CanvasState::default(),
 
             surface_config,
@@ -102,6 +349,24 @@ impl Canvas {
 
             list: Default::default(),
             cached_renderables: Default::default(),
+            prepared_instructions: None,
+            drawlist: Default::default(),
+
+            hit_id: None,
+            hit_regions: Default::default(),
+            last_hit_regions: Default::default(),
+
+            batches: Default::default(),
+
+            frame_ops: Default::default(),
+            frame_steps: Default::default(),
+            layer_stack: Default::default(),
+            layer_cache: Default::default(),
+
+            validate_state: cfg!(debug_assertions),
+            gpu_time: Default::default(),
+            stats: Default::default(),
+            damage: Default::default(),
         }
     }
 
@@ -121,10 +386,62 @@ impl Canvas {
         self.surface_config.height
     }
 
+    /// Size of the render target in physical pixels, same as [`Canvas::screen`]
+    pub fn physical_size(&self) -> Size<u32> {
+        self.screen()
+    }
+
+    /// Size of the drawing area in logical pixels, the units used by all drawing methods
+    pub fn logical_size(&self) -> Size<f32> {
+        let scale = self.scale_factor();
+        Size::new(
+            self.surface_config.width as f32 / scale,
+            self.surface_config.height as f32 / scale,
+        )
+    }
+
+    pub fn scale_factor(&self) -> f32 {
+        self.renderer.scale_factor()
+    }
+
+    /// Sets the number of physical pixels per logical pixel, e.g. from winit `ScaleFactorChanged`
+    pub fn set_scale_factor(&mut self, scale_factor: f32) {
+        self.renderer
+            .set_scale_factor(scale_factor.max(f32::EPSILON));
+        self.invalidate_prepared();
+    }
+
     pub fn atlas(&self) -> &Arc<SkieAtlas> {
         &self.texture_atlas
     }
 
+    /// Size, kind and atlas placement of a texture, `None` for unknown keys and for
+    /// internal or user textures which the canvas doesnt track
+    pub fn texture_info(&self, texture_id: &TextureId) -> Option<TextureInfo> {
+        match texture_id {
+            TextureId::AtlasKey(key) => {
+                let info = self.texture_atlas.get_texture_info(key)?;
+                let size = info.tile.bounds.size;
+                Some(TextureInfo {
+                    size: Size::new(size.width as u32, size.height as u32),
+                    kind: info.tile.texture.kind,
+                    atlas_page: Some(info.tile.texture),
+                    uv_rect: info.uv_rect(),
+                })
+            }
+            TextureId::Atlas(id) => {
+                let size = self.texture_atlas.get_texture_size(id)?;
+                Some(TextureInfo {
+                    size: Size::new(size.width as u32, size.height as u32),
+                    kind: id.kind,
+                    atlas_page: Some(*id),
+                    uv_rect: Rect::xywh(0.0, 0.0, 1.0, 1.0),
+                })
+            }
+            TextureId::Internal(_) | TextureId::User(_) => None,
+        }
+    }
+
     pub fn text_system(&self) -> &Arc<TextSystem> {
         &self.text_system
     }
@@ -138,8 +455,111 @@ impl Canvas {
         self.state_stack.push(self.current_state.clone());
     }
 
+    /// Number of [`Canvas::save`]s not restored yet, layers included
+    pub fn state_depth(&self) -> usize {
+        self.state_stack.len()
+    }
+
+    /// The state the next draws use
+    pub fn current_state(&self) -> &CanvasState {
+        &self.current_state
+    }
+
+    /// Logs a warning when a frame is rendered with saves left unrestored or a transform
+    /// still applied, the usual cause of everything being drawn offset on the next frame.
+    /// On by default in debug builds
+    pub fn set_validate_state(&mut self, enable: bool) {
+        self.validate_state = enable;
+    }
+
+    /// Describes what looks left over from the frame's drawing, see [`Canvas::set_validate_state`]
+    fn state_warning(&self) -> Option<String> {
+        let mut problems = Vec::new();
+        if !self.state_stack.is_empty() {
+            problems.push(format!(
+                "{} save() without a matching restore()",
+                self.state_stack.len()
+            ));
+        }
+        if !self.current_state.transform.is_identity() {
+            problems.push(format!(
+                "transform left applied: {:?}",
+                self.current_state.transform
+            ));
+        }
+
+        (!problems.is_empty()).then(|| problems.join(", "))
+    }
+
     pub fn clear_color(&mut self, clear_color: Color) {
-        self.clear_color = clear_color;
+        self.clear_mode = ClearMode::Color(clear_color);
+    }
+
+    pub fn set_clear_mode(&mut self, mode: impl Into<ClearMode>) {
+        self.clear_mode = mode.into();
+    }
+
+    pub fn clear_mode(&self) -> ClearMode {
+        self.clear_mode
+    }
+
+    /// Draws the texture under everything at the start of each frame, over the clear color.
+    /// [`ImageFit::Contain`] and [`ImageFit::Cover`] need the texture size, so they only work
+    /// for textures known to [`Canvas::texture_info`]. Kept until [`Canvas::reset`]
+    pub fn clear_with_texture(&mut self, texture_id: &TextureId, fit: ImageFit) {
+        self.clear_texture = Some((texture_id.clone(), fit));
+        self.invalidate_prepared();
+    }
+
+    pub fn remove_clear_texture(&mut self) {
+        self.clear_texture = None;
+        self.invalidate_prepared();
+    }
+
+    /// The background image drawn with the fit used
+    fn clear_texture_instruction(&self) -> Option<GraphicsInstruction> {
+        let (texture_id, fit) = self.clear_texture.as_ref()?;
+        let area = Rect::from_origin_size(vec2(0.0, 0.0), self.logical_size());
+        let rect = match (fit, self.texture_info(texture_id)) {
+            (ImageFit::Fill, _) => area,
+            (fit, Some(info)) => fit.rect(info.size.map(|v| *v as f32), &area),
+            (_, None) => {
+                log::warn!("clear_with_texture: unknown size for {}", texture_id);
+                area
+            }
+        };
+        Some(GraphicsInstruction::textured(
+            quad().rect(rect),
+            texture_id.clone(),
+        ))
+    }
+
+    /// Shows the triangles of the drawn shapes, one wireframe color per draw call or a
+    /// heatmap of overdraw. For debugging only
+    pub fn set_debug_view(&mut self, view: DebugView) {
+        self.renderer.set_debug_view(view);
+        self.invalidate_prepared();
+    }
+
+    pub fn debug_view(&self) -> DebugView {
+        self.renderer.debug_view()
+    }
+
+    /// Max distance in pixels between curves and the triangles drawn for them.
+    /// Lower values give smoother curves for more triangles, useful for zoomed in content
+    pub fn set_tessellation_tolerance(&mut self, px: f32) {
+        self.tessellation_tolerance = px.max(MIN_TESSELLATION_TOLERANCE);
+        self.invalidate_prepared();
+    }
+
+    pub fn tessellation_tolerance(&self) -> f32 {
+        self.tessellation_tolerance
+    }
+
+    /// Filter images uploaded for this canvas should be sampled with, set by the
+    /// [`CanvasBuilder`] presets
+    pub fn image_filter(&self) -> FilterMode {
+        self.image_filter
     }
 
     pub fn restore(&mut self) {
@@ -152,18 +572,125 @@ impl Canvas {
     pub fn reset(&mut self) {
         self.stage_changes();
 
-        self.clear_color = Color::WHITE;
-        self.current_state = CanvasState {
-            transform: Mat3::identity(),
-            clip_rect: Rect::EVERYTHING,
-        };
+        self.clear_mode = ClearMode::default();
+        self.clear_texture = None;
+        self.current_state = CanvasState::default();
 
         self.state_stack.clear();
     }
 
+    /// Clips drawing to `rect` in the current transform. Axis aligned clips only narrow the
+    /// scissor rect, rotated or skewed ones are also masked with the stencil buffer
     pub fn clip(&mut self, rect: &Rect<f32>) {
         self.stage_changes();
-        self.current_state.clip_rect = self.current_state.clip_rect.intersect(rect);
+        let transform = self.current_state.transform;
+        if !transform.is_axis_aligned() && *rect != Rect::EVERYTHING {
+            self.current_state
+                .clip_quads
+                .push(transformed_corners(rect, &transform));
+        }
+        self.current_state.clip_rect = self
+            .current_state
+            .clip_rect
+            .intersect(&map_rect(rect, &transform));
+    }
+
+    /// Runs `f` with drawing clipped to `rect`, the state is restored once it returns
+    /// even if `f` left saves without a matching restore
+    pub fn with_clip_rect<R>(&mut self, rect: &Rect<f32>, f: impl FnOnce(&mut Self) -> R) -> R {
+        let depth = self.state_stack.len();
+        self.save();
+        self.clip(rect);
+
+        let res = f(self);
+
+        while self.state_stack.len() > depth {
+            self.restore();
+        }
+        res
+    }
+
+    /// Sets the antialiasing default for brushes that dont choose one,
+    /// saved and restored with the rest of the state
+    pub fn set_antialias(&mut self, enable: bool) {
+        self.stage_changes();
+        self.current_state.antialias = enable;
+    }
+
+    pub fn antialias(&self) -> bool {
+        self.current_state.antialias
+    }
+
+    /// Rounds quads, polylines and path end points to the device pixel grid so 1px borders
+    /// and hairlines stay crisp. Odd stroke widths are centered on pixels, even ones and
+    /// fills on pixel edges. Has no effect while the transform rotates or skews
+    pub fn set_pixel_snapping(&mut self, enable: bool) {
+        self.stage_changes();
+        self.current_state.pixel_snap = enable;
+    }
+
+    pub fn pixel_snapping(&self) -> bool {
+        self.current_state.pixel_snap
+    }
+
+    /// Brush whose fill the draws without a brush use, like [`Canvas::fill_rect`].
+    /// Saved and restored with the rest of the state, black by default
+    pub fn set_fill_brush(&mut self, brush: Brush) {
+        self.current_state.fill_brush = brush;
+    }
+
+    pub fn fill_brush(&self) -> &Brush {
+        &self.current_state.fill_brush
+    }
+
+    /// Brush whose stroke the draws without a brush use, like [`Canvas::stroke_rect`].
+    /// Saved and restored with the rest of the state, a black hairline by default
+    pub fn set_stroke_brush(&mut self, brush: Brush) {
+        self.current_state.stroke_brush = brush;
+    }
+
+    pub fn stroke_brush(&self) -> &Brush {
+        &self.current_state.stroke_brush
+    }
+
+    /// Runs `f` drawing in the viewport's data space, clipped to its screen rect.
+    /// Line widths are scaled along with the data, see [`Viewport::to_screen`]
+    /// to stroke in screen space instead
+    pub fn with_viewport<R>(&mut self, viewport: &Viewport, f: impl FnOnce(&mut Self) -> R) -> R {
+        self.with_clip_rect(&viewport.screen, |canvas| {
+            canvas.transform(&viewport.transform());
+            f(canvas)
+        })
+    }
+
+    /// Fades everything drawn after it by `alpha`, from 0 to 1. Part of the state, so
+    /// [`Canvas::save`] and [`Canvas::restore`] scope it. Unlike [`Canvas::save_layer`]
+    /// overlapping shapes show through each other
+    pub fn set_global_alpha(&mut self, alpha: f32) {
+        self.stage_changes();
+        self.current_state.global_alpha = alpha.clamp(0.0, 1.0);
+    }
+
+    pub fn global_alpha(&self) -> f32 {
+        self.current_state.global_alpha
+    }
+
+    /// The transform the next draws use, see [`Canvas::set_transform`]
+    pub fn get_transform(&self) -> Mat3 {
+        self.current_state.transform
+    }
+
+    /// Replaces the current transform, e.g. with one imported from SVG or a scene graph.
+    /// [`Canvas::transform`] composes with it instead
+    pub fn set_transform(&mut self, transform: &Mat3) {
+        self.stage_changes();
+        self.current_state.transform = *transform;
+    }
+
+    /// Multiplies the current transform by `transform`, applied before the existing one
+    pub fn transform(&mut self, transform: &Mat3) {
+        self.stage_changes();
+        self.current_state.transform = *transform * self.current_state.transform;
     }
 
     pub fn translate(&mut self, dx: f32, dy: f32) {
@@ -181,9 +708,36 @@ impl Canvas {
         self.current_state.transform.rotate(angle_rad);
     }
 
+    /// Shears x by `sx * y` and y by `sy * x`, see [`Mat3::from_skew`]
+    pub fn skew(&mut self, sx: f32, sy: f32) {
+        self.stage_changes();
+        self.current_state.transform.skew(sx, sy);
+    }
+
     pub fn clear(&mut self) {
         self.list.clear();
-        self.cached_renderables.clear();
+        self.frame_ops.clear();
+        self.layer_stack.clear();
+        self.hit_regions.clear();
+        self.last_hit_regions.clear();
+        self.batches.clear();
+        self.recycle_renderables();
+        self.invalidate_prepared();
+    }
+
+    /// Makes the next [`Canvas::prepare`] tessellate again, for changes to anything the
+    /// prepared frame was built from other than the drawn instructions
+    fn invalidate_prepared(&mut self) {
+        self.prepared_instructions = None;
+    }
+
+    /// Clears the prepared renderables, keeping their meshes for the next frame
+    fn recycle_renderables(&mut self) {
+        self.drawlist.recycle(
+            self.cached_renderables
+                .drain(..)
+                .map(|renderable| renderable.mesh),
+        );
     }
 
     #[inline]
@@ -191,36 +745,259 @@ impl Canvas {
         self.list.stage_changes(self.current_state.clone());
     }
 
+    /// Tags everything drawn in `f` with `id` so [`Canvas::pick`] and [`Canvas::hit_test`]
+    /// can find it. Nothing is recorded for drawing outside of it
+    pub fn with_hit_id<R>(&mut self, id: HitId, f: impl FnOnce(&mut Self) -> R) -> R {
+        let prev = self.hit_id.replace(id);
+        let res = f(self);
+        self.hit_id = prev;
+        res
+    }
+
+    /// Returns the id of the topmost primitive tagged with [`Canvas::with_hit_id`] under `point`
+    pub fn pick(&self, point: Vec2<f32>) -> Option<HitId> {
+        self.current_hit_regions()
+            .iter()
+            .rev()
+            .find(|region| region.contains_point(point))
+            .map(|region| region.id)
+    }
+
+    /// Returns the ids of all tagged primitives under `point`, topmost first and each once.
+    /// The transforms and clips they were drawn with are respected
+    pub fn hit_test(&self, point: Vec2<f32>) -> Vec<HitId> {
+        let mut ids = Vec::new();
+        for region in self.current_hit_regions().iter().rev() {
+            if !ids.contains(&region.id) && region.contains_point(point) {
+                ids.push(region.id);
+            }
+        }
+        ids
+    }
+
+    /// The regions of the frame being recorded, or of the finished one until it draws
+    fn current_hit_regions(&self) -> &[HitRegion] {
+        if self.list.instructions.is_empty() {
+            &self.last_hit_regions
+        } else {
+            &self.hit_regions
+        }
+    }
+
+    fn record_hit_region(&mut self, primitive: &Primitive) {
+        if let Some(id) = self.hit_id {
+            self.hit_regions.push(HitRegion {
+                id,
+                primitive: primitive.clone(),
+                inverse_transform: self.current_state.transform.inverse(),
+                clip_rect: self.current_state.clip_rect.clone(),
+                clip_quads: self.current_state.clip_quads.clone(),
+            });
+        }
+    }
+
     #[inline]
     pub fn draw_primitive(&mut self, prim: impl Into<Primitive>, brush: Brush) {
-        self.list
-            .add(GraphicsInstruction::brush(prim, brush.clone()));
+        let instruction = GraphicsInstruction::brush(prim, brush);
+        self.record_hit_region(&instruction.primitive);
+
+        let Some(pattern) = &instruction.brush.fill_style.pattern else {
+            self.list.add(instruction);
+            return;
+        };
+
+        // the fill samples the pattern texture, the stroke and borders the white one
+        let texture = pattern.texture.clone();
+        let outline = GraphicsInstruction::brush(
+            instruction.primitive.clone(),
+            instruction.brush.clone().no_fill(),
+        );
+        self.list.add(GraphicsInstruction::textured_brush(
+            instruction.primitive,
+            texture,
+            instruction.brush,
+        ));
+        if !outline.nothing_to_draw() {
+            self.list.add(outline);
+        }
     }
 
+    /// Contours with pattern fills all use the texture of the first pattern found
     pub fn draw_path(&mut self, path: impl Into<Path>, brush: impl Into<PathBrush>) {
-        self.draw_primitive(
+        let path = path.into();
+        let brush = brush.into();
+
+        let Some(texture) = brush.pattern_texture().cloned() else {
+            // paths are drawn with their own brushes
+            self.draw_primitive(Primitive::Path { path, brush }, Brush::default());
+            return;
+        };
+
+        // the pattern fill and the strokes are in different textures
+        let fill = Primitive::Path {
+            path: path.clone(),
+            brush: brush.map(|brush| brush.no_stroke()),
+        };
+        self.record_hit_region(&fill);
+        self.list.add(GraphicsInstruction::textured_brush(
+            fill,
+            texture,
+            Brush::filled(Color::WHITE),
+        ));
+        self.list.add(GraphicsInstruction::brush(
             Primitive::Path {
-                path: path.into(),
-                brush: brush.into(),
+                path,
+                brush: brush.map(|brush| brush.no_fill()),
             },
-            // FIXME: This is a workaround
             Brush::filled(Color::WHITE),
-        );
+        ));
+    }
+
+    /// Fills every contour of `path` with `brush`, its stroke is ignored
+    pub fn fill_path(&mut self, path: &Path, brush: Brush) {
+        self.draw_path(path.clone(), brush.no_stroke());
+    }
+
+    /// Strokes every contour of `path` with `brush`, its fill is ignored
+    pub fn stroke_path(&mut self, path: &Path, brush: Brush) {
+        self.draw_path(path.clone(), brush.no_fill());
+    }
+
+    /// Fills `path` with the texture tinted by the fill color and placed by the brush's
+    /// [`crate::TextureMapping`], the stroke is drawn on top untextured
+    pub fn draw_textured_path(
+        &mut self,
+        path: impl Into<Path>,
+        texture_id: &TextureId,
+        brush: impl Into<PathBrush>,
+    ) {
+        let path = path.into();
+        let brush = brush.into();
+
+        let fill = Primitive::Path {
+            path: path.clone(),
+            brush: brush.map(|brush| brush.no_stroke()),
+        };
+        self.record_hit_region(&fill);
+        self.list.add(GraphicsInstruction::textured_brush(
+            fill,
+            texture_id.clone(),
+            Brush::filled(Color::WHITE),
+        ));
+        self.list.add(GraphicsInstruction::brush(
+            Primitive::Path {
+                path,
+                brush: brush.map(|brush| brush.no_fill()),
+            },
+            Brush::filled(Color::WHITE),
+        ));
     }
 
     pub fn draw_rect(&mut self, rect: &Rect<f32>, brush: Brush) {
         self.draw_primitive(quad().rect(rect.clone()), brush);
     }
 
+    /// Fills `rect` with the fill of [`Canvas::fill_brush`]
+    pub fn fill_rect(&mut self, rect: &Rect<f32>) {
+        let brush = self.current_state.fill_brush.clone().no_stroke();
+        self.draw_rect(rect, brush);
+    }
+
+    /// Strokes `rect` with the stroke of [`Canvas::stroke_brush`]
+    pub fn stroke_rect(&mut self, rect: &Rect<f32>) {
+        let brush = self.current_state.stroke_brush.clone().no_fill();
+        self.draw_rect(rect, brush);
+    }
+
     pub fn draw_round_rect(&mut self, rect: &Rect<f32>, corners: &Corners<f32>, brush: Brush) {
         self.draw_primitive(quad().rect(rect.clone()).corners(corners.clone()), brush);
     }
 
+    /// Draws the rect with a border per side inside its bounds, like css borders.
+    /// The brush fills it and its stroke, if any, is drawn around the borders
+    pub fn draw_bordered_rect(
+        &mut self,
+        rect: &Rect<f32>,
+        corners: &Corners<f32>,
+        borders: Borders,
+        brush: Brush,
+    ) {
+        self.draw_primitive(
+            quad()
+                .rect(rect.clone())
+                .corners(corners.clone())
+                .borders(borders),
+            brush,
+        );
+    }
+
     pub fn draw_image(&mut self, rect: &Rect<f32>, texture_id: &TextureId) {
-        self.list.add(GraphicsInstruction::textured(
+        let instruction =
+            GraphicsInstruction::textured(quad().rect(rect.clone()), texture_id.clone());
+        self.record_hit_region(&instruction.primitive);
+        self.list.add(instruction);
+    }
+
+    /// Draws the image with its colors multiplied by `tint`. White images, like most icons,
+    /// are drawn in the tint color so one texture serves every color
+    pub fn draw_image_tinted(&mut self, rect: &Rect<f32>, texture_id: &TextureId, tint: Color) {
+        let instruction = GraphicsInstruction::textured_brush(
             quad().rect(rect.clone()),
             texture_id.clone(),
-        ));
+            Brush::filled(tint),
+        );
+        self.record_hit_region(&instruction.primitive);
+        self.list.add(instruction);
+    }
+
+    /// Like [`Canvas::draw_image`] but samples the texture with `options` instead of the ones
+    /// it was added with, e.g. nearest filtering for pixel art
+    pub fn draw_image_with_sampler(
+        &mut self,
+        rect: &Rect<f32>,
+        texture_id: &TextureId,
+        options: &TextureOptions,
+    ) {
+        let instruction =
+            GraphicsInstruction::textured(quad().rect(rect.clone()), texture_id.clone())
+                .sampler(options.clone());
+        self.record_hit_region(&instruction.primitive);
+        self.list.add(instruction);
+    }
+
+    /// Draws the part of the texture in `source` over `rect`, `source` is in normalized
+    /// texture coordinates where `0..1` covers the whole texture
+    pub fn draw_image_uv(&mut self, rect: &Rect<f32>, texture_id: &TextureId, source: &Rect<f32>) {
+        let instruction =
+            GraphicsInstruction::textured(quad().rect(rect.clone()), texture_id.clone())
+                .source(source.clone());
+        self.record_hit_region(&instruction.primitive);
+        self.list.add(instruction);
+    }
+
+    /// Draws the part of the texture in `source` over `rect`, `source` is in texels.
+    /// Sprite sheets and cropped images can be drawn this way. Needs the texture size, so
+    /// only works for textures known to [`Canvas::texture_info`], see [`Canvas::draw_image_uv`]
+    pub fn draw_image_region(
+        &mut self,
+        rect: &Rect<f32>,
+        texture_id: &TextureId,
+        source: &Rect<f32>,
+    ) {
+        let Some(info) = self.texture_info(texture_id) else {
+            log::warn!("draw_image_region: unknown size for {}", texture_id);
+            return;
+        };
+
+        let width = info.size.width.max(1) as f32;
+        let height = info.size.height.max(1) as f32;
+        let uv = Rect::xywh(
+            source.origin.x / width,
+            source.origin.y / height,
+            source.size.width / width,
+            source.size.height / height,
+        );
+        self.draw_image_uv(rect, texture_id, &uv);
     }
 
     pub fn draw_image_rounded(
@@ -229,107 +1006,320 @@ impl Canvas {
         corners: &Corners<f32>,
         texture_id: &TextureId,
     ) {
-        self.list.add(GraphicsInstruction::textured(
+        let instruction = GraphicsInstruction::textured(
             quad().rect(rect.clone()).corners(corners.clone()),
             texture_id.clone(),
-        ));
+        );
+        self.record_hit_region(&instruction.primitive);
+        self.list.add(instruction);
     }
 
     pub fn draw_circle(&mut self, cx: f32, cy: f32, radius: f32, brush: Brush) {
         self.draw_primitive(circle().pos(cx, cy).radius(radius), brush);
     }
 
-    pub fn fill_text(&mut self, text: &Text, fill_color: Color) {
-        self.stage_changes();
-        self.text_system.write(|state| {
-            let line_height_em = 1.4;
-            let metrics = Metrics::new(text.size, text.size * line_height_em);
-            let mut buffer = Buffer::new(&mut state.font_system, metrics);
-            buffer.set_size(
-                &mut state.font_system,
-                Some(self.surface_config.width as f32),
-                Some(self.surface_config.height as f32),
-            );
-
-            let attrs = Attrs::new();
-            attrs.style(text.font.style.into());
-            attrs.weight(text.font.weight.into());
-            attrs.family(cosmic_text::Family::Name(&text.font.family));
-
-            buffer.set_text(&mut state.font_system, &text.text, attrs, Shaping::Advanced);
-
-            buffer.shape_until_scroll(&mut state.font_system, false);
-            // begin run
-            for run in buffer.layout_runs() {
-                let line_y = run.line_y;
-
-                // begin glyps
-                for glyph in run.glyphs.iter() {
-                    let scale = 1.0;
-                    let physical_glyph = glyph.physical((text.pos.x, text.pos.y), scale);
-                    let image = state
-                        .swash_cache
-                        .get_image(&mut state.font_system, physical_glyph.cache_key);
-
-                    if let Some(image) = image {
-                        let kind = match image.content {
-                            cosmic_text::SwashContent::Color => TextureKind::Color,
-                            cosmic_text::SwashContent::Mask => TextureKind::Mask,
-                            // we dont support it for now
-                            cosmic_text::SwashContent::SubpixelMask => TextureKind::Mask,
-                        };
-
-                        let glyph_key = AtlasKey::from(GlyphImage {
-                            key: physical_glyph.cache_key,
-                            is_emoji: kind.is_color(),
-                        });
+    /// Fills the circle with the fill of [`Canvas::fill_brush`]
+    pub fn fill_circle(&mut self, cx: f32, cy: f32, radius: f32) {
+        let brush = self.current_state.fill_brush.clone().no_stroke();
+        self.draw_circle(cx, cy, radius, brush);
+    }
 
-                        let size =
-                            Size::new(image.placement.width as i32, image.placement.height as i32);
+    /// Strokes the circle with the stroke of [`Canvas::stroke_brush`]
+    pub fn stroke_circle(&mut self, cx: f32, cy: f32, radius: f32) {
+        let brush = self.current_state.stroke_brush.clone().no_fill();
+        self.draw_circle(cx, cy, radius, brush);
+    }
 
-                        if size.is_zero() {
-                            continue;
-                        };
+    /// Fills the area between the two radii with the brush fill, the stroke is applied to both edges
+    pub fn draw_ring(
+        &mut self,
+        cx: f32,
+        cy: f32,
+        inner_radius: f32,
+        outer_radius: f32,
+        brush: Brush,
+    ) {
+        self.draw_primitive(ring().pos(cx, cy).radii(inner_radius, outer_radius), brush);
+    }
 
-                        self.texture_atlas
-                            .get_or_insert(&glyph_key, || (size, Cow::Borrowed(&image.data)));
+    /// Draws a wedge starting at `start` radians sweeping `sweep` radians clockwise
+    pub fn draw_pie(
+        &mut self,
+        cx: f32,
+        cy: f32,
+        radius: f32,
+        start: f32,
+        sweep: f32,
+        brush: Brush,
+    ) {
+        self.draw_primitive(pie().pos(cx, cy).radius(radius).angles(start, sweep), brush);
+    }
 
-                        self.renderer.set_texture_from_atlas(
-                            &self.texture_atlas,
-                            &glyph_key,
-                            &TextureOptions::default()
-                                .min_filter(FilterMode::Nearest)
-                                .mag_filter(FilterMode::Nearest),
-                        );
+    /// Draws an ellipse with radii `rx` and `ry`, rotated `rotation` radians clockwise
+    pub fn draw_ellipse(
+        &mut self,
+        cx: f32,
+        cy: f32,
+        rx: f32,
+        ry: f32,
+        rotation: f32,
+        brush: Brush,
+    ) {
+        self.draw_primitive(
+            ellipse().pos(cx, cy).radii(rx, ry).rotation(rotation),
+            brush,
+        );
+    }
 
-                        let x = physical_glyph.x + image.placement.left;
-                        let y = line_y as i32 + physical_glyph.y - image.placement.top;
+    /// Strokes the arc from `start` to `end` radians, clockwise when `end` is greater.
+    /// The fill covers the area between the arc and its chord
+    pub fn draw_arc(&mut self, cx: f32, cy: f32, radius: f32, start: f32, end: f32, brush: Brush) {
+        self.draw_primitive(
+            arc().pos(cx, cy).radius(radius).angles(start, end - start),
+            brush,
+        );
+    }
 
-                        let color = if kind.is_color() {
-                            let mut c = Color::WHITE;
-                            c.a = fill_color.a;
-                            c
-                        } else {
-                            fill_color
-                        };
-
-                        self.list.add(GraphicsInstruction::textured_brush(
-                            quad().rect(Rect::from_origin_size(
-                                (x as f32, y as f32).into(),
-                                size.map(|v| *v as f32),
-                            )),
-                            TextureId::AtlasKey(glyph_key),
-                            Brush::filled(color),
-                        ));
+    /// Fills a marker of `size` at each point in one mesh, for scatter plots and particles.
+    /// The stroke of the brush is not used
+    pub fn draw_points(
+        &mut self,
+        centers: &[Vec2<f32>],
+        size: f32,
+        shape: PointShape,
+        brush: Brush,
+    ) {
+        self.draw_primitive(
+            points()
+                .points(centers.iter().copied())
+                .size(size)
+                .shape(shape),
+            brush,
+        );
+    }
+
+    /// Rasterizes and uploads `charset` at the logical `sizes` ahead of time,
+    /// see [`TextSystem::prewarm`]
+    pub fn prewarm_glyphs(
+        &self,
+        font: &Font,
+        sizes: &[f32],
+        charset: impl Into<ArcString>,
+    ) -> usize {
+        let scale = self.scale_factor();
+        let sizes = sizes.iter().map(|size| size * scale).collect::<Vec<_>>();
+        self.text_system
+            .prewarm(&self.texture_atlas, font, &sizes, charset)
+    }
+
+    pub fn fill_text(&mut self, text: &Text, fill_color: Color) {
+        self.fill_texts(std::slice::from_ref(text), fill_color);
+    }
+
+    /// Draws all the labels as one group, cheaper than a [`Canvas::fill_text`] call for each.
+    /// Labels with [`TextOverflow::Clip`] are drawn after the rest, each in its own clip
+    pub fn fill_texts(&mut self, texts: &[Text], fill_color: Color) {
+        let (clipped, visible): (Vec<_>, Vec<_>) = texts
+            .iter()
+            .partition(|text| text.overflow == TextOverflow::Clip);
+
+        self.fill_text_group(&visible, fill_color);
+        for text in clipped {
+            self.with_clip_rect(&text.clip_rect(), |canvas| {
+                canvas.fill_text_group(&[text], fill_color)
+            });
+        }
+    }
+
+    fn fill_text_group(&mut self, texts: &[&Text], fill_color: Color) {
+        if texts.is_empty() {
+            return;
+        }
+        self.stage_changes();
+        let bounds = Size::new(
+            self.surface_config.width as f32,
+            self.surface_config.height as f32,
+        );
+
+        let scale = self.scale_factor();
+        // glyphs already in the atlas and known to the renderer
+        let mut uploaded = HashSet::<AtlasKey>::default();
+
+        self.text_system.write(|provider| {
+            for text in texts {
+                let text = physical_text(text, scale);
+                let fade = (text.overflow == TextOverflow::Fade)
+                    .then(|| text_fade(provider, &text, bounds))
+                    .flatten();
+                let glyphs = provider.layout(&text, bounds);
+
+                for glyph in glyphs {
+                    let Some(image) = provider.rasterize(&glyph.key) else {
+                        continue;
+                    };
+
+                    if image.size.is_zero() {
+                        continue;
+                    };
+
+                    let glyph_key = AtlasKey::from(
+                        self.text_system
+                            .glyph_image(glyph.key, image.kind.is_color()),
+                    );
+
+                    let size = image.size;
+                    if !uploaded.contains(&glyph_key) {
+                        self.texture_atlas
+                            .get_or_insert(&glyph_key, || (size, image.data));
+
+                        self.renderer.set_texture_from_atlas(
+                            &self.texture_atlas,
+                            &glyph_key,
+                            &TextureOptions::default()
+                                .min_filter(FilterMode::Nearest)
+                                .mag_filter(FilterMode::Nearest),
+                        );
+                        uploaded.insert(glyph_key.clone());
+                    }
+
+                    let x = glyph.x + image.left;
+                    let y = glyph.y - image.top;
+
+                    let color = if image.kind.is_color() {
+                        let mut c = Color::WHITE;
+                        c.a = fill_color.a;
+                        c
+                    } else {
+                        fill_color
+                    };
+
+                    let brush = match &fade {
+                        Some(fade) if glyph.y == fade.line_y => {
+                            if x as f32 >= fade.end {
+                                continue;
+                            }
+                            let mut transparent = color;
+                            transparent.a = 0;
+                            Brush::default().linear_gradient(
+                                vec2(fade.start, 0.0) / scale,
+                                vec2(fade.end, 0.0) / scale,
+                                [(0.0, color), (1.0, transparent)],
+                            )
+                        }
+                        _ => Brush::filled(color),
                     }
+                    // glyph images are anti-aliased when rasterized, feathering would fade
+                    // their outer pixels
+                    .antialias(false);
+
+                    self.list.add(GraphicsInstruction::textured_brush(
+                        quad().rect(Rect::from_origin_size(
+                            vec2(x as f32, y as f32) / scale,
+                            size.map(|v| *v as f32 / scale),
+                        )),
+                        TextureId::AtlasKey(glyph_key),
+                        brush,
+                    ));
                 }
-                // end glyphs
             }
-            // end run
         });
         self.stage_changes();
     }
 
+    /// Strokes the glyph outlines of `text` with the stroke of `brush`, its fill is ignored.
+    /// Draw the text with [`Canvas::fill_text`] first for outlined labels.
+    /// Glyphs without outlines, like emoji, are skipped
+    pub fn stroke_text(&mut self, text: &Text, brush: Brush) {
+        match text.overflow {
+            TextOverflow::Clip => self.with_clip_rect(&text.clip_rect(), |canvas| {
+                canvas.stroke_glyph_outlines(text, brush)
+            }),
+            _ => self.stroke_glyph_outlines(text, brush),
+        }
+    }
+
+    fn stroke_glyph_outlines(&mut self, text: &Text, brush: Brush) {
+        let bounds = Size::new(
+            self.surface_config.width as f32,
+            self.surface_config.height as f32,
+        );
+        let scale = self.scale_factor();
+
+        let mut points = Vec::new();
+        let mut verbs = Vec::new();
+        self.text_system.write(|provider| {
+            for run in provider.glyph_runs(&physical_text(text, scale), bounds) {
+                for glyph in &run.glyphs {
+                    let Some(outline) = provider.outline(&glyph.key) else {
+                        continue;
+                    };
+
+                    let origin = vec2(glyph.x, glyph.y);
+                    points.extend(outline.points.iter().map(|p| (origin + *p) / scale));
+                    verbs.extend_from_slice(&outline.verbs);
+                }
+            }
+        });
+
+        if verbs.is_empty() {
+            return;
+        }
+
+        let path = Path {
+            points: points.into(),
+            verbs: verbs.into(),
+        };
+        self.draw_path(path, brush.no_fill());
+    }
+
+    /// The shaped glyphs of `text` grouped by line, positioned in logical pixels the same way
+    /// [`Canvas::fill_text`] would draw them. Use it for per-glyph effects that reuse the shaping
+    pub fn glyph_runs(&self, text: &Text) -> Vec<GlyphRun> {
+        let bounds = Size::new(
+            self.surface_config.width as f32,
+            self.surface_config.height as f32,
+        );
+        let scale = self.scale_factor();
+
+        let mut runs = self
+            .text_system
+            .write(|provider| provider.glyph_runs(&physical_text(text, scale), bounds));
+
+        if scale != 1.0 {
+            for run in &mut runs {
+                run.baseline /= scale;
+                for glyph in &mut run.glyphs {
+                    glyph.x /= scale;
+                    glyph.y /= scale;
+                    glyph.advance /= scale;
+                }
+            }
+        }
+        runs
+    }
+
+    /// Measures `text` shaped the same way [`Canvas::fill_text`] would draw it, in logical pixels
+    pub fn measure_text(&self, text: &Text) -> TextMeasure {
+        let bounds = Size::new(
+            self.surface_config.width as f32,
+            self.surface_config.height as f32,
+        );
+        let scale = self.scale_factor();
+
+        let mut measure = self
+            .text_system
+            .write(|provider| provider.measure(&physical_text(text, scale), bounds));
+
+        if scale != 1.0 {
+            measure.width /= scale;
+            measure.height /= scale;
+            for advance in &mut measure.line_advances {
+                *advance /= scale;
+            }
+        }
+        measure
+    }
+
     pub fn resize(&mut self, new_width: u32, new_height: u32) {
         let width = new_width.max(1);
         let height = new_height.max(1);
@@ -337,6 +1327,19 @@ impl Canvas {
         self.renderer.resize(width, height);
         self.surface_config.width = width;
         self.surface_config.height = height;
+        self.invalidate_prepared();
+    }
+
+    /// Keeps what was drawn when the canvas is resized, for apps painting incrementally with
+    /// [`ClearMode::Preserve`]. Offscreen targets copy their previous frame into the resized
+    /// framebuffer, growing leaves the new area transparent and shrinking crops it.
+    /// Window surfaces and multisampled targets cannot be copied into and start empty
+    pub fn set_preserve_on_resize(&mut self, enable: bool) {
+        self.surface_config.preserve_on_resize = enable;
+    }
+
+    pub fn preserves_on_resize(&self) -> bool {
+        self.surface_config.preserve_on_resize
     }
 
     pub fn render<Surface, Output>(&mut self, surface: &mut Surface) -> Result<Output>
@@ -351,16 +1354,50 @@ impl Canvas {
         surface.paint(self)
     }
 
+    /// Renders the frame like [`Canvas::render`] and then consumes it with [`Canvas::clear`],
+    /// so the next frame starts empty and nothing drawn is submitted twice.
+    /// [`Canvas::pick`] keeps finding the finished frame until the next one draws
+    pub fn finish<Surface, Output>(&mut self, surface: &mut Surface) -> Result<Output>
+    where
+        Surface: CanvasSurface<PaintOutput = Output>,
+    {
+        let output = self.render(surface);
+        let hit_regions = std::mem::take(&mut self.hit_regions);
+        self.clear();
+        self.last_hit_regions = hit_regions;
+        output
+    }
+
+    /// `persistent` targets keep their content between frames, only their damage is redrawn
     pub(crate) fn render_to_texture(
         &mut self,
         view: &GpuTextureView,
         resolve_target: Option<&wgpu::TextureView>,
+        persistent: bool,
     ) {
-        self.prepare_for_render();
-
+        self.prepare();
         let mut encoder = self.renderer.create_command_encoder();
 
-        {
+        // blurs and layers read what is under them, preserved frames draw over anything
+        let key = self.damage_key();
+        let damage = self.damage.damage(&key).filter(|_| {
+            self.frame_steps.is_empty()
+                && self.clear_mode != ClearMode::Preserve
+                && self.debug_view() == DebugView::Off
+        });
+        let partial = damage.clone().filter(|_| persistent);
+
+        if !self.frame_steps.is_empty() {
+            self.encode_frame_steps(&mut encoder, view, resolve_target);
+        } else {
+            self.renderer.set_target(
+                self.surface_config.format,
+                self.surface_config.msaa_sample_count,
+            );
+            let stencil = self
+                .renderer
+                .stencil_view(self.surface_config.msaa_sample_count);
+
             let mut pass = encoder.begin_render_pass(
                 &(wgpu::RenderPassDescriptor {
                     label: Some("RenderTarget Pass"),
@@ -368,32 +1405,127 @@ impl Canvas {
                         view,
                         resolve_target,
                         ops: wgpu::Operations {
-                            load: wgpu::LoadOp::Clear(self.clear_color.into()),
+                            load: match partial {
+                                Some(_) => wgpu::LoadOp::Load,
+                                None => self.clear_load_op(),
+                            },
                             store: wgpu::StoreOp::Store,
                         },
                     })],
-                    depth_stencil_attachment: None,
+                    depth_stencil_attachment: Some(stencil_attachment(&stencil)),
                     occlusion_query_set: None,
                     timestamp_writes: None,
                 }),
             );
 
+            self.renderer.set_damage(partial);
             self.renderer.prepare(&self.cached_renderables);
+            if let ClearMode::Color(color) = self.clear_mode {
+                self.renderer.clear_damage(&mut pass, color);
+            }
             self.renderer.render(&mut pass, &self.cached_renderables);
+            self.renderer.set_damage(None);
         }
+        self.damage.rendered(key, damage);
+
+        self.stats = RenderStats::new(
+            self.list.instructions.len(),
+            self.batches.len(),
+            self.cached_renderables.iter().map(|renderable| {
+                (
+                    renderable.mesh.vertices.len(),
+                    renderable.mesh.indices.len(),
+                )
+            }),
+            self.renderer.stats(),
+        );
 
-        self.renderer
-            .gpu()
-            .queue
-            .submit(std::iter::once(encoder.finish()));
+        let queue = &self.renderer.gpu().queue;
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let submitted_at = Instant::now();
+        let gpu_time = self.gpu_time.clone();
+        queue.on_submitted_work_done(move || {
+            *gpu_time.lock() = Some(submitted_at.elapsed());
+        });
+    }
+
+    fn clear_load_op(&self) -> wgpu::LoadOp<wgpu::Color> {
+        if self.debug_view() == DebugView::Overdraw {
+            return wgpu::LoadOp::Clear(wgpu::Color::BLACK);
+        }
+        match self.clear_mode {
+            ClearMode::Color(color) => wgpu::LoadOp::Clear(color.into()),
+            ClearMode::Preserve => wgpu::LoadOp::Load,
+        }
+    }
+
+    /// Time from submitting the last finished frame until the gpu completed it, `None` until
+    /// a frame completes. Includes queueing so it is an upper bound of the gpu work
+    pub fn last_gpu_time(&self) -> Option<Duration> {
+        *self.gpu_time.lock()
+    }
+
+    /// Redraws only the part of the target that changed since the last frame, found by
+    /// comparing the tessellated instructions. It needs a target keeping its content between
+    /// frames, like offscreen targets and window surfaces with msaa, others are redrawn fully.
+    /// Every frame is assumed to be rendered to the same target, call [`Canvas::damage_all`]
+    /// after changing the content of a drawn texture
+    pub fn set_damage_tracking(&mut self, enabled: bool) {
+        self.damage.enabled = enabled;
+        self.damage.invalidate();
+        self.invalidate_prepared();
+    }
+
+    pub fn damage_tracking(&self) -> bool {
+        self.damage.enabled
+    }
+
+    /// Redraws the whole target on the next frame
+    pub fn damage_all(&mut self) {
+        self.damage.invalidate();
+    }
+
+    /// The part of the target that changed in the last rendered frame in logical pixels,
+    /// `None` when nothing did. All of it unless damage tracking is on, for presenting
+    /// the damage to the window system
+    pub fn last_damage(&self) -> Option<Rect<f32>> {
+        self.damage.last_damage.clone()
+    }
+
+    fn damage_key(&self) -> DamageKey {
+        DamageKey {
+            size: self.renderer.size(),
+            scale_factor: self.scale_factor(),
+            clear_mode: self.clear_mode,
+        }
+    }
+
+    /// Counts of the last rendered frame, they stay until the next one is rendered
+    pub fn render_stats(&self) -> RenderStats {
+        self.stats
+    }
+
+    /// Prepares the frame and lists its draw calls with the instructions merged into each
+    /// and why they were split from the previous one. Drawing instructions that share a
+    /// texture and state next to each other saves draw calls
+    pub fn debug_batches(&mut self) -> &[BatchInfo] {
+        self.prepare();
+        &self.batches
     }
 
     fn get_required_atlas_keys(&self) -> HashSet<AtlasKey> {
+        let background = self
+            .clear_texture
+            .as_ref()
+            .map(|(texture_id, _)| texture_id);
         self.list
             .into_iter()
             .flat_map(|staged| staged.instructions.iter())
-            .filter_map(|instruction| {
-                if let TextureId::AtlasKey(key) = &instruction.texture_id {
+            .map(|instruction| &instruction.texture_id)
+            .chain(background)
+            .filter_map(|texture_id| {
+                if let TextureId::AtlasKey(key) = texture_id {
                     Some(key.clone())
                 } else {
                     None
@@ -402,10 +1534,28 @@ impl Canvas {
             .collect::<_>()
     }
 
-    fn prepare_for_render(&mut self) {
+    /// Tessellates the current frame. [`Canvas::render`] calls this when something was drawn
+    /// since the last call, so the same frame can be rendered to several surfaces
+    /// (e.g. a window and an offscreen recording target) while tessellating it once
+    pub fn prepare(&mut self) {
         // stage the any remaining changes
         self.stage_changes();
 
+        let instruction_count = self.list.instructions.len();
+        if self.prepared_instructions == Some(instruction_count) {
+            return;
+        }
+        self.prepared_instructions = Some(instruction_count);
+        self.recycle_renderables();
+        let mut footprints = std::mem::take(&mut self.damage.footprints);
+        footprints.clear();
+
+        if self.validate_state {
+            if let Some(warning) = self.state_warning() {
+                log::warn!("Canvas rendered with unbalanced state: {}", warning);
+            }
+        }
+
         // prepare atlas texture infos
         let atlas_keys = self.get_required_atlas_keys();
 
@@ -430,21 +1580,101 @@ impl Canvas {
             _ => None, // the batcher will use the instruction.texture
         };
 
-        let mut drawlist = DrawList::default();
+        let mut drawlist = std::mem::take(&mut self.drawlist);
+        // the tolerance is in physical pixels
+        drawlist.tolerance(self.tessellation_tolerance / self.scale_factor());
+
+        self.frame_steps.clear();
+        self.batches.clear();
+
+        if let Some(background) = self.clear_texture_instruction() {
+            let texture = get_renderer_texture(&background.texture_id)
+                .unwrap_or_else(|| background.texture_id.clone());
+            if let Some(renderable) = self.build_renderable(
+                &mut drawlist,
+                std::iter::once(&background),
+                texture,
+                None,
+                &CanvasState::default(),
+                self.damage.enabled.then_some(&mut footprints),
+            ) {
+                self.cached_renderables.push(renderable);
+            }
+        }
+
+        let mut placer = FrameOpPlacer::new(&self.frame_ops, self.logical_size());
+        let mut offset = 0;
+        // why the next batch is not merged with the last one
+        let mut reason = BatchBreak::Start;
+
         // TODO batch ops in stages too
         for staged in &self.list {
-            let batcher =
-                GraphicsInstructionBatcher::new(staged.instructions, get_renderer_texture);
-
-            for batch in batcher {
-                let render_texture = batch.renderer_texture.clone();
-                if let Some(renderable) =
-                    self.build_renderable(&mut drawlist, batch, render_texture, staged.state)
-                {
-                    self.cached_renderables.push(renderable)
+            let len = staged.instructions.len();
+            let mut start = 0;
+            let steps = self.frame_steps.len();
+            placer.place(offset, &mut self.frame_steps, &mut self.cached_renderables);
+            if !self.batches.is_empty() {
+                reason = if self.frame_steps.len() > steps {
+                    BatchBreak::FrameOp
+                } else {
+                    BatchBreak::State
+                };
+            }
+
+            // batches must not cross a blur or layer
+            while start < len {
+                let end = placer
+                    .next_at()
+                    .map(|at| at - offset)
+                    .filter(|at| *at > start && *at < len)
+                    .unwrap_or(len);
+
+                let batcher = GraphicsInstructionBatcher::new(
+                    &staged.instructions[start..end],
+                    get_renderer_texture,
+                );
+
+                for batch in batcher {
+                    let render_texture = batch.renderer_texture.clone();
+                    let sampler = batch.sampler.clone();
+                    let range = batch.range.clone();
+                    if let Some(renderable) = self.build_renderable(
+                        &mut drawlist,
+                        batch,
+                        render_texture,
+                        sampler,
+                        staged.state,
+                        self.damage.enabled.then_some(&mut footprints),
+                    ) {
+                        let first = offset + start;
+                        self.batches.push(BatchInfo {
+                            instructions: first + range.start..first + range.end,
+                            texture: renderable.mesh.texture.clone(),
+                            reason,
+                            vertex_count: renderable.mesh.vertices.len(),
+                            index_count: renderable.mesh.indices.len(),
+                        });
+                        self.cached_renderables.push(renderable);
+                        reason = BatchBreak::Texture;
+                    }
+                }
+
+                start = end;
+                placer.place(
+                    offset + end,
+                    &mut self.frame_steps,
+                    &mut self.cached_renderables,
+                );
+                if !self.batches.is_empty() && start < len {
+                    reason = BatchBreak::FrameOp;
                 }
             }
+            offset += len;
         }
+
+        placer.finish(&mut self.frame_steps, &mut self.cached_renderables);
+        self.drawlist = drawlist;
+        self.damage.footprints = footprints;
     }
 
     fn build_renderable<'a>(
@@ -452,16 +1682,37 @@ impl Canvas {
         drawlist: &mut DrawList,
         instructions: impl Iterator<Item = &'a GraphicsInstruction>,
         render_texture: TextureId,
+        sampler: Option<TextureOptions>,
         canvas_state: &CanvasState,
+        mut footprints: Option<&mut Vec<Footprint>>,
     ) -> Option<Renderable> {
+        drawlist.antialias(canvas_state.antialias);
+        // hairlines and feathered edges cover physical pixels after the transform's average scale
+        let transform_scale = canvas_state.transform.det().abs().sqrt();
+        let pixel = 1.0 / (self.scale_factor() * transform_scale).max(f32::EPSILON);
+        drawlist.hairline_width(pixel);
+        drawlist.feathering(DEFAULT_FEATHERING * pixel);
+
+        let alpha = canvas_state.global_alpha;
+        // layers hold premultiplied colors, their vertex color scales every channel
+        let premultiplied = alpha < 1.0 && self.renderer.is_premultiplied(&render_texture);
+        let snap = canvas_state
+            .pixel_snap
+            .then(|| pixel_snap::PixelSnap::new(&canvas_state.transform, self.scale_factor()))
+            .flatten();
+
         for instruction in instructions {
-            let primitive = &instruction.primitive;
             let brush = &instruction.brush;
+            let snapped = snap
+                .as_ref()
+                .and_then(|snap| snap.primitive(&instruction.primitive, brush));
+            let primitive = snapped.as_ref().unwrap_or(&instruction.primitive);
 
             if instruction.nothing_to_draw() {
-                return None;
+                continue;
             }
 
+            let first_vertex = drawlist.mesh.vertices.len();
             let tex_id = instruction.texture_id.clone();
             let is_white_texture = tex_id == TextureId::WHITE_TEXTURE;
 
@@ -476,11 +1727,20 @@ impl Canvas {
             };
 
             let identity_transform = canvas_state.transform.is_identity();
+            let source = instruction.source.as_ref().filter(|_| !is_white_texture);
 
-            if identity_transform && info.is_none() {
+            if identity_transform && info.is_none() && source.is_none() && alpha == 1.0 {
                 build(drawlist)
             } else {
                 drawlist.capture(build).map(|vertex| {
+                    if let Some(source) = source {
+                        let [u, v] = vertex.uv;
+                        vertex.uv = [
+                            source.origin.x + u * source.size.width,
+                            source.origin.y + v * source.size.height,
+                        ];
+                    }
+
                     if let Some(info) = info {
                         if is_white_texture {
                             vertex.uv = self.white_texture_uv.into();
@@ -489,6 +1749,15 @@ impl Canvas {
                         }
                     }
 
+                    if alpha < 1.0 {
+                        vertex.color.a *= alpha;
+                        if premultiplied {
+                            vertex.color.r *= alpha;
+                            vertex.color.g *= alpha;
+                            vertex.color.b *= alpha;
+                        }
+                    }
+
                     if !identity_transform {
                         let pos =
                             canvas_state.transform * vec2(vertex.position[0], vertex.position[1]);
@@ -496,18 +1765,1276 @@ impl Canvas {
                     }
                 });
             }
+
+            if let Some(footprints) = footprints.as_deref_mut() {
+                footprints.extend(Footprint::new(
+                    &drawlist.mesh.vertices[first_vertex..],
+                    &render_texture,
+                    sampler.as_ref(),
+                    &canvas_state.clip_rect,
+                    &canvas_state.clip_quads,
+                ));
+            }
         }
 
-        let mut mesh = drawlist.build();
-        if mesh.is_empty() {
+        // empty batches keep the mesh for the next one
+        if drawlist.mesh.is_empty() {
             return None;
         }
+        let mut mesh = drawlist.build();
 
         mesh.texture = render_texture.clone();
+        mesh.sampler = sampler;
 
         Some(Renderable {
             clip_rect: canvas_state.clip_rect.clone(),
+            clip_quads: canvas_state.clip_quads.clone(),
             mesh,
         })
     }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) fn pixel(snapshot: &crate::CanvasSnapshot, x: u32, y: u32) -> [u8; 4] {
+        let i = ((y * snapshot.size.width + x) * 4) as usize;
+        snapshot.data[i..i + 4].try_into().unwrap()
+    }
+
+    /// A canvas whose targets can be snapshotted, `None` when there is no gpu adapter
+    pub(crate) fn test_canvas(width: u32, height: u32) -> Option<Canvas> {
+        build_test_canvas(Canvas::create().width(width).height(height))
+    }
+
+    /// Builds `builder` like [`test_canvas`], for tests needing other options
+    pub(crate) fn build_test_canvas(builder: CanvasBuilder) -> Option<Canvas> {
+        let Ok(gpu) = futures::executor::block_on(crate::GpuContext::new()) else {
+            let test = std::thread::current()
+                .name()
+                .unwrap_or_default()
+                .to_string();
+            eprintln!("skipping {test}: no gpu adapter");
+            return None;
+        };
+        Some(builder.snapshots(true).build(gpu))
+    }
+
+    #[test]
+    fn clear_mode_preserve_keeps_previous_frame() {
+        let Some(mut canvas) = test_canvas(64, 64) else {
+            return;
+        };
+        let mut target = canvas.create_offscreen_target();
+
+        canvas.clear_color(Color::BLACK);
+        canvas.draw_rect(&Rect::xywh(0.0, 0.0, 32.0, 64.0), Brush::filled(Color::RED));
+        canvas.render(&mut target).unwrap();
+
+        canvas.clear();
+        canvas.set_clear_mode(ClearMode::Preserve);
+        canvas.draw_rect(
+            &Rect::xywh(32.0, 0.0, 32.0, 64.0),
+            Brush::filled(Color::BLUE),
+        );
+        canvas.render(&mut target).unwrap();
+
+        let snapshot = canvas.snapshot_sync(&target).unwrap();
+        assert_eq!(pixel(&snapshot, 16, 32), [255, 0, 0, 255]);
+        assert_eq!(pixel(&snapshot, 48, 32), [0, 0, 255, 255]);
+
+        canvas.clear();
+        canvas.clear_color(Color::BLACK);
+        canvas.render(&mut target).unwrap();
+
+        let snapshot = canvas.snapshot_sync(&target).unwrap();
+        assert_eq!(pixel(&snapshot, 16, 32), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn preserve_on_resize_keeps_the_previous_frame() {
+        let Some(mut canvas) = build_test_canvas(
+            Canvas::create()
+                .width(16)
+                .height(16)
+                .preserve_on_resize(true),
+        ) else {
+            return;
+        };
+        let mut target = canvas.create_offscreen_target();
+
+        canvas.clear_color(Color::BLACK);
+        canvas.draw_rect(&Rect::xywh(0.0, 0.0, 8.0, 16.0), Brush::filled(Color::RED));
+        canvas.render(&mut target).unwrap();
+
+        canvas.clear();
+        canvas.resize(32, 24);
+        canvas.set_clear_mode(ClearMode::Preserve);
+        canvas.draw_rect(&Rect::xywh(24.0, 0.0, 8.0, 8.0), Brush::filled(Color::BLUE));
+        canvas.render(&mut target).unwrap();
+
+        let snapshot = canvas.snapshot_sync(&target).unwrap();
+        assert_eq!((snapshot.size.width, snapshot.size.height), (32, 24));
+        assert_eq!(pixel(&snapshot, 4, 8), [255, 0, 0, 255]);
+        assert_eq!(pixel(&snapshot, 12, 8), [0, 0, 0, 255]);
+        assert_eq!(pixel(&snapshot, 28, 4), [0, 0, 255, 255]);
+        // grown area was never drawn
+        assert_eq!(pixel(&snapshot, 4, 20)[3], 0);
+    }
+
+    #[test]
+    fn draw_blurred_blurs_only_earlier_drawing() {
+        let Some(mut canvas) = test_canvas(64, 64) else {
+            return;
+        };
+        let mut target = canvas.create_offscreen_target();
+
+        canvas.clear_color(Color::BLACK);
+        canvas.draw_rect(&Rect::xywh(0.0, 0.0, 32.0, 64.0), Brush::filled(Color::RED));
+        canvas.draw_rect(
+            &Rect::xywh(32.0, 0.0, 32.0, 64.0),
+            Brush::filled(Color::BLUE),
+        );
+        canvas.draw_blurred(&Rect::xywh(16.0, 0.0, 32.0, 32.0), 4.0);
+        canvas.draw_rect(
+            &Rect::xywh(30.0, 8.0, 4.0, 4.0),
+            Brush::filled(Color::WHITE),
+        );
+        canvas.render(&mut target).unwrap();
+
+        let snapshot = canvas.snapshot_sync(&target).unwrap();
+        let [r, _, b, _] = pixel(&snapshot, 31, 20);
+        assert!(r > 60 && b > 60, "edge is not blurred: {:?}", (r, b));
+        // outside the rect and drawn after the blur
+        assert_eq!(pixel(&snapshot, 31, 48), [255, 0, 0, 255]);
+        assert_eq!(pixel(&snapshot, 31, 9), [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn save_layer_fades_overlapping_draws_as_one() {
+        let Some(mut canvas) = test_canvas(64, 64) else {
+            return;
+        };
+        let mut target = canvas.create_offscreen_target();
+
+        canvas.clear_color(Color::BLACK);
+        canvas.save_layer(0.5, None);
+        canvas.draw_rect(&Rect::xywh(0.0, 0.0, 32.0, 32.0), Brush::filled(Color::RED));
+        canvas.draw_rect(
+            &Rect::xywh(16.0, 0.0, 32.0, 32.0),
+            Brush::filled(Color::RED),
+        );
+
+        canvas.save_layer(0.5, Some(&Rect::xywh(0.0, 32.0, 32.0, 32.0)));
+        canvas.draw_rect(
+            &Rect::xywh(0.0, 32.0, 64.0, 32.0),
+            Brush::filled(Color::RED),
+        );
+        canvas.restore_layer();
+        assert_eq!(canvas.layer_depth(), 1);
+        canvas.restore_layer();
+
+        canvas.draw_rect(
+            &Rect::xywh(48.0, 0.0, 16.0, 16.0),
+            Brush::filled(Color::BLUE),
+        );
+        canvas.render(&mut target).unwrap();
+
+        let snapshot = canvas.snapshot_sync(&target).unwrap();
+        let red = |x, y| pixel(&snapshot, x, y)[0] as i32;
+        assert!((red(8, 16) - 128).abs() <= 2, "{}", red(8, 16));
+        assert_eq!(red(8, 16), red(24, 16));
+        // nested layers multiply, the inner bounds clip
+        assert!((red(8, 48) - 64).abs() <= 2, "{}", red(8, 48));
+        assert_eq!(pixel(&snapshot, 48, 48), [0, 0, 0, 255]);
+        assert_eq!(pixel(&snapshot, 56, 8), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn render_prepared_frame_to_several_targets() {
+        let Some(mut canvas) = test_canvas(64, 64) else {
+            return;
+        };
+        let mut window = canvas.create_offscreen_target();
+        let mut recording = canvas.create_offscreen_target();
+
+        canvas.draw_circle(32.0, 32.0, 20.0, Brush::filled(Color::RED));
+        canvas.prepare();
+        let renderables = canvas.cached_renderables.len();
+
+        canvas.render(&mut window).unwrap();
+        canvas.render(&mut recording).unwrap();
+        assert_eq!(canvas.cached_renderables.len(), renderables);
+
+        let a = canvas.snapshot_sync(&window).unwrap();
+        let b = canvas.snapshot_sync(&recording).unwrap();
+        assert_eq!(a.data, b.data);
+        assert_eq!(pixel(&a, 32, 32), [255, 0, 0, 255]);
+
+        // drawing more invalidates the prepared frame
+        canvas.draw_rect(&Rect::xywh(0.0, 0.0, 8.0, 8.0), Brush::filled(Color::BLUE));
+        canvas.render(&mut window).unwrap();
+        let a = canvas.snapshot_sync(&window).unwrap();
+        assert_eq!(pixel(&a, 4, 4), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn finish_consumes_the_frame() {
+        let Some(mut canvas) = test_canvas(32, 32) else {
+            return;
+        };
+        let mut target = canvas.create_offscreen_target();
+
+        canvas.draw_rect(&Rect::xywh(0.0, 0.0, 8.0, 8.0), Brush::filled(Color::RED));
+        canvas.finish(&mut target).unwrap();
+        assert!(canvas.cached_renderables.is_empty());
+
+        // only the new frame is drawn, without clearing by hand
+        canvas.draw_rect(
+            &Rect::xywh(16.0, 16.0, 8.0, 8.0),
+            Brush::filled(Color::BLUE),
+        );
+        canvas.finish(&mut target).unwrap();
+        let snapshot = canvas.snapshot_sync(&target).unwrap();
+        assert_eq!(pixel(&snapshot, 4, 4), [255, 255, 255, 255]);
+        assert_eq!(pixel(&snapshot, 20, 20), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn scale_factor_maps_logical_to_physical() {
+        let Some(mut canvas) =
+            build_test_canvas(Canvas::create().width(64).height(64).scale_factor(2.0))
+        else {
+            return;
+        };
+        let mut target = canvas.create_offscreen_target();
+
+        assert_eq!(canvas.logical_size(), Size::new(32.0, 32.0));
+        assert_eq!(canvas.physical_size(), Size::new(64, 64));
+
+        canvas.clear_color(Color::BLACK);
+        canvas.draw_rect(&Rect::xywh(0.0, 0.0, 16.0, 16.0), Brush::filled(Color::RED));
+        canvas.render(&mut target).unwrap();
+
+        let snapshot = canvas.snapshot_sync(&target).unwrap();
+        assert_eq!(pixel(&snapshot, 30, 30), [255, 0, 0, 255]);
+        assert_eq!(pixel(&snapshot, 34, 34), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn settings_changes_prepare_the_frame_again() {
+        let Some(mut canvas) = test_canvas(64, 64) else {
+            return;
+        };
+        let mut target = canvas.create_offscreen_target();
+
+        canvas.clear_color(Color::BLACK);
+        canvas.set_antialias(false);
+        let mut builder = Path::builder();
+        builder.begin(vec2(16.1, 0.0));
+        builder.line_to(vec2(16.1, 32.0));
+        builder.end(false);
+        let line = builder.build();
+        canvas.stroke_path(
+            &line,
+            Brush::default().stroke_color(Color::RED).hairline(true),
+        );
+        canvas.render(&mut target).unwrap();
+
+        // the hairline stays one physical pixel wide
+        canvas.set_scale_factor(2.0);
+        canvas.render(&mut target).unwrap();
+        let snapshot = canvas.snapshot_sync(&target).unwrap();
+        assert_eq!(pixel(&snapshot, 32, 16), [255, 0, 0, 255]);
+        assert_eq!(pixel(&snapshot, 31, 16), [0, 0, 0, 255]);
+        assert_eq!(pixel(&snapshot, 33, 16), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn warm_up_compiles_pipelines_ahead() {
+        let Some(canvas) = test_canvas(16, 16) else {
+            return;
+        };
+        let renderer = &canvas.renderer;
+        let bgra = crate::gpu::TextureFormat::Bgra8Unorm;
+        let srgb = crate::gpu::TextureFormat::Rgba8UnormSrgb;
+
+        assert!(renderer.has_pipelines(crate::gpu::TextureFormat::Rgba8Unorm, 1));
+        assert!(!renderer.has_pipelines(bgra, 1));
+
+        renderer.warm_up(&[bgra], &[1]);
+        assert!(renderer.has_pipelines(bgra, 1));
+
+        renderer.warm_up_async(&[srgb], &[1]).join().unwrap();
+        assert!(renderer.has_pipelines(srgb, 1));
+    }
+
+    #[test]
+    fn rotated_clips_mask_with_the_stencil() {
+        let Some(mut canvas) =
+            build_test_canvas(Canvas::create().width(64).height(64).msaa_samples(4))
+        else {
+            return;
+        };
+        let mut target = canvas.create_offscreen_target();
+
+        // a diamond around the center, its bounding box is the scissor rect
+        canvas.translate(32.0, 32.0);
+        canvas.rotate(std::f32::consts::FRAC_PI_4);
+        canvas.clip(&Rect::xywh(-10.0, -10.0, 20.0, 20.0));
+        assert_eq!(canvas.current_state.clip_quads.len(), 1);
+
+        canvas.with_hit_id(1, |canvas| {
+            canvas.draw_rect(
+                &Rect::xywh(-64.0, -64.0, 128.0, 128.0),
+                Brush::filled(Color::BLACK),
+            )
+        });
+        assert_eq!(canvas.pick(vec2(40.0, 32.0)), Some(1));
+        assert_eq!(canvas.pick(vec2(44.0, 44.0)), None);
+
+        canvas.render(&mut target).unwrap();
+        let snapshot = canvas.snapshot_sync(&target).unwrap();
+        assert_eq!(pixel(&snapshot, 32, 32), [0, 0, 0, 255]);
+        assert_eq!(pixel(&snapshot, 42, 32), [0, 0, 0, 255]);
+        assert_eq!(pixel(&snapshot, 43, 43), [255, 255, 255, 255]);
+        assert_eq!(pixel(&snapshot, 20, 20), [255, 255, 255, 255]);
+        assert_eq!(pixel(&snapshot, 2, 32), [255, 255, 255, 255]);
+
+        // axis aligned clips follow the transform without a mask
+        canvas.reset();
+        canvas.translate(10.0, 0.0);
+        canvas.scale(2.0, 2.0);
+        canvas.clip(&Rect::xywh(0.0, 0.0, 5.0, 5.0));
+        assert_eq!(canvas.get_clip_rect(), Rect::xywh(10.0, 0.0, 10.0, 10.0));
+        assert!(canvas.current_state.clip_quads.is_empty());
+    }
+
+    #[test]
+    fn with_clip_rect_restores_state() {
+        let Some(mut canvas) = test_canvas(16, 16) else {
+            return;
+        };
+        let rect = Rect::xywh(2.0, 2.0, 8.0, 8.0);
+
+        let clip = canvas.with_clip_rect(&rect, |canvas| {
+            canvas.save();
+            canvas.translate(4.0, 4.0);
+            canvas.get_clip_rect()
+        });
+
+        assert_eq!(clip, rect);
+        assert_eq!(canvas.get_clip_rect(), Rect::EVERYTHING);
+        assert_eq!(canvas.current_state.transform, Mat3::identity());
+        assert!(canvas.state_stack.is_empty());
+    }
+
+    #[test]
+    fn state_warning_reports_leftover_state() {
+        let Some(mut canvas) = test_canvas(16, 16) else {
+            return;
+        };
+        assert_eq!(canvas.state_warning(), None);
+
+        canvas.save();
+        canvas.translate(4.0, 0.0);
+        assert_eq!(canvas.state_depth(), 1);
+        assert_eq!(
+            canvas.current_state().transform,
+            Mat3::from_translation(4.0, 0.0)
+        );
+        let warning = canvas.state_warning().unwrap();
+        assert!(warning.contains("1 save()") && warning.contains("transform"));
+
+        canvas.restore();
+        assert_eq!(canvas.state_depth(), 0);
+        assert_eq!(canvas.state_warning(), None);
+
+        // a transform applied without a save is left over too
+        canvas.scale(2.0, 2.0);
+        assert!(!canvas.state_warning().unwrap().contains("save()"));
+    }
+
+    #[test]
+    fn set_transform_replaces_and_skew_shears() {
+        let Some(mut canvas) = test_canvas(32, 32) else {
+            return;
+        };
+        let mut target = canvas.create_offscreen_target();
+
+        canvas.translate(100.0, 0.0);
+        let imported = Mat3::from_affine(1.0, 0.0, 0.0, 1.0, 0.0, 8.0);
+        canvas.set_transform(&imported);
+        assert_eq!(canvas.get_transform(), imported);
+
+        canvas.save();
+        canvas.skew(1.0, 0.0);
+        assert_eq!(canvas.get_transform() * vec2(0.0, 4.0), vec2(4.0, 12.0));
+        canvas.clear_color(Color::BLACK);
+        canvas.draw_rect(&Rect::xywh(0.0, 0.0, 8.0, 8.0), Brush::filled(Color::RED));
+        canvas.restore();
+        assert_eq!(canvas.get_transform(), imported);
+
+        canvas.render(&mut target).unwrap();
+        let snapshot = canvas.snapshot_sync(&target).unwrap();
+        // the bottom row of the rect is pushed right by its height
+        assert_eq!(pixel(&snapshot, 12, 14), [255, 0, 0, 255]);
+        assert_eq!(pixel(&snapshot, 12, 9), [0, 0, 0, 255]);
+        assert_eq!(pixel(&snapshot, 1, 9), [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn global_alpha_fades_draws_until_restore() {
+        let Some(mut canvas) = test_canvas(32, 32) else {
+            return;
+        };
+        let mut target = canvas.create_offscreen_target();
+
+        canvas.clear_color(Color::BLACK);
+        canvas.save();
+        canvas.set_global_alpha(0.5);
+        canvas.draw_rect(&Rect::xywh(0.0, 0.0, 8.0, 8.0), Brush::filled(Color::RED));
+        // premultiplied layer textures fade every channel
+        canvas.cache_layer("faded", &Rect::xywh(16.0, 0.0, 8.0, 8.0), |canvas| {
+            canvas.draw_rect(
+                &Rect::xywh(16.0, 0.0, 8.0, 8.0),
+                Brush::filled(Color::WHITE),
+            );
+        });
+        canvas.restore();
+        assert_eq!(canvas.global_alpha(), 1.0);
+        canvas.draw_rect(&Rect::xywh(8.0, 0.0, 8.0, 8.0), Brush::filled(Color::RED));
+
+        canvas.render(&mut target).unwrap();
+        let snapshot = canvas.snapshot_sync(&target).unwrap();
+        let faded = pixel(&snapshot, 4, 4);
+        assert!(
+            (126..=129).contains(&faded[0]) && faded[1] == 0,
+            "{:?}",
+            faded
+        );
+        assert_eq!(pixel(&snapshot, 12, 4), [255, 0, 0, 255]);
+        let layer = pixel(&snapshot, 20, 4);
+        assert!(
+            layer[..3].iter().all(|c| (126..=129).contains(c)),
+            "{:?}",
+            layer
+        );
+    }
+
+    #[test]
+    fn pick_returns_topmost_hit_id() {
+        let Some(mut canvas) = test_canvas(100, 100) else {
+            return;
+        };
+        let brush = Brush::filled(Color::RED);
+
+        canvas.with_hit_id(1, |canvas| {
+            canvas.draw_rect(&Rect::xywh(0.0, 0.0, 50.0, 50.0), brush.clone());
+        });
+        canvas.draw_rect(&Rect::xywh(0.0, 0.0, 100.0, 100.0), brush.clone());
+        canvas.with_hit_id(2, |canvas| {
+            canvas.save();
+            canvas.translate(40.0, 40.0);
+            canvas.draw_circle(0.0, 0.0, 10.0, brush.clone());
+            canvas.restore();
+        });
+
+        assert_eq!(canvas.pick(vec2(10.0, 10.0)), Some(1));
+        assert_eq!(canvas.pick(vec2(45.0, 45.0)), Some(2));
+        assert_eq!(canvas.pick(vec2(80.0, 80.0)), None);
+        assert_eq!(canvas.hit_test(vec2(45.0, 45.0)), [2, 1]);
+        assert_eq!(canvas.hit_test(vec2(80.0, 80.0)), []);
+
+        canvas.clear();
+        assert_eq!(canvas.pick(vec2(10.0, 10.0)), None);
+    }
+
+    #[test]
+    fn pick_finds_the_finished_frame() {
+        let Some(mut canvas) = test_canvas(32, 32) else {
+            return;
+        };
+        let mut target = canvas.create_offscreen_target();
+        let brush = Brush::filled(Color::RED);
+
+        canvas.with_hit_id(1, |canvas| {
+            canvas.draw_rect(&Rect::xywh(0.0, 0.0, 8.0, 8.0), brush.clone());
+        });
+        canvas.finish(&mut target).unwrap();
+        assert_eq!(canvas.pick(vec2(4.0, 4.0)), Some(1));
+        assert_eq!(canvas.hit_test(vec2(4.0, 4.0)), [1]);
+
+        // replaced once the next frame draws
+        canvas.with_hit_id(2, |canvas| {
+            canvas.draw_rect(&Rect::xywh(16.0, 16.0, 8.0, 8.0), brush.clone());
+        });
+        assert_eq!(canvas.pick(vec2(4.0, 4.0)), None);
+        assert_eq!(canvas.pick(vec2(20.0, 20.0)), Some(2));
+    }
+
+    #[test]
+    fn fill_texts_matches_fill_text() {
+        let Some(mut canvas) = test_canvas(200, 200) else {
+            return;
+        };
+        let labels = ["10", "20", "30"].map(|label| Text::new(label).pos(10.0, 10.0));
+
+        for label in &labels {
+            canvas.fill_text(label, Color::BLACK);
+        }
+        let one_by_one = canvas.list.instructions.clone();
+
+        canvas.clear();
+        canvas.fill_texts(&labels, Color::BLACK);
+
+        assert_eq!(canvas.list.instructions.len(), one_by_one.len());
+        for (a, b) in canvas.list.instructions.iter().zip(&one_by_one) {
+            assert_eq!(a.texture_id, b.texture_id);
+        }
+    }
+
+    #[test]
+    fn text_ignores_the_antialias_default() {
+        let Some(mut canvas) = test_canvas(128, 64) else {
+            return;
+        };
+        canvas.set_antialias(true);
+        canvas.fill_text(&Text::new("Aa").size_px(32.0), Color::BLACK);
+
+        // glyph images are anti-aliased when rasterized, their quads are never feathered
+        assert!(!canvas.list.instructions.is_empty());
+        for instruction in &canvas.list.instructions {
+            assert_eq!(instruction.brush.get_antialias(), crate::Antialias::Off);
+        }
+    }
+
+    #[test]
+    fn prewarm_uploads_glyphs() {
+        let Some(canvas) = test_canvas(64, 64) else {
+            return;
+        };
+        let font = Text::default().get_font().clone();
+
+        let count = canvas.prewarm_glyphs(&font, &[12.0, 24.0], "ab");
+        if count == 0 {
+            eprintln!("skipping prewarm_uploads_glyphs: no fonts installed");
+            return;
+        }
+        // one image for each character, size and horizontal subpixel bin
+        assert_eq!(count, 16);
+
+        let mut glyphs = Vec::new();
+        canvas.text_system.write(|provider| {
+            // a different string at a fractional position lands the glyphs in other bins
+            let text = Text::new("b aab")
+                .font(font.clone())
+                .size_px(24.0)
+                .pos(10.3, 0.0);
+            for glyph in provider.layout(&text, Size::new(200.0, 100.0)) {
+                // the space has nothing to upload
+                let Some(image) = provider
+                    .rasterize(&glyph.key)
+                    .filter(|image| !image.size.is_zero())
+                else {
+                    continue;
+                };
+                glyphs.push(AtlasKey::from(
+                    canvas
+                        .text_system
+                        .glyph_image(glyph.key, image.kind.is_color()),
+                ));
+            }
+        });
+
+        assert_eq!(glyphs.len(), 4);
+        for key in glyphs {
+            assert!(canvas.texture_atlas.get_texture_info(&key).is_some());
+        }
+    }
+
+    #[test]
+    fn with_viewport_draws_in_data_space() {
+        let Some(mut canvas) = test_canvas(300, 200) else {
+            return;
+        };
+        let viewport = Viewport::new(
+            Rect::xywh(0.0, 0.0, 10.0, 100.0),
+            Rect::xywh(50.0, 50.0, 200.0, 100.0),
+        );
+
+        canvas.with_viewport(&viewport, |canvas| {
+            canvas.with_hit_id(1, |canvas| {
+                // the lower left quarter of the data
+                canvas.draw_rect(&Rect::xywh(0.0, 0.0, 5.0, 50.0), Brush::filled(Color::RED));
+            });
+        });
+
+        assert_eq!(canvas.pick(vec2(100.0, 120.0)), Some(1));
+        assert_eq!(canvas.pick(vec2(100.0, 80.0)), None);
+        assert_eq!(canvas.pick(vec2(200.0, 120.0)), None);
+        assert_eq!(canvas.current_state.transform, Mat3::identity());
+    }
+
+    #[test]
+    fn texture_info_reports_atlas_tile() {
+        let Some(canvas) = test_canvas(64, 64) else {
+            return;
+        };
+        let key = AtlasKey::from(crate::paint::AtlasImage::new(7));
+        assert!(canvas.texture_info(&key.clone().into()).is_none());
+
+        let tile = canvas.atlas().create_texture(&key, Size::new(40, 20));
+        let info = canvas.texture_info(&key.into()).unwrap();
+        assert_eq!(info.size, Size::new(40, 20));
+        assert_eq!(info.kind, crate::TextureKind::Color);
+        assert_eq!(info.atlas_page, Some(tile.texture));
+        assert_eq!(info.aspect_ratio(), 2.0);
+
+        let page = canvas.texture_info(&tile.texture.into()).unwrap();
+        assert_eq!(page.atlas_page, Some(tile.texture));
+        let page_width = page.size.width as f32;
+        assert!((info.uv_rect.width() - 40.0 / page_width).abs() < 1e-6);
+        assert!(canvas.texture_info(&TextureId::User(1)).is_none());
+    }
+
+    #[test]
+    fn debug_views_show_the_triangles() {
+        let Some(mut canvas) = test_canvas(64, 64) else {
+            return;
+        };
+        let mut target = canvas.create_offscreen_target();
+        let draw = |canvas: &mut Canvas| {
+            canvas.draw_rect(&Rect::xywh(8.0, 8.0, 32.0, 32.0), Brush::filled(Color::RED));
+            canvas.draw_rect(
+                &Rect::xywh(24.0, 24.0, 32.0, 32.0),
+                Brush::filled(Color::RED),
+            );
+        };
+
+        canvas.set_debug_view(DebugView::Wireframe);
+        draw(&mut canvas);
+        canvas.render(&mut target).unwrap();
+        let snapshot = canvas.snapshot_sync(&target).unwrap();
+        // the fill stays with the triangle edges across it
+        let outlined = (10..22)
+            .flat_map(|y| (10..22).map(move |x| (x, y)))
+            .filter(|&(x, y)| pixel(&snapshot, x, y) != [255, 0, 0, 255])
+            .count();
+        assert!(outlined > 0 && outlined < 40, "{outlined}");
+        assert_eq!(canvas.render_stats().draw_calls, 2);
+        canvas.clear();
+
+        canvas.set_debug_view(DebugView::Overdraw);
+        draw(&mut canvas);
+        canvas.finish(&mut target).unwrap();
+        let snapshot = canvas.snapshot_sync(&target).unwrap();
+        let once = pixel(&snapshot, 12, 12);
+        let twice = pixel(&snapshot, 30, 30);
+        assert_eq!(pixel(&snapshot, 60, 4), [0, 0, 0, 255]);
+        assert!(once[0] > 0 && twice[0] > once[0], "{once:?} {twice:?}");
+
+        canvas.set_debug_view(DebugView::Off);
+        draw(&mut canvas);
+        canvas.finish(&mut target).unwrap();
+        let snapshot = canvas.snapshot_sync(&target).unwrap();
+        assert_eq!(pixel(&snapshot, 30, 30), [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn damage_tracking_redraws_what_changed() {
+        let Some(mut canvas) = test_canvas(64, 64) else {
+            return;
+        };
+        let mut target = canvas.create_offscreen_target();
+        canvas.set_damage_tracking(true);
+        let draw = |canvas: &mut Canvas, x: f32| {
+            canvas.draw_rect(&Rect::xywh(0.0, 0.0, 16.0, 16.0), Brush::filled(Color::RED));
+            canvas.draw_rect(&Rect::xywh(x, 32.0, 16.0, 16.0), Brush::filled(Color::BLUE));
+        };
+
+        draw(&mut canvas, 32.0);
+        canvas.finish(&mut target).unwrap();
+        assert_eq!(canvas.last_damage(), Some(Rect::xywh(0.0, 0.0, 64.0, 64.0)));
+
+        draw(&mut canvas, 40.0);
+        canvas.finish(&mut target).unwrap();
+        let damage = canvas.last_damage().unwrap();
+        assert!(
+            damage.contains(&Rect::xywh(32.0, 32.0, 24.0, 16.0)),
+            "{damage:?}"
+        );
+        assert!(!damage.intersects(&Rect::xywh(0.0, 0.0, 16.0, 16.0)));
+
+        let snapshot = canvas.snapshot_sync(&target).unwrap();
+        assert_eq!(pixel(&snapshot, 8, 8), [255, 0, 0, 255]);
+        assert_eq!(pixel(&snapshot, 34, 40), [255, 255, 255, 255]);
+        assert_eq!(pixel(&snapshot, 50, 40), [0, 0, 255, 255]);
+
+        // nothing changed, nothing drawn
+        draw(&mut canvas, 40.0);
+        canvas.finish(&mut target).unwrap();
+        assert_eq!(canvas.last_damage(), None);
+        assert_eq!(canvas.render_stats().draw_calls, 0);
+        let snapshot = canvas.snapshot_sync(&target).unwrap();
+        assert_eq!(pixel(&snapshot, 8, 8), [255, 0, 0, 255]);
+        assert_eq!(pixel(&snapshot, 50, 40), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn render_stats_count_the_last_frame() {
+        let Some(mut canvas) = test_canvas(64, 64) else {
+            return;
+        };
+        let mut target = canvas.create_offscreen_target();
+        let rect = Rect::xywh(0.0, 0.0, 8.0, 8.0);
+        assert_eq!(canvas.render_stats(), RenderStats::default());
+
+        canvas.draw_rect(&rect, Brush::filled(Color::RED));
+        canvas.draw_rect(&rect, Brush::filled(Color::BLUE));
+        canvas.translate(8.0, 0.0);
+        canvas.draw_rect(&rect, Brush::filled(Color::RED));
+        let (vertices, indices) = canvas.debug_batches().iter().fold((0, 0), |(v, i), batch| {
+            (v + batch.vertex_count, i + batch.index_count)
+        });
+        canvas.finish(&mut target).unwrap();
+
+        let stats = canvas.render_stats();
+        assert_eq!(stats.instructions, 3);
+        assert_eq!(stats.batches, 2);
+        assert_eq!((stats.draw_calls, stats.texture_binds), (2, 2));
+        assert_eq!((stats.vertices, stats.indices), (vertices, indices));
+        assert!(
+            stats.vertex_buffer_size
+                >= (vertices * std::mem::size_of::<crate::paint::Vertex>()) as u64
+        );
+        assert!(stats.index_buffer_size >= (indices * std::mem::size_of::<u32>()) as u64);
+
+        // kept after finish clears the list
+        assert_eq!(canvas.render_stats(), stats);
+    }
+
+    #[test]
+    fn debug_batches_explain_each_draw_call() {
+        let Some(mut canvas) = test_canvas(64, 64) else {
+            return;
+        };
+        let rect = Rect::xywh(0.0, 0.0, 8.0, 8.0);
+        let image = TextureId::User(7);
+
+        canvas.draw_rect(&rect, Brush::filled(Color::RED));
+        canvas.draw_rect(&rect, Brush::filled(Color::BLUE));
+        canvas.draw_image(&rect, &image);
+        canvas.translate(8.0, 0.0);
+        canvas.draw_rect(&rect, Brush::filled(Color::RED));
+        canvas.draw_blurred(&rect, 2.0);
+        canvas.draw_rect(&rect, Brush::filled(Color::RED));
+
+        let batches = canvas.debug_batches();
+        assert_eq!(
+            batches
+                .iter()
+                .map(|batch| (batch.instructions.clone(), batch.reason))
+                .collect::<Vec<_>>(),
+            [
+                (0..2, BatchBreak::Start),
+                (2..3, BatchBreak::Texture),
+                (3..4, BatchBreak::State),
+                (4..5, BatchBreak::FrameOp),
+            ]
+        );
+        // the white texture is reported as the atlas texture it is drawn from
+        assert_eq!(batches[1].texture, image);
+        assert_ne!(batches[0].texture, image);
+        assert!(batches.iter().all(|batch| batch.index_count > 0));
+    }
+
+    #[test]
+    fn draw_image_region_maps_the_source_rect() {
+        let Some(mut canvas) = test_canvas(64, 64) else {
+            return;
+        };
+        let mut target = canvas.create_offscreen_target();
+
+        // a sprite sheet with a red and a blue texel
+        let key = AtlasKey::from(crate::paint::AtlasImage::new(3));
+        let atlas = canvas.atlas().clone();
+        atlas.create_texture_init(&key, Size::new(2, 1), &[255, 0, 0, 255, 0, 0, 255, 255]);
+        canvas.renderer.set_texture_from_atlas(
+            &atlas,
+            &key,
+            &TextureOptions::default()
+                .min_filter(FilterMode::Nearest)
+                .mag_filter(FilterMode::Nearest),
+        );
+
+        let texture = TextureId::from(key);
+        canvas.clear_color(Color::BLACK);
+        canvas.draw_image_region(
+            &Rect::xywh(0.0, 0.0, 32.0, 64.0),
+            &texture,
+            &Rect::xywh(1.0, 0.0, 1.0, 1.0),
+        );
+        canvas.draw_image_uv(
+            &Rect::xywh(32.0, 0.0, 32.0, 64.0),
+            &texture,
+            &Rect::xywh(0.0, 0.0, 0.5, 1.0),
+        );
+        canvas.render(&mut target).unwrap();
+
+        let snapshot = canvas.snapshot_sync(&target).unwrap();
+        assert_eq!(pixel(&snapshot, 16, 32), [0, 0, 255, 255]);
+        assert_eq!(pixel(&snapshot, 48, 32), [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn default_brushes_are_part_of_the_state() {
+        let Some(mut canvas) = test_canvas(64, 64) else {
+            return;
+        };
+        let rect = Rect::xywh(0.0, 0.0, 8.0, 8.0);
+
+        canvas.set_fill_brush(Brush::filled(Color::RED).stroke_color(Color::BLUE));
+        canvas.save();
+        canvas.set_fill_brush(Brush::filled(Color::BLUE));
+        canvas.fill_rect(&rect);
+        canvas.restore();
+        canvas.fill_rect(&rect);
+        canvas.stroke_rect(&rect);
+
+        let brushes: Vec<_> = canvas
+            .list
+            .instructions
+            .iter()
+            .map(|instruction| &instruction.brush)
+            .collect();
+        assert_eq!(brushes[0].fill_style.color, Color::BLUE);
+        assert_eq!(brushes[1].fill_style.color, Color::RED);
+        assert!(brushes[1].stroke_style.color.is_transparent());
+        assert!(brushes[2].fill_style.color.is_transparent());
+        assert_eq!(brushes[2].stroke_style.color, Color::BLACK);
+
+        // changing them does not split batches
+        assert_eq!(canvas.debug_batches().len(), 1);
+    }
+
+    #[test]
+    fn image_fit_rects() {
+        let area = Rect::xywh(0.0, 0.0, 100.0, 50.0);
+        let size = Size::new(20.0, 20.0);
+        assert_eq!(ImageFit::Fill.rect(size, &area), area);
+        assert_eq!(
+            ImageFit::Contain.rect(size, &area),
+            Rect::xywh(25.0, 0.0, 50.0, 50.0)
+        );
+        assert_eq!(
+            ImageFit::Cover.rect(size, &area),
+            Rect::xywh(0.0, -25.0, 100.0, 100.0)
+        );
+        assert_eq!(ImageFit::Cover.rect(Size::new(0.0, 0.0), &area), area);
+    }
+
+    #[test]
+    fn clear_with_texture_draws_under_every_frame() {
+        let Some(mut canvas) = test_canvas(64, 64) else {
+            return;
+        };
+        let mut target = canvas.create_offscreen_target();
+
+        let key = AtlasKey::from(crate::paint::AtlasImage::new(6));
+        let atlas = canvas.atlas().clone();
+        atlas.create_texture_init(&key, Size::new(2, 1), &[255, 0, 0, 255, 255, 0, 0, 255]);
+        canvas
+            .renderer
+            .set_texture_from_atlas(&atlas, &key, &TextureOptions::default());
+
+        // a 2:1 image fits the middle of the square canvas
+        canvas.clear_color(Color::BLACK);
+        canvas.clear_with_texture(&TextureId::from(key), ImageFit::Contain);
+        canvas.draw_rect(&Rect::xywh(0.0, 28.0, 8.0, 8.0), Brush::filled(Color::BLUE));
+        canvas.finish(&mut target).unwrap();
+
+        let snapshot = canvas.snapshot_sync(&target).unwrap();
+        assert_eq!(pixel(&snapshot, 32, 8), [0, 0, 0, 255]);
+        assert_eq!(pixel(&snapshot, 32, 32), [255, 0, 0, 255]);
+        assert_eq!(pixel(&snapshot, 4, 32), [0, 0, 255, 255]);
+
+        // kept for the next frames
+        canvas.finish(&mut target).unwrap();
+        let snapshot = canvas.snapshot_sync(&target).unwrap();
+        assert_eq!(pixel(&snapshot, 4, 32), [255, 0, 0, 255]);
+
+        canvas.remove_clear_texture();
+        canvas.finish(&mut target).unwrap();
+        let snapshot = canvas.snapshot_sync(&target).unwrap();
+        assert_eq!(pixel(&snapshot, 32, 32), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn draw_image_tinted_multiplies_the_texture() {
+        let Some(mut canvas) = test_canvas(64, 64) else {
+            return;
+        };
+        let mut target = canvas.create_offscreen_target();
+
+        // an opaque white texel and a transparent one
+        let key = AtlasKey::from(crate::paint::AtlasImage::new(5));
+        let atlas = canvas.atlas().clone();
+        atlas.create_texture_init(&key, Size::new(2, 1), &[255, 255, 255, 255, 0, 0, 0, 0]);
+        canvas
+            .renderer
+            .set_texture_from_atlas(&atlas, &key, &TextureOptions::default());
+
+        let texture = TextureId::from(key);
+        let nearest = TextureOptions::default().filter(FilterMode::Nearest);
+        canvas.clear_color(Color::BLACK);
+        canvas.draw_image_with_sampler(&Rect::xywh(0.0, 0.0, 64.0, 32.0), &texture, &nearest);
+        canvas.draw_image_tinted(&Rect::xywh(0.0, 32.0, 64.0, 32.0), &texture, Color::RED);
+        canvas.render(&mut target).unwrap();
+
+        let snapshot = canvas.snapshot_sync(&target).unwrap();
+        assert_eq!(pixel(&snapshot, 8, 16), [255, 255, 255, 255]);
+        assert_eq!(pixel(&snapshot, 8, 48), [255, 0, 0, 255]);
+        assert_eq!(pixel(&snapshot, 56, 48), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn draw_image_with_sampler_overrides_the_filter() {
+        let Some(mut canvas) = test_canvas(64, 64) else {
+            return;
+        };
+        let mut target = canvas.create_offscreen_target();
+
+        let key = AtlasKey::from(crate::paint::AtlasImage::new(4));
+        let atlas = canvas.atlas().clone();
+        atlas.create_texture_init(&key, Size::new(2, 1), &[255, 0, 0, 255, 0, 0, 255, 255]);
+        canvas
+            .renderer
+            .set_texture_from_atlas(&atlas, &key, &TextureOptions::default());
+
+        let texture = TextureId::from(key);
+        canvas.draw_image_with_sampler(
+            &Rect::xywh(0.0, 0.0, 64.0, 32.0),
+            &texture,
+            &TextureOptions::default().filter(FilterMode::Linear),
+        );
+        canvas.draw_image_with_sampler(
+            &Rect::xywh(0.0, 32.0, 64.0, 32.0),
+            &texture,
+            &TextureOptions::default().filter(FilterMode::Nearest),
+        );
+        canvas.render(&mut target).unwrap();
+
+        let snapshot = canvas.snapshot_sync(&target).unwrap();
+        let [r, _, b, _] = pixel(&snapshot, 28, 16);
+        assert!(r > 0 && b > 0, "linear sampling blends the texels");
+        assert_eq!(pixel(&snapshot, 28, 48), [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn fill_and_stroke_path_use_one_part_of_the_brush() {
+        let Some(mut canvas) = test_canvas(64, 64) else {
+            return;
+        };
+        let mut target = canvas.create_offscreen_target();
+
+        let square = |x: f32| {
+            let mut builder = Path::builder();
+            builder.rect(&Rect::xywh(x, 16.0, 24.0, 24.0));
+            builder.build()
+        };
+        let brush = Brush::filled(Color::RED)
+            .stroke_color(Color::BLUE)
+            .line_width(4);
+        canvas.fill_path(&square(4.0), brush.clone());
+        canvas.stroke_path(&square(36.0), brush);
+        canvas.render(&mut target).unwrap();
+
+        let snapshot = canvas.snapshot_sync(&target).unwrap();
+        let color = |x, y| Color::from(pixel(&snapshot, x, y));
+        assert_eq!(color(16, 28), Color::RED);
+        assert_eq!(color(4, 28), Color::RED);
+        assert_eq!(color(48, 28), Color::WHITE);
+        assert_eq!(color(36, 28), Color::BLUE);
+    }
+
+    #[test]
+    fn bordered_rect_colors_each_side() {
+        use crate::BorderSide;
+
+        let Some(mut canvas) = test_canvas(64, 64) else {
+            return;
+        };
+        let mut target = canvas.create_offscreen_target();
+
+        canvas.draw_bordered_rect(
+            &Rect::xywh(8.0, 8.0, 48.0, 48.0),
+            &Corners::default(),
+            Borders::with_each(
+                BorderSide::new(8.0, Color::RED),
+                BorderSide::new(4.0, Color::GREEN),
+                BorderSide::default(),
+                BorderSide::new(8.0, Color::BLUE),
+            ),
+            Brush::filled(Color::BLACK),
+        );
+        canvas.render(&mut target).unwrap();
+
+        let snapshot = canvas.snapshot_sync(&target).unwrap();
+        let color = |x, y| Color::from(pixel(&snapshot, x, y));
+        assert_eq!(color(32, 10), Color::RED);
+        assert_eq!(color(54, 32), Color::GREEN);
+        assert_eq!(color(10, 32), Color::BLUE);
+        assert_eq!(color(32, 54), Color::BLACK);
+    }
+
+    #[test]
+    fn pixel_snapping_keeps_edges_crisp() {
+        let Some(mut canvas) = test_canvas(64, 64) else {
+            return;
+        };
+        let mut target = canvas.create_offscreen_target();
+
+        // feathered edges between pixels blend into both of them
+        canvas.set_antialias(true);
+        let rect = Rect::xywh(10.3, 10.3, 20.0, 20.0);
+        let fill = Brush::filled(Color::BLACK).stroke_color(Color::TRANSPARENT);
+        canvas.draw_rect(&rect, fill.clone());
+
+        canvas.set_pixel_snapping(true);
+        assert!(canvas.pixel_snapping());
+        canvas.draw_rect(&Rect::xywh(40.3, 10.3, 20.0, 20.0), fill);
+        canvas.draw_rect(
+            &Rect::xywh(10.3, 40.3, 40.0, 10.0),
+            Brush::filled(Color::TRANSPARENT)
+                .stroke_color(Color::BLACK)
+                .line_width(1),
+        );
+        canvas.render(&mut target).unwrap();
+
+        let snapshot = canvas.snapshot_sync(&target).unwrap();
+        assert_ne!(pixel(&snapshot, 10, 20), [0, 0, 0, 255]);
+        assert_eq!(pixel(&snapshot, 39, 20), [255, 255, 255, 255]);
+        assert_eq!(pixel(&snapshot, 40, 20), [0, 0, 0, 255]);
+        assert_eq!(pixel(&snapshot, 10, 45), [0, 0, 0, 255]);
+        assert_eq!(pixel(&snapshot, 11, 45), [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn builder_presets_validate_at_build() {
+        let Ok(gpu) = futures::executor::block_on(crate::GpuContext::new()) else {
+            eprintln!("skipping builder_presets_validate_at_build: no gpu adapter");
+            return;
+        };
+
+        let canvas = Canvas::create().pixel_art().build(gpu.clone());
+        assert!(!canvas.antialias());
+        assert_eq!(canvas.image_filter(), crate::gpu::FilterMode::Nearest);
+
+        let canvas = Canvas::create()
+            .export()
+            .width(64)
+            .height(64)
+            .build(gpu.clone());
+        let target = canvas.create_offscreen_target();
+        assert!(canvas.antialias());
+        assert!(canvas.snapshot_sync(&target).is_ok());
+
+        let err = Canvas::create()
+            .export()
+            .surface_usage(crate::gpu::TextureUsages::RENDER_ATTACHMENT)
+            .try_build(gpu.clone())
+            .err();
+        assert_eq!(err, Some(CanvasBuildError::SnapshotsWithoutCopySrc));
+
+        let err = Canvas::create().msaa_samples(3).try_build(gpu).err();
+        assert!(matches!(
+            err,
+            Some(CanvasBuildError::UnsupportedSampleCount { count: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn record_and_replay_picture() {
+        let Some(mut canvas) = test_canvas(64, 64) else {
+            return;
+        };
+        let mut target = canvas.create_offscreen_target();
+
+        canvas.translate(5.0, 5.0);
+        let picture = canvas.record(|canvas| {
+            canvas.clip(&Rect::xywh(0.0, 0.0, 16.0, 8.0));
+            canvas.draw_rect(&Rect::xywh(0.0, 0.0, 16.0, 16.0), Brush::filled(Color::RED));
+        });
+        assert_eq!(picture.instruction_count(), 1);
+        assert!(canvas.list.is_empty());
+        assert_eq!(
+            canvas.current_state().transform,
+            Mat3::from_translation(5.0, 5.0)
+        );
+        canvas.reset();
+
+        canvas.clear_color(Color::BLACK);
+        canvas.draw_picture(&picture, &Mat3::identity());
+        canvas.draw_picture(&picture, &Mat3::from_translation(32.0, 32.0));
+        assert_eq!(canvas.state_depth(), 0);
+        canvas.render(&mut target).unwrap();
+
+        let snapshot = canvas.snapshot_sync(&target).unwrap();
+        assert_eq!(pixel(&snapshot, 8, 4), [255, 0, 0, 255]);
+        assert_eq!(pixel(&snapshot, 8, 12), [0, 0, 0, 255]);
+        assert_eq!(pixel(&snapshot, 40, 36), [255, 0, 0, 255]);
+        assert_eq!(pixel(&snapshot, 40, 44), [0, 0, 0, 255]);
+        assert_eq!(pixel(&snapshot, 24, 4), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn read_pixel_reads_logical_points() {
+        // rows narrower than the copy alignment
+        let Some(mut canvas) =
+            build_test_canvas(Canvas::create().width(20).height(20).scale_factor(2.0))
+        else {
+            return;
+        };
+        let mut target = canvas.create_offscreen_target();
+
+        canvas.clear_color(Color::BLACK);
+        canvas.draw_rect(&Rect::xywh(0.0, 0.0, 5.0, 10.0), Brush::filled(Color::RED));
+        canvas.render(&mut target).unwrap();
+
+        assert_eq!(
+            canvas.read_pixel_sync(&target, 2.0, 8.0).unwrap(),
+            Color::RED
+        );
+        assert_eq!(
+            futures::executor::block_on(canvas.read_pixel(&target, 7.0, 8.0)).unwrap(),
+            Color::BLACK
+        );
+        assert!(canvas.read_pixel_sync(&target, 10.0, 2.0).is_err());
+
+        let region = canvas
+            .snapshot_region_sync(&target, &Rect::xywh(8, 4, 4, 2))
+            .unwrap();
+        assert_eq!(region.data.len(), 4 * 2 * 4);
+        assert_eq!(pixel(&region, 1, 1), [255, 0, 0, 255]);
+        assert_eq!(pixel(&region, 2, 1), [0, 0, 0, 255]);
+
+        let snapshot = canvas.snapshot_sync(&target).unwrap();
+        assert_eq!(snapshot.data.len(), 20 * 20 * 4);
+        assert_eq!(pixel(&snapshot, 19, 19), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn cached_layers_draw_once_until_invalidated() {
+        let Some(mut canvas) = test_canvas(64, 64) else {
+            return;
+        };
+        let mut target = canvas.create_offscreen_target();
+        let bounds = Rect::xywh(0.0, 0.0, 16.0, 16.0);
+
+        let mut draws = 0;
+        let mut frame = |canvas: &mut Canvas, color: Color, dx: f32| {
+            canvas.clear();
+            canvas.clear_color(Color::BLACK);
+            canvas.draw_rect(
+                &Rect::xywh(48.0, 48.0, 8.0, 8.0),
+                Brush::filled(Color::WHITE),
+            );
+            canvas.save();
+            canvas.translate(dx, 0.0);
+            canvas.cache_layer("background", &bounds, |canvas| {
+                draws += 1;
+                canvas.draw_rect(&Rect::xywh(4.0, 4.0, 8.0, 8.0), Brush::filled(color));
+            });
+            canvas.restore();
+            canvas.render(&mut target).unwrap();
+            canvas.snapshot_sync(&target).unwrap()
+        };
+
+        let first = frame(&mut canvas, Color::RED, 0.0);
+        assert_eq!(pixel(&first, 8, 8), [255, 0, 0, 255]);
+        assert_eq!(pixel(&first, 2, 2), [0, 0, 0, 255]);
+        // drawn before the layer was rendered offscreen
+        assert_eq!(pixel(&first, 52, 52), [255, 255, 255, 255]);
+
+        // the closure is skipped and the texture follows the transform
+        let moved = frame(&mut canvas, Color::GREEN, 32.0);
+        assert_eq!(pixel(&moved, 40, 8), [255, 0, 0, 255]);
+        assert_eq!(pixel(&moved, 8, 8), [0, 0, 0, 255]);
+
+        assert!(canvas.invalidate_layer("background"));
+        assert_eq!(canvas.cached_layer_count(), 0);
+        let redrawn = frame(&mut canvas, Color::BLUE, 0.0);
+        assert_eq!(pixel(&redrawn, 8, 8), [0, 0, 255, 255]);
+        assert_eq!(canvas.cached_layer_count(), 1);
+        assert_eq!(draws, 2);
+    }
+
+    #[test]
+    fn cached_save_layers_fade_as_one() {
+        let Some(mut canvas) = test_canvas(64, 64) else {
+            return;
+        };
+        let mut target = canvas.create_offscreen_target();
+
+        let mut draws = 0;
+        let mut frame = |canvas: &mut Canvas| {
+            canvas.clear_color(Color::BLACK);
+            canvas.save_layer_cached("faded", 0.5, &Rect::xywh(0.0, 0.0, 48.0, 32.0), |c| {
+                draws += 1;
+                c.draw_rect(&Rect::xywh(0.0, 0.0, 32.0, 32.0), Brush::filled(Color::RED));
+                c.draw_rect(
+                    &Rect::xywh(16.0, 0.0, 32.0, 32.0),
+                    Brush::filled(Color::RED),
+                );
+            });
+            canvas.finish(&mut target).unwrap();
+            canvas.snapshot_sync(&target).unwrap()
+        };
+
+        for snapshot in [frame(&mut canvas), frame(&mut canvas)] {
+            let red = |x, y| pixel(&snapshot, x, y)[0] as i32;
+            assert!((red(8, 16) - 128).abs() <= 2, "{}", red(8, 16));
+            assert_eq!(red(8, 16), red(24, 16));
+            assert_eq!(pixel(&snapshot, 8, 48), [0, 0, 0, 255]);
+        }
+        assert_eq!(draws, 1);
+        assert_eq!(canvas.global_alpha(), 1.0);
+    }
+
+    #[test]
+    fn clipped_text_stays_inside_its_box() {
+        let Some(mut canvas) = test_canvas(128, 128) else {
+            return;
+        };
+        let mut target = canvas.create_offscreen_target();
+
+        let paragraph = Text::new("wrapped words keep on going past the box")
+            .size_px(16.0)
+            .wrap_in(&Rect::xywh(8.0, 8.0, 60.0, 40.0))
+            .overflow(TextOverflow::Clip);
+        if canvas.measure_text(&paragraph).line_count == 0 {
+            eprintln!("skipping clipped_text_stays_inside_its_box: no fonts installed");
+            return;
+        }
+        assert_eq!(canvas.measure_text(&paragraph).line_count, 2);
+        assert!(canvas.measure_text(&paragraph).width <= 60.0);
+
+        canvas.clear_color(Color::BLACK);
+        canvas.fill_text(&paragraph, Color::WHITE);
+        canvas.render(&mut target).unwrap();
+
+        let snapshot = canvas.snapshot_sync(&target).unwrap();
+        let lit = |x: u32, y: u32| pixel(&snapshot, x, y)[1] > 0;
+        let inside = (0..128)
+            .flat_map(|y| (0..128).map(move |x| (x, y)))
+            .filter(|&(x, y)| lit(x, y))
+            .collect::<Vec<_>>();
+
+        assert!(!inside.is_empty());
+        assert!(inside
+            .iter()
+            .all(|&(x, y)| (8..68).contains(&x) && (8..48).contains(&y)));
+    }
+}
@@ -19,6 +19,8 @@ pub struct BackendRenderTarget<'a> {
     config: wgpu::SurfaceConfiguration,
     msaa_sample_count: u32,
     msaa_view: Option<wgpu::TextureView>,
+    /// not supported by swapchains, only reported back
+    preserve_on_resize: bool,
 }
 
 impl<'a> Deref for BackendRenderTarget<'a> {
@@ -57,6 +59,7 @@ impl<'a> BackendRenderTarget<'a> {
             config: surface_config,
             msaa_sample_count: config.msaa_sample_count,
             msaa_view: create_mssa_view(gpu, config),
+            preserve_on_resize: config.preserve_on_resize,
         })
     }
 }
@@ -86,7 +89,7 @@ impl<'a> CanvasSurface for BackendRenderTarget<'a> {
             .flatten()
             .map_or((&view, None), |texture_view| (texture_view, Some(&view)));
 
-        canvas.render_to_texture(view, resolve_target);
+        canvas.render_to_texture(view, resolve_target, resolve_target.is_some());
 
         Ok(PaintedSurface(surface_texture))
     }
@@ -97,6 +100,9 @@ impl<'a> CanvasSurface for BackendRenderTarget<'a> {
         self.config.usage = config.usage | wgpu::TextureUsages::RENDER_ATTACHMENT;
         self.config.format = config.format;
 
+        self.msaa_sample_count = config.msaa_sample_count;
+        self.preserve_on_resize = config.preserve_on_resize;
+
         self.msaa_view = create_mssa_view(gpu, config);
         self.surface.configure(&gpu.device, &self.config);
     }
@@ -108,6 +114,7 @@ impl<'a> CanvasSurface for BackendRenderTarget<'a> {
             format: self.config.format,
             usage: self.config.usage,
             msaa_sample_count: self.msaa_sample_count,
+            preserve_on_resize: self.preserve_on_resize,
         }
     }
 }
@@ -0,0 +1,27 @@
+use std::ops::Range;
+
+use crate::TextureId;
+
+/// Why a batch could not be merged with the one before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchBreak {
+    /// the first batch of the frame
+    Start,
+    /// the instructions sample another texture
+    Texture,
+    /// the transform, clip or antialiasing changed
+    State,
+    /// a blur or a layer starts or ends here
+    FrameOp,
+}
+
+/// A draw call built by [`super::Canvas::prepare`], see [`super::Canvas::debug_batches`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchInfo {
+    /// indices of the merged instructions in drawing order
+    pub instructions: Range<usize>,
+    pub texture: TextureId,
+    pub reason: BatchBreak,
+    pub vertex_count: usize,
+    pub index_count: usize,
+}
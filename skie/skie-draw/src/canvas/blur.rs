@@ -0,0 +1,47 @@
+use skie_math::Rect;
+
+use super::{frame_ops::FrameOp, Canvas};
+
+/// A backdrop blur recorded by [`Canvas::draw_blurred`]
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct BlurOp {
+    /// clipped bounds in canvas space, logical pixels
+    pub rect: Rect<f32>,
+    /// standard deviation in logical pixels
+    pub sigma: f32,
+}
+
+impl Canvas {
+    /// Blurs what was drawn under `rect` so far with a gaussian of standard deviation `sigma`,
+    /// for frosted glass panels and soft shadows. Anything drawn afterwards stays sharp.
+    ///
+    /// `rect` follows the current transform and clip, rotated rects blur their bounds.
+    /// Frames with blurs are drawn into an offscreen texture first, without msaa.
+    /// Inside a [`Canvas::save_layer`] it blurs what is under the layer
+    pub fn draw_blurred(&mut self, rect: &Rect<f32>, sigma: f32) {
+        if sigma <= 0.0 {
+            return;
+        }
+
+        let transform = self.current_state.transform;
+        let corners = [
+            rect.top_left(),
+            rect.top_right(),
+            rect.bottom_left(),
+            rect.bottom_right(),
+        ]
+        .map(|corner| transform * corner);
+
+        let min = corners.iter().fold(corners[0], |min, p| min.min(p));
+        let max = corners.iter().fold(corners[0], |max, p| max.max(p));
+        let bounds = Rect::from_corners(min, max).intersect(&self.current_state.clip_rect);
+        if bounds.width() <= 0.0 || bounds.height() <= 0.0 {
+            return;
+        }
+
+        self.push_frame_op(FrameOp::Blur(BlurOp {
+            rect: bounds,
+            sigma: sigma * transform.det().abs().sqrt(),
+        }));
+    }
+}
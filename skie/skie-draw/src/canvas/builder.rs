@@ -1,19 +1,61 @@
 use std::sync::Arc;
 
-use wgpu::{TextureFormat, TextureUsages};
+use wgpu::{FilterMode, TextureFormat, TextureUsages};
 
 use crate::{renderer::create_skie_renderer, GpuContext, Renderer2DSpecs, SkieAtlas, TextSystem};
 
 use super::{surface::CanvasSurfaceConfig, Canvas};
 
+/// A [`CanvasBuilder`] configuration the gpu can't render with
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum CanvasBuildError {
+    #[error("{count}x msaa is not supported for {format:?} surfaces")]
+    UnsupportedSampleCount { count: u32, format: TextureFormat },
+    #[error("{0:?} surfaces can't be rendered to")]
+    FormatNotRenderable(TextureFormat),
+    #[error("snapshots need TextureUsages::COPY_SRC on the surface")]
+    SnapshotsWithoutCopySrc,
+    #[error("{width}x{height} is larger than the max texture size {max}")]
+    SizeTooLarge { width: u32, height: u32, max: u32 },
+}
+
 #[derive(Default)]
 pub struct CanvasBuilder {
     pub(super) texture_atlas: Option<Arc<SkieAtlas>>,
     pub(super) text_system: Option<Arc<TextSystem>>,
     pub(super) surface_config: CanvasSurfaceConfig,
+    pub(super) scale_factor: Option<f32>,
+    pub(super) antialias: Option<bool>,
+    pub(super) image_filter: Option<FilterMode>,
+    pub(super) snapshots: bool,
 }
 
 impl CanvasBuilder {
+    /// Interactive windows: 4x msaa without feathering and smoothly filtered images
+    pub fn ui(self) -> Self {
+        self.msaa_samples(4)
+            .surface_format(TextureFormat::Rgba8Unorm)
+            .antialias(false)
+            .image_filter(FilterMode::Linear)
+    }
+
+    /// Offscreen rendering read back with snapshots, with feathered edges on top of 4x msaa
+    pub fn export(self) -> Self {
+        self.msaa_samples(4)
+            .surface_format(TextureFormat::Rgba8Unorm)
+            .antialias(true)
+            .image_filter(FilterMode::Linear)
+            .snapshots(true)
+    }
+
+    /// Hard edges and nearest filtered images, without msaa or feathering
+    pub fn pixel_art(self) -> Self {
+        self.msaa_samples(1)
+            .surface_format(TextureFormat::Rgba8Unorm)
+            .antialias(false)
+            .image_filter(FilterMode::Nearest)
+    }
+
     pub fn msaa_samples(mut self, sample_count: u32) -> Self {
         self.surface_config.msaa_sample_count = sample_count;
         self
@@ -29,17 +71,100 @@ impl CanvasBuilder {
         self
     }
 
+    /// Physical pixels per logical pixel, `width` and `height` stay in physical pixels
+    pub fn scale_factor(mut self, scale_factor: f32) -> Self {
+        self.scale_factor = Some(scale_factor);
+        self
+    }
+
     pub fn add_surface_usage(mut self, usage: TextureUsages) -> Self {
         self.surface_config.usage |= usage;
         self
     }
 
+    /// Replaces the surface usages, `RENDER_ATTACHMENT` is always kept
+    pub fn surface_usage(mut self, usage: TextureUsages) -> Self {
+        self.surface_config.usage = usage | TextureUsages::RENDER_ATTACHMENT;
+        self
+    }
+
     pub fn surface_format(mut self, format: TextureFormat) -> Self {
         self.surface_config.format = format;
         self
     }
 
+    /// Initial [`Canvas::set_antialias`]
+    pub fn antialias(mut self, enable: bool) -> Self {
+        self.antialias = Some(enable);
+        self
+    }
+
+    /// Filter for images uploaded for the canvas, see [`Canvas::image_filter`]
+    pub fn image_filter(mut self, filter: FilterMode) -> Self {
+        self.image_filter = Some(filter);
+        self
+    }
+
+    /// Whether the canvas will be read back with snapshots, adds `COPY_SRC` to the surface usage
+    pub fn snapshots(mut self, enable: bool) -> Self {
+        self.snapshots = enable;
+        if enable {
+            self.surface_config.usage |= TextureUsages::COPY_SRC;
+        }
+        self
+    }
+
+    /// See [`Canvas::set_preserve_on_resize`]
+    pub fn preserve_on_resize(mut self, enable: bool) -> Self {
+        self.surface_config.preserve_on_resize = enable;
+        self
+    }
+
+    /// Checks the configuration against what the gpu supports
+    pub fn validate(&self, gpu: &GpuContext) -> Result<(), CanvasBuildError> {
+        let config = &self.surface_config;
+        let features = gpu.adapter.get_texture_format_features(config.format);
+
+        if !features
+            .allowed_usages
+            .contains(TextureUsages::RENDER_ATTACHMENT)
+        {
+            return Err(CanvasBuildError::FormatNotRenderable(config.format));
+        }
+
+        let count = config.msaa_sample_count;
+        if count != 1 && !features.flags.sample_count_supported(count) {
+            return Err(CanvasBuildError::UnsupportedSampleCount {
+                count,
+                format: config.format,
+            });
+        }
+
+        if self.snapshots && !config.usage.contains(TextureUsages::COPY_SRC) {
+            return Err(CanvasBuildError::SnapshotsWithoutCopySrc);
+        }
+
+        let max = gpu.device.limits().max_texture_dimension_2d;
+        if config.width > max || config.height > max {
+            return Err(CanvasBuildError::SizeTooLarge {
+                width: config.width,
+                height: config.height,
+                max,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Builds the canvas, panics for configurations that fail [`CanvasBuilder::validate`]
     pub fn build(self, gpu: GpuContext) -> Canvas {
+        self.try_build(gpu)
+            .unwrap_or_else(|err| panic!("Invalid canvas configuration: {err}"))
+    }
+
+    pub fn try_build(self, gpu: GpuContext) -> Result<Canvas, CanvasBuildError> {
+        self.validate(&gpu)?;
+
         log::info!(
             "Creating canvas with surface_config: {:#?}",
             self.surface_config
@@ -57,11 +182,22 @@ impl CanvasBuilder {
             &Renderer2DSpecs {
                 width: self.surface_config.width,
                 height: self.surface_config.height,
+                format: self.surface_config.format,
                 msaa_sample_count: self.surface_config.msaa_sample_count,
             },
         );
 
-        Canvas::new(self.surface_config, renderer, texture_atlas, text_system)
+        let mut canvas = Canvas::new(self.surface_config, renderer, texture_atlas, text_system);
+        if let Some(scale_factor) = self.scale_factor {
+            canvas.set_scale_factor(scale_factor);
+        }
+        if let Some(antialias) = self.antialias {
+            canvas.set_antialias(antialias);
+        }
+        if let Some(filter) = self.image_filter {
+            canvas.image_filter = filter;
+        }
+        Ok(canvas)
     }
 
     pub fn with_texture_atlas(mut self, atlas: Arc<SkieAtlas>) -> Self {
@@ -0,0 +1,146 @@
+use std::hash::{Hash, Hasher};
+
+use skie_math::{vec2, Rect, Size, Vec2};
+
+use crate::{paint::Vertex, TextureId, TextureOptions};
+
+use super::ClearMode;
+
+/// What an instruction drew, compared between frames to find what changed
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Footprint {
+    hash: u64,
+    bounds: Rect<f32>,
+}
+
+impl Footprint {
+    /// `None` when nothing was drawn, `vertices` are the final ones in canvas space
+    pub fn new(
+        vertices: &[Vertex],
+        texture: &TextureId,
+        sampler: Option<&TextureOptions>,
+        clip_rect: &Rect<f32>,
+        clip_quads: &[[Vec2<f32>; 4]],
+    ) -> Option<Self> {
+        let first = vertices.first()?;
+        let mut min = Vec2::from(first.position);
+        let mut max = min;
+        for vertex in vertices {
+            let position = Vec2::from(vertex.position);
+            min = min.min(&position);
+            max = max.max(&position);
+        }
+
+        let mut hasher = std::hash::DefaultHasher::new();
+        bytemuck::cast_slice::<_, u8>(vertices).hash(&mut hasher);
+        texture.hash(&mut hasher);
+        sampler.hash(&mut hasher);
+        let clip_points = clip_quads.iter().flatten().flat_map(|p| [p.x, p.y]);
+        [
+            clip_rect.origin.x,
+            clip_rect.origin.y,
+            clip_rect.size.width,
+            clip_rect.size.height,
+        ]
+        .into_iter()
+        .chain(clip_points)
+        .for_each(|value| value.to_bits().hash(&mut hasher));
+
+        Some(Self {
+            hash: hasher.finish(),
+            bounds: Rect::from_corners(min, max).intersect(clip_rect),
+        })
+    }
+}
+
+/// Target properties that repaint everything when they change
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct DamageKey {
+    pub size: Size<u32>,
+    pub scale_factor: f32,
+    pub clear_mode: ClearMode,
+}
+
+/// Finds the part of the target that changed since the last rendered frame,
+/// see [`super::Canvas::set_damage_tracking`]
+#[derive(Debug, Default)]
+pub(super) struct DamageTracker {
+    pub enabled: bool,
+    /// footprints of the prepared frame in drawing order
+    pub footprints: Vec<Footprint>,
+    /// of the last rendered frame, `None` repaints everything
+    previous: Option<(Vec<Footprint>, DamageKey)>,
+    /// see [`super::Canvas::last_damage`]
+    pub last_damage: Option<Rect<f32>>,
+}
+
+impl DamageTracker {
+    /// Region to redraw rounded out to physical pixels, `None` to redraw everything.
+    /// The region is empty when nothing changed
+    pub fn damage(&self, key: &DamageKey) -> Option<Rect<f32>> {
+        let (previous, previous_key) = self.previous.as_ref().filter(|_| self.enabled)?;
+        if previous_key != key {
+            return None;
+        }
+
+        // an instruction changing also changes how the ones drawn over it blend,
+        // so they are compared in order
+        let len = previous.len().max(self.footprints.len());
+        let changed = (0..len)
+            .filter_map(|i| match (previous.get(i), self.footprints.get(i)) {
+                (Some(a), Some(b)) if a == b => None,
+                (Some(a), Some(b)) => Some(a.bounds.union(&b.bounds)),
+                (Some(footprint), None) | (None, Some(footprint)) => Some(footprint.bounds.clone()),
+                (None, None) => None,
+            })
+            .reduce(|a, b| a.union(&b));
+
+        let Some(changed) = changed else {
+            return Some(Rect::default());
+        };
+
+        let scale = key.scale_factor;
+        let min = (changed.min() * scale).floor() * (1.0 / scale);
+        let max = (changed.max() * scale).ceil() * (1.0 / scale);
+        let canvas = Rect::from_origin_size(
+            vec2(0.0, 0.0),
+            Size::new(
+                key.size.width as f32 / scale,
+                key.size.height as f32 / scale,
+            ),
+        );
+        Some(Rect::from_corners(min, max).intersect(&canvas))
+    }
+
+    /// Remembers the prepared frame as the content of the target
+    pub fn rendered(&mut self, key: DamageKey, damage: Option<Rect<f32>>) {
+        self.last_damage = match damage {
+            Some(damage) if damage.size.width <= 0.0 || damage.size.height <= 0.0 => None,
+            Some(damage) => Some(damage),
+            None => Some(Rect::from_origin_size(
+                vec2(0.0, 0.0),
+                Size::new(
+                    key.size.width as f32 / key.scale_factor,
+                    key.size.height as f32 / key.scale_factor,
+                ),
+            )),
+        };
+
+        if !self.enabled {
+            self.previous = None;
+            return;
+        }
+        match &mut self.previous {
+            Some((previous, previous_key)) => {
+                previous.clone_from(&self.footprints);
+                *previous_key = key;
+            }
+            None => self.previous = Some((self.footprints.clone(), key)),
+        }
+    }
+
+    /// Repaints everything on the next frame
+    pub fn invalidate(&mut self) {
+        self.previous = None;
+    }
+}
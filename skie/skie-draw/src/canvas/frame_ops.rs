@@ -0,0 +1,270 @@
+use skie_math::{vec2, Rect, Size};
+
+use crate::{
+    paint::{Mesh, Vertex},
+    renderer::{stencil_attachment, Renderable},
+    Color, GpuTextureView, Renderer2D,
+};
+
+use super::{blur::BlurOp, layer::LayerOp, Canvas};
+
+/// Work recorded between draws that needs its own render passes
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum FrameOp {
+    Blur(BlurOp),
+    PushLayer(LayerOp),
+    PopLayer,
+}
+
+/// A [`FrameOp`] placed between the prepared renderables
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum FrameStep {
+    /// index into the frame ops
+    Blur(usize),
+    PushLayer,
+    /// followed by the renderable compositing the layer
+    PopLayer,
+}
+
+/// Places the frame ops between the renderables while [`Canvas::prepare`] builds them
+pub(super) struct FrameOpPlacer<'a> {
+    ops: &'a [(usize, FrameOp)],
+    next: usize,
+    /// pushed layers waiting for their pop
+    open_layers: Vec<usize>,
+    canvas_size: Size<f32>,
+}
+
+impl<'a> FrameOpPlacer<'a> {
+    pub fn new(ops: &'a [(usize, FrameOp)], canvas_size: Size<f32>) -> Self {
+        Self {
+            ops,
+            next: 0,
+            open_layers: Vec::new(),
+            canvas_size,
+        }
+    }
+
+    /// Instruction index of the next op to place
+    pub fn next_at(&self) -> Option<usize> {
+        self.ops.get(self.next).map(|(at, _)| *at)
+    }
+
+    /// Places the ops recorded after at most `at` instructions behind `renderables`
+    pub fn place(
+        &mut self,
+        at: usize,
+        steps: &mut Vec<(usize, FrameStep)>,
+        renderables: &mut Vec<Renderable>,
+    ) {
+        while let Some((instruction, op)) = self.ops.get(self.next) {
+            if *instruction > at {
+                break;
+            }
+
+            match op {
+                FrameOp::Blur(_) => steps.push((renderables.len(), FrameStep::Blur(self.next))),
+                FrameOp::PushLayer(_) => {
+                    self.open_layers.push(self.next);
+                    steps.push((renderables.len(), FrameStep::PushLayer));
+                }
+                FrameOp::PopLayer => self.pop_layer(steps, renderables),
+            }
+            self.next += 1;
+        }
+    }
+
+    /// Places the remaining ops and closes the layers left open
+    pub fn finish(
+        mut self,
+        steps: &mut Vec<(usize, FrameStep)>,
+        renderables: &mut Vec<Renderable>,
+    ) {
+        self.place(usize::MAX, steps, renderables);
+        while !self.open_layers.is_empty() {
+            self.pop_layer(steps, renderables);
+        }
+    }
+
+    /// Ends the innermost open layer, followed by the renderable compositing it
+    fn pop_layer(
+        &mut self,
+        steps: &mut Vec<(usize, FrameStep)>,
+        renderables: &mut Vec<Renderable>,
+    ) {
+        let Some(push) = self.open_layers.pop() else {
+            return;
+        };
+        let (_, FrameOp::PushLayer(layer)) = &self.ops[push] else {
+            return;
+        };
+
+        steps.push((renderables.len(), FrameStep::PopLayer));
+        renderables.push(Renderable {
+            clip_rect: layer.bounds.clone(),
+            clip_quads: Vec::new(),
+            mesh: layer.composite_mesh(self.open_layers.len(), self.canvas_size),
+        });
+    }
+}
+
+impl Canvas {
+    pub(super) fn push_frame_op(&mut self, op: FrameOp) {
+        self.stage_changes();
+        self.frame_ops.push((self.list.instructions.len(), op));
+        self.invalidate_prepared();
+    }
+
+    /// Draws the prepared frame pass by pass, switching targets at each [`FrameStep`].
+    /// Frames with blurs are drawn into the blur scene without msaa, then onto the target
+    pub(super) fn encode_frame_steps(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &GpuTextureView,
+        resolve_target: Option<&wgpu::TextureView>,
+    ) {
+        let format = self.surface_config.format;
+        let has_blur = self
+            .frame_steps
+            .iter()
+            .any(|(_, step)| matches!(step, FrameStep::Blur(_)));
+
+        let frame_renderables = self.cached_renderables.len();
+        let (base, sample_count) = if has_blur {
+            let scene = self.renderer.blur_scene(format);
+            self.cached_renderables
+                .push(scene_blit(self.logical_size()));
+            ((scene, None), 1)
+        } else {
+            (
+                (view.clone(), resolve_target.cloned()),
+                self.surface_config.msaa_sample_count,
+            )
+        };
+
+        self.renderer.prepare(&self.cached_renderables);
+        self.renderer.set_target(format, sample_count);
+
+        // the pass targets of the frame and the open layers, innermost last
+        let mut targets = vec![(base.0, base.1, self.clear_load_op())];
+        let mut start = 0;
+
+        let steps = self
+            .frame_steps
+            .iter()
+            .map(|(end, step)| (*end, Some(*step)))
+            .chain([(frame_renderables, None)]);
+
+        for (end, step) in steps {
+            if let Some((view, resolve_target, load)) = targets.last_mut() {
+                let stencil = self.renderer.stencil_view(sample_count);
+                let mut pass = begin_pass(
+                    encoder,
+                    "Frame step pass",
+                    view,
+                    resolve_target.as_ref(),
+                    &stencil,
+                    *load,
+                );
+                *load = wgpu::LoadOp::Load;
+                self.renderer
+                    .render_range(&mut pass, &self.cached_renderables, start..end);
+            }
+            start = end;
+
+            match step {
+                Some(FrameStep::Blur(index)) => {
+                    if let (_, FrameOp::Blur(blur)) = &self.frame_ops[index] {
+                        self.renderer.blur(encoder, &blur.rect, blur.sigma);
+                    }
+                }
+                Some(FrameStep::PushLayer) => {
+                    let (view, resolve_target) =
+                        self.renderer
+                            .layer_target(targets.len() - 1, format, sample_count);
+                    targets.push((
+                        view,
+                        resolve_target,
+                        wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    ));
+                }
+                Some(FrameStep::PopLayer) => {
+                    targets.pop();
+                }
+                None => {}
+            }
+        }
+
+        if has_blur {
+            self.renderer
+                .set_target(format, self.surface_config.msaa_sample_count);
+            {
+                let stencil = self
+                    .renderer
+                    .stencil_view(self.surface_config.msaa_sample_count);
+                let mut pass = begin_pass(
+                    encoder,
+                    "RenderTarget Pass",
+                    view,
+                    resolve_target,
+                    &stencil,
+                    self.clear_load_op(),
+                );
+                self.renderer.render_range(
+                    &mut pass,
+                    &self.cached_renderables,
+                    frame_renderables..frame_renderables + 1,
+                );
+            }
+            self.cached_renderables.pop();
+        }
+    }
+}
+
+/// A quad drawing the blur scene over the whole canvas
+fn scene_blit(size: Size<f32>) -> Renderable {
+    let mut mesh = Mesh {
+        texture: Renderer2D::BLUR_SCENE_TEXTURE,
+        ..Default::default()
+    };
+    for (x, y) in [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)] {
+        mesh.vertices.push(Vertex::new(
+            vec2(x * size.width, y * size.height),
+            Color::WHITE,
+            (x, y),
+        ));
+    }
+    mesh.indices.extend([0, 1, 2, 0, 2, 3]);
+
+    Renderable {
+        clip_rect: Rect::EVERYTHING,
+        clip_quads: Vec::new(),
+        mesh,
+    }
+}
+
+fn begin_pass<'encoder>(
+    encoder: &'encoder mut wgpu::CommandEncoder,
+    label: &str,
+    view: &wgpu::TextureView,
+    resolve_target: Option<&wgpu::TextureView>,
+    stencil: &wgpu::TextureView,
+    load: wgpu::LoadOp<wgpu::Color>,
+) -> wgpu::RenderPass<'encoder> {
+    encoder.begin_render_pass(
+        &(wgpu::RenderPassDescriptor {
+            label: Some(label),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target,
+                ops: wgpu::Operations {
+                    load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(stencil_attachment(stencil)),
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        }),
+    )
+}
@@ -0,0 +1,107 @@
+use skie_math::{Rect, Size};
+
+use crate::{
+    paint::{Mesh, Vertex},
+    Renderer2D, Rgba,
+};
+
+use super::{frame_ops::FrameOp, Canvas};
+
+/// A group opened by [`Canvas::save_layer`]
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct LayerOp {
+    /// clipped bounds in canvas space, logical pixels
+    pub bounds: Rect<f32>,
+    pub opacity: f32,
+}
+
+impl LayerOp {
+    /// A quad drawing the layer texture nested `depth` deep over its bounds, faded by the opacity
+    pub fn composite_mesh(&self, depth: usize, canvas_size: Size<f32>) -> Mesh {
+        let mut mesh = Mesh {
+            texture: Renderer2D::layer_texture(depth),
+            ..Default::default()
+        };
+
+        // the layer holds premultiplied colors, so the opacity scales every channel
+        let opacity = self.opacity;
+        let color = Rgba {
+            r: opacity,
+            g: opacity,
+            b: opacity,
+            a: opacity,
+        };
+
+        for corner in [
+            self.bounds.top_left(),
+            self.bounds.top_right(),
+            self.bounds.bottom_right(),
+            self.bounds.bottom_left(),
+        ] {
+            let uv = (
+                corner.x / canvas_size.width.max(f32::EPSILON),
+                corner.y / canvas_size.height.max(f32::EPSILON),
+            );
+            mesh.vertices.push(Vertex::new(corner, color, uv));
+        }
+        mesh.indices.extend([0, 1, 2, 0, 2, 3]);
+        mesh
+    }
+}
+
+impl Canvas {
+    /// Saves the state like [`Canvas::save`] and draws everything until the matching
+    /// [`Canvas::restore_layer`] into a separate layer, which is then drawn with `opacity`.
+    /// Overlapping shapes inside the layer fade as one instead of showing through each other.
+    ///
+    /// `bounds` follows the current transform and clips the layer, rotated bounds clip to
+    /// their bounding box. Without bounds the layer covers the current clip
+    pub fn save_layer(&mut self, opacity: f32, bounds: Option<&Rect<f32>>) {
+        self.save();
+
+        let transform = self.current_state.transform;
+        let mut clip = self.current_state.clip_rect.clone();
+        if let Some(rect) = bounds {
+            let corners = [
+                rect.top_left(),
+                rect.top_right(),
+                rect.bottom_left(),
+                rect.bottom_right(),
+            ]
+            .map(|corner| transform * corner);
+
+            let min = corners.iter().fold(corners[0], |min, p| min.min(p));
+            let max = corners.iter().fold(corners[0], |max, p| max.max(p));
+            clip = clip.intersect(&Rect::from_corners(min, max));
+        }
+
+        let size = self.logical_size();
+        let bounds = clip.intersect(&Rect::xywh(0.0, 0.0, size.width, size.height));
+        self.current_state.clip_rect = bounds.clone();
+
+        self.layer_stack.push(self.state_stack.len());
+        self.push_frame_op(FrameOp::PushLayer(LayerOp {
+            bounds,
+            opacity: opacity.clamp(0.0, 1.0),
+        }));
+    }
+
+    /// Composites the layer opened by the last [`Canvas::save_layer`] and restores the state
+    /// saved with it. Does nothing without an open layer, layers left open are closed when
+    /// the frame is rendered
+    pub fn restore_layer(&mut self) {
+        let Some(depth) = self.layer_stack.pop() else {
+            return;
+        };
+
+        self.push_frame_op(FrameOp::PopLayer);
+        while self.state_stack.len() >= depth {
+            self.restore();
+        }
+    }
+
+    /// Number of layers opened with [`Canvas::save_layer`] and not restored yet
+    pub fn layer_depth(&self) -> usize {
+        self.layer_stack.len()
+    }
+}
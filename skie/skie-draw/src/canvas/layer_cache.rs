@@ -0,0 +1,235 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use ahash::AHashMap;
+use skie_math::{Rect, Size};
+
+use crate::{Color, Renderer2D, TextureOptions};
+
+use super::{Canvas, CanvasState, ClearMode};
+
+/// A layer drawn by [`Canvas::cache_layer`]
+#[derive(Debug)]
+struct CachedLayer {
+    slot: usize,
+    /// kept alive while the renderer samples it
+    _texture: wgpu::Texture,
+    /// physical size of the texture
+    size: Size<u32>,
+    scale_factor: f32,
+}
+
+/// Textures of the layers drawn with [`Canvas::cache_layer`], by key
+#[derive(Debug, Default)]
+pub(super) struct LayerCache {
+    layers: AHashMap<u64, CachedLayer>,
+    /// slots of invalidated layers, reused before new ones
+    free_slots: Vec<usize>,
+    next_slot: usize,
+}
+
+impl LayerCache {
+    fn alloc_slot(&mut self) -> usize {
+        self.free_slots.pop().unwrap_or_else(|| {
+            self.next_slot += 1;
+            self.next_slot - 1
+        })
+    }
+}
+
+fn layer_key(key: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl Canvas {
+    /// Draws `f` once into a texture covering `rect` and composites the texture on later
+    /// calls with the same `key` without running `f`, until [`Canvas::invalidate_layer`].
+    /// Trades texture memory for the tessellation of complex static content.
+    ///
+    /// `f` draws in the current coordinate space clipped to `rect`, and the texture follows the
+    /// current transform. It is redrawn when the size of `rect` in physical pixels changes.
+    /// Hit regions drawn by `f` are not recorded
+    pub fn cache_layer(&mut self, key: impl Hash, rect: &Rect<f32>, f: impl FnOnce(&mut Self)) {
+        let key = layer_key(&key);
+        let scale = self.scale_factor();
+        let size = Size::new(
+            (rect.size.width * scale).ceil().max(1.0) as u32,
+            (rect.size.height * scale).ceil().max(1.0) as u32,
+        );
+
+        let max_size = self.renderer.gpu().device.limits().max_texture_dimension_2d;
+        if size.width > max_size || size.height > max_size {
+            log::warn!(
+                "cache_layer: {}x{} is larger than the max texture size {}, drawing uncached",
+                size.width,
+                size.height,
+                max_size
+            );
+            self.invalidate_layer_key(key);
+            return self.with_clip_rect(rect, f);
+        }
+
+        let is_fresh = self
+            .layer_cache
+            .layers
+            .get(&key)
+            .is_some_and(|layer| layer.size == size && layer.scale_factor == scale);
+        if !is_fresh {
+            self.invalidate_layer_key(key);
+            let layer = self.draw_cached_layer(rect, size, f);
+            self.layer_cache.layers.insert(key, layer);
+        }
+
+        let slot = self.layer_cache.layers[&key].slot;
+        let logical = Rect::from_origin_size(
+            rect.origin,
+            Size::new(size.width as f32 / scale, size.height as f32 / scale),
+        );
+        self.draw_image(&logical, &Renderer2D::cached_layer_texture(slot));
+    }
+
+    /// A [`Canvas::save_layer`] kept across frames: `f` draws into the layer only when it is
+    /// not cached under `key`, and the texture is composited with `opacity`, fading
+    /// overlapping shapes as one. Caching follows [`Canvas::cache_layer`]
+    pub fn save_layer_cached(
+        &mut self,
+        key: impl Hash,
+        opacity: f32,
+        rect: &Rect<f32>,
+        f: impl FnOnce(&mut Self),
+    ) {
+        self.save();
+        let alpha = self.global_alpha() * opacity.clamp(0.0, 1.0);
+        self.set_global_alpha(alpha);
+        self.cache_layer(key, rect, f);
+        self.restore();
+    }
+
+    /// Drops the texture cached for `key` so the next [`Canvas::cache_layer`] redraws it.
+    /// Returns whether there was one. Layers already drawn this frame are skipped
+    pub fn invalidate_layer(&mut self, key: impl Hash) -> bool {
+        self.invalidate_layer_key(layer_key(&key))
+    }
+
+    /// Drops every cached layer, see [`Canvas::invalidate_layer`]
+    pub fn clear_layer_cache(&mut self) {
+        let keys = self.layer_cache.layers.keys().copied().collect::<Vec<_>>();
+        for key in keys {
+            self.invalidate_layer_key(key);
+        }
+    }
+
+    /// Number of layers holding a texture
+    pub fn cached_layer_count(&self) -> usize {
+        self.layer_cache.layers.len()
+    }
+
+    fn invalidate_layer_key(&mut self, key: u64) -> bool {
+        let Some(layer) = self.layer_cache.layers.remove(&key) else {
+            return false;
+        };
+
+        self.renderer
+            .remove_texture(&Renderer2D::cached_layer_texture(layer.slot));
+        self.layer_cache.free_slots.push(layer.slot);
+        true
+    }
+
+    /// Renders `f` into a new texture of `size` physical pixels showing `rect`.
+    /// The frame drawn so far is set aside meanwhile
+    fn draw_cached_layer(
+        &mut self,
+        rect: &Rect<f32>,
+        size: Size<u32>,
+        f: impl FnOnce(&mut Self),
+    ) -> CachedLayer {
+        let texture = self
+            .renderer
+            .gpu()
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some("skie cached layer"),
+                size: wgpu::Extent3d {
+                    width: size.width,
+                    height: size.height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: self.surface_config.format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            });
+        let view = texture.create_view(&Default::default());
+
+        self.stage_changes();
+        let list = std::mem::take(&mut self.list);
+        let state_stack = std::mem::take(&mut self.state_stack);
+        let antialias = self.current_state.antialias;
+        let pixel_snap = self.current_state.pixel_snap;
+        let current_state = std::mem::replace(
+            &mut self.current_state,
+            CanvasState {
+                antialias,
+                pixel_snap,
+                ..Default::default()
+            },
+        );
+        let frame_ops = std::mem::take(&mut self.frame_ops);
+        let frame_steps = std::mem::take(&mut self.frame_steps);
+        let layer_stack = std::mem::take(&mut self.layer_stack);
+        let cached_renderables = std::mem::take(&mut self.cached_renderables);
+        let prepared_instructions = self.prepared_instructions.take();
+        let batches = std::mem::take(&mut self.batches);
+        let hit_regions = std::mem::take(&mut self.hit_regions);
+        let hit_id = self.hit_id.take();
+        let damage = std::mem::take(&mut self.damage);
+        let surface_config = self.surface_config.clone();
+        let clear_mode = self.clear_mode;
+        let clear_texture = self.clear_texture.take();
+
+        self.surface_config.width = size.width;
+        self.surface_config.height = size.height;
+        self.surface_config.msaa_sample_count = 1;
+        self.clear_mode = ClearMode::Color(Color::TRANSPARENT);
+        self.renderer.resize(size.width, size.height);
+
+        self.translate(-rect.origin.x, -rect.origin.y);
+        f(self);
+        self.render_to_texture(&view, None, false);
+
+        self.renderer
+            .resize(surface_config.width, surface_config.height);
+        self.list = list;
+        self.state_stack = state_stack;
+        self.current_state = current_state;
+        self.frame_ops = frame_ops;
+        self.frame_steps = frame_steps;
+        self.layer_stack = layer_stack;
+        self.cached_renderables = cached_renderables;
+        self.prepared_instructions = prepared_instructions;
+        self.batches = batches;
+        self.hit_regions = hit_regions;
+        self.hit_id = hit_id;
+        self.damage = damage;
+        self.surface_config = surface_config;
+        self.clear_mode = clear_mode;
+        self.clear_texture = clear_texture;
+
+        let slot = self.layer_cache.alloc_slot();
+        self.renderer.set_premultiplied_texture(
+            &Renderer2D::cached_layer_texture(slot),
+            &view,
+            &TextureOptions::default(),
+        );
+
+        CachedLayer {
+            slot,
+            _texture: texture,
+            size,
+            scale_factor: self.scale_factor(),
+        }
+    }
+}
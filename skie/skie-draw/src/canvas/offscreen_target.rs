@@ -10,8 +10,11 @@ use anyhow::Result;
 pub struct OffscreenRenderTarget {
     texture: wgpu::Texture,
     view: wgpu::TextureView,
+    /// the requested usage, the texture may have more
+    usage: wgpu::TextureUsages,
     msaa_sample_count: u32,
     mssa_view: Option<wgpu::TextureView>,
+    preserve_on_resize: bool,
 }
 
 impl OffscreenRenderTarget {
@@ -22,8 +25,10 @@ impl OffscreenRenderTarget {
         Self {
             texture,
             view,
+            usage: config.usage,
             msaa_sample_count: config.msaa_sample_count,
             mssa_view: create_mssa_view(gpu, config),
+            preserve_on_resize: config.preserve_on_resize,
         }
     }
 }
@@ -38,9 +43,16 @@ impl CanvasSurface for OffscreenRenderTarget {
 
         let texture = create_fb_texture(gpu, config);
         let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        if config.preserve_on_resize {
+            copy_overlap(gpu, &self.texture, &texture);
+        }
+
         self.mssa_view = create_mssa_view(gpu, config);
         self.texture = texture;
         self.view = view;
+        self.usage = config.usage;
+        self.msaa_sample_count = config.msaa_sample_count;
+        self.preserve_on_resize = config.preserve_on_resize;
     }
 
     fn get_config(&self) -> CanvasSurfaceConfig {
@@ -48,8 +60,9 @@ impl CanvasSurface for OffscreenRenderTarget {
             width: self.texture.width(),
             height: self.texture.height(),
             format: self.texture.format(),
-            usage: self.texture.usage(),
+            usage: self.usage,
             msaa_sample_count: self.msaa_sample_count,
+            preserve_on_resize: self.preserve_on_resize,
         }
     }
 
@@ -61,7 +74,7 @@ impl CanvasSurface for OffscreenRenderTarget {
                 (texture_view, Some(&self.view))
             });
 
-        canvas.render_to_texture(view, resolve_target);
+        canvas.render_to_texture(view, resolve_target, true);
         Ok(())
     }
 }
@@ -78,7 +91,37 @@ impl CanvasSnapshotSource for OffscreenRenderTarget {
     }
 }
 
+/// Copies the top left of `from` that fits into `to`. Skipped when a texture cannot be copied,
+/// like the framebuffer of a target created before preserving was turned on
+fn copy_overlap(gpu: &GpuContext, from: &wgpu::Texture, to: &wgpu::Texture) {
+    let copyable = from.usage().contains(wgpu::TextureUsages::COPY_SRC)
+        && to.usage().contains(wgpu::TextureUsages::COPY_DST)
+        && from.format() == to.format();
+    if !copyable {
+        log::debug!("OffscreenRenderTarget: previous frame not preserved, textures not copyable");
+        return;
+    }
+
+    let mut encoder = gpu.create_command_encoder(Some("preserve on resize"));
+    encoder.copy_texture_to_texture(
+        from.as_image_copy(),
+        to.as_image_copy(),
+        wgpu::Extent3d {
+            width: from.width().min(to.width()),
+            height: from.height().min(to.height()),
+            depth_or_array_layers: 1,
+        },
+    );
+    gpu.queue.submit(std::iter::once(encoder.finish()));
+}
+
 fn create_fb_texture(gpu: &GpuContext, config: &CanvasSurfaceConfig) -> wgpu::Texture {
+    let preserve_usage = if config.preserve_on_resize {
+        wgpu::TextureUsages::COPY_SRC | wgpu::TextureUsages::COPY_DST
+    } else {
+        wgpu::TextureUsages::empty()
+    };
+
     gpu.create_texture(&wgpu::TextureDescriptor {
         label: Some("framebuffer"),
         size: wgpu::Extent3d {
@@ -90,7 +133,7 @@ fn create_fb_texture(gpu: &GpuContext, config: &CanvasSurfaceConfig) -> wgpu::Te
         sample_count: 1,
         dimension: wgpu::TextureDimension::D2,
         format: config.format,
-        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | config.usage,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | config.usage | preserve_usage,
         view_formats: &[config.format],
     })
 }
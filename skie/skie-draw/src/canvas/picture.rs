@@ -0,0 +1,119 @@
+use std::sync::Arc;
+
+use skie_math::{Mat3, Rect};
+
+use crate::paint::GraphicsInstruction;
+
+use super::{map_rect, transformed_corners, Canvas, CanvasState};
+
+/// Instructions recorded under one state, relative to the state at the start of the recording
+#[derive(Debug, Clone)]
+struct PictureStage {
+    state: CanvasState,
+    instructions: Vec<GraphicsInstruction>,
+}
+
+/// Drawing recorded with [`Canvas::record`] and replayed with [`Canvas::draw_picture`].
+/// Cheap to clone, so the same picture can be shared between canvases
+#[derive(Debug, Clone, Default)]
+pub struct Picture {
+    stages: Arc<[PictureStage]>,
+}
+
+impl Picture {
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    /// Number of recorded draw instructions
+    pub fn instruction_count(&self) -> usize {
+        self.stages
+            .iter()
+            .map(|stage| stage.instructions.len())
+            .sum()
+    }
+}
+
+impl Canvas {
+    /// Records the drawing done in `f` into a [`Picture`] instead of the frame. `f` starts
+    /// from the default state and the canvas state is left as it was.
+    ///
+    /// Blurs and layers are not recorded, neither are hit ids
+    pub fn record(&mut self, f: impl FnOnce(&mut Self)) -> Picture {
+        self.stage_changes();
+
+        let list = std::mem::take(&mut self.list);
+        let state_stack = std::mem::take(&mut self.state_stack);
+        let current_state = std::mem::take(&mut self.current_state);
+        let frame_ops = std::mem::take(&mut self.frame_ops);
+        let layer_stack = std::mem::take(&mut self.layer_stack);
+        let hit_regions = std::mem::take(&mut self.hit_regions);
+        let hit_id = self.hit_id.take();
+
+        f(self);
+        self.stage_changes();
+
+        if !self.frame_ops.is_empty() {
+            log::warn!("Canvas::record: blurs and layers are not recorded in pictures");
+        }
+
+        let stages = (&self.list)
+            .into_iter()
+            .map(|staged| PictureStage {
+                state: staged.state.clone(),
+                instructions: staged.instructions.to_vec(),
+            })
+            .collect();
+
+        self.list = list;
+        self.state_stack = state_stack;
+        self.current_state = current_state;
+        self.frame_ops = frame_ops;
+        self.layer_stack = layer_stack;
+        self.hit_regions = hit_regions;
+        self.hit_id = hit_id;
+
+        Picture { stages }
+    }
+
+    /// Replays `picture` with `transform` applied before the current transform.
+    /// Recorded clips follow the transform, rotated clips clip to their bounding box
+    pub fn draw_picture(&mut self, picture: &Picture, transform: &Mat3) {
+        self.save();
+        self.transform(transform);
+        let base = self.current_state.clone();
+
+        for stage in picture.stages.iter() {
+            let mut clip_quads = base.clip_quads.clone();
+            clip_quads.extend(
+                stage
+                    .state
+                    .clip_quads
+                    .iter()
+                    .map(|quad| quad.map(|corner| base.transform * corner)),
+            );
+            if !base.transform.is_axis_aligned() && stage.state.clip_rect != Rect::EVERYTHING {
+                clip_quads.push(transformed_corners(&stage.state.clip_rect, &base.transform));
+            }
+
+            self.current_state = CanvasState {
+                transform: stage.state.transform * base.transform,
+                clip_rect: base
+                    .clip_rect
+                    .intersect(&map_rect(&stage.state.clip_rect, &base.transform)),
+                clip_quads,
+                antialias: stage.state.antialias,
+                global_alpha: stage.state.global_alpha * base.global_alpha,
+                pixel_snap: stage.state.pixel_snap,
+                ..base.clone()
+            };
+            for instruction in &stage.instructions {
+                self.list.add(instruction.clone());
+            }
+            self.stage_changes();
+        }
+
+        self.current_state = base;
+        self.restore();
+    }
+}
@@ -0,0 +1,166 @@
+use skie_math::{Mat3, Rect, Vec2};
+
+use crate::{
+    paint::{Brush, PathBrush, Primitive, Quad},
+    path::{Path, PathVerb},
+    Polyline,
+};
+
+/// Rounds primitive coordinates to the device pixel grid, see [`crate::Canvas::set_pixel_snapping`]
+pub(crate) struct PixelSnap {
+    /// local space to physical pixels
+    to_device: Mat3,
+    from_device: Mat3,
+    /// physical pixels per local unit
+    scale: f32,
+}
+
+impl PixelSnap {
+    /// `None` when the transform rotates or skews, nothing lines up with the grid then
+    pub fn new(transform: &Mat3, scale_factor: f32) -> Option<Self> {
+        let [a, b, c, d, ..] = transform.to_affine();
+        if b != 0.0 || c != 0.0 || a == 0.0 || d == 0.0 {
+            return None;
+        }
+
+        let to_device = *transform * Mat3::from_scale(scale_factor, scale_factor);
+        Some(Self {
+            to_device,
+            from_device: to_device.inverse(),
+            scale: (a * d).abs().sqrt() * scale_factor,
+        })
+    }
+
+    /// A snapped copy of the primitive, `None` for primitives that are not snapped
+    pub fn primitive(&self, primitive: &Primitive, brush: &Brush) -> Option<Primitive> {
+        match primitive {
+            Primitive::Quad(quad) => Some(Primitive::Quad(self.quad(quad, brush))),
+            Primitive::Polyline(polyline) => Some(Primitive::Polyline(Polyline {
+                points: polyline
+                    .points
+                    .iter()
+                    .map(|point| self.point(*point, self.offset(brush)))
+                    .collect(),
+                widths: polyline.widths.clone(),
+            })),
+            Primitive::Path { path, brush } => Some(Primitive::Path {
+                path: self.path(path, brush),
+                brush: brush.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    fn quad(&self, quad: &Quad, brush: &Brush) -> Quad {
+        let offset = self.offset(brush);
+        let min = self.point(quad.bounds.origin, offset);
+        let max = self.point(quad.bounds.origin + quad.bounds.size, offset);
+        Quad {
+            bounds: Rect::from_corners(min, max),
+            ..quad.clone()
+        }
+    }
+
+    /// Snaps the end points of the segments, curve control points are left as they are
+    fn path(&self, path: &Path, brush: &PathBrush) -> Path {
+        let offset = self
+            .offset(brush.default_brush())
+            .max(self.path_offset(brush));
+        let mut points = path.points.to_vec();
+        let mut index = 0;
+
+        for verb in path.verbs.iter() {
+            let len = match verb {
+                PathVerb::Begin | PathVerb::LineTo | PathVerb::Close => 1,
+                PathVerb::QuadraticTo => 2,
+                PathVerb::CubicTo => 3,
+                PathVerb::End => 0,
+            };
+            if len > 0 {
+                let end = index + len - 1;
+                points[end] = self.point(points[end], offset);
+            }
+            index += len;
+        }
+
+        Path {
+            points: points.into_boxed_slice(),
+            verbs: path.verbs.clone(),
+        }
+    }
+
+    /// Half a pixel for strokes an odd number of pixels wide, their edges land between
+    /// pixels when centered on a pixel
+    fn offset(&self, brush: &Brush) -> f32 {
+        let stroke = &brush.stroke_style;
+        if stroke.color.is_transparent() {
+            return 0.0;
+        }
+
+        if stroke.hairline {
+            return 0.5;
+        }
+        Self::offset_for(stroke.width() * self.scale)
+    }
+
+    fn path_offset(&self, brush: &PathBrush) -> f32 {
+        Self::offset_for(brush.max_stroke_width() * self.scale)
+    }
+
+    fn offset_for(device_width: f32) -> f32 {
+        if device_width.round() as i64 % 2 == 1 {
+            0.5
+        } else {
+            0.0
+        }
+    }
+
+    fn point(&self, point: Vec2<f32>, offset: f32) -> Vec2<f32> {
+        let device = self.to_device * point;
+        let snapped = Vec2::new(
+            (device.x - offset).round() + offset,
+            (device.y - offset).round() + offset,
+        );
+        self.from_device * snapped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{quad, vec2, Color};
+
+    fn assert_near(a: Vec2<f32>, b: Vec2<f32>) {
+        assert!((a - b).magnitude() < 1e-4, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn odd_strokes_land_on_pixel_centers() {
+        let snap = PixelSnap::new(&Mat3::from_translation(0.3, 0.0), 2.0).unwrap();
+
+        let filled = quad().rect(Rect::xywh(10.1, 10.4, 5.0, 5.0));
+        let Primitive::Quad(fill) = snap
+            .primitive(&filled.clone().into(), &Brush::filled(Color::RED))
+            .unwrap()
+        else {
+            unreachable!()
+        };
+        // 10.4 and 10.3 are at 20.8 and 20.6 physical pixels
+        assert_near(fill.bounds.origin, vec2(10.2, 10.5));
+        assert_near(
+            vec2(fill.bounds.size.width, fill.bounds.size.height),
+            vec2(5.0, 5.0),
+        );
+
+        // a hairline is one physical pixel wide
+        let hairline = Brush::filled(Color::RED)
+            .stroke_color(Color::BLACK)
+            .hairline(true);
+        let Primitive::Quad(stroked) = snap.primitive(&filled.into(), &hairline).unwrap() else {
+            unreachable!()
+        };
+        assert_near(stroked.bounds.origin, vec2(9.95, 10.25));
+
+        assert!(PixelSnap::new(&Mat3::from_rotation(0.1), 1.0).is_none());
+    }
+}
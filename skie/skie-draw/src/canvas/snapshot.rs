@@ -1,10 +1,10 @@
 use anyhow::{bail, Result};
 
 use futures::channel::oneshot::{self};
-use skie_math::Size;
-use wgpu::{BufferAsyncError, Maintain, TextureUsages};
+use skie_math::{Rect, Size};
+use wgpu::{BufferAsyncError, Maintain, TextureFormat, TextureUsages};
 
-use crate::GpuContext;
+use crate::{Color, GpuContext};
 
 use super::Canvas;
 
@@ -20,21 +20,35 @@ pub trait CanvasSnapshotSource {
 
     fn read_texture_data_async(&self, canvas: &Canvas) -> Result<SnapshotReceiver> {
         let source_texture = self.get_source_texture();
+        let region = Rect::xywh(0, 0, source_texture.width(), source_texture.height());
+        self.read_region_async(canvas, &region)
+    }
+
+    /// Reads back `region` of the source texture, in texels
+    fn read_region_async(&self, canvas: &Canvas, region: &Rect<u32>) -> Result<SnapshotReceiver> {
+        let source_texture = self.get_source_texture();
 
         if !source_texture.usage().contains(TextureUsages::COPY_SRC) {
             bail!("required TextureUsages::COPY_SRC in source texture")
         }
 
-        let size = Size {
-            width: source_texture.width(),
-            height: source_texture.height(),
-        };
+        let in_bounds = region.origin.x + region.size.width <= source_texture.width()
+            && region.origin.y + region.size.height <= source_texture.height();
+        if !in_bounds || region.size.width == 0 || region.size.height == 0 {
+            bail!(
+                "region {:?} is outside of the {}x{} source texture",
+                region,
+                source_texture.width(),
+                source_texture.height()
+            )
+        }
 
+        let size = region.size;
         let gpu = canvas.renderer.gpu();
 
         let (sender, receiver) = oneshot::channel::<CanvasSnapshotResult>();
 
-        read_texels_async(gpu, &source_texture, move |res| {
+        read_texels_region_async(gpu, &source_texture, region, move |res| {
             let res = match res {
                 Ok(data) => anyhow::Result::Ok(CanvasSnapshot { data, size }),
                 Err(err) => anyhow::Result::Err(anyhow::anyhow!("Error reading texels {:#?}", err)),
@@ -54,6 +68,30 @@ pub struct CanvasSnapshot {
     pub data: Vec<u8>,
 }
 
+/// The first texel of `snapshot`, in the channel order of `format`
+fn first_pixel(snapshot: &CanvasSnapshot, format: TextureFormat) -> Result<Color> {
+    let Some(&[a, b, c, d]) = snapshot.data.get(..4) else {
+        bail!("empty snapshot")
+    };
+
+    let color = match format {
+        TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => Color::from([a, b, c, d]),
+        TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => Color::from([c, b, a, d]),
+        format => bail!("cannot read pixels of {:?} textures", format),
+    };
+    Ok(color)
+}
+
+/// The texel under the logical point `(x, y)`
+fn pixel_region(canvas: &Canvas, x: f32, y: f32) -> Result<Rect<u32>> {
+    let scale = canvas.scale_factor();
+    let (x, y) = ((x * scale).floor(), (y * scale).floor());
+    if x < 0.0 || y < 0.0 {
+        bail!("pixel ({}, {}) is outside of the canvas", x, y)
+    }
+    Ok(Rect::xywh(x as u32, y as u32, 1, 1))
+}
+
 impl Canvas {
     pub fn snapshot_sync<Source: CanvasSnapshotSource>(
         &self,
@@ -79,13 +117,75 @@ impl Canvas {
 
         receiver.await?
     }
+
+    /// Like [`Canvas::snapshot_sync`] for `region` of the source, in physical pixels
+    pub fn snapshot_region_sync<Source: CanvasSnapshotSource>(
+        &self,
+        source: &Source,
+        region: &Rect<u32>,
+    ) -> CanvasSnapshotResult {
+        let receiver = source.read_region_async(self, region)?;
+
+        self.renderer.gpu().device.poll(Maintain::Wait);
+
+        futures::executor::block_on(receiver)?
+    }
+
+    pub async fn snapshot_region<Source: CanvasSnapshotSource>(
+        &self,
+        source: &Source,
+        region: &Rect<u32>,
+    ) -> CanvasSnapshotResult {
+        let gpu = self.renderer.gpu();
+
+        let receiver = source.read_region_async(self, region)?;
+
+        while !gpu.device.poll(wgpu::Maintain::Poll).is_queue_empty() {}
+
+        receiver.await?
+    }
+
+    /// Color of the pixel under the logical point `(x, y)` in what was last rendered to
+    /// `source`, for eyedroppers and pixel assertions. Blocks until the gpu is done
+    pub fn read_pixel_sync<Source: CanvasSnapshotSource>(
+        &self,
+        source: &Source,
+        x: f32,
+        y: f32,
+    ) -> Result<Color> {
+        let snapshot = self.snapshot_region_sync(source, &pixel_region(self, x, y)?)?;
+        first_pixel(&snapshot, source.get_source_texture().format())
+    }
+
+    /// Async [`Canvas::read_pixel_sync`]
+    pub async fn read_pixel<Source: CanvasSnapshotSource>(
+        &self,
+        source: &Source,
+        x: f32,
+        y: f32,
+    ) -> Result<Color> {
+        let snapshot = self
+            .snapshot_region(source, &pixel_region(self, x, y)?)
+            .await?;
+        first_pixel(&snapshot, source.get_source_texture().format())
+    }
 }
 
-// FIXME: Alignment for copy buffer
 pub fn read_texels_async(
     gpu: &GpuContext,
     src: &wgpu::Texture,
     read: impl FnOnce(Result<Vec<u8>, BufferAsyncError>) + Send + 'static,
+) -> Result<()> {
+    let region = Rect::xywh(0, 0, src.width(), src.height());
+    read_texels_region_async(gpu, src, &region, read)
+}
+
+/// Reads back `region` of `src`, rows are tightly packed in the result
+pub fn read_texels_region_async(
+    gpu: &GpuContext,
+    src: &wgpu::Texture,
+    region: &Rect<u32>,
+    read: impl FnOnce(Result<Vec<u8>, BufferAsyncError>) + Send + 'static,
 ) -> Result<()> {
     let bytes_per_texel = src
         .format()
@@ -94,7 +194,11 @@ pub fn read_texels_async(
         )
         .ok_or(anyhow::anyhow!("Invalid format unable to get texel size"))?;
 
-    let buffer_size = (src.width() * src.height() * bytes_per_texel) as u64;
+    let Size { width, height } = region.size;
+    // buffer rows have to be aligned for the copy
+    let row_bytes = width * bytes_per_texel;
+    let padded_row_bytes = row_bytes.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+    let buffer_size = (padded_row_bytes * height) as u64;
 
     let output_buffer = gpu.device.create_buffer(&wgpu::BufferDescriptor {
         label: Some("Output Buffer"),
@@ -109,20 +213,24 @@ pub fn read_texels_async(
         wgpu::TexelCopyTextureInfo {
             texture: src,
             mip_level: 0,
-            origin: wgpu::Origin3d::ZERO,
+            origin: wgpu::Origin3d {
+                x: region.origin.x,
+                y: region.origin.y,
+                z: 0,
+            },
             aspect: wgpu::TextureAspect::All,
         },
         wgpu::TexelCopyBufferInfo {
             buffer: &output_buffer,
             layout: wgpu::TexelCopyBufferLayout {
                 offset: 0,
-                bytes_per_row: Some(src.width() * bytes_per_texel),
-                rows_per_image: Some(src.height()),
+                bytes_per_row: Some(padded_row_bytes),
+                rows_per_image: Some(height),
             },
         },
         wgpu::Extent3d {
-            width: src.width(),
-            height: src.height(),
+            width,
+            height,
             depth_or_array_layers: 1,
         },
     );
@@ -134,8 +242,13 @@ pub fn read_texels_async(
     buffer_slice.map_async(wgpu::MapMode::Read, {
         let buffer = output_buffer.clone();
         move |res| {
-            let data = buffer.slice(..).get_mapped_range();
-            let res = res.map(|_| data.to_vec());
+            let res = res.map(|_| {
+                let data = buffer.slice(..).get_mapped_range();
+                data.chunks(padded_row_bytes as usize)
+                    .flat_map(|row| &row[..row_bytes as usize])
+                    .copied()
+                    .collect()
+            });
             read(res)
         }
     });
@@ -0,0 +1,42 @@
+use crate::renderer::RendererStats;
+
+/// What the last rendered frame cost, see [`super::Canvas::render_stats`].
+/// Comparing them between builds shows batching regressions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    /// draw instructions recorded for the frame
+    pub instructions: usize,
+    /// batches the instructions were merged into, see [`super::Canvas::debug_batches`]
+    pub batches: usize,
+    pub vertices: usize,
+    pub indices: usize,
+    /// draw calls issued, clip masks included
+    pub draw_calls: usize,
+    /// texture bind groups set
+    pub texture_binds: usize,
+    /// size in bytes of the vertex buffer
+    pub vertex_buffer_size: u64,
+    /// size in bytes of the index buffer
+    pub index_buffer_size: u64,
+}
+
+impl RenderStats {
+    pub(super) fn new(
+        instructions: usize,
+        batches: usize,
+        meshes: impl Iterator<Item = (usize, usize)>,
+        renderer: RendererStats,
+    ) -> Self {
+        let (vertices, indices) = meshes.fold((0, 0), |(v, i), mesh| (v + mesh.0, i + mesh.1));
+        Self {
+            instructions,
+            batches,
+            vertices,
+            indices,
+            draw_calls: renderer.draw_calls,
+            texture_binds: renderer.texture_binds,
+            vertex_buffer_size: renderer.vertex_buffer_size,
+            index_buffer_size: renderer.index_buffer_size,
+        }
+    }
+}
@@ -19,6 +19,8 @@ pub struct CanvasSurfaceConfig {
     pub format: gpu::TextureFormat,
     pub usage: gpu::TextureUsages,
     pub(crate) msaa_sample_count: u32,
+    /// see [`Canvas::set_preserve_on_resize`]
+    pub(crate) preserve_on_resize: bool,
 }
 
 impl Default for CanvasSurfaceConfig {
@@ -29,6 +31,7 @@ impl Default for CanvasSurfaceConfig {
             format: gpu::TextureFormat::Rgba8Unorm,
             usage: gpu::TextureUsages::RENDER_ATTACHMENT,
             msaa_sample_count: 1,
+            preserve_on_resize: false,
         }
     }
 }
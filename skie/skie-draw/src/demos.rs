@@ -0,0 +1,351 @@
+//! Demo scenes
+//!
+//! A registry of small scenes showing off the api. Each one can be rendered offscreen
+//! into a png to explore the api, or compared against a reference with [`Demo::golden`].
+//!
+//! ```ignore
+//! let gpu = GpuContext::new().block_on()?;
+//! let demo = demos::find("shapes").unwrap();
+//! let path = DemoRunner::new().size(512, 512).write(&gpu, demo)?;
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{ensure, Result};
+
+use crate::{
+    gpu,
+    testing::{save_png, GoldenTest},
+    vec2, Brush, Canvas, CanvasSnapshot, Color, Corners, GpuContext, Half, Path as SkiePath, Rect,
+    Size, Text,
+};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Demo {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Draws the scene, it should adapt to [`Canvas::logical_size`]
+    pub draw: fn(&mut Canvas),
+}
+
+impl Demo {
+    /// A golden test named after the demo, run it with `demo.draw`
+    pub fn golden(&self) -> GoldenTest {
+        GoldenTest::new(format!("demo_{}", self.name))
+    }
+}
+
+pub static DEMOS: &[Demo] = &[
+    Demo {
+        name: "hello",
+        description: "A round rect, a circle and some text",
+        draw: hello,
+    },
+    Demo {
+        name: "shapes",
+        description: "The built in primitives with fills and strokes",
+        draw: shapes,
+    },
+    Demo {
+        name: "gradients",
+        description: "Linear and conic gradient fills",
+        draw: gradients,
+    },
+    Demo {
+        name: "paths",
+        description: "Filled and stroked paths with curves",
+        draw: paths,
+    },
+    #[cfg(feature = "svg")]
+    Demo {
+        name: "svg",
+        description: "An svg icon drawn at a few sizes",
+        draw: svg,
+    },
+];
+
+pub fn find(name: &str) -> Option<&'static Demo> {
+    DEMOS.iter().find(|demo| demo.name == name)
+}
+
+/// Renders demos offscreen and writes them as `<name>.png`
+#[derive(Debug, Clone)]
+pub struct DemoRunner {
+    size: Size<u32>,
+    msaa_samples: u32,
+    clear_color: Color,
+    output_dir: PathBuf,
+}
+
+impl Default for DemoRunner {
+    fn default() -> Self {
+        Self {
+            size: Size::new(512, 512),
+            msaa_samples: 4,
+            clear_color: Color::THAMAR_BLACK,
+            output_dir: PathBuf::from("output"),
+        }
+    }
+}
+
+impl DemoRunner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Width should be a multiple of 64 (row alignment of texture copies)
+    pub fn size(mut self, width: u32, height: u32) -> Self {
+        self.size = Size::new(width, height);
+        self
+    }
+
+    pub fn msaa_samples(mut self, sample_count: u32) -> Self {
+        self.msaa_samples = sample_count;
+        self
+    }
+
+    pub fn clear_color(mut self, color: Color) -> Self {
+        self.clear_color = color;
+        self
+    }
+
+    pub fn output_dir(mut self, dir: impl AsRef<Path>) -> Self {
+        self.output_dir = dir.as_ref().to_path_buf();
+        self
+    }
+
+    pub fn output_path(&self, demo: &Demo) -> PathBuf {
+        self.output_dir.join(format!("{}.png", demo.name))
+    }
+
+    pub fn render(&self, gpu: &GpuContext, demo: &Demo) -> Result<CanvasSnapshot> {
+        ensure!(
+            (self.size.width * 4).is_multiple_of(gpu::COPY_BYTES_PER_ROW_ALIGNMENT),
+            "demo width must be a multiple of {} got {}",
+            gpu::COPY_BYTES_PER_ROW_ALIGNMENT / 4,
+            self.size.width
+        );
+
+        let mut canvas = Canvas::create()
+            .width(self.size.width)
+            .height(self.size.height)
+            .msaa_samples(self.msaa_samples)
+            .add_surface_usage(gpu::TextureUsages::COPY_SRC)
+            .build(gpu.clone());
+        let mut target = canvas.create_offscreen_target();
+
+        canvas.clear_color(self.clear_color);
+        (demo.draw)(&mut canvas);
+        canvas.finish(&mut target)?;
+
+        canvas.snapshot_sync(&target)
+    }
+
+    /// Renders the demo and returns the path of the written png
+    pub fn write(&self, gpu: &GpuContext, demo: &Demo) -> Result<PathBuf> {
+        let snapshot = self.render(gpu, demo)?;
+        let path = self.output_path(demo);
+
+        std::fs::create_dir_all(&self.output_dir)?;
+        save_png(&path, &snapshot.data, snapshot.size)?;
+        Ok(path)
+    }
+
+    /// Writes every registered demo
+    pub fn write_all(&self, gpu: &GpuContext) -> Result<Vec<PathBuf>> {
+        DEMOS.iter().map(|demo| self.write(gpu, demo)).collect()
+    }
+}
+
+fn hello(canvas: &mut Canvas) {
+    let size = canvas.logical_size();
+    let side = size.width.min(size.height) * 0.8;
+    let rect = Rect::xywh(size.width.half(), size.height.half(), side, side).centered();
+
+    canvas.draw_round_rect(
+        &rect,
+        &Corners::with_all(side * 0.02),
+        Brush::filled(Color::TORCH_RED)
+            .stroke_color(Color::WHITE)
+            .line_width(5),
+    );
+
+    let center = rect.center();
+    canvas.draw_circle(center.x, center.y, side * 0.4, Brush::filled(Color::WHITE));
+
+    let font_size = side * 0.12;
+    let text = Text::new("✨ Hello ✨")
+        .pos(center.x - font_size * 2.6, center.y - font_size * 0.8)
+        .size_px(font_size);
+    canvas.fill_text(&text, Color::BLACK);
+}
+
+fn shapes(canvas: &mut Canvas) {
+    let size = canvas.logical_size();
+    let cell = Size::new(size.width / 3.0, size.height / 2.0);
+    let radius = cell.width.min(cell.height) * 0.35;
+    let center = |col: f32, row: f32| vec2((col + 0.5) * cell.width, (row + 0.5) * cell.height);
+    let stroke = |color: Color| {
+        Brush::filled(color)
+            .stroke_color(Color::WHITE)
+            .line_width(3)
+    };
+
+    let c = center(0.0, 0.0);
+    canvas.draw_rect(
+        &Rect::xywh(c.x, c.y, radius * 2.0, radius * 2.0).centered(),
+        stroke(Color::TORCH_RED),
+    );
+
+    let c = center(1.0, 0.0);
+    canvas.draw_round_rect(
+        &Rect::xywh(c.x, c.y, radius * 2.0, radius * 1.4).centered(),
+        &Corners::with_all(radius * 0.3),
+        stroke(Color::ORANGE),
+    );
+
+    let c = center(2.0, 0.0);
+    canvas.draw_circle(c.x, c.y, radius, stroke(Color::KHAKI));
+
+    let c = center(0.0, 1.0);
+    canvas.draw_ellipse(
+        c.x,
+        c.y,
+        radius,
+        radius * 0.6,
+        0.4,
+        stroke(Color::LIGHT_GREEN),
+    );
+
+    let c = center(1.0, 1.0);
+    canvas.draw_ring(c.x, c.y, radius * 0.5, radius, stroke(Color::DARK_BLUE));
+
+    let c = center(2.0, 1.0);
+    canvas.draw_pie(c.x, c.y, radius, -0.5, 5.0, stroke(Color::BLUE));
+}
+
+fn gradients(canvas: &mut Canvas) {
+    let size = canvas.logical_size();
+    let half = size.height.half();
+    let pad = size.width * 0.05;
+
+    let top = Rect::xywh(pad, pad, size.width - pad * 2.0, half - pad * 1.5);
+    canvas.draw_round_rect(
+        &top,
+        &Corners::with_all(pad),
+        Brush::default().linear_gradient(
+            top.origin,
+            vec2(top.origin.x + top.size.width, top.origin.y),
+            [
+                (0.0, Color::TORCH_RED),
+                (0.5, Color::ORANGE),
+                (1.0, Color::KHAKI),
+            ],
+        ),
+    );
+
+    let center = vec2(size.width.half(), half + half.half());
+    canvas.draw_circle(
+        center.x,
+        center.y,
+        half.half() - pad.half(),
+        Brush::default().conic_gradient(
+            center,
+            0.0,
+            [
+                (0.0, Color::DARK_BLUE),
+                (0.5, Color::LIGHT_GREEN),
+                (1.0, Color::DARK_BLUE),
+            ],
+        ),
+    );
+}
+
+fn paths(canvas: &mut Canvas) {
+    let size = canvas.logical_size();
+    let (w, h) = (size.width, size.height);
+
+    let mut builder = SkiePath::builder();
+    builder.begin(vec2(w * 0.5, h * 0.1));
+    builder.cubic_to(
+        vec2(w * 0.9, h * 0.1),
+        vec2(w * 0.9, h * 0.5),
+        vec2(w * 0.5, h * 0.5),
+    );
+    builder.cubic_to(
+        vec2(w * 0.1, h * 0.5),
+        vec2(w * 0.1, h * 0.1),
+        vec2(w * 0.5, h * 0.1),
+    );
+    builder.close();
+    canvas.draw_path(builder.build(), Brush::filled(Color::ORANGE));
+
+    let mut builder = SkiePath::builder();
+    builder.begin(vec2(w * 0.1, h * 0.8));
+    builder.quadratic_to(vec2(w * 0.3, h * 0.55), vec2(w * 0.5, h * 0.8));
+    builder.quadratic_to(vec2(w * 0.7, h * 1.05), vec2(w * 0.9, h * 0.8));
+    builder.end(false);
+    canvas.draw_path(
+        builder.build(),
+        Brush::filled(Color::TRANSPARENT)
+            .stroke_color(Color::WHITE)
+            .line_width(6),
+    );
+}
+
+#[cfg(feature = "svg")]
+fn svg(canvas: &mut Canvas) {
+    const ICON: &str = r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24">
+        <circle cx="12" cy="12" r="10" fill="#ff2233"/>
+        <path d="M8 12.5l2.5 2.5L16 9" fill="none" stroke="currentColor" stroke-width="2"
+            stroke-linecap="round" stroke-linejoin="round"/>
+    </svg>"##;
+
+    let icon = crate::SvgDocument::parse(ICON)
+        .expect("valid icon")
+        .current_color(Color::WHITE);
+
+    let size = canvas.logical_size();
+    let mut x = size.width * 0.05;
+    for side in [24.0, 48.0, 96.0, 192.0] {
+        let y = (size.height - side).half();
+        canvas.draw_svg(&icon, &Rect::xywh(x, y, side, side));
+        x += side + size.width * 0.04;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn demo_names_are_unique() {
+        for (i, demo) in DEMOS.iter().enumerate() {
+            assert!(
+                DEMOS[i + 1..].iter().all(|other| other.name != demo.name),
+                "duplicate demo `{}`",
+                demo.name
+            );
+        }
+        assert!(find("hello").is_some());
+        assert!(find("missing").is_none());
+    }
+
+    #[test]
+    fn write_renders_a_png() {
+        let Ok(gpu) = futures::executor::block_on(GpuContext::new()) else {
+            eprintln!("skipping write_renders_a_png: no gpu adapter");
+            return;
+        };
+
+        let dir = std::env::temp_dir().join(format!("skie-demos-{}", std::process::id()));
+        let runner = DemoRunner::new().size(128, 64).output_dir(&dir);
+        let path = runner.write(&gpu, find("shapes").unwrap()).unwrap();
+
+        let image = image::open(&path).unwrap();
+        assert_eq!((image.width(), image.height()), (128, 64));
+        assert!(runner.size(100, 64).render(&gpu, &DEMOS[0]).is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
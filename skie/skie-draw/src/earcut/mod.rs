@@ -446,8 +446,7 @@ fn is_ear_hashed<'a, T: Float>(
     let mut o_n = ear.next_z_i.map(|i| node!(nodes, i));
 
     // look for points inside the triangle in both directions
-    loop {
-        let Some(p) = o_p else { break };
+    while let Some(p) = o_p {
         if p.z < min_z {
             break;
         };
@@ -1,29 +1,32 @@
 pub mod atlas;
+pub mod border;
 pub mod brush;
 pub mod color;
 pub mod draw_list;
 pub mod geometry;
+pub mod gradient;
 pub mod graphics_instruction;
 pub mod image;
 pub mod mesh;
+pub mod pattern;
 pub mod primitives;
 pub mod stroke_tesselate;
 pub mod text;
 pub mod texture;
 
-// pub mod path;
-// pub use path::*;
-
 use crate::{math::Vec2, text::GlyphImage};
 
 pub use atlas::*;
+pub use border::*;
 pub use brush::*;
 pub use color::*;
 pub use draw_list::*;
 pub use geometry::*;
+pub use gradient::*;
 pub use graphics_instruction::*;
 pub use image::*;
 pub use mesh::*;
+pub use pattern::*;
 pub use primitives::*;
 pub use stroke_tesselate::*;
 pub use text::*;
@@ -38,6 +41,7 @@ pub enum AtlasKey {
     Image(AtlasImage),
     Glyf(GlyphImage),
     WhiteTexture,
+    User(UserAtlasKey),
 }
 
 impl AtlasKeySource for AtlasKey {
@@ -52,6 +56,7 @@ impl AtlasKeySource for AtlasKey {
             }
             AtlasKey::Image(image) => image.texture_kind,
             AtlasKey::WhiteTexture => TextureKind::Color,
+            AtlasKey::User(key) => key.texture_kind,
         }
     }
 }
@@ -67,3 +72,9 @@ impl From<AtlasImage> for AtlasKey {
         Self::Image(image)
     }
 }
+
+impl From<UserAtlasKey> for AtlasKey {
+    fn from(key: UserAtlasKey) -> Self {
+        Self::User(key)
+    }
+}
@@ -73,6 +73,17 @@ impl<Key: AtlasKeySource> TextureAtlas<Key> {
         lock.get_texture_info(key)
     }
 
+    /// Size of an atlas page
+    pub fn get_texture_size(&self, id: &AtlasTextureId) -> Option<Size<i32>> {
+        let lock = self.0.lock();
+        let storage = lock.get_storage_read(&id.kind);
+        storage
+            .slots
+            .get(id.slot)?
+            .as_ref()
+            .map(|texture| texture.size)
+    }
+
     pub fn get_texture_infos(
         &self,
         keys: impl Iterator<Item = Key>,
@@ -123,6 +134,12 @@ impl<Key: AtlasKeySource> TextureAtlas<Key> {
         let lock = self.0.lock();
         lock.upload_texture(tile, data)
     }
+
+    /// Uploads whole rows of the tile starting at `first_row`, for filling large tiles in chunks
+    pub fn upload_texture_rows(&self, tile: &AtlasTile, first_row: u32, data: &[u8]) {
+        let lock = self.0.lock();
+        lock.upload_texture_rows(tile, first_row, data)
+    }
 }
 
 impl<Key: AtlasKeySource> AtlasStorage<Key> {
@@ -189,12 +206,24 @@ impl<Key: AtlasKeySource> AtlasStorage<Key> {
 
     /// Uploads data for the given tile
     pub fn upload_texture(&self, tile: &AtlasTile, data: &[u8]) {
+        self.upload_texture_rows(tile, 0, data)
+    }
+
+    fn upload_texture_rows(&self, tile: &AtlasTile, first_row: u32, data: &[u8]) {
         let storage = self.get_storage_read(&tile.texture.kind);
         let texture = storage[tile.texture.slot].as_ref();
 
         if let Some(texture) = texture {
             let tile_width: u32 = tile.bounds.size.width as _;
             let tile_height: u32 = tile.bounds.size.height as _;
+            let bytes_per_row = texture.kind.bytes_per_pixel() * tile_width;
+            let rows =
+                (data.len() as u32 / bytes_per_row).min(tile_height.saturating_sub(first_row));
+
+            if rows == 0 {
+                return;
+            }
+
             self.gpu.queue.write_texture(
                 wgpu::TexelCopyTextureInfo {
                     texture: &texture.raw,
@@ -202,19 +231,19 @@ impl<Key: AtlasKeySource> AtlasStorage<Key> {
                     mip_level: 0,
                     origin: wgpu::Origin3d {
                         x: tile.bounds.origin.x as _,
-                        y: tile.bounds.origin.y as _,
+                        y: tile.bounds.origin.y as u32 + first_row,
                         z: 0,
                     },
                 },
-                data,
+                &data[..(rows * bytes_per_row) as usize],
                 wgpu::TexelCopyBufferLayout {
                     offset: 0,
-                    bytes_per_row: Some(texture.kind.bytes_per_pixel() * tile_width),
+                    bytes_per_row: Some(bytes_per_row),
                     rows_per_image: None,
                 },
                 wgpu::Extent3d {
                     width: tile_width,
-                    height: tile_height,
+                    height: rows,
                     depth_or_array_layers: 1,
                 },
             );
@@ -399,6 +428,14 @@ pub struct AtlasTextureInfo {
 }
 
 impl AtlasTextureInfo {
+    /// The tile's bounds normalized to the atlas texture
+    pub fn uv_rect(&self) -> Rect<f32> {
+        Rect::from_corners(
+            self.uv_to_atlas_space(0.0, 0.0),
+            self.uv_to_atlas_space(1.0, 1.0),
+        )
+    }
+
     pub fn uv_to_atlas_space(&self, u: f32, v: f32) -> Vec2<f32> {
         // Scale the normalized coordinates (u, v) to the bounds of the texture tile in the atlas
         let tex_x = self.tile.bounds.origin.x as f32 + u * self.tile.bounds.size.width as f32;
@@ -0,0 +1,534 @@
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+use skie_math::{vec2, Corners, Rect};
+
+use crate::{clamp_corner_radii, Vec2};
+
+use super::{Color, Mesh, WHITE_UV};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum BorderStyle {
+    #[default]
+    Solid,
+    Dashed,
+    Dotted,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BorderSide {
+    pub width: f32,
+    pub color: Color,
+    pub style: BorderStyle,
+}
+
+impl Default for BorderSide {
+    fn default() -> Self {
+        Self {
+            width: 0.0,
+            color: Color::TRANSPARENT,
+            style: BorderStyle::Solid,
+        }
+    }
+}
+
+impl BorderSide {
+    pub fn new(width: f32, color: Color) -> Self {
+        Self {
+            width,
+            color,
+            style: BorderStyle::Solid,
+        }
+    }
+
+    pub fn style(mut self, style: BorderStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn dashed(self) -> Self {
+        self.style(BorderStyle::Dashed)
+    }
+
+    pub fn dotted(self) -> Self {
+        self.style(BorderStyle::Dotted)
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.width > 0.0 && !self.color.is_transparent()
+    }
+}
+
+/// Per side borders of a [`crate::Quad`], drawn inside the quad bounds like css `border-box`
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Borders {
+    pub top: BorderSide,
+    pub right: BorderSide,
+    pub bottom: BorderSide,
+    pub left: BorderSide,
+}
+
+impl Borders {
+    pub fn with_all(side: BorderSide) -> Self {
+        Self {
+            top: side,
+            right: side,
+            bottom: side,
+            left: side,
+        }
+    }
+
+    pub fn with_each(
+        top: BorderSide,
+        right: BorderSide,
+        bottom: BorderSide,
+        left: BorderSide,
+    ) -> Self {
+        Self {
+            top,
+            right,
+            bottom,
+            left,
+        }
+    }
+
+    pub fn with_top(mut self, side: BorderSide) -> Self {
+        self.top = side;
+        self
+    }
+
+    pub fn with_right(mut self, side: BorderSide) -> Self {
+        self.right = side;
+        self
+    }
+
+    pub fn with_bottom(mut self, side: BorderSide) -> Self {
+        self.bottom = side;
+        self
+    }
+
+    pub fn with_left(mut self, side: BorderSide) -> Self {
+        self.left = side;
+        self
+    }
+
+    pub fn is_visible(&self) -> bool {
+        [self.top, self.right, self.bottom, self.left]
+            .iter()
+            .any(BorderSide::is_visible)
+    }
+}
+
+// dash and gap lengths in multiples of the border width
+const DASH_LENGTH: f32 = 3.0;
+const DASH_GAP: f32 = 2.0;
+const DOT_SEGMENTS: usize = 12;
+
+/// One rounded corner of the border, the outer edge is a circle and the inner edge an
+/// ellipse shrunk by the widths of the two sides meeting at the corner
+struct CornerArc {
+    outer_center: Vec2<f32>,
+    outer_radius: f32,
+    inner_center: Vec2<f32>,
+    inner_radii: Vec2<f32>,
+    start: f32,
+    /// angle where the corner is split between the incoming and outgoing sides
+    split: f32,
+}
+
+impl CornerArc {
+    fn point(&self, angle: f32) -> (Vec2<f32>, Vec2<f32>) {
+        let dir = vec2(angle.cos(), angle.sin());
+        (
+            self.outer_center + dir * self.outer_radius,
+            self.inner_center + dir * self.inner_radii,
+        )
+    }
+
+    fn sample(&self, from: f32, to: f32, output: &mut Vec<(Vec2<f32>, Vec2<f32>)>) {
+        let radius = self
+            .outer_radius
+            .max(self.inner_radii.x.max(self.inner_radii.y));
+        let quarter_segments = (radius.sqrt() * 2.0).ceil().clamp(1.0, 16.0);
+        let segments = (quarter_segments * (to - from).abs() / FRAC_PI_2)
+            .ceil()
+            .max(1.0) as usize;
+
+        for i in 0..=segments {
+            output.push(self.point(from + (to - from) * (i as f32 / segments as f32)));
+        }
+    }
+}
+
+pub(crate) fn add_quad_borders(
+    mesh: &mut Mesh,
+    bounds: &Rect<f32>,
+    corners: &Corners<f32>,
+    borders: &Borders,
+) {
+    if !borders.is_visible() {
+        return;
+    }
+
+    // rects with a negative size span the other way
+    let bounds = &Rect::from_corners(
+        bounds.min().min(&bounds.max()),
+        bounds.min().max(&bounds.max()),
+    );
+    let min = bounds.min();
+    let max = bounds.max();
+    let w = bounds.size.width;
+    let h = bounds.size.height;
+
+    let top = borders.top.width.max(0.0).min(h);
+    let right = borders.right.width.max(0.0).min(w);
+    let bottom = borders.bottom.width.max(0.0).min(h);
+    let left = borders.left.width.max(0.0).min(w);
+
+    let radii = clamp_corner_radii(bounds, corners);
+
+    // the portion of a corner owned by each side is proportional to its width
+    let split = |start: f32, incoming: f32, outgoing: f32| {
+        let total = incoming + outgoing;
+        let ratio = if total > 0.0 { incoming / total } else { 0.5 };
+        start + FRAC_PI_2 * ratio
+    };
+
+    let corner = |outer_center: Vec2<f32>,
+                  radius: f32,
+                  inner_radii: Vec2<f32>,
+                  inner_center: Vec2<f32>,
+                  start: f32,
+                  incoming: f32,
+                  outgoing: f32| CornerArc {
+        outer_center,
+        outer_radius: radius,
+        inner_center,
+        inner_radii,
+        start,
+        split: split(start, incoming, outgoing),
+    };
+
+    // clockwise starting from the top left
+    let arcs = [
+        {
+            let r = radii.top_left;
+            let inner = vec2((r - left).max(0.0), (r - top).max(0.0));
+            corner(
+                vec2(min.x + r, min.y + r),
+                r,
+                inner,
+                vec2(min.x + left, min.y + top) + inner,
+                PI,
+                left,
+                top,
+            )
+        },
+        {
+            let r = radii.top_right;
+            let inner = vec2((r - right).max(0.0), (r - top).max(0.0));
+            corner(
+                vec2(max.x - r, min.y + r),
+                r,
+                inner,
+                vec2(max.x - right - inner.x, min.y + top + inner.y),
+                PI + FRAC_PI_2,
+                top,
+                right,
+            )
+        },
+        {
+            let r = radii.bottom_right;
+            let inner = vec2((r - right).max(0.0), (r - bottom).max(0.0));
+            corner(
+                vec2(max.x - r, max.y - r),
+                r,
+                inner,
+                vec2(max.x - right, max.y - bottom) - inner,
+                0.0,
+                right,
+                bottom,
+            )
+        },
+        {
+            let r = radii.bottom_left;
+            let inner = vec2((r - left).max(0.0), (r - bottom).max(0.0));
+            corner(
+                vec2(min.x + r, max.y - r),
+                r,
+                inner,
+                vec2(min.x + left + inner.x, max.y - bottom - inner.y),
+                FRAC_PI_2,
+                bottom,
+                left,
+            )
+        },
+    ];
+
+    let sides = [borders.top, borders.right, borders.bottom, borders.left];
+    let mut strip = Vec::new();
+
+    for (i, side) in sides.iter().enumerate() {
+        if !side.is_visible() {
+            continue;
+        }
+
+        let from = &arcs[i];
+        let to = &arcs[(i + 1) % 4];
+
+        strip.clear();
+        from.sample(from.split, from.start + FRAC_PI_2, &mut strip);
+        to.sample(to.start, to.split, &mut strip);
+
+        match side.style {
+            BorderStyle::Solid => add_strip(mesh, &strip, side.color),
+            BorderStyle::Dashed => add_dashed_strip(mesh, &strip, side),
+            BorderStyle::Dotted => add_dotted_strip(mesh, &strip, side),
+        }
+    }
+}
+
+fn add_strip(mesh: &mut Mesh, strip: &[(Vec2<f32>, Vec2<f32>)], color: Color) {
+    if strip.len() < 2 {
+        return;
+    }
+
+    let base = mesh.vertex_count();
+    mesh.reserve_prim(strip.len() * 2, (strip.len() - 1) * 6);
+
+    for (outer, inner) in strip {
+        mesh.add_vertex(*outer, color, WHITE_UV);
+        mesh.add_vertex(*inner, color, WHITE_UV);
+    }
+
+    for i in 0..strip.len() as u32 - 1 {
+        let outer = base + i * 2;
+        let inner = outer + 1;
+        mesh.add_triangle(outer, outer + 2, inner);
+        mesh.add_triangle(inner, outer + 2, inner + 2);
+    }
+}
+
+/// cumulative length along the center line of the strip
+fn strip_lengths(strip: &[(Vec2<f32>, Vec2<f32>)]) -> Vec<f32> {
+    let mut total = 0.0;
+    let mut prev: Option<Vec2<f32>> = None;
+
+    strip
+        .iter()
+        .map(|(outer, inner)| {
+            let mid = (*outer + *inner) * 0.5;
+            if let Some(prev) = prev {
+                total += (mid - prev).magnitude();
+            }
+            prev = Some(mid);
+            total
+        })
+        .collect()
+}
+
+fn sample_strip(
+    strip: &[(Vec2<f32>, Vec2<f32>)],
+    lengths: &[f32],
+    at: f32,
+) -> (Vec2<f32>, Vec2<f32>) {
+    let i = lengths
+        .partition_point(|l| *l < at)
+        .clamp(1, strip.len() - 1);
+    let (l0, l1) = (lengths[i - 1], lengths[i]);
+    let t = if l1 > l0 { (at - l0) / (l1 - l0) } else { 0.0 };
+
+    let (o0, i0) = strip[i - 1];
+    let (o1, i1) = strip[i];
+    (o0 + (o1 - o0) * t, i0 + (i1 - i0) * t)
+}
+
+fn add_dashed_strip(mesh: &mut Mesh, strip: &[(Vec2<f32>, Vec2<f32>)], side: &BorderSide) {
+    let lengths = strip_lengths(strip);
+    let Some(&total) = lengths.last() else {
+        return;
+    };
+
+    let dash = side.width * DASH_LENGTH;
+    let gap = side.width * DASH_GAP;
+
+    // stretch the pattern so that the side starts and ends with a dash
+    let count = ((total + gap) / (dash + gap)).round().max(1.0);
+    let period = (total + gap) / count;
+    let dash = period * DASH_LENGTH / (DASH_LENGTH + DASH_GAP);
+
+    let mut sub_strip = Vec::new();
+
+    for n in 0..count as usize {
+        let start = n as f32 * period;
+        let end = (start + dash).min(total);
+
+        sub_strip.clear();
+        sub_strip.push(sample_strip(strip, &lengths, start));
+        sub_strip.extend(
+            lengths
+                .iter()
+                .zip(strip)
+                .filter(|(l, _)| **l > start && **l < end)
+                .map(|(_, pair)| *pair),
+        );
+        sub_strip.push(sample_strip(strip, &lengths, end));
+
+        add_strip(mesh, &sub_strip, side.color);
+    }
+}
+
+fn add_dotted_strip(mesh: &mut Mesh, strip: &[(Vec2<f32>, Vec2<f32>)], side: &BorderSide) {
+    let lengths = strip_lengths(strip);
+    let Some(&total) = lengths.last() else {
+        return;
+    };
+
+    let radius = side.width * 0.5;
+    let count = (total / (side.width * 2.0)).round().max(1.0);
+    let spacing = total / count;
+
+    for n in 0..count as usize {
+        let (outer, inner) = sample_strip(strip, &lengths, (n as f32 + 0.5) * spacing);
+        let center = (outer + inner) * 0.5;
+
+        let base = mesh.vertex_count();
+        mesh.reserve_prim(DOT_SEGMENTS + 1, DOT_SEGMENTS * 3);
+        mesh.add_vertex(center, side.color, WHITE_UV);
+
+        for i in 0..DOT_SEGMENTS {
+            let angle = TAU * i as f32 / DOT_SEGMENTS as f32;
+            mesh.add_vertex(
+                center + vec2(angle.cos(), angle.sin()) * radius,
+                side.color,
+                WHITE_UV,
+            );
+        }
+
+        for i in 0..DOT_SEGMENTS as u32 {
+            mesh.add_triangle(base, base + 1 + i, base + 1 + (i + 1) % DOT_SEGMENTS as u32);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signed_area(mesh: &Mesh) -> f32 {
+        mesh.indices
+            .chunks_exact(3)
+            .map(|tri| {
+                let [a, b, c] = [tri[0], tri[1], tri[2]].map(|i| {
+                    let p = mesh.vertices[i as usize].position;
+                    vec2(p[0], p[1])
+                });
+                (b - a).cross(&(c - a)) * 0.5
+            })
+            .sum()
+    }
+
+    #[test]
+    fn solid_square_border_covers_frame() {
+        let mut mesh = Mesh::default();
+        let side = BorderSide::new(10.0, Color::RED);
+
+        add_quad_borders(
+            &mut mesh,
+            &Rect::xywh(0.0, 0.0, 100.0, 100.0),
+            &Corners::default(),
+            &Borders::with_all(side),
+        );
+
+        assert!(mesh.is_valid());
+        // 100 * 100 - 80 * 80
+        assert!((signed_area(&mesh).abs() - 3600.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn negative_and_nan_sizes_do_not_panic() {
+        let side = BorderSide::new(10.0, Color::RED);
+
+        let mut flipped = Mesh::default();
+        add_quad_borders(
+            &mut flipped,
+            &Rect::xywh(100.0, 100.0, -100.0, -100.0),
+            &Corners::default(),
+            &Borders::with_all(side),
+        );
+        assert!((signed_area(&flipped).abs() - 3600.0).abs() < 0.01);
+
+        let mut mesh = Mesh::default();
+        add_quad_borders(
+            &mut mesh,
+            &Rect::xywh(0.0, 0.0, f32::NAN, 100.0),
+            &Corners::default(),
+            &Borders::with_all(side),
+        );
+    }
+
+    #[test]
+    fn only_visible_sides_are_emitted() {
+        let mut mesh = Mesh::default();
+
+        add_quad_borders(
+            &mut mesh,
+            &Rect::xywh(0.0, 0.0, 100.0, 50.0),
+            &Corners::default(),
+            &Borders::default().with_top(BorderSide::new(10.0, Color::RED)),
+        );
+
+        // the top side owns the full corners since the left and right widths are zero
+        assert!((signed_area(&mesh).abs() - 1000.0).abs() < 0.01);
+
+        let mut mesh = Mesh::default();
+        add_quad_borders(
+            &mut mesh,
+            &Rect::xywh(0.0, 0.0, 100.0, 50.0),
+            &Corners::default(),
+            &Borders::default(),
+        );
+        assert!(mesh.is_empty());
+    }
+
+    #[test]
+    fn rounded_border_matches_ring_area() {
+        let mut mesh = Mesh::default();
+        let side = BorderSide::new(5.0, Color::RED);
+
+        // a fully rounded square is a circle
+        add_quad_borders(
+            &mut mesh,
+            &Rect::xywh(0.0, 0.0, 100.0, 100.0),
+            &Corners::with_all(50.0),
+            &Borders::with_all(side),
+        );
+
+        let expected = PI * (50.0 * 50.0 - 45.0 * 45.0);
+        let area = signed_area(&mesh).abs();
+        assert!(
+            (area - expected).abs() / expected < 0.01,
+            "{area} {expected}"
+        );
+    }
+
+    #[test]
+    fn dashed_and_dotted_cover_less_than_solid() {
+        let rect = Rect::xywh(0.0, 0.0, 200.0, 100.0);
+        let corners = Corners::with_all(12.0);
+
+        let area = |style| {
+            let mut mesh = Mesh::default();
+            let side = BorderSide::new(4.0, Color::RED).style(style);
+            add_quad_borders(&mut mesh, &rect, &corners, &Borders::with_all(side));
+            assert!(mesh.is_valid());
+            signed_area(&mesh).abs()
+        };
+
+        let solid = area(BorderStyle::Solid);
+        let dashed = area(BorderStyle::Dashed);
+        let dotted = area(BorderStyle::Dotted);
+
+        assert!(dashed < solid && dashed > solid * 0.4);
+        assert!(dotted < dashed && dotted > 0.0);
+    }
+}
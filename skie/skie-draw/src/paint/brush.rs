@@ -1,36 +1,46 @@
 use std::ops::{Deref, DerefMut};
 
-use skie_math::{Corners, Rect};
+use skie_math::{Corners, Mat3, Rect, Size, Vec2};
 
 use crate::{
     path::{Contour, Point},
-    Canvas, PathBuilder, Polygon,
+    Canvas, PathBuilder, Polygon, TextureId,
 };
 
-use super::Color;
+use super::{Color, ConicGradient, Gradient, LinearGradient, Pattern};
 
 /// Represents a brush used for drawing operations, which includes properties for fill style, stroke style, and anti-aliasing.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Brush {
     pub(crate) fill_style: FillStyle,
     pub(crate) stroke_style: StrokeStyle,
-    pub(crate) antialias: bool,
-    pub(crate) feathering: f32,
+    pub(crate) antialias: Antialias,
+}
+
+/// How the edges of a brush's fills are anti-aliased. Strokes are not feathered
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Antialias {
+    /// Follows [`Canvas::set_antialias`]
+    #[default]
+    Inherit,
+    /// Edges fade out over one physical pixel, whatever the scale factor and transform
+    On,
+    /// Crisp edges
+    Off,
+    /// Edges fade out over the given width in physical pixels
+    Feather(f32),
 }
 
 impl Default for Brush {
-    /// Creates a default brush with transparent fill and stroke, and anti-aliasing disabled.
+    /// Creates a default brush with transparent fill and stroke, anti-aliasing follows the canvas.
     fn default() -> Self {
         Self {
-            fill_style: FillStyle {
-                color: Color::TRANSPARENT,
-            },
+            fill_style: FillStyle::default(),
             stroke_style: StrokeStyle {
                 color: Color::TRANSPARENT,
                 ..Default::default()
             },
-            antialias: false,
-            feathering: 0.0,
+            antialias: Antialias::Inherit,
         }
     }
 }
@@ -38,27 +48,33 @@ impl Default for Brush {
 impl Brush {
     pub fn filled(fill_color: Color) -> Self {
         Self {
-            fill_style: FillStyle { color: fill_color },
+            fill_style: FillStyle::default().color(fill_color),
             ..Default::default()
         }
     }
-    /// Returns whether anti-aliasing is enabled for the brush.
-    pub fn is_antialias(&self) -> bool {
+    /// Returns the anti-aliasing setting of the brush
+    pub fn get_antialias(&self) -> Antialias {
         self.antialias
     }
 
-    /// Enables or disables anti-aliasing for the brush.
+    /// Enables or disables anti-aliasing for the brush, overriding the canvas default.
+    /// Shorthand for [`Antialias::On`] and [`Antialias::Off`]
     ///
     /// # Arguments
     ///
     /// * `enable` - A boolean value to enable (true) or disable (false) anti-aliasing.
     pub fn antialias(mut self, enable: bool) -> Self {
-        self.antialias = enable;
+        self.antialias = if enable {
+            Antialias::On
+        } else {
+            Antialias::Off
+        };
         self
     }
 
-    pub fn feathering(mut self, value: f32) -> Self {
-        self.feathering = value;
+    /// Sets how the edges are anti-aliased, see [`Antialias`]
+    pub fn antialiasing(mut self, antialias: Antialias) -> Self {
+        self.antialias = antialias;
         self
     }
 
@@ -73,7 +89,51 @@ impl Brush {
     ///
     /// * `color` - The new fill color to be applied.
     pub fn fill_color(mut self, color: Color) -> Self {
-        self.fill_style.color = color;
+        self.fill_style = FillStyle::default().color(color);
+        self
+    }
+
+    /// Fills with colors interpolated from `start` to `end` instead of the fill color.
+    /// `stops` are `(offset, color)` pairs with offsets in `0..=1`
+    pub fn linear_gradient(
+        mut self,
+        start: impl Into<Vec2<f32>>,
+        end: impl Into<Vec2<f32>>,
+        stops: impl IntoIterator<Item = (f32, Color)>,
+    ) -> Self {
+        let gradient = stops.into_iter().fold(
+            LinearGradient::new(start.into(), end.into()),
+            |gradient, (offset, color)| gradient.stop(offset, color),
+        );
+        self.fill_style = FillStyle::default().gradient(gradient);
+        self
+    }
+
+    /// Fills with colors swept clockwise around `center` from `start_angle` in radians,
+    /// the stops wrap around so offset 1 meets offset 0 at the start angle
+    pub fn conic_gradient(
+        mut self,
+        center: impl Into<Vec2<f32>>,
+        start_angle: f32,
+        stops: impl IntoIterator<Item = (f32, Color)>,
+    ) -> Self {
+        let gradient = stops.into_iter().fold(
+            ConicGradient::new(center.into(), start_angle),
+            |gradient, (offset, color)| gradient.stop(offset, color),
+        );
+        self.fill_style = FillStyle::default().gradient(gradient);
+        self
+    }
+
+    /// Fills with a tiled texture, the stroke is drawn on top with its color as usual
+    pub fn pattern(mut self, pattern: Pattern) -> Self {
+        self.fill_style = FillStyle::default().color(Color::WHITE).pattern(pattern);
+        self
+    }
+
+    /// Places the texture of textured fills, set after the fill color which resets it
+    pub fn texture_mapping(mut self, mapping: TextureMapping) -> Self {
+        self.fill_style.texture_mapping = mapping;
         self
     }
 
@@ -88,7 +148,7 @@ impl Brush {
     }
 
     pub fn no_fill(mut self) -> Self {
-        self.fill_style.color = Color::TRANSPARENT;
+        self.fill_style = FillStyle::default();
         self
     }
 
@@ -132,6 +192,13 @@ impl Brush {
         self
     }
 
+    /// Strokes one physical pixel wide regardless of the transform and scale factor,
+    /// for grids and technical drawings that are zoomed
+    pub fn hairline(mut self, hairline: bool) -> Self {
+        self.stroke_style.hairline = hairline;
+        self
+    }
+
     /// Sets the stroke style of the brush.
     ///
     /// # Arguments
@@ -169,7 +236,7 @@ impl Brush {
 
     /// Checks if there is nothing to draw with the brush (i.e., both the fill and stroke colors are transparent).
     pub fn noting_to_draw(&self) -> bool {
-        self.fill_style.color.is_transparent() && self.stroke_style.color.is_transparent()
+        !self.fill_style.is_visible() && self.stroke_style.color.is_transparent()
     }
 
     pub fn when<F>(self, cond: bool, consequent: F) -> Self
@@ -196,15 +263,62 @@ impl Brush {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// How a textured fill places its texture over the shape
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum TextureMapping {
+    /// The texture is stretched over the bounds of the shape
+    #[default]
+    Bounds,
+    /// The texture spans `0..size` from the origin of the local space, shapes sharing the
+    /// mapping show the parts of one texture that line up across them
+    Absolute(Size<f32>),
+    /// Maps positions in local space to texture coordinates
+    Transform(Mat3),
+}
+
+impl TextureMapping {
+    /// Texture coordinates at `point` of a shape covering `bounds`
+    pub fn uv_at(&self, point: Vec2<f32>, bounds: &Rect<f32>) -> Vec2<f32> {
+        match self {
+            TextureMapping::Bounds => {
+                let (min, max) = (bounds.min(), bounds.max());
+                Vec2::new(
+                    (point.x - min.x) / (max.x - min.x),
+                    (point.y - min.y) / (max.y - min.y),
+                )
+            }
+            TextureMapping::Absolute(size) => Vec2::new(
+                point.x / size.width.max(f32::EPSILON),
+                point.y / size.height.max(f32::EPSILON),
+            ),
+            TextureMapping::Transform(transform) => *transform * point,
+        }
+    }
+
+    /// Whether [`TextureMapping::uv_at`] reads the bounds
+    pub(crate) fn needs_bounds(&self) -> bool {
+        matches!(self, TextureMapping::Bounds)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct FillStyle {
     pub color: Color,
+    /// Used instead of `color` when set
+    pub gradient: Option<Gradient>,
+    /// Textures the fill, tinted by `color`
+    pub pattern: Option<Pattern>,
+    /// Placement of the texture of textured fills like images, patterns use their own
+    pub texture_mapping: TextureMapping,
 }
 
 impl Default for FillStyle {
     fn default() -> Self {
         Self {
             color: Color::TRANSPARENT,
+            gradient: None,
+            pattern: None,
+            texture_mapping: TextureMapping::default(),
         }
     }
 }
@@ -214,6 +328,25 @@ impl FillStyle {
         self.color = color;
         self
     }
+
+    pub fn gradient(mut self, gradient: impl Into<Gradient>) -> Self {
+        self.gradient = Some(gradient.into());
+        self
+    }
+
+    pub fn pattern(mut self, pattern: Pattern) -> Self {
+        self.pattern = Some(pattern);
+        self
+    }
+
+    pub fn texture_mapping(mut self, mapping: TextureMapping) -> Self {
+        self.texture_mapping = mapping;
+        self
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.gradient.is_some() || !self.color.is_transparent()
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -237,6 +370,8 @@ pub struct StrokeStyle {
     pub line_join: LineJoin,
     pub line_cap: LineCap,
     pub allow_overlap: bool,
+    /// Always one physical pixel wide whatever the transform, `line_width` is ignored
+    pub hairline: bool,
 }
 
 impl Default for StrokeStyle {
@@ -247,6 +382,7 @@ impl Default for StrokeStyle {
             line_join: LineJoin::Miter,
             line_cap: LineCap::Butt,
             allow_overlap: false,
+            hairline: false,
         }
     }
 }
@@ -267,6 +403,16 @@ impl StrokeStyle {
         self
     }
 
+    pub fn hairline(mut self, hairline: bool) -> Self {
+        self.hairline = hairline;
+        self
+    }
+
+    /// Width in local units for strokes that are not hairlines
+    pub fn width(&self) -> f32 {
+        self.line_width.max(1) as f32
+    }
+
     pub fn line_join(mut self, line_join: LineJoin) -> Self {
         self.line_join = line_join;
         self
@@ -328,6 +474,18 @@ impl PathBrush {
         }
     }
 
+    /// Brush used for every contour without an override
+    pub fn with_default(mut self, default: Brush) -> Self {
+        self.default = default;
+        self
+    }
+
+    /// Overrides the brush of a single contour
+    pub fn with(mut self, contour: Contour, brush: Brush) -> Self {
+        self.set(contour, brush);
+        self
+    }
+
     #[inline]
     pub fn set(&mut self, contour: Contour, brush: Brush) {
         self.overrides.insert(contour, brush);
@@ -338,12 +496,62 @@ impl PathBrush {
         self.default = default;
     }
 
+    /// Uses `brush` for every contour, dropping all overrides
+    pub fn set_all(&mut self, brush: Brush) {
+        self.overrides.clear();
+        self.default = brush;
+    }
+
+    #[inline]
+    pub fn default_brush(&self) -> &Brush {
+        &self.default
+    }
+
+    /// The override for `contour`, if one was set
+    #[inline]
+    pub fn get(&self, contour: &Contour) -> Option<&Brush> {
+        self.overrides.get(contour)
+    }
+
+    /// Texture of the first pattern fill, the default brush is checked first
+    pub(crate) fn pattern_texture(&self) -> Option<&TextureId> {
+        std::iter::once(&self.default)
+            .chain(self.overrides.values())
+            .find_map(|brush| brush.fill_style.pattern.as_ref())
+            .map(|pattern| &pattern.texture)
+    }
+
+    /// Whether neither the default brush nor the overrides fill or stroke
+    pub(crate) fn nothing_to_draw(&self) -> bool {
+        std::iter::once(&self.default)
+            .chain(self.overrides.values())
+            .all(Brush::noting_to_draw)
+    }
+
+    /// Widest visible stroke of the default brush and the overrides, 0 without strokes
+    pub(crate) fn max_stroke_width(&self) -> f32 {
+        std::iter::once(&self.default)
+            .chain(self.overrides.values())
+            .filter(|brush| !brush.stroke_style.color.is_transparent())
+            .map(|brush| brush.stroke_style.width())
+            .fold(0.0, f32::max)
+    }
+
+    /// Applies `f` to the default brush and every override
+    pub(crate) fn map(&self, f: impl Fn(Brush) -> Brush) -> Self {
+        Self {
+            default: f(self.default.clone()),
+            overrides: self
+                .overrides
+                .iter()
+                .map(|(contour, brush)| (*contour, f(brush.clone())))
+                .collect(),
+        }
+    }
+
     #[inline]
     pub fn get_or_default(&self, contour: &Contour) -> Brush {
-        self.overrides
-            .get(contour)
-            .cloned()
-            .unwrap_or(self.default.clone())
+        self.get(contour).unwrap_or(&self.default).clone()
     }
 }
 
@@ -523,4 +731,27 @@ mod tests {
         assert_eq!(brush.get_or_default(&leg_r_build), leg_paint);
         assert_eq!(brush.get_or_default(&head_build), head_paint);
     }
+
+    #[test]
+    fn path_brush_chaining() {
+        let mut path = PathBuilder::default();
+        let a = path.circle(vec2(0.0, 0.0), 10.0);
+        let b = path.circle(vec2(30.0, 0.0), 10.0);
+
+        let fill = Brush::filled(Color::RED);
+        let stroke = Brush::default().stroke_color(Color::BLUE);
+
+        let mut brush = PathBrush::default()
+            .with_default(fill.clone())
+            .with(b, stroke.clone());
+
+        assert_eq!(brush.get(&a), None);
+        assert_eq!(brush.get_or_default(&a), fill);
+        assert_eq!(brush.get_or_default(&b), stroke);
+
+        brush.set_all(stroke.clone());
+        assert_eq!(brush.get(&b), None);
+        assert_eq!(brush.get_or_default(&a), stroke);
+        assert_eq!(brush.default_brush(), &stroke);
+    }
 }
@@ -1,3 +1,5 @@
+use skie_math::Lerp;
+
 // TODO: add bytemuck_feature
 #[derive(Clone, Copy, Eq, PartialEq, Hash, bytemuck::Pod, bytemuck::Zeroable)]
 #[repr(C)]
@@ -166,6 +168,18 @@ impl std::ops::IndexMut<usize> for Color {
     }
 }
 
+impl Lerp for Color {
+    fn lerp(&self, to: &Self, t: f32) -> Self {
+        let channel = |from: u8, to: u8| (from as f32).lerp(&(to as f32), t).round() as u8;
+        Self {
+            r: channel(self.r, to.r),
+            g: channel(self.g, to.g),
+            b: channel(self.b, to.b),
+            a: channel(self.a, to.a),
+        }
+    }
+}
+
 #[derive(PartialEq, Clone, Copy, Default, bytemuck::Pod, bytemuck::Zeroable)]
 #[repr(C)]
 pub struct Rgba {
@@ -213,6 +227,17 @@ impl From<Rgba> for wgpu::Color {
     }
 }
 
+impl Lerp for Rgba {
+    fn lerp(&self, to: &Self, t: f32) -> Self {
+        Self {
+            r: self.r.lerp(&to.r, t),
+            g: self.g.lerp(&to.g, t),
+            b: self.b.lerp(&to.b, t),
+            a: self.a.lerp(&to.a, t),
+        }
+    }
+}
+
 impl Rgba {
     pub const TRANSPARENT: Self = Rgba {
         r: 0.0,
@@ -252,14 +277,14 @@ impl Rgba {
         if other.a >= 1.0 {
             other
         } else if other.a <= 0.0 {
-            return *self;
+            *self
         } else {
-            return Rgba {
+            Rgba {
                 r: (self.r * (1.0 - other.a)) + (other.r * other.a),
                 g: (self.g * (1.0 - other.a)) + (other.g * other.a),
                 b: (self.b * (1.0 - other.a)) + (other.b * other.a),
                 a: self.a,
-            };
+            }
         }
     }
 }
@@ -5,7 +5,9 @@ use std::ops::Range;
 use skie_math::IsZero;
 
 use super::{
-    Brush, Circle, Color, FillStyle, Mesh, PathBrush, Primitive, Quad, StrokeTesellator, Vertex,
+    add_quad_borders, apply_gradient, apply_pattern, Antialias, Brush, Circle, CircleArc, Color,
+    Ellipse, FillStyle, Gradient, Mesh, PathBrush, Pattern, Pie, PointShape, Points, Polyline,
+    Primitive, Quad, Ring, StrokeTesellator, TextureMapping, Vertex,
 };
 
 use crate::earcut::Earcut;
@@ -42,22 +44,90 @@ impl DerefMut for ScratchPathBuilder {
     }
 }
 
-#[derive(Default)]
 pub struct DrawList {
+    pub(crate) antialias: bool,
     pub(crate) feathering: f32,
+    pub(crate) tolerance: f32,
+    pub(crate) hairline_width: f32,
     pub(crate) mesh: Mesh,
     pub(crate) temp_path: ScratchPathBuilder,
     pub(crate) temp_path_data: Vec<Point>,
+    /// see [`DrawList::fill_epsilon`]
+    fill_epsilon: f32,
+    /// path contour points left for filling by [`filter_degenerate_fill`]
+    temp_fill_points: Vec<Point>,
     earcut: Earcut<f32>,
+    /// cleared meshes whose buffers the next builds reuse
+    spare_meshes: Vec<Mesh>,
+}
+
+impl Default for DrawList {
+    fn default() -> Self {
+        Self {
+            antialias: false,
+            feathering: DEFAULT_FEATHERING,
+            tolerance: DEFAULT_TOLERANCE,
+            hairline_width: 1.0,
+            mesh: Default::default(),
+            temp_path: Default::default(),
+            temp_path_data: Default::default(),
+            fill_epsilon: DEFAULT_FILL_EPSILON,
+            temp_fill_points: Default::default(),
+            earcut: Default::default(),
+            spare_meshes: Default::default(),
+        }
+    }
 }
 
 impl DrawList {
+    /// Sets whether brushes without their own antialias setting are feathered, returns the old value
+    pub fn antialias(&mut self, value: bool) -> bool {
+        let old = self.antialias;
+        self.antialias = value;
+        old
+    }
+
+    /// Sets the local width [`Antialias::On`] edges fade out over, returns the old value
     pub fn feathering(&mut self, value: f32) -> f32 {
         let old = self.feathering;
         self.feathering = value;
         old
     }
 
+    /// Feathering to build the brush's fills with
+    fn feathering_for(&self, brush: &Brush) -> f32 {
+        brush_feathering(brush, self.antialias, self.feathering, self.hairline_width)
+    }
+
+    /// Sets the max distance in pixels between curves and their flattened geometry, returns the old value
+    pub fn tolerance(&mut self, value: f32) -> f32 {
+        let old = self.tolerance;
+        self.tolerance = value;
+        old
+    }
+
+    /// Sets the distance under which path points count as repeated before filling, returns
+    /// the old value. Contours left with less than 3 points, or thinner than it on average,
+    /// are not filled. Strokes are unaffected
+    pub fn fill_epsilon(&mut self, value: f32) -> f32 {
+        let old = self.fill_epsilon;
+        self.fill_epsilon = value.max(0.0);
+        old
+    }
+
+    /// Sets the local width that covers one physical pixel, used for hairline strokes and
+    /// [`Antialias::Feather`] widths. Returns the old value
+    pub fn hairline_width(&mut self, value: f32) -> f32 {
+        let old = self.hairline_width;
+        self.hairline_width = value;
+        old
+    }
+
+    /// Width to stroke the brush with
+    fn stroke_width(&self, brush: &Brush) -> f32 {
+        stroke_width(brush, self.hairline_width)
+    }
+
     pub fn clear(&mut self) {
         self.mesh.clear();
         self.temp_path.clear();
@@ -92,16 +162,21 @@ impl DrawList {
     }
 
     pub fn add_quad(&mut self, quad: &Quad, brush: &Brush, textured: bool) {
+        let feathering = self.feathering_for(brush);
+        let line_width = self.stroke_width(brush);
         let fill_color = brush.fill_style.color;
-        let stroke_color = brush.stroke_style.color;
 
         self.temp_path.clear();
         self.temp_path_data.clear();
 
         let no_round = quad.corners.is_zero();
+        let smooth = !no_round && quad.smoothing > 0.0;
 
         if no_round {
             self.temp_path.rect(&quad.bounds);
+        } else if smooth {
+            self.temp_path
+                .squircle(&quad.bounds, &quad.corners, quad.smoothing);
         } else {
             self.temp_path.round_rect(&quad.bounds, &quad.corners);
         }
@@ -109,28 +184,55 @@ impl DrawList {
         build_path_single_contour(
             self.temp_path.path_events(),
             &mut self.temp_path_data,
+            self.tolerance,
             |path| {
                 fill_path_convex(
                     &mut self.mesh,
-                    if no_round {
-                        &path[..path.len() - 1]
-                    } else {
-                        &path[..path.len() - 2]
-                    },
+                    &mut self.earcut,
+                    // the closing point repeats the first one
+                    &path[..path.len() - 1],
                     fill_color,
-                    textured,
-                    brush.feathering,
-                    (!stroke_color.is_transparent()).then_some(stroke_color),
+                    textured.then_some(&brush.fill_style.texture_mapping),
+                    feathering,
+                    stroke_fade(brush),
+                );
+                StrokeTesellator::add_to_mesh_with_width(
+                    &mut self.mesh,
+                    path,
+                    &brush.stroke_style,
+                    line_width,
                 );
-                StrokeTesellator::add_to_mesh(&mut self.mesh, path, &brush.stroke_style);
             },
         );
+
+        if let Some(borders) = &quad.borders {
+            add_quad_borders(&mut self.mesh, &quad.bounds, &quad.corners, borders);
+        }
     }
 
     pub fn add_circle(&mut self, circle: &Circle, brush: &Brush, textured: bool) {
+        let feathering = self.feathering_for(brush);
         let fill_color = brush.fill_style.color;
         let stroke_color = brush.stroke_style.color;
 
+        let line_width = self.stroke_width(brush);
+
+        // stroke only circles dont need the path
+        if fill_color.is_transparent() {
+            let half_width = line_width / 2.0;
+            fill_annulus(
+                &mut self.mesh,
+                circle.center,
+                circle.radius - half_width,
+                circle.radius + half_width,
+                stroke_color,
+                feathering,
+                None,
+                self.tolerance,
+            );
+            return;
+        }
+
         self.temp_path.clear();
         self.temp_path_data.clear();
 
@@ -139,43 +241,426 @@ impl DrawList {
         build_path_single_contour(
             self.temp_path.path_events(),
             &mut self.temp_path_data,
+            self.tolerance,
             |path| {
                 fill_path_convex(
                     &mut self.mesh,
+                    &mut self.earcut,
                     &path[0..path.len() - 2],
                     fill_color,
-                    textured,
-                    brush.feathering,
-                    (!stroke_color.is_transparent()).then_some(stroke_color),
+                    textured.then_some(&brush.fill_style.texture_mapping),
+                    feathering,
+                    stroke_fade(brush),
+                );
+                StrokeTesellator::add_to_mesh_with_width(
+                    &mut self.mesh,
+                    path,
+                    &brush.stroke_style,
+                    line_width,
                 );
-                StrokeTesellator::add_to_mesh(&mut self.mesh, path, &brush.stroke_style);
             },
         );
     }
 
-    pub fn add_path(&mut self, path: &Path, brush: &PathBrush) {
+    pub fn add_ring(&mut self, ring: &Ring, brush: &Brush) {
+        let feathering = self.feathering_for(brush);
+        let inner = ring.inner_radius.min(ring.outer_radius);
+        let outer = ring.inner_radius.max(ring.outer_radius);
+
+        fill_annulus(
+            &mut self.mesh,
+            ring.center,
+            inner,
+            outer,
+            brush.fill_style.color,
+            feathering,
+            stroke_fade(brush),
+            self.tolerance,
+        );
+
+        let half_width = self.stroke_width(brush) / 2.0;
+        for radius in [inner, outer] {
+            fill_annulus(
+                &mut self.mesh,
+                ring.center,
+                radius - half_width,
+                radius + half_width,
+                brush.stroke_style.color,
+                feathering,
+                None,
+                self.tolerance,
+            );
+        }
+    }
+
+    pub fn add_pie(&mut self, pie: &Pie, brush: &Brush) {
+        let feathering = self.feathering_for(brush);
+        let sweep = pie.sweep.clamp(-f32::consts::TAU, f32::consts::TAU);
+        let inner = pie.inner_radius.clamp(0.0, pie.radius.abs());
+        let outer = pie.radius.abs();
+
+        if outer <= 0.0 || sweep == 0.0 {
+            return;
+        }
+
+        if sweep.abs() >= f32::consts::TAU && inner > 0.0 {
+            return self.add_ring(
+                &Ring {
+                    center: pie.center,
+                    inner_radius: inner,
+                    outer_radius: outer,
+                },
+                brush,
+            );
+        }
+
+        self.temp_path_data.clear();
+        add_pie_outline(
+            &mut self.temp_path_data,
+            pie.center,
+            inner,
+            outer,
+            pie.start,
+            sweep,
+            self.tolerance,
+        );
+
+        fill_polygon(
+            &mut self.mesh,
+            &mut self.earcut,
+            &self.temp_path_data,
+            brush.fill_style.color,
+            feathering,
+            stroke_fade(brush),
+        );
+
+        if !brush.stroke_style.color.is_transparent() {
+            if let Some(first) = self.temp_path_data.first().copied() {
+                self.temp_path_data.push(first);
+            }
+            let line_width = self.stroke_width(brush);
+            StrokeTesellator::add_to_mesh_with_width(
+                &mut self.mesh,
+                &self.temp_path_data,
+                &brush.stroke_style,
+                line_width,
+            );
+        }
+    }
+
+    pub fn add_ellipse(&mut self, ellipse: &Ellipse, brush: &Brush, textured: bool) {
+        let (rx, ry) = (ellipse.radii.x.abs(), ellipse.radii.y.abs());
+        if rx <= 0.0 || ry <= 0.0 {
+            return;
+        }
+
+        let segments = circle_segments(rx.max(ry), self.tolerance);
+        self.temp_path_data.clear();
+        self.temp_path_data.extend(
+            (0..segments).map(|i| ellipse.point_at(f32::consts::TAU * i as f32 / segments as f32)),
+        );
+
+        self.add_outline(brush, textured, true);
+    }
+
+    /// Fills the segment between the arc and its chord and strokes the arc
+    pub fn add_arc(&mut self, arc: &CircleArc, brush: &Brush, textured: bool) {
+        let radius = arc.radius.abs();
+        let sweep = arc.sweep.clamp(-f32::consts::TAU, f32::consts::TAU);
+        if radius <= 0.0 || sweep == 0.0 {
+            return;
+        }
+
+        let full = sweep.abs() >= f32::consts::TAU;
+        let segments = ((circle_segments(radius, self.tolerance) as f32 * sweep.abs()
+            / f32::consts::TAU)
+            .ceil() as usize)
+            .max(1);
+        // a full circle would repeat the first point
+        let last = if full { segments - 1 } else { segments };
+
+        self.temp_path_data.clear();
+        self.temp_path_data.extend((0..=last).map(|i| {
+            let angle = arc.start + sweep * (i as f32 / segments as f32);
+            arc.center + Vec2::new(angle.cos(), angle.sin()) * radius
+        }));
+
+        self.add_outline(brush, textured, full);
+    }
+
+    /// Fills the convex outline in `temp_path_data` and strokes it, closing the stroke
+    /// when `closed`
+    fn add_outline(&mut self, brush: &Brush, textured: bool, closed: bool) {
+        let feathering = self.feathering_for(brush);
+        fill_path_convex(
+            &mut self.mesh,
+            &mut self.earcut,
+            &self.temp_path_data,
+            brush.fill_style.color,
+            textured.then_some(&brush.fill_style.texture_mapping),
+            feathering,
+            stroke_fade(brush),
+        );
+
+        if brush.stroke_style.color.is_transparent() {
+            return;
+        }
+        if closed {
+            if let Some(first) = self.temp_path_data.first().copied() {
+                self.temp_path_data.push(first);
+            }
+        }
+        let line_width = self.stroke_width(brush);
+        StrokeTesellator::add_to_mesh_with_width(
+            &mut self.mesh,
+            &self.temp_path_data,
+            &brush.stroke_style,
+            line_width,
+        );
+    }
+
+    /// Strokes the polyline with its own widths, see [`Polyline`]
+    pub fn add_polyline(&mut self, polyline: &Polyline, brush: &Brush) {
+        if brush.stroke_style.color.is_transparent() {
+            return;
+        }
+        StrokeTesellator::add_to_mesh_with_widths(
+            &mut self.mesh,
+            &polyline.points,
+            &polyline.widths,
+            &brush.stroke_style,
+        );
+    }
+
+    /// Fills a marker at each point, see [`Points`]
+    pub fn add_points(&mut self, points: &Points, brush: &Brush) {
+        let fill = brush.fill_style.color;
+        if fill.is_transparent() || points.size <= 0.0 || points.points.is_empty() {
+            return;
+        }
+        let feathering = self.feathering_for(brush);
+        let half = points.size / 2.0;
+
+        // the marker around the origin, moved to each point
+        let shape: Vec<Point> = match points.shape {
+            PointShape::Circle => {
+                let segments = circle_segments(half, self.tolerance);
+                (0..segments)
+                    .map(|i| {
+                        let angle = f32::consts::TAU * i as f32 / segments as f32;
+                        Vec2::new(angle.cos(), angle.sin()) * half
+                    })
+                    .collect()
+            }
+            PointShape::Square => vec![
+                Vec2::new(-half, -half),
+                Vec2::new(half, -half),
+                Vec2::new(half, half),
+                Vec2::new(-half, half),
+            ],
+            PointShape::Cross => {
+                let arm = points.cross_half_width();
+                [
+                    (-arm, -half),
+                    (arm, -half),
+                    (arm, -arm),
+                    (half, -arm),
+                    (half, arm),
+                    (arm, arm),
+                    (arm, half),
+                    (-arm, half),
+                    (-arm, arm),
+                    (-half, arm),
+                    (-half, -arm),
+                    (-arm, -arm),
+                ]
+                .map(|(x, y)| Vec2::new(x, y))
+                .to_vec()
+            }
+        };
+
+        let mut marker = std::mem::take(&mut self.temp_fill_points);
+        for center in &points.points {
+            marker.clear();
+            marker.extend(shape.iter().map(|point| *point + *center));
+            fill_path_convex(
+                &mut self.mesh,
+                &mut self.earcut,
+                &marker,
+                fill,
+                None,
+                feathering,
+                None,
+            );
+        }
+        self.temp_fill_points = marker;
+    }
+
+    /// Fills and strokes `path`. `textured` fills map the texture over the whole path with
+    /// the [`TextureMapping`] of each contour's brush
+    pub fn add_path(&mut self, path: &Path, brush: &PathBrush, textured: bool) {
+        let (antialias, default_feathering) = (self.antialias, self.feathering);
+        let (hairline_width, fill_epsilon) = (self.hairline_width, self.fill_epsilon);
+        let bounds = if textured {
+            get_path_bounds(&path.points)
+        } else {
+            Default::default()
+        };
+
         self.temp_path_data.clear();
         build_path(
             path.events(),
             &mut self.temp_path_data,
+            self.tolerance,
             brush,
             |brush, points| {
-                Self::fill_earcut(points, &mut self.mesh, &mut self.earcut, &brush.fill_style);
-                StrokeTesellator::add_to_mesh(&mut self.mesh, points, &brush.stroke_style);
+                let feathering =
+                    brush_feathering(brush, antialias, default_feathering, hairline_width);
+                let gradient = brush.fill_style.gradient.as_ref();
+                let pattern = brush.fill_style.pattern.as_ref();
+                // gradients are filled white and colored afterwards
+                let (fill_style, fade_to) = match (gradient, pattern) {
+                    (Some(_), _) => (FillStyle::default().color(Color::WHITE), None),
+                    (None, Some(_)) => (brush.fill_style.clone(), None),
+                    (None, None) => (brush.fill_style.clone(), stroke_fade(brush)),
+                };
+                let (first_vertex, first_index) =
+                    (self.mesh.vertices.len(), self.mesh.indices.len());
+
+                let fill_points = &mut self.temp_fill_points;
+                let visible = !fill_style.color.is_transparent();
+                if let Err(reason) = filter_degenerate_fill(points, fill_epsilon, fill_points) {
+                    if visible {
+                        log::debug!(
+                            "add_path: skipped filling a contour of {} points, {}",
+                            points.len(),
+                            reason
+                        );
+                    }
+                } else if feathering > 0.0 {
+                    fill_polygon(
+                        &mut self.mesh,
+                        &mut self.earcut,
+                        fill_points,
+                        fill_style.color,
+                        feathering,
+                        fade_to,
+                    );
+                } else {
+                    Self::fill_earcut(fill_points, &mut self.mesh, &mut self.earcut, &fill_style);
+                }
+
+                if let Some(gradient) = gradient {
+                    apply_gradient(&mut self.mesh, first_vertex, first_index, gradient);
+                } else if let Some(pattern) = pattern {
+                    // the stroke samples the white texture, see `add_pattern_primitive`
+                    apply_pattern(&mut self.mesh, first_vertex, first_index, pattern);
+                    return;
+                } else if textured {
+                    map_uv(
+                        &mut self.mesh.vertices[first_vertex..],
+                        &brush.fill_style.texture_mapping,
+                        &bounds,
+                    );
+                }
+                StrokeTesellator::add_to_mesh_with_width(
+                    &mut self.mesh,
+                    points,
+                    &brush.stroke_style,
+                    stroke_width(brush, hairline_width),
+                );
             },
         );
     }
 
     pub fn add_primitive(&mut self, primitive: &Primitive, brush: &Brush, textured: bool) {
+        if let (Some(pattern), false) = (
+            &brush.fill_style.pattern,
+            matches!(primitive, Primitive::Path { .. }),
+        ) {
+            return self.add_pattern_primitive(primitive, brush, pattern);
+        }
+
+        if let (Some(gradient), false) = (
+            &brush.fill_style.gradient,
+            matches!(primitive, Primitive::Path { .. }),
+        ) {
+            return self.add_gradient_primitive(primitive, brush, gradient, textured);
+        }
+
         match primitive {
             Primitive::Circle(circle) => self.add_circle(circle, brush, textured),
 
             Primitive::Quad(quad) => self.add_quad(quad, brush, textured),
 
-            Primitive::Path { path, brush } => self.add_path(path, brush),
+            Primitive::Ring(ring) => self.add_ring(ring, brush),
+
+            Primitive::Pie(pie) => self.add_pie(pie, brush),
+
+            Primitive::Ellipse(ellipse) => self.add_ellipse(ellipse, brush, textured),
+
+            Primitive::Arc(arc) => self.add_arc(arc, brush, textured),
+
+            Primitive::Polyline(polyline) => self.add_polyline(polyline, brush),
+
+            Primitive::Points(points) => self.add_points(points, brush),
+
+            Primitive::Path { path, brush } => self.add_path(path, brush, textured),
         };
     }
 
+    /// Fills white without the stroke and colors the fill with the gradient,
+    /// the stroke and quad borders are added on top afterwards
+    fn add_gradient_primitive(
+        &mut self,
+        primitive: &Primitive,
+        brush: &Brush,
+        gradient: &Gradient,
+        textured: bool,
+    ) {
+        let fill = match primitive {
+            Primitive::Quad(quad) if quad.borders.is_some() => Primitive::Quad(Quad {
+                borders: None,
+                ..quad.clone()
+            }),
+            _ => primitive.clone(),
+        };
+
+        let (first_vertex, first_index) = (self.mesh.vertices.len(), self.mesh.indices.len());
+        self.add_primitive(
+            &fill,
+            &brush.clone().no_stroke().fill_color(Color::WHITE),
+            textured,
+        );
+        apply_gradient(&mut self.mesh, first_vertex, first_index, gradient);
+
+        let stroke = brush.clone().no_fill();
+        if !stroke.noting_to_draw()
+            || matches!(primitive, Primitive::Quad(q) if q.borders.is_some())
+        {
+            self.add_primitive(primitive, &stroke, textured);
+        }
+    }
+
+    /// Adds only the fill mapped to the pattern texture. The stroke and quad borders sample the
+    /// white texture so they go into another mesh, [`crate::Canvas`] draws them separately
+    fn add_pattern_primitive(&mut self, primitive: &Primitive, brush: &Brush, pattern: &Pattern) {
+        let fill = match primitive {
+            Primitive::Quad(quad) if quad.borders.is_some() => Primitive::Quad(Quad {
+                borders: None,
+                ..quad.clone()
+            }),
+            _ => primitive.clone(),
+        };
+
+        let mut fill_brush = brush.clone().no_stroke();
+        fill_brush.fill_style.pattern = None;
+
+        let (first_vertex, first_index) = (self.mesh.vertices.len(), self.mesh.indices.len());
+        self.add_primitive(&fill, &fill_brush, false);
+        apply_pattern(&mut self.mesh, first_vertex, first_index, pattern);
+    }
+
     fn fill_earcut(
         points: &[Vec2<f32>],
         mesh: &mut Mesh,
@@ -248,8 +733,25 @@ impl DrawList {
             .add_triangle_fan(color, connect_to, origin, start, end, clockwise);
     }
 
+    /// Takes the built mesh, the list continues with a recycled one if there is any
     pub fn build(&mut self) -> Mesh {
-        std::mem::take(&mut self.mesh)
+        let spare = self.spare_meshes.pop().unwrap_or_default();
+        std::mem::replace(&mut self.mesh, spare)
+    }
+
+    /// Hands back meshes that are no longer used, so the next [`DrawList::build`]s reuse
+    /// their buffers instead of allocating new ones
+    pub fn recycle(&mut self, meshes: impl IntoIterator<Item = Mesh>) {
+        self.spare_meshes.extend(
+            meshes
+                .into_iter()
+                .filter(|mesh| mesh.vertices.capacity() > 0)
+                .map(|mut mesh| {
+                    mesh.clear();
+                    mesh.texture = Default::default();
+                    mesh
+                }),
+        );
     }
 }
 
@@ -268,10 +770,14 @@ impl<'a> DrawListCapture<'a> {
 pub fn build_path(
     iter: PathEventsIter,
     output: &mut Vec<Point>,
+    tolerance: f32,
     brush: &PathBrush,
     mut f: impl FnMut(&Brush, &[Point]),
 ) {
-    let geo_build = <PathGeometryBuilder<PathEventsIter>>::new(iter, output).collect::<Vec<_>>();
+    let geo_build = <PathGeometryBuilder<PathEventsIter>>::new(iter, output)
+        .tolerance(tolerance)
+        .stroke_width(brush.max_stroke_width())
+        .collect::<Vec<_>>();
 
     for (contour, range) in geo_build {
         let this_brush = brush.get_or_default(&contour);
@@ -283,9 +789,13 @@ pub fn build_path(
 pub fn build_path_single_contour(
     iter: PathEventsIter,
     output: &mut Vec<Point>,
+    tolerance: f32,
     mut f: impl FnMut(&[Point]),
 ) {
-    if let Some((_, range)) = <PathGeometryBuilder<PathEventsIter>>::new(iter, output).next() {
+    if let Some((_, range)) = <PathGeometryBuilder<PathEventsIter>>::new(iter, output)
+        .tolerance(tolerance)
+        .next()
+    {
         f(&output[range])
     } else {
         log::warn!("build_path_single_contour called with path with no contour!");
@@ -297,13 +807,17 @@ thread_local! {
 
 }
 
+/// Fills `path` as a triangle fan. Reversed outlines are fanned the same way, anything
+/// that turns out not to be convex is handed to earcut instead
+#[allow(clippy::too_many_arguments)]
 fn fill_path_convex(
     mesh: &mut Mesh,
+    earcut: &mut Earcut<f32>,
     path: &[Point],
     fill: Color,
-    textured: bool,
+    mapping: Option<&TextureMapping>,
     feathering: f32,
-    _fade_to: Option<Color>,
+    fade_to: Option<Color>,
 ) {
     let points_count = path.len() as u32;
 
@@ -311,33 +825,33 @@ fn fill_path_convex(
         return;
     }
 
-    debug_assert!(cw_signed_area(path) > 0.0);
-
-    let bounds = if textured {
-        get_path_bounds(path)
-    } else {
-        Default::default()
+    let bounds = match mapping {
+        Some(mapping) if mapping.needs_bounds() => get_path_bounds(path),
+        _ => Default::default(),
     };
 
-    let min = bounds.min();
-    let max = bounds.max();
+    let winding = match convex_winding(path) {
+        Some(winding) => winding,
+        None => {
+            let first_vertex = mesh.vertices.len();
+            fill_polygon(mesh, earcut, path, fill, feathering, fade_to);
+            if let Some(mapping) = mapping {
+                map_uv(&mut mesh.vertices[first_vertex..], mapping, &bounds);
+            }
+            return;
+        }
+    };
 
-    let get_uv = |point: &Vec2<f32>| {
-        if textured {
-            let uv_x = (point.x - min.x) / (max.x - min.x);
-            let uv_y = (point.y - min.y) / (max.y - min.y);
-            (uv_x, uv_y)
-        } else {
-            WHITE_UV
+    let get_uv = |point: &Vec2<f32>| match mapping {
+        Some(mapping) => {
+            let uv = mapping.uv_at(*point, &bounds);
+            (uv.x, uv.y)
         }
+        None => WHITE_UV,
     };
 
     if feathering > 0.0 {
-        let out_color = _fade_to.unwrap_or_else(|| {
-            let mut c = fill;
-            c.a = 0;
-            c
-        });
+        let out_color = fade_color(fill, fade_to);
 
         mesh.reserve_prim(2 * points_count as usize, 3 * points_count as usize);
 
@@ -357,7 +871,7 @@ fn fill_path_convex(
             for i1 in 0..points_count {
                 let p0 = path[i0 as usize];
                 let p1 = path[i1 as usize];
-                let edge = (p1 - p0).normalize().rot90();
+                let edge = (p1 - p0).normalize().rot90() * winding;
                 normals.push(edge);
                 i0 = i1;
             }
@@ -402,6 +916,362 @@ fn fill_path_convex(
     }
 }
 
+/// Number of segments needed to keep a circle within `tolerance` pixels of the true curve
+#[inline]
+pub(crate) fn circle_segments(radius: f32, tolerance: f32) -> usize {
+    if radius <= tolerance {
+        return 8;
+    }
+
+    let angle = (1.0 - tolerance / radius).acos();
+    ((f32::consts::PI / angle).ceil() as usize).clamp(8, 256)
+}
+
+/// Default [`DrawList::tolerance`]
+pub const DEFAULT_TOLERANCE: f32 = 0.25;
+
+/// Default [`DrawList::feathering`], the width antialiased edges fade out over
+pub const DEFAULT_FEATHERING: f32 = 1.0;
+
+/// Default [`DrawList::fill_epsilon`]
+pub const DEFAULT_FILL_EPSILON: f32 = 1e-3;
+
+/// Copies `points` into `out` without points closer than `epsilon` to the previous one.
+/// Fails with the reason when the rest can't be filled without slivers
+fn filter_degenerate_fill(
+    points: &[Point],
+    epsilon: f32,
+    out: &mut Vec<Point>,
+) -> Result<(), &'static str> {
+    out.clear();
+    for point in points {
+        if !point.x.is_finite() || !point.y.is_finite() {
+            return Err("it has non finite points");
+        }
+        if out
+            .last()
+            .is_none_or(|last| (*point - *last).magnitude() > epsilon)
+        {
+            out.push(*point);
+        }
+    }
+    // closed contours can repeat the first point at the end
+    while out.len() > 1 && (out[0] - out[out.len() - 1]).magnitude() <= epsilon {
+        out.pop();
+    }
+
+    if out.len() < 3 {
+        return Err("less than 3 distinct points");
+    }
+
+    // unsigned so the lobes of self intersecting contours dont cancel out
+    let first = out[0];
+    let area: f32 = out[1..]
+        .windows(2)
+        .map(|edge| ((edge[0] - first).cross(&(edge[1] - first)) * 0.5).abs())
+        .sum();
+    let perimeter: f32 = out
+        .iter()
+        .zip(out.iter().cycle().skip(1))
+        .map(|(a, b)| (*b - *a).magnitude())
+        .sum();
+    if area <= epsilon * perimeter {
+        return Err("it encloses no area");
+    }
+
+    Ok(())
+}
+
+fn stroke_width(brush: &Brush, hairline_width: f32) -> f32 {
+    if brush.stroke_style.hairline {
+        hairline_width
+    } else {
+        brush.stroke_style.width()
+    }
+}
+
+/// Outline of a wedge (or ring segment) with a positive clockwise winding
+fn add_pie_outline(
+    output: &mut Vec<Point>,
+    center: Point,
+    inner_radius: f32,
+    outer_radius: f32,
+    start: f32,
+    sweep: f32,
+    tolerance: f32,
+) {
+    let (start, sweep) = if sweep < 0.0 {
+        (start + sweep, -sweep)
+    } else {
+        (start, sweep)
+    };
+
+    let full = sweep >= f32::consts::TAU;
+    let arc_segments =
+        ((circle_segments(outer_radius, tolerance) as f32 * sweep / f32::consts::TAU).ceil()
+            as usize)
+            .max(1);
+
+    let arc_point = |radius: f32, i: usize| {
+        let angle = start + sweep * (i as f32 / arc_segments as f32);
+        center + Vec2::new(angle.cos(), angle.sin()) * radius
+    };
+
+    if full {
+        // the last point would be the same as the first
+        output.extend((0..arc_segments).map(|i| arc_point(outer_radius, i)));
+        return;
+    }
+
+    output.extend((0..=arc_segments).map(|i| arc_point(outer_radius, i)));
+
+    if inner_radius > 0.0 {
+        output.extend((0..=arc_segments).rev().map(|i| arc_point(inner_radius, i)));
+    } else {
+        output.push(center);
+    }
+}
+
+/// `pixel` is the local width of one physical pixel
+fn brush_feathering(brush: &Brush, antialias: bool, default_feathering: f32, pixel: f32) -> f32 {
+    match brush.antialias {
+        Antialias::Off => 0.0,
+        Antialias::Feather(width) => width.max(0.0) * pixel,
+        Antialias::On => default_feathering,
+        Antialias::Inherit if antialias => default_feathering,
+        Antialias::Inherit => 0.0,
+    }
+}
+
+/// The stroke color when the brush has a visible stroke
+#[inline]
+fn stroke_fade(brush: &Brush) -> Option<Color> {
+    let stroke = brush.stroke_style.color;
+    (!stroke.is_transparent()).then_some(stroke)
+}
+
+/// Color the feathered edge of a `fill` fades to, the adjacent stroke when there is one
+/// so no transparent seam shows between the fill and the stroke
+#[inline]
+fn fade_color(fill: Color, fade_to: Option<Color>) -> Color {
+    fade_to.unwrap_or_else(|| {
+        let mut fade = fill;
+        fade.a = 0;
+        fade
+    })
+}
+
+/// Fills a simple (possibly concave) polygon. With feathering the edges are faded out
+/// across `feathering` pixels, half inside and half outside the outline
+fn fill_polygon(
+    mesh: &mut Mesh,
+    earcut: &mut Earcut<f32>,
+    points: &[Point],
+    color: Color,
+    feathering: f32,
+    fade_to: Option<Color>,
+) {
+    let count = points.len() as u32;
+    if count < 3 || color.is_transparent() {
+        return;
+    }
+
+    let winding = if cw_signed_area(points) >= 0.0 {
+        1.0
+    } else {
+        -1.0
+    };
+
+    if feathering <= 0.0 {
+        let vertex_offset = mesh.vertex_count();
+        let index_offset = mesh.indices.len();
+
+        earcut.earcut(
+            points.iter().map(|p| [p.x, p.y]),
+            &[],
+            &mut mesh.indices,
+            false,
+        );
+
+        for point in points {
+            mesh.add_vertex(*point, color, WHITE_UV);
+        }
+
+        for i in &mut mesh.indices[index_offset..] {
+            *i += vertex_offset;
+        }
+        return;
+    }
+
+    let fade = fade_color(color, fade_to);
+
+    // repeated points have no edge normal
+    let points = TEMP_BUFFER.with_borrow_mut(|unique| {
+        unique.clear();
+        unique.extend_from_slice(points);
+        unique.dedup();
+        if unique.len() > 1 && unique.first() == unique.last() {
+            unique.pop();
+        }
+        std::mem::take(unique)
+    });
+
+    let count = points.len() as u32;
+    if count < 3 {
+        TEMP_BUFFER.set(points);
+        return;
+    }
+
+    let half_feather = feathering * 0.5;
+    let base = mesh.vertex_count();
+    mesh.reserve_prim(count as usize * 2, count as usize * 9);
+
+    // inner and outer vertices are interleaved
+    for i in 0..points.len() {
+        let prev = points[(i + points.len() - 1) % points.len()];
+        let current = points[i];
+        let next = points[(i + 1) % points.len()];
+
+        let n0 = (current - prev).normalize().rot90() * winding;
+        let n1 = (next - current).normalize().rot90() * winding;
+
+        // miter offset, limited so that sharp corners dont spike
+        let normal = (n0 + n1).normalize();
+        let miter = 1.0 / normal.dot(&n0).max(0.25);
+        let dm = normal * (half_feather * miter);
+
+        mesh.add_vertex(current - dm, color, WHITE_UV);
+        mesh.add_vertex(current + dm, fade, WHITE_UV);
+    }
+    TEMP_BUFFER.set(points);
+
+    let index_offset = mesh.indices.len();
+    earcut.earcut(
+        mesh.vertices[base as usize..]
+            .iter()
+            .step_by(2)
+            .map(|v| v.position),
+        &[],
+        &mut mesh.indices,
+        false,
+    );
+
+    // earcut indexes the inner vertices only
+    for i in &mut mesh.indices[index_offset..] {
+        *i = base + *i * 2;
+    }
+
+    for i0 in 0..count {
+        let i1 = (i0 + 1) % count;
+        let (inner0, outer0) = (base + i0 * 2, base + i0 * 2 + 1);
+        let (inner1, outer1) = (base + i1 * 2, base + i1 * 2 + 1);
+
+        mesh.add_triangle(inner0, outer0, inner1);
+        mesh.add_triangle(inner1, outer0, outer1);
+    }
+}
+
+/// Emits a triangle strip between two concentric circles. With feathering the edges
+/// fade out to `fade_to` or transparent on both sides
+#[allow(clippy::too_many_arguments)]
+fn fill_annulus(
+    mesh: &mut Mesh,
+    center: Point,
+    inner_radius: f32,
+    outer_radius: f32,
+    color: Color,
+    feathering: f32,
+    fade_to: Option<Color>,
+    tolerance: f32,
+) {
+    let inner_radius = inner_radius.max(0.0);
+    if color.is_transparent() || outer_radius <= inner_radius {
+        return;
+    }
+
+    let segments = circle_segments(outer_radius, tolerance) as u32;
+
+    let fade = fade_color(color, fade_to);
+
+    let half_feather = feathering * 0.5;
+    let rings: &[(f32, Color)] = if feathering > 0.0 {
+        &[
+            ((inner_radius - half_feather).max(0.0), fade),
+            (inner_radius + half_feather, color),
+            (outer_radius - half_feather, color),
+            (outer_radius + half_feather, fade),
+        ]
+    } else {
+        &[(inner_radius, color), (outer_radius, color)]
+    };
+
+    let ring_count = rings.len() as u32;
+    let base = mesh.vertex_count();
+    mesh.reserve_prim(
+        (segments * ring_count) as usize,
+        (segments * (ring_count - 1) * 6) as usize,
+    );
+
+    for i in 0..segments {
+        let angle = f32::consts::TAU * i as f32 / segments as f32;
+        let dir = Vec2::new(angle.cos(), angle.sin());
+
+        for (radius, color) in rings {
+            mesh.add_vertex(center + dir * *radius, *color, WHITE_UV);
+        }
+    }
+
+    for i in 0..segments {
+        let current = base + i * ring_count;
+        let next = base + ((i + 1) % segments) * ring_count;
+
+        for r in 0..ring_count - 1 {
+            mesh.add_triangle(current + r, next + r, current + r + 1);
+            mesh.add_triangle(current + r + 1, next + r, next + r + 1);
+        }
+    }
+}
+
+/// Sign of the winding when `path` is a convex polygon, 1.0 for clockwise.
+/// Returns `None` for concave, self intersecting or degenerate outlines
+fn convex_winding(path: &[Point]) -> Option<f32> {
+    const EPSILON: f32 = 1e-6;
+
+    let area = cw_signed_area(path);
+    if area.abs() <= EPSILON as f64 {
+        return None;
+    }
+    let winding = if area > 0.0 { 1.0 } else { -1.0 };
+
+    // every corner turns the same way and all of them add up to a single turn,
+    // which rules out stars that wind around more than once
+    let edges = (0..path.len())
+        .map(|i| path[(i + 1) % path.len()] - path[i])
+        .filter(|edge| edge.x.abs() > EPSILON || edge.y.abs() > EPSILON);
+
+    // starting from the last edge counts the corner at the first point too
+    let mut previous = edges.clone().next_back()?;
+    let mut turning = 0.0;
+    for edge in edges {
+        let cross = previous.cross(&edge);
+        if cross * winding < -EPSILON * previous.magnitude() * edge.magnitude() {
+            return None;
+        }
+        turning += cross.atan2(previous.dot(&edge));
+        previous = edge;
+    }
+
+    ((turning.abs() - f32::consts::TAU).abs() < 0.01).then_some(winding)
+}
+
+/// Maps the uvs of `vertices` of a shape covering `bounds` with `mapping`
+fn map_uv(vertices: &mut [Vertex], mapping: &TextureMapping, bounds: &Rect<f32>) {
+    for vertex in vertices {
+        let uv = mapping.uv_at(vertex.position.into(), bounds);
+        vertex.uv = [uv.x, uv.y];
+    }
+}
+
 fn cw_signed_area(path: &[Point]) -> f64 {
     if let Some(last) = path.last() {
         let mut previous = *last;
@@ -415,3 +1285,452 @@ fn cw_signed_area(path: &[Point]) -> f64 {
         0.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{arc, circle, ellipse, pie, polyline, quad, ring, vec2, Size};
+
+    fn mesh_area(mesh: &Mesh) -> f32 {
+        mesh.indices
+            .chunks_exact(3)
+            .map(|tri| {
+                let [a, b, c] = [tri[0], tri[1], tri[2]].map(|i| {
+                    let p = mesh.vertices[i as usize].position;
+                    Vec2::new(p[0], p[1])
+                });
+                ((b - a).cross(&(c - a)) * 0.5).abs()
+            })
+            .sum()
+    }
+
+    #[test]
+    fn ring_covers_annulus() {
+        let mut list = DrawList::default();
+        list.add_ring(
+            &ring().pos(50.0, 50.0).radii(20.0, 40.0),
+            &Brush::filled(Color::RED),
+        );
+
+        let mesh = list.build();
+        assert!(mesh.is_valid());
+
+        let expected = f32::consts::PI * (40.0 * 40.0 - 20.0 * 20.0);
+        assert!((mesh_area(&mesh) - expected).abs() / expected < 0.01);
+    }
+
+    #[test]
+    fn stroke_only_circle_is_an_annulus() {
+        let mut list = DrawList::default();
+        let brush = Brush::default().stroke_color(Color::RED).line_width(4);
+        list.add_circle(&circle().pos(0.0, 0.0).radius(30.0), &brush, false);
+
+        let mesh = list.build();
+        let segments = circle_segments(32.0, DEFAULT_TOLERANCE);
+        assert_eq!(mesh.vertices.len(), segments * 2);
+
+        let expected = f32::consts::PI * (32.0 * 32.0 - 28.0 * 28.0);
+        assert!((mesh_area(&mesh) - expected).abs() / expected < 0.01);
+    }
+
+    #[test]
+    fn ellipse_and_arc_cover_their_area() {
+        let mut list = DrawList::default();
+        list.add_ellipse(
+            &ellipse().pos(10.0, 10.0).radii(40.0, 20.0).rotation(0.5),
+            &Brush::filled(Color::RED),
+            false,
+        );
+        let mesh = list.build();
+        assert!(mesh.is_valid());
+
+        let expected = f32::consts::PI * 40.0 * 20.0;
+        assert!((mesh_area(&mesh) - expected).abs() / expected < 0.01);
+
+        // a half circle arc fills the half disc up to its chord
+        list.add_arc(
+            &arc().radius(30.0).angles(1.0, -f32::consts::PI),
+            &Brush::filled(Color::RED),
+            false,
+        );
+        let mesh = list.build();
+        let expected = f32::consts::PI * 30.0 * 30.0 * 0.5;
+        assert!((mesh_area(&mesh) - expected).abs() / expected < 0.01);
+    }
+
+    #[test]
+    fn points_fill_their_markers() {
+        let mut list = DrawList::default();
+        let centers = [vec2(0.0, 0.0), vec2(20.0, 0.0), vec2(40.0, 10.0)];
+        let brush = Brush::filled(Color::RED);
+
+        let markers = crate::points().points(centers).size(10.0);
+        list.add_points(&markers.clone().shape(PointShape::Square), &brush);
+        let mesh = list.build();
+        assert!(mesh.is_valid());
+        assert!((mesh_area(&mesh) - 300.0).abs() < 1e-3);
+
+        // circles are polygons within the tolerance
+        list.add_points(&markers.clone(), &brush);
+        let n = circle_segments(5.0, DEFAULT_TOLERANCE) as f32;
+        let expected = 3.0 * n / 2.0 * 25.0 * (f32::consts::TAU / n).sin();
+        assert!((mesh_area(&list.build()) - expected).abs() < 1e-3);
+
+        // two 10x2 arms sharing a 2x2 center
+        list.add_points(&markers.shape(PointShape::Cross), &brush);
+        let mesh = list.build();
+        assert!(mesh.is_valid());
+        assert!((mesh_area(&mesh) - 3.0 * 36.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn circle_segments_grow_with_radius() {
+        assert_eq!(circle_segments(0.1, DEFAULT_TOLERANCE), 8);
+        assert!(circle_segments(10.0, 0.25) < circle_segments(100.0, 0.25));
+        assert!(circle_segments(100.0, 0.25) < circle_segments(100.0, 0.05));
+        assert_eq!(circle_segments(1e6, 0.25), 256);
+    }
+
+    #[test]
+    fn pie_covers_wedge() {
+        let mut list = DrawList::default();
+        list.add_pie(
+            &pie()
+                .pos(0.0, 0.0)
+                .radius(50.0)
+                .angles(0.0, -f32::consts::PI * 1.5),
+            &Brush::filled(Color::RED),
+        );
+
+        let mesh = list.build();
+        assert!(mesh.is_valid());
+
+        let expected = f32::consts::PI * 50.0 * 50.0 * 0.75;
+        assert!((mesh_area(&mesh) - expected).abs() / expected < 0.01);
+    }
+
+    #[test]
+    fn pie_with_inner_radius_is_a_ring_segment() {
+        let mut list = DrawList::default();
+        list.add_pie(
+            &pie()
+                .pos(10.0, 10.0)
+                .radius(50.0)
+                .inner_radius(30.0)
+                .angles(1.0, f32::consts::PI),
+            &Brush::filled(Color::RED),
+        );
+
+        let mesh = list.build();
+        let expected = f32::consts::PI * (50.0 * 50.0 - 30.0 * 30.0) * 0.5;
+        assert!((mesh_area(&mesh) - expected).abs() / expected < 0.01);
+    }
+
+    #[test]
+    fn feathered_pie_fades_outwards() {
+        let mut list = DrawList::default();
+        list.add_pie(
+            &pie().radius(50.0).angles(0.0, 1.0),
+            &Brush::filled(Color::RED).antialiasing(Antialias::Feather(2.0)),
+        );
+
+        let mesh = list.build();
+        assert!(mesh.is_valid());
+
+        // inner and outer vertices alternate
+        for pair in mesh.vertices.chunks_exact(2) {
+            assert_eq!(pair[0].color, Color::RED.into());
+            assert_eq!(pair[1].color.a, 0.0);
+        }
+    }
+
+    #[test]
+    fn antialias_selects_feathering() {
+        let mut list = DrawList::default();
+        let plain = Brush::filled(Color::RED);
+
+        assert_eq!(list.feathering_for(&plain), 0.0);
+        let feather = plain.clone().antialiasing(Antialias::Feather(3.0));
+        assert_eq!(list.feathering_for(&feather), 3.0);
+        // feather widths are in physical pixels
+        list.hairline_width(0.5);
+        assert_eq!(list.feathering_for(&feather), 1.5);
+        list.hairline_width(1.0);
+        assert_eq!(
+            list.feathering_for(&plain.clone().antialias(true)),
+            DEFAULT_FEATHERING
+        );
+
+        list.antialias(true);
+        assert_eq!(list.feathering_for(&plain), DEFAULT_FEATHERING);
+        assert_eq!(list.feathering_for(&feather.antialias(false)), 0.0);
+
+        // crisp edges dont add the fade out vertices
+        list.add_circle(
+            &circle().radius(10.0),
+            &plain.clone().antialias(false),
+            false,
+        );
+        let crisp = list.build().vertices.len();
+        list.add_circle(&circle().radius(10.0), &plain, false);
+        assert!(list.build().vertices.len() > crisp);
+    }
+
+    #[test]
+    fn feathered_fills_fade_to_stroke() {
+        let mut list = DrawList::default();
+        list.add_pie(
+            &pie().radius(50.0).angles(0.0, 1.0),
+            &Brush::filled(Color::RED)
+                .stroke_color(Color::BLUE)
+                .antialiasing(Antialias::Feather(2.0)),
+        );
+
+        let mesh = list.build();
+        let fringe = mesh.vertices.iter().skip(1).step_by(2);
+        assert!(fringe
+            .take_while(|v| v.color != Color::RED.into())
+            .all(|v| v.color == Color::BLUE.into()));
+
+        // closed paths repeat their first point, which must not break the fringe normals
+        let mut builder = Path::builder();
+        builder.begin(Point::new(0.0, 0.0));
+        builder.line_to(Point::new(10.0, 0.0));
+        builder.line_to(Point::new(10.0, 10.0));
+        builder.line_to(Point::new(10.0, 10.0));
+        builder.close();
+        let path = builder.build();
+
+        list.add_path(
+            &path,
+            &Brush::filled(Color::RED)
+                .antialiasing(Antialias::Feather(2.0))
+                .into(),
+            false,
+        );
+        let mesh = list.build();
+        assert!(mesh.is_valid());
+        assert!(mesh
+            .vertices
+            .iter()
+            .all(|v| v.position.iter().all(|c| c.is_finite())));
+    }
+
+    #[test]
+    fn hairline_ignores_line_width() {
+        let mut list = DrawList::default();
+        list.hairline_width(0.25);
+
+        let extent = |list: &mut DrawList, brush: &Brush| {
+            list.add_circle(&circle().radius(10.0), brush, false);
+            list.build()
+                .vertices
+                .iter()
+                .map(|v| Vec2::new(v.position[0], v.position[1]).magnitude())
+                .fold(0.0f32, f32::max)
+        };
+
+        let brush = Brush::default().stroke_color(Color::RED).line_width(8);
+        assert!((extent(&mut list, &brush) - 14.0).abs() < 1e-3);
+        assert!((extent(&mut list, &brush.hairline(true)) - 10.125).abs() < 1e-3);
+    }
+
+    #[test]
+    fn convex_fill_falls_back_for_other_polygons() {
+        let points = |coords: &[(f32, f32)]| {
+            coords
+                .iter()
+                .map(|(x, y)| Vec2::new(*x, *y))
+                .collect::<Vec<_>>()
+        };
+        let square = points(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)]);
+        let reversed = square.iter().rev().copied().collect::<Vec<_>>();
+        let concave = points(&[
+            (0.0, 0.0),
+            (10.0, 0.0),
+            (10.0, 10.0),
+            (5.0, 2.0),
+            (0.0, 10.0),
+        ]);
+        let star = points(&[
+            (0.0, 0.0),
+            (10.0, 5.0),
+            (0.0, 10.0),
+            (6.0, -2.0),
+            (6.0, 12.0),
+        ]);
+
+        assert_eq!(convex_winding(&square), Some(1.0));
+        assert_eq!(convex_winding(&reversed), Some(-1.0));
+        assert_eq!(convex_winding(&concave), None);
+        assert_eq!(convex_winding(&star), None);
+
+        for (path, area) in [(&square, 100.0), (&reversed, 100.0), (&concave, 60.0)] {
+            for feathering in [0.0, 1.0] {
+                let mut mesh = Mesh::default();
+                let mut earcut = Earcut::default();
+                fill_path_convex(
+                    &mut mesh,
+                    &mut earcut,
+                    path,
+                    Color::RED,
+                    Some(&TextureMapping::Bounds),
+                    feathering,
+                    None,
+                );
+                assert!(mesh.is_valid());
+                assert!(mesh
+                    .vertices
+                    .iter()
+                    .all(|v| v.uv.iter().all(|c| c.is_finite())));
+
+                // the feathered edge reaches half its width outside
+                let covered = mesh_area(&mesh);
+                if feathering > 0.0 {
+                    assert!(covered > area, "{path:?}");
+                } else {
+                    assert!((covered - area).abs() < 0.1, "{path:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn build_reuses_recycled_meshes() {
+        let mut list = DrawList::default();
+        let circle = circle().radius(10.0);
+        let brush = Brush::filled(Color::RED);
+
+        list.add_circle(&circle, &brush, false);
+        let mut mesh = list.build();
+        mesh.texture = crate::TextureId::WHITE_TEXTURE;
+        let buffers = (mesh.vertices.as_ptr(), mesh.indices.as_ptr());
+        list.recycle([mesh]);
+
+        // the recycled mesh is picked up after the mesh in progress is built
+        list.build();
+        list.add_circle(&circle, &brush, false);
+        let mesh = list.build();
+        assert_eq!((mesh.vertices.as_ptr(), mesh.indices.as_ptr()), buffers);
+        assert_eq!(mesh.texture, crate::TextureId::default());
+        assert!(mesh.is_valid());
+    }
+
+    #[test]
+    fn degenerate_path_contours_are_not_filled() {
+        let path = |points: &[(f32, f32)]| {
+            let mut builder = Path::builder();
+            builder.begin(Vec2::new(points[0].0, points[0].1));
+            for (x, y) in &points[1..] {
+                builder.line_to(Vec2::new(*x, *y));
+            }
+            builder.close();
+            builder.build()
+        };
+        // the triangles of the fill, the transparent stroke is tessellated too
+        let filled = |list: &mut DrawList, path: &Path| {
+            list.add_path(path, &PathBrush::from(Brush::filled(Color::RED)), false);
+            let mut mesh = list.build();
+            let indices = mesh
+                .indices
+                .chunks_exact(3)
+                .filter(|tri| mesh.vertices[tri[0] as usize].color.a > 0.0)
+                .flatten()
+                .copied()
+                .collect();
+            mesh.indices = indices;
+            mesh
+        };
+
+        let mut list = DrawList::default();
+        let square = path(&[
+            (0.0, 0.0),
+            (10.0, 0.0),
+            (10.0, 0.0),
+            (10.0, 10.0),
+            (0.0, 10.0),
+            (0.0, 0.0),
+        ]);
+        let mesh = filled(&mut list, &square);
+        assert!(mesh.is_valid());
+        assert!((mesh_area(&mesh) - 100.0).abs() < 0.1);
+
+        let collinear = path(&[(0.0, 0.0), (5.0, 5.0), (10.0, 10.0)]);
+        assert!(filled(&mut list, &collinear).indices.is_empty());
+
+        let repeated = path(&[(1.0, 1.0), (1.0, 1.0), (1.0005, 1.0)]);
+        assert!(filled(&mut list, &repeated).indices.is_empty());
+
+        // the lobes of a bowtie have opposite windings
+        let bowtie = path(&[(0.0, 0.0), (10.0, 10.0), (10.0, 0.0), (0.0, 10.0)]);
+        assert!(!filled(&mut list, &bowtie).indices.is_empty());
+
+        list.fill_epsilon(2.0);
+        let sliver = path(&[(0.0, 0.0), (100.0, 0.0), (100.0, 1.0), (0.0, 1.0)]);
+        assert!(filled(&mut list, &sliver).indices.is_empty());
+    }
+
+    #[test]
+    fn polyline_widens_with_its_widths() {
+        let mut list = DrawList::default();
+        list.add_polyline(
+            &polyline().point(0.0, 0.0, 2.0).point(100.0, 0.0, 12.0),
+            &Brush::default().stroke_color(Color::RED),
+        );
+
+        let mesh = list.build();
+        assert!(mesh.is_valid());
+        let extent = |range: std::ops::Range<f32>| {
+            mesh.vertices
+                .iter()
+                .filter(|v| range.contains(&v.position[0]))
+                .map(|v| v.position[1].abs())
+                .fold(0.0f32, f32::max)
+        };
+        assert!(extent(-1.0..1.0) < 2.0);
+        assert!(extent(99.0..101.0) > 5.0);
+    }
+
+    #[test]
+    fn absolute_texture_mapping_lines_up_across_shapes() {
+        let mut list = DrawList::default();
+        list.antialias(false);
+        let brush = Brush::filled(Color::WHITE)
+            .no_stroke()
+            .texture_mapping(TextureMapping::Absolute(Size::new(20.0, 10.0)));
+        list.add_quad(&quad().rect(Rect::xywh(0.0, 0.0, 10.0, 10.0)), &brush, true);
+        list.add_quad(
+            &quad().rect(Rect::xywh(10.0, 0.0, 10.0, 10.0)),
+            &brush,
+            true,
+        );
+
+        let mut builder = PathBuilder::default();
+        builder.begin(vec2(0.0, 10.0));
+        builder.line_to(vec2(20.0, 10.0));
+        builder.line_to(vec2(20.0, 20.0));
+        builder.end(true);
+        list.add_path(&builder.build(), &brush.into(), true);
+
+        let mesh = list.build();
+        assert!(!mesh.vertices.is_empty());
+        // the transparent stroke is still tessellated
+        for vertex in mesh.vertices.iter().filter(|v| v.color.a > 0.0) {
+            let [x, y] = vertex.position;
+            assert_eq!(vertex.uv, [x / 20.0, y / 10.0]);
+        }
+
+        // the default fits the texture over each shape
+        let mut list = DrawList::default();
+        list.antialias(false);
+        let brush = Brush::filled(Color::WHITE).no_stroke();
+        list.add_quad(
+            &quad().rect(Rect::xywh(10.0, 0.0, 10.0, 10.0)),
+            &brush,
+            true,
+        );
+        let mesh = list.build();
+        assert!(mesh.vertices.iter().any(|v| v.uv == [0.0, 0.0]));
+        assert!(mesh.vertices.iter().any(|v| v.uv == [1.0, 1.0]));
+    }
+}
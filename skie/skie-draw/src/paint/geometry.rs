@@ -1,4 +1,7 @@
-use crate::path::Point;
+use skie_math::Rect;
+use smallvec::SmallVec;
+
+use crate::path::{get_path_bounds, Point};
 
 pub struct QuadraticBezier {
     pub from: Point,
@@ -15,6 +18,42 @@ impl QuadraticBezier {
 
         p0 + p1 + p2
     }
+
+    /// Number of uniform segments needed to stay within `tolerance` of the curve
+    pub fn flattening_segments(&self, tolerance: f32) -> u32 {
+        let dd = (self.from - self.ctrl * 2.0 + self.to).magnitude();
+        segments_for_deviation(dd / 4.0, tolerance)
+    }
+
+    /// Tight bounds of the curve from its ends and extrema, not its control point
+    pub fn bounds(&self) -> Rect<f32> {
+        let extremum = |p0: f32, p1: f32, p2: f32| {
+            let denom = p0 - 2.0 * p1 + p2;
+            (denom != 0.0)
+                .then(|| (p0 - p1) / denom)
+                .filter(|t| *t > 0.0 && *t < 1.0)
+        };
+
+        let extrema = [
+            extremum(self.from.x, self.ctrl.x, self.to.x),
+            extremum(self.from.y, self.ctrl.y, self.to.y),
+        ];
+        let points = [self.from, self.to]
+            .into_iter()
+            .chain(extrema.into_iter().flatten().map(|t| self.sample(t)))
+            .collect::<SmallVec<[Point; 4]>>();
+        get_path_bounds(&points)
+    }
+
+    /// Number of uniform segments keeping the edges of a stroke `half_width` away from the
+    /// curve within `tolerance`, see [`segments_for_turning`]
+    pub fn stroke_segments(&self, half_width: f32, tolerance: f32) -> u32 {
+        segments_for_turning(
+            |t| (self.ctrl - self.from) * (1.0 - t) + (self.to - self.ctrl) * t,
+            half_width,
+            tolerance,
+        )
+    }
 }
 
 pub struct CubicBezier {
@@ -39,4 +78,184 @@ impl CubicBezier {
 
         p0 + p1 + p2 + p3
     }
+
+    /// Number of uniform segments needed to stay within `tolerance` of the curve
+    pub fn flattening_segments(&self, tolerance: f32) -> u32 {
+        let dd1 = (self.from - self.ctrl1 * 2.0 + self.ctrl2).magnitude();
+        let dd2 = (self.ctrl1 - self.ctrl2 * 2.0 + self.to).magnitude();
+        segments_for_deviation(dd1.max(dd2) * 0.75, tolerance)
+    }
+
+    /// Tight bounds of the curve from its ends and extrema, not its control points
+    pub fn bounds(&self) -> Rect<f32> {
+        // roots of the derivative, a quadratic per axis
+        let extrema = |p0: f32, p1: f32, p2: f32, p3: f32| {
+            let a = -p0 + 3.0 * p1 - 3.0 * p2 + p3;
+            let b = 2.0 * (p0 - 2.0 * p1 + p2);
+            let c = p1 - p0;
+
+            let mut roots = [None, None];
+            if a.abs() <= f32::EPSILON {
+                if b != 0.0 {
+                    roots[0] = Some(-c / b);
+                }
+            } else {
+                let discriminant = b * b - 4.0 * a * c;
+                if discriminant >= 0.0 {
+                    let sqrt = discriminant.sqrt();
+                    roots = [Some((-b + sqrt) / (2.0 * a)), Some((-b - sqrt) / (2.0 * a))];
+                }
+            }
+            roots.into_iter().flatten().filter(|t| *t > 0.0 && *t < 1.0)
+        };
+
+        let points = [self.from, self.to]
+            .into_iter()
+            .chain(
+                extrema(self.from.x, self.ctrl1.x, self.ctrl2.x, self.to.x)
+                    .chain(extrema(self.from.y, self.ctrl1.y, self.ctrl2.y, self.to.y))
+                    .map(|t| self.sample(t)),
+            )
+            .collect::<SmallVec<[Point; 6]>>();
+        get_path_bounds(&points)
+    }
+
+    /// Number of uniform segments keeping the edges of a stroke `half_width` away from the
+    /// curve within `tolerance`, see [`segments_for_turning`]
+    pub fn stroke_segments(&self, half_width: f32, tolerance: f32) -> u32 {
+        segments_for_turning(
+            |t| {
+                let one_minus_t = 1.0 - t;
+                (self.ctrl1 - self.from) * (one_minus_t * one_minus_t)
+                    + (self.ctrl2 - self.ctrl1) * (2.0 * one_minus_t * t)
+                    + (self.to - self.ctrl2) * (t * t)
+            },
+            half_width,
+            tolerance,
+        )
+    }
+}
+
+const MAX_SEGMENTS: f32 = 256.0;
+
+/// The chord error of a curve split in `n` uniform segments is bounded by `deviation / n^2`
+#[inline]
+fn segments_for_deviation(deviation: f32, tolerance: f32) -> u32 {
+    let tolerance = tolerance.max(f32::EPSILON);
+
+    (deviation / tolerance)
+        .sqrt()
+        .ceil()
+        .clamp(1.0, MAX_SEGMENTS) as u32
+}
+
+/// Stroke edges sit `half_width` off the flattened curve, so a segment turning by `angle`
+/// cuts its edge's arc by about `half_width * angle^2 / 8` on top of the curve's own error.
+/// Counts the uniform segments keeping that within `tolerance`, from the direction of the
+/// curve (any length) sampled along `0..=1`
+fn segments_for_turning(direction: impl Fn(f32) -> Point, half_width: f32, tolerance: f32) -> u32 {
+    const SAMPLES: u32 = 16;
+    let tolerance = tolerance.max(f32::EPSILON);
+    if half_width <= tolerance {
+        return 1;
+    }
+    let max_angle = (8.0 * tolerance / half_width).sqrt();
+
+    let mut prev = direction(0.0);
+    let mut max_turn: f32 = 0.0;
+    for i in 1..=SAMPLES {
+        let dir = direction(i as f32 / SAMPLES as f32);
+        if dir.magnitude() <= f32::EPSILON {
+            continue;
+        }
+        if prev.magnitude() > f32::EPSILON {
+            max_turn = max_turn.max(prev.angle(&dir));
+        }
+        prev = dir;
+    }
+
+    (max_turn * SAMPLES as f32 / max_angle)
+        .ceil()
+        .clamp(1.0, MAX_SEGMENTS) as u32
+}
+
+/// A rational quadratic bezier. `weight == 1` is a plain quadratic, `weight < 1` traces an
+/// ellipse arc (`sqrt(2) / 2` for a quarter circle) and `weight > 1` a hyperbola
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConicBezier {
+    pub from: Point,
+    pub ctrl: Point,
+    pub to: Point,
+    pub weight: f32,
+}
+
+impl ConicBezier {
+    /// Subdivision depth is capped so a single conic never produces more than 32 quadratics
+    const MAX_SUBDIVISIONS: u32 = 5;
+
+    pub fn sample(&self, t: f32) -> Point {
+        let one_minus_t = 1.0 - t;
+        let a = one_minus_t * one_minus_t;
+        let b = 2.0 * one_minus_t * t * self.weight;
+        let c = t * t;
+
+        (self.from * a + self.ctrl * b + self.to * c) * (1.0 / (a + b + c))
+    }
+
+    /// Splits the conic at `t = 0.5`
+    pub fn split(&self) -> (ConicBezier, ConicBezier) {
+        let w = self.weight;
+        let scale = 1.0 / (1.0 + w);
+        let ctrl_a = (self.from + self.ctrl * w) * scale;
+        let ctrl_b = (self.ctrl * w + self.to) * scale;
+        let mid = (ctrl_a + ctrl_b) * 0.5;
+        let weight = (0.5 + w * 0.5).sqrt();
+
+        (
+            ConicBezier {
+                from: self.from,
+                ctrl: ctrl_a,
+                to: mid,
+                weight,
+            },
+            ConicBezier {
+                from: mid,
+                ctrl: ctrl_b,
+                to: self.to,
+                weight,
+            },
+        )
+    }
+
+    /// Approximates the conic with quadratics that stay within `tolerance` of the curve
+    pub fn for_each_quadratic(&self, tolerance: f32, mut f: impl FnMut(QuadraticBezier)) {
+        let a = self.weight - 1.0;
+        let k = a / (4.0 * (2.0 + a));
+        let error = (self.from - self.ctrl * 2.0 + self.to).magnitude() * k.abs();
+
+        // every subdivision cuts the error by 4
+        let mut subdivisions = 0;
+        let mut error = error;
+        while error > tolerance && subdivisions < Self::MAX_SUBDIVISIONS {
+            error *= 0.25;
+            subdivisions += 1;
+        }
+
+        self.subdivide(subdivisions, &mut f);
+    }
+
+    fn subdivide(&self, depth: u32, f: &mut impl FnMut(QuadraticBezier)) {
+        if depth == 0 {
+            f(QuadraticBezier {
+                from: self.from,
+                ctrl: self.ctrl,
+                to: self.to,
+            });
+            return;
+        }
+
+        let (a, b) = self.split();
+        a.subdivide(depth - 1, f);
+        b.subdivide(depth - 1, f);
+    }
 }
@@ -0,0 +1,436 @@
+use std::f32::consts::TAU;
+
+use skie_math::{Lerp, Vec2};
+
+use super::{Color, Mesh, Rgba, Vertex};
+
+/// Color of a gradient at `offset`, which goes from 0 at the start to 1 at the end
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GradientStop {
+    pub offset: f32,
+    pub color: Color,
+}
+
+impl GradientStop {
+    pub fn new(offset: f32, color: Color) -> Self {
+        Self { offset, color }
+    }
+}
+
+impl From<(f32, Color)> for GradientStop {
+    fn from((offset, color): (f32, Color)) -> Self {
+        Self::new(offset, color)
+    }
+}
+
+/// Interpolates colors along the line from `start` to `end`, points are in the brush's local space.
+/// Past either end the color of the first or last stop is used
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinearGradient {
+    pub start: Vec2<f32>,
+    pub end: Vec2<f32>,
+    stops: Vec<GradientStop>,
+}
+
+impl LinearGradient {
+    pub fn new(start: Vec2<f32>, end: Vec2<f32>) -> Self {
+        Self {
+            start,
+            end,
+            stops: Vec::new(),
+        }
+    }
+
+    /// Adds a stop, the offset is clamped to `0..=1` and stops can be added in any order
+    pub fn stop(mut self, offset: f32, color: Color) -> Self {
+        insert_stop(&mut self.stops, offset, color);
+        self
+    }
+
+    pub fn stops(&self) -> &[GradientStop] {
+        &self.stops
+    }
+
+    /// Where `point` projects onto the gradient line, not clamped
+    pub fn offset_at(&self, point: Vec2<f32>) -> f32 {
+        let dir = self.end - self.start;
+        let len_sq = dir.dot(&dir);
+        if len_sq <= f32::EPSILON {
+            return 0.0;
+        }
+        (point - self.start).dot(&dir) / len_sq
+    }
+
+    pub fn color_at(&self, point: Vec2<f32>) -> Rgba {
+        self.color_at_offset(self.offset_at(point))
+    }
+
+    pub fn color_at_offset(&self, offset: f32) -> Rgba {
+        sample_stops(&self.stops, offset)
+    }
+}
+
+/// Sweeps the stops clockwise around `center`, starting at `start_angle` in radians.
+/// Offsets 0 and 1 meet at the start angle, like the edges of a pie chart
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConicGradient {
+    pub center: Vec2<f32>,
+    pub start_angle: f32,
+    stops: Vec<GradientStop>,
+}
+
+impl ConicGradient {
+    pub fn new(center: Vec2<f32>, start_angle: f32) -> Self {
+        Self {
+            center,
+            start_angle,
+            stops: Vec::new(),
+        }
+    }
+
+    /// Adds a stop, the offset is clamped to `0..=1` and stops can be added in any order
+    pub fn stop(mut self, offset: f32, color: Color) -> Self {
+        insert_stop(&mut self.stops, offset, color);
+        self
+    }
+
+    pub fn stops(&self) -> &[GradientStop] {
+        &self.stops
+    }
+
+    /// Fraction of the turn from the start angle to `point`, in `0..1`
+    pub fn offset_at(&self, point: Vec2<f32>) -> f32 {
+        let d = point - self.center;
+        ((d.y.atan2(d.x) - self.start_angle) / TAU).rem_euclid(1.0)
+    }
+
+    pub fn color_at(&self, point: Vec2<f32>) -> Rgba {
+        self.color_at_offset(self.offset_at(point))
+    }
+
+    pub fn color_at_offset(&self, offset: f32) -> Rgba {
+        sample_stops(&self.stops, offset)
+    }
+
+    /// Like `offset_at` but on the same side of the seam as `reference`,
+    /// the center itself takes the reference offset
+    fn offset_near(&self, point: Vec2<f32>, reference: f32) -> f32 {
+        let d = point - self.center;
+        if d.dot(&d) <= f32::EPSILON {
+            return reference;
+        }
+        let offset = self.offset_at(point);
+        (reference + (offset - reference + 0.5).rem_euclid(1.0) - 0.5).clamp(0.0, 1.0)
+    }
+}
+
+/// Fill gradient of a brush
+#[derive(Debug, Clone, PartialEq)]
+pub enum Gradient {
+    Linear(LinearGradient),
+    Conic(ConicGradient),
+}
+
+impl From<LinearGradient> for Gradient {
+    fn from(gradient: LinearGradient) -> Self {
+        Self::Linear(gradient)
+    }
+}
+
+impl From<ConicGradient> for Gradient {
+    fn from(gradient: ConicGradient) -> Self {
+        Self::Conic(gradient)
+    }
+}
+
+/// Conic fills are cut at least this often so the colors follow the sweep between stops
+const CONIC_SEGMENTS: usize = 64;
+
+impl Gradient {
+    pub fn stops(&self) -> &[GradientStop] {
+        match self {
+            Self::Linear(linear) => linear.stops(),
+            Self::Conic(conic) => conic.stops(),
+        }
+    }
+
+    pub fn color_at(&self, point: Vec2<f32>) -> Rgba {
+        match self {
+            Self::Linear(linear) => linear.color_at(point),
+            Self::Conic(conic) => conic.color_at(point),
+        }
+    }
+
+    /// Lines the fill is cut along, as a point on the line and its normal
+    fn cut_lines(&self) -> Vec<(Vec2<f32>, Vec2<f32>)> {
+        match self {
+            Self::Linear(linear) => {
+                let dir = linear.end - linear.start;
+                linear
+                    .stops()
+                    .iter()
+                    .map(|stop| (linear.start + dir * stop.offset, dir))
+                    .collect()
+            }
+            Self::Conic(conic) => {
+                // a line through the center cuts along two opposite rays
+                let segment = TAU / CONIC_SEGMENTS as f32;
+                let segments = (0..CONIC_SEGMENTS / 2).map(|i| i as f32 * segment);
+                let stops = conic.stops().iter().map(|stop| stop.offset * TAU);
+
+                segments
+                    .chain(stops)
+                    .map(|angle| {
+                        let angle = conic.start_angle + angle;
+                        (conic.center, Vec2::new(-angle.sin(), angle.cos()))
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Color of a vertex of a cut piece centered at `centroid`
+    fn piece_color(&self, point: Vec2<f32>, centroid: Vec2<f32>) -> Rgba {
+        match self {
+            Self::Linear(linear) => linear.color_at(point),
+            Self::Conic(conic) => {
+                conic.color_at_offset(conic.offset_near(point, conic.offset_at(centroid)))
+            }
+        }
+    }
+}
+
+fn insert_stop(stops: &mut Vec<GradientStop>, offset: f32, color: Color) {
+    let offset = offset.clamp(0.0, 1.0);
+    let index = stops.partition_point(|stop| stop.offset <= offset);
+    stops.insert(index, GradientStop::new(offset, color));
+}
+
+fn sample_stops(stops: &[GradientStop], offset: f32) -> Rgba {
+    let (Some(first), Some(last)) = (stops.first(), stops.last()) else {
+        return Rgba::TRANSPARENT;
+    };
+
+    let next = stops.partition_point(|stop| stop.offset <= offset);
+    if next == 0 {
+        return first.color.into();
+    }
+    if next == stops.len() {
+        return last.color.into();
+    }
+
+    let (from, to) = (&stops[next - 1], &stops[next]);
+    let t = (offset - from.offset) / (to.offset - from.offset);
+    Rgba::from(from.color).lerp(&to.color.into(), t)
+}
+
+/// Colors the triangles added to `mesh` since `first_vertex` and `first_index` with the gradient.
+/// They are expected to be filled white, their alpha is kept as coverage. Triangles are cut
+/// along the stops so the colors between vertices follow the gradient
+pub(crate) fn apply_gradient(
+    mesh: &mut Mesh,
+    first_vertex: usize,
+    first_index: usize,
+    gradient: &Gradient,
+) {
+    cut_triangles(
+        mesh,
+        first_vertex,
+        first_index,
+        &gradient.cut_lines(),
+        |mesh, piece| add_gradient_polygon(mesh, piece, gradient),
+    );
+}
+
+/// Replaces the triangles added to `mesh` since `first_vertex` and `first_index` with the
+/// convex pieces left after cutting them along `lines`, each given as a point and a normal.
+/// `add_piece` adds a piece back to the mesh
+pub(crate) fn cut_triangles(
+    mesh: &mut Mesh,
+    first_vertex: usize,
+    first_index: usize,
+    lines: &[(Vec2<f32>, Vec2<f32>)],
+    mut add_piece: impl FnMut(&mut Mesh, &[Vertex]),
+) {
+    let triangles: Vec<[Vertex; 3]> = mesh.indices[first_index..]
+        .chunks_exact(3)
+        .map(|tri| [tri[0], tri[1], tri[2]].map(|index| mesh.vertices[index as usize]))
+        .collect();
+
+    mesh.indices.truncate(first_index);
+    mesh.vertices.truncate(first_vertex);
+
+    let mut pieces = Vec::new();
+    let mut next = Vec::new();
+
+    for triangle in triangles {
+        pieces.clear();
+        pieces.push(triangle.to_vec());
+
+        for (origin, normal) in lines {
+            let side = |v: &Vertex| (Vec2::from(v.position) - *origin).dot(normal);
+
+            for piece in pieces.drain(..) {
+                let crosses =
+                    piece.iter().any(|v| side(v) < 0.0) && piece.iter().any(|v| side(v) > 0.0);
+                if crosses {
+                    let (below, above) = split_polygon(&piece, side);
+                    next.extend([below, above]);
+                } else {
+                    next.push(piece);
+                }
+            }
+            std::mem::swap(&mut pieces, &mut next);
+        }
+
+        for piece in &pieces {
+            if piece.len() >= 3 {
+                add_piece(mesh, piece);
+            }
+        }
+    }
+}
+
+/// Adds a convex polygon as a triangle fan
+pub(crate) fn add_convex_polygon(mesh: &mut Mesh, polygon: impl ExactSizeIterator<Item = Vertex>) {
+    let base = mesh.vertex_count();
+    let len = polygon.len() as u32;
+    mesh.vertices.extend(polygon);
+
+    for i in 1..len.saturating_sub(1) {
+        mesh.add_triangle(base, base + i, base + i + 1);
+    }
+}
+
+/// Splits a convex polygon where `side` crosses zero
+fn split_polygon(polygon: &[Vertex], side: impl Fn(&Vertex) -> f32) -> (Vec<Vertex>, Vec<Vertex>) {
+    let mut below = Vec::with_capacity(polygon.len() + 1);
+    let mut above = Vec::with_capacity(polygon.len() + 1);
+
+    for (i, a) in polygon.iter().enumerate() {
+        let b = &polygon[(i + 1) % polygon.len()];
+        let (da, db) = (side(a), side(b));
+
+        if da <= 0.0 {
+            below.push(*a);
+        }
+        if da >= 0.0 {
+            above.push(*a);
+        }
+        if (da < 0.0 && db > 0.0) || (da > 0.0 && db < 0.0) {
+            let cut = lerp_vertex(a, b, da / (da - db));
+            below.push(cut);
+            above.push(cut);
+        }
+    }
+
+    (below, above)
+}
+
+fn lerp_vertex(a: &Vertex, b: &Vertex, t: f32) -> Vertex {
+    Vertex {
+        position: [
+            a.position[0].lerp(&b.position[0], t),
+            a.position[1].lerp(&b.position[1], t),
+        ],
+        uv: [a.uv[0].lerp(&b.uv[0], t), a.uv[1].lerp(&b.uv[1], t)],
+        color: a.color.lerp(&b.color, t),
+    }
+}
+
+fn add_gradient_polygon(mesh: &mut Mesh, polygon: &[Vertex], gradient: &Gradient) {
+    let centroid = polygon
+        .iter()
+        .fold(Vec2::new(0.0, 0.0), |sum, v| sum + Vec2::from(v.position))
+        / polygon.len() as f32;
+
+    add_convex_polygon(
+        mesh,
+        polygon.iter().map(|vertex| {
+            let color = gradient.piece_color(vertex.position.into(), centroid);
+            let coverage = vertex.color;
+            Vertex {
+                color: Rgba {
+                    r: color.r * coverage.r,
+                    g: color.g * coverage.g,
+                    b: color.b * coverage.b,
+                    a: color.a * coverage.a,
+                },
+                ..*vertex
+            }
+        }),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gradient_samples_between_stops() {
+        let gradient = LinearGradient::new(Vec2::new(0.0, 0.0), Vec2::new(100.0, 0.0))
+            .stop(1.0, Color::BLUE)
+            .stop(0.0, Color::RED);
+
+        assert_eq!(gradient.color_at(Vec2::new(-10.0, 5.0)), Color::RED.into());
+        assert_eq!(gradient.color_at(Vec2::new(200.0, 5.0)), Color::BLUE.into());
+
+        let mid = gradient.color_at(Vec2::new(50.0, 30.0));
+        assert!((mid.r - 0.5).abs() < 1e-6 && (mid.b - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn gradient_splits_triangles_at_stops() {
+        let gradient = LinearGradient::new(Vec2::new(0.0, 0.0), Vec2::new(10.0, 0.0))
+            .stop(0.0, Color::RED)
+            .stop(0.5, Color::GREEN)
+            .stop(1.0, Color::BLUE);
+
+        let mut mesh = Mesh::default();
+        for (x, y) in [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)] {
+            mesh.add_vertex(Vec2::new(x, y), Color::WHITE, (0.0, 0.0));
+        }
+        mesh.add_triangle(0, 1, 2);
+
+        apply_gradient(&mut mesh, 0, 0, &gradient.into());
+
+        assert!(mesh.is_valid());
+        assert!(mesh.indices.len() > 3);
+        // the middle stop is hit exactly on the cut
+        assert!(mesh
+            .vertices
+            .iter()
+            .any(|v| v.position[0] == 5.0 && v.color == Color::GREEN.into()));
+    }
+
+    #[test]
+    fn conic_gradient_sweeps_around_center() {
+        let gradient = ConicGradient::new(Vec2::new(0.0, 0.0), 0.0)
+            .stop(0.0, Color::RED)
+            .stop(1.0, Color::BLUE);
+
+        assert_eq!(gradient.offset_at(Vec2::new(1.0, 0.0)), 0.0);
+        assert!((gradient.offset_at(Vec2::new(0.0, 1.0)) - 0.25).abs() < 1e-6);
+        assert!((gradient.offset_at(Vec2::new(-1.0, 0.0)) - 0.5).abs() < 1e-6);
+
+        // a square around the center, cut into wedges that meet at the seam
+        let mut mesh = Mesh::default();
+        for (x, y) in [(-10.0, -10.0), (10.0, -10.0), (10.0, 10.0), (-10.0, 10.0)] {
+            mesh.add_vertex(Vec2::new(x, y), Color::WHITE, (0.0, 0.0));
+        }
+        mesh.add_triangle(0, 1, 2);
+        mesh.add_triangle(0, 2, 3);
+
+        apply_gradient(&mut mesh, 0, 0, &gradient.into());
+        assert!(mesh.is_valid());
+
+        let seam: Vec<Rgba> = mesh
+            .vertices
+            .iter()
+            .filter(|v| v.position[0] == 10.0 && v.position[1].abs() < 1e-4)
+            .map(|v| v.color)
+            .collect();
+        assert!(seam.contains(&Color::RED.into()));
+        assert!(seam.contains(&Color::BLUE.into()));
+    }
+}
@@ -1,5 +1,5 @@
-use crate::{paint::Primitive, Brush, TextureId};
-use std::{iter::Peekable, slice};
+use crate::{paint::Primitive, Brush, Quad, Rect, TextureId, TextureOptions};
+use std::{iter::Peekable, ops::Range, slice};
 
 use super::Color;
 
@@ -9,11 +9,25 @@ pub struct GraphicsInstruction {
     pub primitive: Primitive,
     pub brush: Brush,
     pub texture_id: TextureId,
+    /// normalized part of the texture mapped over the primitive, all of it when `None`
+    pub source: Option<Rect<f32>>,
+    /// samples the texture with these options instead of the ones it was added with
+    pub sampler: Option<TextureOptions>,
 }
 
 impl GraphicsInstruction {
     pub fn nothing_to_draw(&self) -> bool {
-        self.brush.noting_to_draw()
+        // paths are drawn with their own brushes
+        if let Primitive::Path { brush, .. } = &self.primitive {
+            return brush.nothing_to_draw();
+        }
+
+        let has_borders = matches!(
+            &self.primitive,
+            Primitive::Quad(Quad { borders: Some(borders), .. }) if borders.is_visible()
+        );
+
+        self.brush.noting_to_draw() && !has_borders
     }
 
     pub fn textured(primitive: impl Into<Primitive>, texture_id: TextureId) -> Self {
@@ -21,6 +35,8 @@ impl GraphicsInstruction {
             primitive: primitive.into(),
             texture_id,
             brush: Brush::filled(Color::WHITE),
+            source: None,
+            sampler: None,
         }
     }
 
@@ -29,6 +45,8 @@ impl GraphicsInstruction {
             primitive: primitive.into(),
             texture_id: TextureId::WHITE_TEXTURE,
             brush,
+            source: None,
+            sampler: None,
         }
     }
 
@@ -41,11 +59,26 @@ impl GraphicsInstruction {
             primitive: primitive.into(),
             texture_id,
             brush,
+            source: None,
+            sampler: None,
         }
     }
+
+    /// Maps `source`, in normalized texture coordinates, over the primitive instead of the
+    /// whole texture
+    pub fn source(mut self, source: Rect<f32>) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    /// Samples the texture with `options` for this draw only, the `kind` is ignored
+    pub fn sampler(mut self, options: TextureOptions) -> Self {
+        self.sampler = Some(options);
+        self
+    }
 }
 
-// batches instructions with the same texture
+// batches instructions with the same texture and sampler
 pub(crate) struct GraphicsInstructionBatcher<'a, TexMap>
 where
     TexMap: Fn(&'a TextureId) -> Option<TextureId> + 'a,
@@ -92,13 +125,15 @@ where
         let render_texture = (self.get_renderer_texture)(&first_instr.texture_id)
             .unwrap_or(first_instr.texture_id.clone());
 
+        let sampler = first_instr.sampler.as_ref();
+
         let mut end = self.instruction_start;
 
         while let Some(next_instr) = self.instructions_iter.peek() {
             let next_render_texture = (self.get_renderer_texture)(&next_instr.texture_id)
                 .unwrap_or(next_instr.texture_id.clone());
 
-            if next_render_texture != render_texture {
+            if next_render_texture != render_texture || next_instr.sampler.as_ref() != sampler {
                 break;
             }
 
@@ -108,7 +143,9 @@ where
 
         let batch = InstructionBatch {
             instructions_iter: self.instructions[self.instruction_start..end].iter(),
+            range: self.instruction_start..end,
             renderer_texture: render_texture,
+            sampler: sampler.cloned(),
         };
 
         self.instruction_start = end;
@@ -118,7 +155,10 @@ where
 
 pub struct InstructionBatch<'a> {
     instructions_iter: std::slice::Iter<'a, GraphicsInstruction>,
+    /// indices of the batched instructions in the batcher's instructions
+    pub range: Range<usize>,
     pub renderer_texture: TextureId,
+    pub sampler: Option<TextureOptions>,
 }
 
 impl<'a> Iterator for InstructionBatch<'a> {
@@ -131,9 +171,9 @@ impl<'a> Iterator for InstructionBatch<'a> {
 
 #[cfg(test)]
 mod tests {
-    use crate::{quad, TextureId};
+    use crate::{quad, Brush, Color, Path, Rect, TextureFilterMode, TextureId, TextureOptions};
 
-    use super::{GraphicsInstruction, GraphicsInstructionBatcher};
+    use super::{GraphicsInstruction, GraphicsInstructionBatcher, Primitive};
 
     fn get_instructions() -> [GraphicsInstruction; 12] {
         [
@@ -152,6 +192,26 @@ mod tests {
         ]
     }
 
+    #[test]
+    fn paths_draw_with_their_own_brush() {
+        let mut builder = Path::builder();
+        builder.rect(&Rect::xywh(0.0, 0.0, 10.0, 10.0));
+        let path = builder.build();
+
+        let visible = Primitive::Path {
+            path: path.clone(),
+            brush: Brush::filled(Color::RED).into(),
+        };
+        assert!(!GraphicsInstruction::brush(visible, Brush::default()).nothing_to_draw());
+
+        let hidden = Primitive::Path {
+            path,
+            brush: Brush::default().into(),
+        };
+        let white = Brush::filled(Color::WHITE);
+        assert!(GraphicsInstruction::brush(hidden, white).nothing_to_draw());
+    }
+
     #[test]
     fn test_batcher() {
         let instructions = get_instructions();
@@ -161,6 +221,7 @@ mod tests {
         let mut test_next_exists = |id: TextureId, len: usize| {
             let next = iter.next().expect("batch not found");
             assert_eq!(next.renderer_texture, id);
+            assert_eq!(next.range.len(), len);
             let vec: Vec<_> = next.collect();
             assert_eq!(vec.len(), len);
         };
@@ -204,4 +265,20 @@ mod tests {
 
         assert!(iter.next().is_none());
     }
+
+    #[test]
+    fn samplers_split_batches() {
+        let nearest = TextureOptions::default().filter(TextureFilterMode::Nearest);
+        let instructions = [
+            GraphicsInstruction::textured(quad(), TextureId::User(1)),
+            GraphicsInstruction::textured(quad(), TextureId::User(1)).sampler(nearest.clone()),
+            GraphicsInstruction::textured(quad(), TextureId::User(1)).sampler(nearest.clone()),
+            GraphicsInstruction::textured(quad(), TextureId::User(1)),
+        ];
+
+        let batches: Vec<_> = GraphicsInstructionBatcher::new(&instructions, |_| None)
+            .map(|batch| (batch.range.len(), batch.sampler))
+            .collect();
+        assert_eq!(batches, [(1, None), (2, Some(nearest)), (1, None)]);
+    }
 }
@@ -1,3 +1,5 @@
+use std::any::TypeId;
+
 use super::TextureKind;
 
 #[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
@@ -37,3 +39,164 @@ impl AtlasImage {
         self
     }
 }
+
+/// Atlas key for tiles owned by other crates. The namespace type keeps the ids of different
+/// crates apart, so an icon cache and another plugin can both use id 1
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
+pub struct UserAtlasKey {
+    pub(crate) namespace: TypeId,
+    pub(crate) id: u64,
+    pub(crate) texture_kind: TextureKind,
+}
+
+impl UserAtlasKey {
+    /// A color key for `id` within the namespace of `T`, usually a type private to the crate
+    pub fn new<T: 'static>(id: u64) -> Self {
+        Self {
+            namespace: TypeId::of::<T>(),
+            id,
+            texture_kind: TextureKind::Color,
+        }
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn namespace(&self) -> TypeId {
+        self.namespace
+    }
+
+    pub fn texture_kind(&self) -> &TextureKind {
+        &self.texture_kind
+    }
+
+    pub fn color(mut self) -> Self {
+        self.texture_kind = TextureKind::Color;
+        self
+    }
+
+    pub fn greyscale(mut self) -> Self {
+        self.texture_kind = TextureKind::Mask;
+        self
+    }
+}
+
+/// How the color channels of an image are encoded
+#[derive(Clone, Copy, Debug, Default, Hash, Eq, PartialEq)]
+pub enum ImageColorSpace {
+    /// gamma encoded like the rest of the canvas colors, what most image files hold
+    #[default]
+    Srgb,
+    /// linear light values, e.g. rendered or computed images
+    Linear,
+}
+
+/// Whether the color channels of an image are already multiplied by its alpha
+#[derive(Clone, Copy, Debug, Default, Hash, Eq, PartialEq)]
+pub enum ImageAlpha {
+    #[default]
+    Straight,
+    Premultiplied,
+}
+
+/// Describes the pixels of an image so they can be converted to what the canvas blends with,
+/// sRGB colors with straight alpha
+#[derive(Clone, Copy, Debug, Default, Hash, Eq, PartialEq)]
+pub struct ImageDecodeOptions {
+    pub color_space: ImageColorSpace,
+    pub alpha: ImageAlpha,
+}
+
+impl ImageDecodeOptions {
+    pub fn color_space(mut self, color_space: ImageColorSpace) -> Self {
+        self.color_space = color_space;
+        self
+    }
+
+    pub fn alpha(mut self, alpha: ImageAlpha) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Whether [`ImageDecodeOptions::convert`] leaves the pixels as they are
+    pub fn is_canvas_native(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Converts rgba8 pixels described by these options to sRGB with straight alpha in place
+    pub fn convert(&self, rgba: &mut [u8]) {
+        if self.is_canvas_native() {
+            return;
+        }
+
+        let encode = (self.color_space == ImageColorSpace::Linear).then(|| {
+            let mut table = [0u8; 256];
+            for (i, value) in table.iter_mut().enumerate() {
+                *value = (linear_to_srgb(i as f32 / 255.0) * 255.0).round() as u8;
+            }
+            table
+        });
+
+        for pixel in rgba.chunks_exact_mut(4) {
+            let alpha = pixel[3];
+            for channel in &mut pixel[..3] {
+                if self.alpha == ImageAlpha::Premultiplied && alpha > 0 {
+                    *channel =
+                        ((*channel as u32 * 255 + alpha as u32 / 2) / alpha as u32).min(255) as u8;
+                }
+                if let Some(encode) = &encode {
+                    *channel = encode[*channel as usize];
+                }
+            }
+        }
+    }
+}
+
+#[inline]
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn user_keys_are_namespaced() {
+        struct Icons;
+        struct Thumbnails;
+
+        let icon = UserAtlasKey::new::<Icons>(1);
+        assert_eq!(icon, UserAtlasKey::new::<Icons>(1));
+        assert_ne!(icon, UserAtlasKey::new::<Thumbnails>(1));
+        assert_ne!(icon, UserAtlasKey::new::<Icons>(2));
+        assert_ne!(icon, icon.greyscale());
+    }
+
+    #[test]
+    fn decode_options_convert_to_straight_srgb() {
+        let pixels = [128, 64, 0, 128, 10, 20, 30, 0];
+
+        let mut same = pixels;
+        ImageDecodeOptions::default().convert(&mut same);
+        assert_eq!(same, pixels);
+
+        let mut straight = pixels;
+        ImageDecodeOptions::default()
+            .alpha(ImageAlpha::Premultiplied)
+            .convert(&mut straight);
+        assert_eq!(straight, [255, 128, 0, 128, 10, 20, 30, 0]);
+
+        // linear mid grey is much lighter once encoded
+        let mut encoded = [0, 128, 255, 255];
+        ImageDecodeOptions::default()
+            .color_space(ImageColorSpace::Linear)
+            .convert(&mut encoded);
+        assert_eq!(encoded, [0, 188, 255, 255]);
+    }
+}
@@ -3,7 +3,7 @@ use std::ops::Range;
 
 use crate::{paint::WHITE_UV, Vec2};
 
-use super::{Color, Rgba, TextureId};
+use super::{Color, Rgba, TextureId, TextureOptions};
 
 #[repr(C)]
 #[derive(Debug, Clone, Copy, bytemuck::Zeroable, bytemuck::Pod)]
@@ -28,12 +28,15 @@ pub struct Mesh {
     pub vertices: Vec<Vertex>,
     pub indices: Vec<u32>,
     pub texture: TextureId,
+    /// Sampler used instead of the one the texture was added with
+    pub sampler: Option<TextureOptions>,
 }
 
 impl Mesh {
+    /// Removes the vertices and indices, keeping the allocations
     pub fn clear(&mut self) {
         self.indices.clear();
-        self.vertices = Default::default();
+        self.vertices.clear();
     }
 
     #[inline]
@@ -0,0 +1,208 @@
+use skie_math::{Mat3, Size, Vec2};
+
+use super::{add_convex_polygon, cut_triangles, Mesh, TextureId, Vertex};
+
+/// How a [`Pattern`] fills the area outside its first tile
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PatternRepeat {
+    #[default]
+    Repeat,
+    /// Every other tile is flipped so neighbouring tiles meet at matching edges
+    Mirror,
+    /// The edge pixels of the single tile are stretched outwards
+    Clamp,
+}
+
+/// Fills with a texture tiled across the shape.
+///
+/// The first tile spans `0..tile_size` in pattern space, `transform` maps pattern space into
+/// the brush's local space so the tiles can be moved, scaled and rotated
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pattern {
+    pub texture: TextureId,
+    pub tile_size: Size<f32>,
+    pub repeat: PatternRepeat,
+    pub transform: Mat3,
+}
+
+/// Fills spanning more tiles than this along an axis are not cut further, the extra tiles
+/// stretch the last one instead of producing huge meshes
+const MAX_TILES: i32 = 1024;
+
+/// Tile edges stop just short of 1 so nearest filtering stays on the tile's last texel
+/// instead of the next one in the atlas
+const MAX_UV: f32 = 1.0 - 1e-4;
+
+impl Pattern {
+    pub fn new(texture: TextureId, tile_size: Size<f32>) -> Self {
+        Self {
+            texture,
+            tile_size,
+            repeat: PatternRepeat::default(),
+            transform: Mat3::IDENTITY,
+        }
+    }
+
+    pub fn repeat(mut self, repeat: PatternRepeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    pub fn transform(mut self, transform: Mat3) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    /// Texture coordinates at `point` in local space, `0..1` within a tile
+    pub fn uv_at(&self, point: Vec2<f32>) -> Vec2<f32> {
+        let tile = self.tile_at(point);
+        self.tile_uv(tile, point)
+    }
+
+    /// Position in tiles, the first tile spans `0..1`
+    fn tile_space(&self, point: Vec2<f32>) -> Vec2<f32> {
+        let p = self.transform.inverse() * point;
+        Vec2::new(
+            p.x / self.tile_size.width.max(f32::EPSILON),
+            p.y / self.tile_size.height.max(f32::EPSILON),
+        )
+    }
+
+    fn tile_at(&self, point: Vec2<f32>) -> Vec2<f32> {
+        self.tile_space(point).floor()
+    }
+
+    fn tile_uv(&self, tile: Vec2<f32>, point: Vec2<f32>) -> Vec2<f32> {
+        let t = self.tile_space(point);
+        let uv = match self.repeat {
+            PatternRepeat::Repeat => t - tile,
+            PatternRepeat::Mirror => {
+                let flip = |uv: f32, tile: f32| {
+                    if (tile as i32).rem_euclid(2) == 1 {
+                        1.0 - uv
+                    } else {
+                        uv
+                    }
+                };
+                let uv = t - tile;
+                Vec2::new(flip(uv.x, tile.x), flip(uv.y, tile.y))
+            }
+            PatternRepeat::Clamp => t,
+        };
+        Vec2::new(uv.x.clamp(0.0, MAX_UV), uv.y.clamp(0.0, MAX_UV))
+    }
+
+    /// Lines between the tiles covering `min..max` in tile space, as a point and normal in local space
+    fn cut_lines(&self, min: Vec2<f32>, max: Vec2<f32>) -> Vec<(Vec2<f32>, Vec2<f32>)> {
+        let to_local = |x: f32, y: f32| {
+            self.transform * Vec2::new(x * self.tile_size.width, y * self.tile_size.height)
+        };
+
+        let edges = |min: f32, max: f32| -> Vec<i32> {
+            match self.repeat {
+                PatternRepeat::Clamp => vec![0, 1],
+                _ => {
+                    let first = min.floor() as i32 + 1;
+                    let last = (max.ceil() as i32 - 1).min(first.saturating_add(MAX_TILES));
+                    (first..=last).collect()
+                }
+            }
+        };
+
+        let mut lines = Vec::new();
+        for x in edges(min.x, max.x) {
+            let (a, b) = (to_local(x as f32, 0.0), to_local(x as f32, 1.0));
+            lines.push((a, Vec2::new(a.y - b.y, b.x - a.x)));
+        }
+        for y in edges(min.y, max.y) {
+            let (a, b) = (to_local(0.0, y as f32), to_local(1.0, y as f32));
+            lines.push((a, Vec2::new(a.y - b.y, b.x - a.x)));
+        }
+        lines
+    }
+}
+
+/// Maps the pattern onto the triangles added to `mesh` since `first_vertex` and `first_index`.
+/// Triangles are cut along the tile edges so each piece samples a single tile, the vertex
+/// colors are kept
+pub(crate) fn apply_pattern(
+    mesh: &mut Mesh,
+    first_vertex: usize,
+    first_index: usize,
+    pattern: &Pattern,
+) {
+    let Some((min, max)) = mesh.vertices[first_vertex..]
+        .iter()
+        .map(|v| pattern.tile_space(v.position.into()))
+        .fold(None, |bounds: Option<(Vec2<f32>, Vec2<f32>)>, t| {
+            Some(match bounds {
+                Some((min, max)) => (min.min(&t), max.max(&t)),
+                None => (t, t),
+            })
+        })
+    else {
+        return;
+    };
+
+    let lines = pattern.cut_lines(min, max);
+    cut_triangles(mesh, first_vertex, first_index, &lines, |mesh, piece| {
+        let centroid = piece
+            .iter()
+            .fold(Vec2::new(0.0, 0.0), |sum, v| sum + Vec2::from(v.position))
+            / piece.len() as f32;
+        let tile = pattern.tile_at(centroid);
+
+        add_convex_polygon(
+            mesh,
+            piece.iter().map(|vertex| Vertex {
+                uv: pattern.tile_uv(tile, vertex.position.into()).into(),
+                ..*vertex
+            }),
+        );
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Brush, DrawList, Rect};
+
+    fn uvs_at(mesh: &Mesh, point: Vec2<f32>) -> Vec<[f32; 2]> {
+        mesh.vertices
+            .iter()
+            .filter(|v| (Vec2::from(v.position) - point).magnitude() < 1e-3)
+            .map(|v| v.uv)
+            .collect()
+    }
+
+    #[test]
+    fn pattern_tiles_are_cut_and_mapped() {
+        let pattern = Pattern::new(TextureId::User(1), Size::new(10.0, 10.0));
+        let brush = Brush::default().pattern(pattern.clone()).antialias(false);
+
+        let mut list = DrawList::default();
+        list.add_primitive(
+            &crate::quad().rect(Rect::xywh(0.0, 0.0, 25.0, 10.0)).into(),
+            &brush,
+            false,
+        );
+        let mesh = list.build();
+
+        // cut at x = 10 and 20, the pieces on both sides of a cut meet at u = 1 and u = 0
+        let near =
+            |uv: &[f32; 2], u: f32, v: f32| (uv[0] - u).abs() < 1e-4 && (uv[1] - v).abs() < 1e-4;
+        let at_cut = uvs_at(&mesh, Vec2::new(10.0, 0.0));
+        assert!(at_cut.iter().any(|uv| near(uv, 0.0, 0.0)));
+        assert!(at_cut.iter().any(|uv| near(uv, MAX_UV, 0.0)));
+        assert!((pattern.uv_at(Vec2::new(25.0, 10.0)).x - 0.5).abs() < 1e-4);
+
+        let mirror = pattern.clone().repeat(PatternRepeat::Mirror);
+        assert!((mirror.uv_at(Vec2::new(12.0, 3.0)).x - 0.8).abs() < 1e-4);
+
+        let clamp = pattern
+            .repeat(PatternRepeat::Clamp)
+            .transform(Mat3::from_translation(5.0, 0.0));
+        assert_eq!(clamp.uv_at(Vec2::new(40.0, 5.0)), Vec2::new(MAX_UV, 0.5));
+        assert_eq!(clamp.uv_at(Vec2::new(0.0, 5.0)), Vec2::new(0.0, 0.5));
+    }
+}
@@ -1,15 +1,82 @@
-use crate::{math::Corners, path::Path, Zero};
+use crate::{
+    math::Corners,
+    path::{Path, PathEventsIter, PathGeometryBuilder},
+    Zero,
+};
 use std::fmt::Debug;
 
 use crate::math::{Rect, Vec2};
 
-use super::PathBrush;
+use super::{Borders, PathBrush, DEFAULT_TOLERANCE};
 
 #[derive(Debug, Clone)]
+// paths carry their brushes inline, boxing them would allocate for every path drawn
+#[allow(clippy::large_enum_variant)]
 pub enum Primitive {
     Quad(Quad),
     Path { path: Path, brush: PathBrush },
     Circle(Circle),
+    Ring(Ring),
+    Pie(Pie),
+    Ellipse(Ellipse),
+    Arc(CircleArc),
+    Polyline(Polyline),
+    Points(Points),
+}
+
+impl Primitive {
+    /// Whether the point is inside the filled area, strokes are not included.
+    /// Paths are tested against the flattened outlines of their contours
+    pub fn contains_point(&self, point: &Vec2<f32>) -> bool {
+        match self {
+            Primitive::Quad(quad) => quad.contains_point(point),
+            Primitive::Path { path, .. } => path_contains_point(path, point),
+            Primitive::Circle(circle) => (*point - circle.center).magnitude() <= circle.radius,
+            Primitive::Ring(ring) => {
+                let distance = (*point - ring.center).magnitude();
+                distance >= ring.inner_radius.min(ring.outer_radius)
+                    && distance <= ring.inner_radius.max(ring.outer_radius)
+            }
+            Primitive::Pie(pie) => pie.contains_point(point),
+            Primitive::Ellipse(ellipse) => ellipse.contains_point(point),
+            Primitive::Arc(arc) => arc.contains_point(point),
+            Primitive::Polyline(polyline) => polyline.contains_point(point),
+            Primitive::Points(points) => points.contains_point(point),
+        }
+    }
+}
+
+/// Contours are filled on their own, so the point only has to be inside one of them
+fn path_contains_point(path: &Path, point: &Vec2<f32>) -> bool {
+    let mut outline = Vec::new();
+    path.contours()
+        .filter(|contour| contour.bounds().contains_point(point))
+        .any(|contour| {
+            outline.clear();
+            PathGeometryBuilder::<PathEventsIter>::new(contour.events(), &mut outline)
+                .tolerance(DEFAULT_TOLERANCE)
+                .next()
+                .is_some_and(|(_, range)| polygon_contains_point(&outline[range], point))
+        })
+}
+
+/// Even-odd test against the polygon closed from its last point to the first
+fn polygon_contains_point(polygon: &[Vec2<f32>], point: &Vec2<f32>) -> bool {
+    let Some(mut previous) = polygon.last() else {
+        return false;
+    };
+
+    let mut inside = false;
+    for current in polygon {
+        if (current.y > point.y) != (previous.y > point.y) {
+            let t = (point.y - current.y) / (previous.y - current.y);
+            if point.x < current.x + t * (previous.x - current.x) {
+                inside = !inside;
+            }
+        }
+        previous = current;
+    }
+    inside
 }
 
 #[derive(Debug, Default, Clone)]
@@ -31,13 +98,321 @@ impl Circle {
     }
 }
 
+#[derive(Debug, Default, Clone)]
+pub struct Ring {
+    pub center: Vec2<f32>,
+    pub inner_radius: f32,
+    pub outer_radius: f32,
+}
+
+impl Ring {
+    pub fn radii(mut self, inner_radius: f32, outer_radius: f32) -> Self {
+        self.inner_radius = inner_radius;
+        self.outer_radius = outer_radius;
+        self
+    }
+
+    pub fn pos(mut self, cx: f32, cy: f32) -> Self {
+        self.center.x = cx;
+        self.center.y = cy;
+        self
+    }
+}
+
+/// A circular wedge, or a segment of a ring when `inner_radius` is greater than zero.
+/// Angles are in radians, measured clockwise from the positive x axis
+#[derive(Debug, Default, Clone)]
+pub struct Pie {
+    pub center: Vec2<f32>,
+    pub radius: f32,
+    pub inner_radius: f32,
+    pub start: f32,
+    pub sweep: f32,
+}
+
+impl Pie {
+    fn contains_point(&self, point: &Vec2<f32>) -> bool {
+        let d = *point - self.center;
+        let distance = d.magnitude();
+        if distance > self.radius.abs() || distance < self.inner_radius {
+            return false;
+        }
+
+        if self.sweep.abs() >= std::f32::consts::TAU {
+            return true;
+        }
+
+        // angle from the start in the direction of the sweep
+        let angle = (d.y.atan2(d.x) - self.start) * self.sweep.signum();
+        angle.rem_euclid(std::f32::consts::TAU) <= self.sweep.abs()
+    }
+
+    pub fn pos(mut self, cx: f32, cy: f32) -> Self {
+        self.center.x = cx;
+        self.center.y = cy;
+        self
+    }
+
+    pub fn radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    pub fn inner_radius(mut self, inner_radius: f32) -> Self {
+        self.inner_radius = inner_radius;
+        self
+    }
+
+    pub fn angles(mut self, start: f32, sweep: f32) -> Self {
+        self.start = start;
+        self.sweep = sweep;
+        self
+    }
+}
+
+/// An ellipse rotated by `rotation` radians clockwise around its center
+#[derive(Debug, Default, Clone)]
+pub struct Ellipse {
+    pub center: Vec2<f32>,
+    pub radii: Vec2<f32>,
+    pub rotation: f32,
+}
+
+impl Ellipse {
+    fn contains_point(&self, point: &Vec2<f32>) -> bool {
+        let (rx, ry) = (self.radii.x.abs(), self.radii.y.abs());
+        if rx <= 0.0 || ry <= 0.0 {
+            return false;
+        }
+
+        // into the unrotated ellipse
+        let (sin, cos) = self.rotation.sin_cos();
+        let d = *point - self.center;
+        let x = d.x * cos + d.y * sin;
+        let y = d.y * cos - d.x * sin;
+        (x / rx).powi(2) + (y / ry).powi(2) <= 1.0
+    }
+
+    /// Point on the outline at the parametric `angle` in radians
+    pub fn point_at(&self, angle: f32) -> Vec2<f32> {
+        let (sin, cos) = self.rotation.sin_cos();
+        let x = angle.cos() * self.radii.x;
+        let y = angle.sin() * self.radii.y;
+        self.center + Vec2::new(x * cos - y * sin, x * sin + y * cos)
+    }
+
+    pub fn pos(mut self, cx: f32, cy: f32) -> Self {
+        self.center.x = cx;
+        self.center.y = cy;
+        self
+    }
+
+    pub fn radii(mut self, rx: f32, ry: f32) -> Self {
+        self.radii = Vec2::new(rx, ry);
+        self
+    }
+
+    pub fn rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+}
+
+/// An open line through points with a width at each point, interpolated along the segments,
+/// like ink following stylus pressure. Drawn with the stroke color, caps and joins of the
+/// brush, its fill and stroke width are not used
+#[derive(Debug, Default, Clone)]
+pub struct Polyline {
+    pub points: Vec<Vec2<f32>>,
+    pub widths: Vec<f32>,
+}
+
+impl Polyline {
+    pub fn point(mut self, x: f32, y: f32, width: f32) -> Self {
+        self.points.push(Vec2::new(x, y));
+        self.widths.push(width);
+        self
+    }
+
+    pub fn points(mut self, points: impl IntoIterator<Item = (Vec2<f32>, f32)>) -> Self {
+        for (point, width) in points {
+            self.points.push(point);
+            self.widths.push(width);
+        }
+        self
+    }
+
+    /// Whether the point is within half the interpolated width of a segment
+    fn contains_point(&self, point: &Vec2<f32>) -> bool {
+        let half_width = |i: usize| self.widths.get(i).copied().unwrap_or_default() / 2.0;
+
+        self.points.windows(2).enumerate().any(|(i, segment)| {
+            let (a, b) = (segment[0], segment[1]);
+            let ab = b - a;
+            let length_sq = ab.dot(&ab);
+            let t = if length_sq > 0.0 {
+                ((*point - a).dot(&ab) / length_sq).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let width = half_width(i) + (half_width(i + 1) - half_width(i)) * t;
+            (*point - (a + ab * t)).magnitude() <= width
+        })
+    }
+}
+
+/// Shape of the markers drawn by [`Points`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PointShape {
+    #[default]
+    Circle,
+    Square,
+    /// A plus sign with arms a fifth of the size thick
+    Cross,
+}
+
+/// A marker of `size` centered on each point, for scatter plots and particles. All of
+/// them go into one mesh. Filled with the fill of the brush, its stroke is not used
+#[derive(Debug, Clone)]
+pub struct Points {
+    pub points: Vec<Vec2<f32>>,
+    /// width and height of each marker
+    pub size: f32,
+    pub shape: PointShape,
+}
+
+impl Default for Points {
+    fn default() -> Self {
+        Self {
+            points: Vec::new(),
+            size: 1.0,
+            shape: PointShape::default(),
+        }
+    }
+}
+
+impl Points {
+    pub fn points(mut self, points: impl IntoIterator<Item = Vec2<f32>>) -> Self {
+        self.points.extend(points);
+        self
+    }
+
+    pub fn size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn shape(mut self, shape: PointShape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// Half the thickness of the arms of [`PointShape::Cross`]
+    pub(crate) fn cross_half_width(&self) -> f32 {
+        self.size * 0.1
+    }
+
+    fn contains_point(&self, point: &Vec2<f32>) -> bool {
+        let half = self.size / 2.0;
+        let arm = self.cross_half_width();
+
+        self.points.iter().any(|center| {
+            let d = *point - *center;
+            let (x, y) = (d.x.abs(), d.y.abs());
+            match self.shape {
+                PointShape::Circle => d.magnitude() <= half,
+                PointShape::Square => x <= half && y <= half,
+                PointShape::Cross => x <= half && y <= half && (x <= arm || y <= arm),
+            }
+        })
+    }
+}
+
+/// A circular arc. The stroke follows the arc and the fill covers the area between the arc
+/// and its chord. Angles are in radians, measured clockwise from the positive x axis
+#[derive(Debug, Default, Clone)]
+pub struct CircleArc {
+    pub center: Vec2<f32>,
+    pub radius: f32,
+    pub start: f32,
+    pub sweep: f32,
+}
+
+impl CircleArc {
+    fn contains_point(&self, point: &Vec2<f32>) -> bool {
+        let d = *point - self.center;
+        if d.magnitude() > self.radius.abs() {
+            return false;
+        }
+
+        let sweep = self
+            .sweep
+            .clamp(-std::f32::consts::TAU, std::f32::consts::TAU);
+        if sweep.abs() >= std::f32::consts::TAU {
+            return true;
+        }
+
+        // on the same side of the chord as the middle of the arc
+        let at = |angle: f32| Vec2::new(angle.cos(), angle.sin()) * self.radius.abs();
+        let (from, to) = (at(self.start), at(self.start + sweep));
+        let middle = at(self.start + sweep * 0.5);
+        let chord = to - from;
+        chord.cross(&(d - from)) * chord.cross(&(middle - from)) >= 0.0
+    }
+
+    pub fn pos(mut self, cx: f32, cy: f32) -> Self {
+        self.center.x = cx;
+        self.center.y = cy;
+        self
+    }
+
+    pub fn radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    pub fn angles(mut self, start: f32, sweep: f32) -> Self {
+        self.start = start;
+        self.sweep = sweep;
+        self
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Quad {
     pub bounds: Rect<f32>,
     pub corners: Corners<f32>,
+    /// squircle corner smoothing in `0..=1`, see [`crate::PathBuilder::squircle`]
+    pub smoothing: f32,
+    pub borders: Option<Borders>,
 }
 
 impl Quad {
+    fn contains_point(&self, point: &Vec2<f32>) -> bool {
+        if !self.bounds.contains_point(point) {
+            return false;
+        }
+
+        let min = self.bounds.min();
+        let max = self.bounds.max();
+        let max_radius = self.bounds.size.width.min(self.bounds.size.height) * 0.5;
+
+        // outside a rounded corner when past the corner center and outside its circle
+        let corners = [
+            (self.corners.top_left, min.x, min.y, -1.0, -1.0),
+            (self.corners.top_right, max.x, min.y, 1.0, -1.0),
+            (self.corners.bottom_left, min.x, max.y, -1.0, 1.0),
+            (self.corners.bottom_right, max.x, max.y, 1.0, 1.0),
+        ];
+
+        corners.into_iter().all(|(radius, x, y, sx, sy)| {
+            let radius = radius.clamp(0.0, max_radius);
+            let center = Vec2::new(x - sx * radius, y - sy * radius);
+            let d = *point - center;
+            d.x * sx <= 0.0 || d.y * sy <= 0.0 || d.magnitude() <= radius
+        })
+    }
+
     pub fn size(mut self, width: f32, height: f32) -> Self {
         self.bounds.size.width = width;
         self.bounds.size.height = height;
@@ -59,6 +434,16 @@ impl Quad {
         self.corners = corners;
         self
     }
+
+    pub fn smoothing(mut self, smoothing: f32) -> Self {
+        self.smoothing = smoothing;
+        self
+    }
+
+    pub fn borders(mut self, borders: Borders) -> Self {
+        self.borders = Some(borders);
+        self
+    }
 }
 
 impl Default for Quad {
@@ -66,6 +451,8 @@ impl Default for Quad {
         Self {
             bounds: Rect::zero(),
             corners: Corners::default(),
+            smoothing: 0.0,
+            borders: None,
         }
     }
 }
@@ -80,6 +467,36 @@ pub fn circle() -> Circle {
     Circle::default()
 }
 
+#[inline]
+pub fn ring() -> Ring {
+    Ring::default()
+}
+
+#[inline]
+pub fn pie() -> Pie {
+    Pie::default()
+}
+
+#[inline]
+pub fn ellipse() -> Ellipse {
+    Ellipse::default()
+}
+
+#[inline]
+pub fn arc() -> CircleArc {
+    CircleArc::default()
+}
+
+#[inline]
+pub fn polyline() -> Polyline {
+    Polyline::default()
+}
+
+#[inline]
+pub fn points() -> Points {
+    Points::default()
+}
+
 impl From<Quad> for Primitive {
     #[inline]
     fn from(quad: Quad) -> Self {
@@ -93,3 +510,118 @@ impl From<Circle> for Primitive {
         Primitive::Circle(circle)
     }
 }
+
+impl From<Ring> for Primitive {
+    #[inline]
+    fn from(ring: Ring) -> Self {
+        Primitive::Ring(ring)
+    }
+}
+
+impl From<Pie> for Primitive {
+    #[inline]
+    fn from(pie: Pie) -> Self {
+        Primitive::Pie(pie)
+    }
+}
+
+impl From<Ellipse> for Primitive {
+    #[inline]
+    fn from(ellipse: Ellipse) -> Self {
+        Primitive::Ellipse(ellipse)
+    }
+}
+
+impl From<CircleArc> for Primitive {
+    #[inline]
+    fn from(arc: CircleArc) -> Self {
+        Primitive::Arc(arc)
+    }
+}
+
+impl From<Polyline> for Primitive {
+    #[inline]
+    fn from(polyline: Polyline) -> Self {
+        Primitive::Polyline(polyline)
+    }
+}
+
+impl From<Points> for Primitive {
+    #[inline]
+    fn from(points: Points) -> Self {
+        Primitive::Points(points)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::vec2;
+
+    #[test]
+    fn primitive_contains_point() {
+        let rounded: Primitive = quad()
+            .rect(Rect::xywh(0.0, 0.0, 100.0, 100.0))
+            .corners(Corners::with_all(20.0))
+            .into();
+        assert!(rounded.contains_point(&vec2(50.0, 1.0)));
+        assert!(rounded.contains_point(&vec2(10.0, 10.0)));
+        assert!(!rounded.contains_point(&vec2(1.0, 1.0)));
+        assert!(!rounded.contains_point(&vec2(99.0, 99.0)));
+
+        let annulus: Primitive = ring().radii(5.0, 10.0).into();
+        assert!(annulus.contains_point(&vec2(7.0, 0.0)));
+        assert!(!annulus.contains_point(&vec2(2.0, 0.0)));
+
+        // quarter from the positive x axis towards positive y
+        let wedge: Primitive = pie()
+            .radius(10.0)
+            .angles(0.0, std::f32::consts::FRAC_PI_2)
+            .into();
+        assert!(wedge.contains_point(&vec2(5.0, 5.0)));
+        assert!(!wedge.contains_point(&vec2(5.0, -5.0)));
+
+        let reversed: Primitive = pie()
+            .radius(10.0)
+            .angles(0.0, -std::f32::consts::FRAC_PI_2)
+            .into();
+        assert!(reversed.contains_point(&vec2(5.0, -5.0)));
+        assert!(!reversed.contains_point(&vec2(5.0, 5.0)));
+
+        let rotated: Primitive = ellipse()
+            .radii(20.0, 5.0)
+            .rotation(std::f32::consts::FRAC_PI_2)
+            .into();
+        assert!(rotated.contains_point(&vec2(0.0, 18.0)));
+        assert!(!rotated.contains_point(&vec2(18.0, 0.0)));
+
+        // the segment cut off by the chord of a quarter arc
+        let segment: Primitive = arc()
+            .radius(10.0)
+            .angles(0.0, std::f32::consts::FRAC_PI_2)
+            .into();
+        assert!(segment.contains_point(&vec2(6.0, 6.0)));
+        assert!(!segment.contains_point(&vec2(4.0, 4.0)));
+
+        // the notch of the triangle is outside its bounds test
+        let mut builder = crate::PathBuilder::default();
+        builder.begin(vec2(0.0, 0.0));
+        builder.line_to(vec2(20.0, 0.0));
+        builder.line_to(vec2(0.0, 20.0));
+        builder.close();
+        let triangle = Primitive::Path {
+            path: builder.build(),
+            brush: Default::default(),
+        };
+        assert!(triangle.contains_point(&vec2(5.0, 5.0)));
+        assert!(!triangle.contains_point(&vec2(15.0, 15.0)));
+
+        // tapers from 2 to 12 wide
+        let stroke: Primitive = polyline()
+            .point(0.0, 0.0, 2.0)
+            .point(100.0, 0.0, 12.0)
+            .into();
+        assert!(stroke.contains_point(&vec2(95.0, 5.0)));
+        assert!(!stroke.contains_point(&vec2(5.0, 5.0)));
+    }
+}
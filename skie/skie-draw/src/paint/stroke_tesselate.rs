@@ -17,11 +17,41 @@ pub struct StrokeTesellator<'a> {
 
 impl<'a> StrokeTesellator<'a> {
     pub fn add_to_mesh(mesh: &'a mut Mesh, points: &[Vec2<f32>], stroke_style: &StrokeStyle) {
+        Self::add_to_mesh_with_width(mesh, points, stroke_style, stroke_style.width());
+    }
+
+    /// Strokes with `line_width` instead of the style's, for widths that depend on the transform
+    pub fn add_to_mesh_with_width(
+        mesh: &'a mut Mesh,
+        points: &[Vec2<f32>],
+        stroke_style: &StrokeStyle,
+        line_width: f32,
+    ) {
+        let mut polyline = Self {
+            mesh: StrokeTesellatorMesh::Borrowed(mesh),
+        };
+
+        polyline.add_polyline(points, stroke_style, |_| line_width / 2.0);
+    }
+
+    /// Strokes with a width for every point, interpolated along the segments. Points without
+    /// a width use the last one
+    pub fn add_to_mesh_with_widths(
+        mesh: &'a mut Mesh,
+        points: &[Vec2<f32>],
+        widths: &[f32],
+        stroke_style: &StrokeStyle,
+    ) {
+        let Some(last) = widths.last().copied() else {
+            return;
+        };
         let mut polyline = Self {
             mesh: StrokeTesellatorMesh::Borrowed(mesh),
         };
 
-        polyline.add_polyline(points, stroke_style);
+        polyline.add_polyline(points, stroke_style, |i| {
+            widths.get(i).copied().unwrap_or(last).max(0.0) / 2.0
+        });
     }
 
     pub fn create(points: &[Vec2<f32>], stroke_style: &StrokeStyle) -> Mesh {
@@ -29,7 +59,8 @@ impl<'a> StrokeTesellator<'a> {
             mesh: StrokeTesellatorMesh::Owned(Default::default()),
         };
 
-        polyline.add_polyline(points, stroke_style);
+        let half_width = stroke_style.width() / 2.0;
+        polyline.add_polyline(points, stroke_style, |_| half_width);
 
         match polyline.mesh {
             StrokeTesellatorMesh::Owned(mesh) => mesh,
@@ -37,17 +68,28 @@ impl<'a> StrokeTesellator<'a> {
         }
     }
 
-    fn add_polyline(&mut self, points: &[Vec2<f32>], stroke_style: &StrokeStyle) {
+    /// `half_width` is the half line width at the point of an index
+    fn add_polyline(
+        &mut self,
+        points: &[Vec2<f32>],
+        stroke_style: &StrokeStyle,
+        half_width: impl Fn(usize) -> f32,
+    ) {
         if points.len() < 2 {
             return;
         }
 
-        let h_linewidth = stroke_style.line_width.max(1) as f32 / 2.0;
-
         let segments: Vec<PolySegment> = points
             .windows(2)
-            .filter(|p| p[0] != p[1])
-            .map(|p| PolySegment::new(LineSegment::new(p[0], p[1]), h_linewidth))
+            .enumerate()
+            .filter(|(_, p)| p[0] != p[1])
+            .map(|(i, p)| {
+                PolySegment::new(
+                    LineSegment::new(p[0], p[1]),
+                    half_width(i),
+                    half_width(i + 1),
+                )
+            })
             .collect();
 
         let mut join = false;
@@ -109,10 +151,12 @@ impl<'a> StrokeTesellator<'a> {
                 }
                 LineCap::Square => {
                     // offset the start and end with the half line width
-                    path_start_1 += first_segment.edge1.direction() * h_linewidth;
-                    path_start_2 += first_segment.edge2.direction() * h_linewidth;
-                    path_end_1 -= last_segment.edge1.direction() * h_linewidth;
-                    path_end_2 -= last_segment.edge2.direction() * h_linewidth;
+                    let direction = first_segment.center.direction();
+                    path_start_1 += direction * first_segment.half_width_a;
+                    path_start_2 += direction * first_segment.half_width_a;
+                    let direction = last_segment.center.direction();
+                    path_end_1 -= direction * last_segment.half_width_b;
+                    path_end_2 -= direction * last_segment.half_width_b;
                 }
             }
         }
@@ -319,19 +363,31 @@ struct PolySegment {
     pub edge1: LineSegment,
     pub center: LineSegment,
     pub edge2: LineSegment,
+    pub half_width_a: f32,
+    pub half_width_b: f32,
 }
 
 impl PolySegment {
+    /// The edges are `half_width_a` away from the center at its start and `half_width_b`
+    /// at its end
     #[inline]
-    pub fn new(center: LineSegment, line_width: f32) -> Self {
+    pub fn new(center: LineSegment, half_width_a: f32, half_width_b: f32) -> Self {
         let normal = center.normal();
-        let edge_1 = center.clone() + normal * line_width;
-        let edge_2 = center.clone() - (normal * line_width);
+        let edge_1 = LineSegment::new(
+            center.a + normal * half_width_a,
+            center.b + normal * half_width_b,
+        );
+        let edge_2 = LineSegment::new(
+            center.a - normal * half_width_a,
+            center.b - normal * half_width_b,
+        );
 
         Self {
             center,
             edge1: edge_1,
             edge2: edge_2,
+            half_width_a,
+            half_width_b,
         }
     }
 }
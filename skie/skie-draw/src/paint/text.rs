@@ -1,4 +1,7 @@
-use crate::{arc_string::ArcString, Font, FontStyle, FontWeight, Vec2, Zero};
+use crate::{
+    arc_string::ArcString, text::Locale, Font, FontFeatureTag, FontFeatures, FontStyle, FontWeight,
+    Rect, Size, Vec2, Zero,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum TextBaseline {
@@ -18,6 +21,35 @@ pub enum TextAlign {
     Center,
 }
 
+/// Where lines break when they reach the wrap width of a [`Text`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TextWrap {
+    /// Lines only break at newlines
+    None,
+    /// Breaks between any two glyphs
+    Glyph,
+    /// Breaks between words, words longer than a line overflow it
+    Word,
+    /// Breaks between words, and inside words longer than a line
+    #[default]
+    WordOrGlyph,
+}
+
+/// What happens to text outside the wrap box of a [`Text`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TextOverflow {
+    /// Lines past the max height are still laid out and drawn
+    #[default]
+    Visible,
+    /// Lines past the max height are dropped and drawing is clipped to the box
+    Clip,
+    /// The last line that fits ends with `…` when the text doesn't. Lines only wrapping at
+    /// newlines are also cut at the max width
+    Ellipsis,
+    /// The last line that fits fades out towards its end when the text doesn't fit
+    Fade,
+}
+
 #[derive(Debug, Clone)]
 pub struct Text {
     pub(crate) text: ArcString,
@@ -27,6 +59,12 @@ pub struct Text {
     pub(crate) align: TextAlign,
     pub(crate) word_spacing: f32,
     pub(crate) baseline: TextBaseline,
+    pub(crate) locale: Option<Locale>,
+    pub(crate) max_width: Option<f32>,
+    pub(crate) max_height: Option<f32>,
+    pub(crate) wrap: TextWrap,
+    pub(crate) overflow: TextOverflow,
+    pub(crate) max_lines: Option<usize>,
 }
 
 impl Default for Text {
@@ -39,10 +77,17 @@ impl Default for Text {
                 family: ArcString::new_static("Segoe UI"),
                 weight: FontWeight::default(),
                 style: FontStyle::default(),
+                features: FontFeatures::default(),
             },
             align: Default::default(),
             baseline: Default::default(),
             word_spacing: f32::zero(),
+            locale: None,
+            max_width: None,
+            max_height: None,
+            wrap: TextWrap::default(),
+            overflow: TextOverflow::default(),
+            max_lines: None,
         }
     }
 }
@@ -98,8 +143,135 @@ impl Text {
         self
     }
 
+    /// See [`Font::feature`]
+    pub fn font_feature(mut self, tag: FontFeatureTag, enabled: bool) -> Self {
+        self.font.features.set(tag, enabled as u32);
+        self
+    }
+
     pub fn word_spacing(mut self, spacing_in_px: f32) -> Self {
         self.word_spacing = spacing_in_px;
         self
     }
+
+    /// Language of the text, picks digit shapes and fallback fonts, see [`Locale`]
+    pub fn locale(mut self, locale: impl Into<Locale>) -> Self {
+        self.locale = Some(locale.into());
+        self
+    }
+
+    /// Width lines wrap at, [`TextAlign`] aligns lines inside it.
+    /// Without one lines wrap at the edge of the canvas
+    pub fn max_width(mut self, width: f32) -> Self {
+        self.max_width = Some(width);
+        self
+    }
+
+    /// Height of the text box, lines past it are dropped unless the overflow is
+    /// [`TextOverflow::Visible`]
+    pub fn max_height(mut self, height: f32) -> Self {
+        self.max_height = Some(height);
+        self
+    }
+
+    /// Lays the text out as a paragraph inside `rect`, its origin becomes the text position
+    pub fn wrap_in(self, rect: &Rect<f32>) -> Self {
+        self.pos(rect.origin.x, rect.origin.y)
+            .max_width(rect.size.width)
+            .max_height(rect.size.height)
+    }
+
+    pub fn wrap(mut self, wrap: TextWrap) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    pub fn overflow(mut self, overflow: TextOverflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Drops the visual lines after the first `lines`, the overflow marks the cut
+    pub fn max_lines(mut self, lines: usize) -> Self {
+        self.max_lines = Some(lines);
+        self
+    }
+
+    pub fn get_text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn get_font(&self) -> &Font {
+        &self.font
+    }
+
+    pub fn get_size(&self) -> f32 {
+        self.size
+    }
+
+    pub fn get_pos(&self) -> Vec2<f32> {
+        self.pos
+    }
+
+    pub fn get_align(&self) -> TextAlign {
+        self.align
+    }
+
+    pub fn get_baseline(&self) -> TextBaseline {
+        self.baseline
+    }
+
+    pub fn get_word_spacing(&self) -> f32 {
+        self.word_spacing
+    }
+
+    pub fn get_locale(&self) -> Option<&Locale> {
+        self.locale.as_ref()
+    }
+
+    pub fn get_max_width(&self) -> Option<f32> {
+        self.max_width
+    }
+
+    pub fn get_max_height(&self) -> Option<f32> {
+        self.max_height
+    }
+
+    pub fn get_wrap(&self) -> TextWrap {
+        self.wrap
+    }
+
+    pub fn get_overflow(&self) -> TextOverflow {
+        self.overflow
+    }
+
+    pub fn get_max_lines(&self) -> Option<usize> {
+        self.max_lines
+    }
+
+    /// Visual lines kept by [`Text::max_lines`] and the max height. Clipped text also keeps
+    /// the line cut by the bottom of the box
+    pub(crate) fn line_limit(&self, line_height: f32) -> Option<usize> {
+        let by_height = match self.overflow {
+            TextOverflow::Visible | TextOverflow::Clip => None,
+            TextOverflow::Ellipsis | TextOverflow::Fade => self
+                .max_height
+                .map(|height| (height / line_height + 1e-3).floor().max(1.0) as usize),
+        };
+        match (self.max_lines, by_height) {
+            (Some(lines), Some(by_height)) => Some(lines.min(by_height)),
+            (lines, by_height) => lines.or(by_height),
+        }
+    }
+
+    /// The box drawing is clipped to with [`TextOverflow::Clip`], unbounded sides are infinite
+    pub fn clip_rect(&self) -> Rect<f32> {
+        Rect::from_origin_size(
+            self.pos,
+            Size::new(
+                self.max_width.unwrap_or(f32::INFINITY),
+                self.max_height.unwrap_or(f32::INFINITY),
+            ),
+        )
+    }
 }
@@ -1,6 +1,6 @@
-use crate::math::Size;
+use crate::math::{Rect, Size};
 
-use super::{atlas::AtlasTextureId, AtlasImage, AtlasKey};
+use super::{atlas::AtlasTextureId, AtlasImage, AtlasKey, UserAtlasKey};
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub enum TextureId {
@@ -20,6 +20,12 @@ impl From<AtlasImage> for TextureId {
     }
 }
 
+impl From<UserAtlasKey> for TextureId {
+    fn from(key: UserAtlasKey) -> Self {
+        Self::AtlasKey(key.into())
+    }
+}
+
 impl From<AtlasKey> for TextureId {
     fn from(atlas: AtlasKey) -> Self {
         Self::AtlasKey(atlas)
@@ -48,6 +54,25 @@ impl TextureId {
 
 pub static WHITE_UV: (f32, f32) = (0.0, 0.0);
 
+/// Where a texture lives and how big it is, see [`crate::Canvas::texture_info`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextureInfo {
+    /// Size in pixels of the texture, or of its tile for atlas textures
+    pub size: Size<u32>,
+    pub kind: TextureKind,
+    /// Atlas texture holding the tile, `None` for textures outside the atlas
+    pub atlas_page: Option<AtlasTextureId>,
+    /// Normalized rect of the texture within its page, the full `0..1` range outside the atlas
+    pub uv_rect: Rect<f32>,
+}
+
+impl TextureInfo {
+    /// Width over height
+    pub fn aspect_ratio(&self) -> f32 {
+        self.size.width as f32 / self.size.height.max(1) as f32
+    }
+}
+
 pub struct Texture2DSpecs {
     pub size: Size<u32>,
     pub format: TextureFormat,
@@ -56,7 +81,7 @@ pub struct Texture2DSpecs {
 pub type TextureAddressMode = wgpu::AddressMode;
 pub type TextureFilterMode = wgpu::FilterMode;
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
 pub struct TextureOptions {
     pub address_mode_u: TextureAddressMode,
     pub address_mode_v: TextureAddressMode,
@@ -78,6 +103,13 @@ impl TextureOptions {
         self
     }
 
+    /// Sets both the mag and min filters, nearest keeps pixel art crisp
+    pub fn filter(mut self, mode: TextureFilterMode) -> Self {
+        self.mag_filter = mode;
+        self.min_filter = mode;
+        self
+    }
+
     pub fn min_filter(mut self, mode: TextureFilterMode) -> Self {
         self.min_filter = mode;
         self
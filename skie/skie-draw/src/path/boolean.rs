@@ -0,0 +1,411 @@
+use std::collections::{HashMap, HashSet};
+
+use skie_math::vec2;
+
+use super::{Path, PathEventsIter, PathGeometryBuilder, Point};
+
+/// Max distance in pixels between the curves of the operands of a [`PathOp`] and the lines replacing them
+pub const PATH_OP_TOLERANCE: f32 = 0.1;
+
+/// Points of the result are snapped to a grid this fine so that the split edges meet exactly
+const GRID: f32 = 4096.0;
+
+/// Edge parameters closer than this to an end are treated as the end
+const T_EPSILON: f32 = 1e-5;
+
+/// How [`Path::op`] combines the areas of two paths
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PathOp {
+    /// Area covered by either path
+    Union,
+    /// Area covered by both paths
+    Intersection,
+    /// Area of the first path not covered by the second one
+    Difference,
+    /// Area covered by exactly one of the paths
+    Xor,
+}
+
+impl PathOp {
+    fn keeps(self, in_a: bool, in_b: bool) -> bool {
+        match self {
+            PathOp::Union => in_a || in_b,
+            PathOp::Intersection => in_a && in_b,
+            PathOp::Difference => in_a && !in_b,
+            PathOp::Xor => in_a != in_b,
+        }
+    }
+}
+
+pub(super) type Edge = (Point, Point);
+type Key = (i64, i64);
+
+impl Path {
+    /// Combines the areas filled by `self` and `other` into a new path. Both are filled with
+    /// the nonzero rule and their open contours are closed. Curves are flattened within
+    /// [`PATH_OP_TOLERANCE`], so the result is made of lines. Its outlines wind clockwise
+    /// and its holes counter-clockwise
+    pub fn op(&self, other: &Path, op: PathOp) -> Path {
+        let a = polygon_edges(self, PATH_OP_TOLERANCE);
+        let b = polygon_edges(other, PATH_OP_TOLERANCE);
+
+        let mut seen = HashSet::new();
+        let mut kept: Vec<(Key, Key)> = Vec::new();
+
+        for (from, to) in split_edges(a.iter().chain(&b).copied().collect()) {
+            let (from, to) = (key(from), key(to));
+            if from == to || !seen.insert((from.min(to), from.max(to))) {
+                continue;
+            }
+
+            let (p0, p1) = (point(from), point(to));
+            let dir = p1 - p0;
+            let len = dir.magnitude();
+            let mid = (p0 + p1) * 0.5;
+            let scale = mid.x.abs().max(mid.y.abs()).max(1.0);
+            let offset = dir.normal() * ((1e-3 * scale).min(len * 0.25) / len);
+
+            let inside = |p: Point| op.keeps(winding(&a, p) != 0, winding(&b, p) != 0);
+            // outlines wind clockwise, keeping the filled side along the normal
+            match (inside(mid + offset), inside(mid - offset)) {
+                (true, false) => kept.push((from, to)),
+                (false, true) => kept.push((to, from)),
+                _ => {}
+            }
+        }
+
+        let mut builder = Path::builder();
+        for contour in link_edges(&kept) {
+            let points = remove_collinear(&contour);
+            if points.len() < 3 {
+                continue;
+            }
+            builder.begin(points[0]);
+            for &p in &points[1..] {
+                builder.line_to(p);
+            }
+            builder.close();
+        }
+        builder.build()
+    }
+
+    /// Area covered by either path, see [`Path::op`]
+    pub fn union(&self, other: &Path) -> Path {
+        self.op(other, PathOp::Union)
+    }
+
+    /// Area covered by both paths, see [`Path::op`]
+    pub fn intersection(&self, other: &Path) -> Path {
+        self.op(other, PathOp::Intersection)
+    }
+
+    /// Area of this path with `other` punched out of it, see [`Path::op`]
+    pub fn difference(&self, other: &Path) -> Path {
+        self.op(other, PathOp::Difference)
+    }
+
+    /// Area covered by exactly one of the paths, see [`Path::op`]
+    pub fn xor(&self, other: &Path) -> Path {
+        self.op(other, PathOp::Xor)
+    }
+}
+
+/// Edges of the flattened contours of `path`, open contours closed
+pub(super) fn polygon_edges(path: &Path, tolerance: f32) -> Vec<Edge> {
+    let mut points = Vec::new();
+    let ranges = <PathGeometryBuilder<PathEventsIter>>::new(path.events(), &mut points)
+        .tolerance(tolerance)
+        .map(|(_, range)| range)
+        .collect::<Vec<_>>();
+
+    let mut edges = Vec::new();
+    for range in ranges {
+        let contour = &points[range];
+        let Some(&first) = contour.first() else {
+            continue;
+        };
+        let mut from = first;
+        for &to in contour[1..].iter().chain([&first]) {
+            if to != from {
+                edges.push((from, to));
+            }
+            from = to;
+        }
+    }
+    edges
+}
+
+/// Nonzero winding number of `edges` around `p`
+pub(super) fn winding(edges: &[Edge], p: Point) -> i32 {
+    edges.iter().fold(0, |winding, (a, b)| {
+        let side = (*b - *a).cross(&(p - *a));
+        if a.y <= p.y {
+            if b.y > p.y && side > 0.0 {
+                return winding + 1;
+            }
+        } else if b.y <= p.y && side < 0.0 {
+            return winding - 1;
+        }
+        winding
+    })
+}
+
+/// Splits the edges where they cross, touch or overlap each other
+fn split_edges(edges: Vec<Edge>) -> Vec<Edge> {
+    let mut splits: Vec<Vec<(f32, Point)>> = vec![Vec::new(); edges.len()];
+
+    for i in 0..edges.len() {
+        for j in i + 1..edges.len() {
+            intersect(&edges, i, j, &mut splits);
+        }
+    }
+
+    let mut pieces = Vec::with_capacity(edges.len());
+    for ((from, to), mut splits) in edges.into_iter().zip(splits) {
+        splits.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut start = from;
+        for (_, p) in splits {
+            pieces.push((start, p));
+            start = p;
+        }
+        pieces.push((start, to));
+    }
+    pieces
+}
+
+fn intersect(edges: &[Edge], i: usize, j: usize, splits: &mut [Vec<(f32, Point)>]) {
+    let (a0, a1) = edges[i];
+    let (b0, b1) = edges[j];
+
+    if a0.x.max(a1.x) < b0.x.min(b1.x)
+        || b0.x.max(b1.x) < a0.x.min(a1.x)
+        || a0.y.max(a1.y) < b0.y.min(b1.y)
+        || b0.y.max(b1.y) < a0.y.min(a1.y)
+    {
+        return;
+    }
+
+    let r = a1 - a0;
+    let s = b1 - b0;
+    let q = b0 - a0;
+    let denom = r.cross(&s);
+
+    let interior = |t: f32| t > T_EPSILON && t < 1.0 - T_EPSILON;
+
+    if denom.abs() <= 1e-6 * r.magnitude() * s.magnitude() {
+        // parallel, only collinear edges overlap
+        if q.cross(&r).abs() > 1e-6 * r.magnitude() * q.magnitude() {
+            return;
+        }
+        for (edge, (p0, p1), others) in [(i, (a0, a1), [b0, b1]), (j, (b0, b1), [a0, a1])] {
+            let dir = p1 - p0;
+            for p in others {
+                let t = (p - p0).dot(&dir) / dir.dot(&dir);
+                if interior(t) {
+                    splits[edge].push((t, p));
+                }
+            }
+        }
+        return;
+    }
+
+    let t = q.cross(&s) / denom;
+    let u = q.cross(&r) / denom;
+    let range = -T_EPSILON..=1.0 + T_EPSILON;
+    if !range.contains(&t) || !range.contains(&u) {
+        return;
+    }
+
+    // touching ends are split at the end itself so the pieces meet exactly
+    let p = if t <= T_EPSILON {
+        a0
+    } else if t >= 1.0 - T_EPSILON {
+        a1
+    } else if u <= T_EPSILON {
+        b0
+    } else if u >= 1.0 - T_EPSILON {
+        b1
+    } else {
+        a0 + r * t
+    };
+
+    if interior(t) {
+        splits[i].push((t, p));
+    }
+    if interior(u) {
+        splits[j].push((u, p));
+    }
+}
+
+/// Joins the directed edges into closed contours
+fn link_edges(edges: &[(Key, Key)]) -> Vec<Vec<Point>> {
+    let mut outgoing: HashMap<Key, Vec<usize>> = HashMap::new();
+    for (index, (from, _)) in edges.iter().enumerate() {
+        outgoing.entry(*from).or_default().push(index);
+    }
+
+    let mut used = vec![false; edges.len()];
+    let mut contours = Vec::new();
+
+    for first in 0..edges.len() {
+        if used[first] {
+            continue;
+        }
+
+        let start = edges[first].0;
+        let mut contour = vec![point(start)];
+        let mut current = first;
+        loop {
+            used[current] = true;
+            let end = edges[current].1;
+            if end == start {
+                break;
+            }
+            contour.push(point(end));
+
+            let next = outgoing
+                .get(&end)
+                .and_then(|next| next.iter().copied().find(|&next| !used[next]));
+            match next {
+                Some(next) => current = next,
+                None => break,
+            }
+        }
+        contours.push(contour);
+    }
+    contours
+}
+
+/// Drops the points in the middle of straight runs, left behind by the splits
+fn remove_collinear(contour: &[Point]) -> Vec<Point> {
+    let mut points = contour.to_vec();
+    let mut i = 0;
+    while points.len() >= 3 && i < points.len() {
+        let prev = points[(i + points.len() - 1) % points.len()];
+        let next = points[(i + 1) % points.len()];
+        let (d0, d1) = (points[i] - prev, next - points[i]);
+        if d0.cross(&d1).abs() <= 1e-5 * d0.magnitude() * d1.magnitude() && d0.dot(&d1) >= 0.0 {
+            points.remove(i);
+            i = i.saturating_sub(1);
+        } else {
+            i += 1;
+        }
+    }
+    points
+}
+
+fn key(p: Point) -> Key {
+    ((p.x * GRID).round() as i64, (p.y * GRID).round() as i64)
+}
+
+fn point((x, y): Key) -> Point {
+    vec2(x as f32 / GRID, y as f32 / GRID)
+}
+
+#[cfg(test)]
+mod tests {
+    use skie_math::Rect;
+
+    use super::*;
+
+    fn rect(x: f32, y: f32, w: f32, h: f32) -> Path {
+        let mut builder = Path::builder();
+        builder.rect(&Rect::xywh(x, y, w, h));
+        builder.build()
+    }
+
+    fn circle(center: Point, radius: f32) -> Path {
+        let mut builder = Path::builder();
+        builder.circle(center, radius);
+        builder.build()
+    }
+
+    /// Clockwise signed areas of the contours
+    fn areas(path: &Path) -> Vec<f32> {
+        path.contours()
+            .map(|contour| {
+                let points = contour.points();
+                points
+                    .iter()
+                    .zip(points.iter().cycle().skip(1))
+                    .map(|(a, b)| a.cross(b) / 2.0)
+                    .sum()
+            })
+            .collect()
+    }
+
+    fn area(path: &Path) -> f32 {
+        areas(path).iter().sum()
+    }
+
+    #[test]
+    fn overlapping_rects() {
+        let a = rect(0.0, 0.0, 10.0, 10.0);
+        let b = rect(5.0, 5.0, 10.0, 10.0);
+
+        let union = a.union(&b);
+        assert_eq!(union.contours().count(), 1);
+        // the two intersections and six corners
+        assert_eq!(union.contours().next().unwrap().points().len(), 8 + 1);
+        assert_eq!(area(&union), 175.0);
+
+        assert_eq!(area(&a.intersection(&b)), 25.0);
+        assert_eq!(area(&a.difference(&b)), 75.0);
+        assert_eq!(area(&b.difference(&a)), 75.0);
+        assert_eq!(area(&a.xor(&b)), 150.0);
+        assert!(areas(&a.xor(&b)).iter().all(|area| *area > 0.0));
+    }
+
+    #[test]
+    fn punching_a_hole() {
+        let outer = rect(0.0, 0.0, 10.0, 10.0);
+        let hole = rect(3.0, 3.0, 4.0, 4.0);
+
+        let ring = outer.difference(&hole);
+        let mut areas = areas(&ring);
+        areas.sort_by(f32::total_cmp);
+        assert_eq!(areas, [-16.0, 100.0]);
+
+        let edges = polygon_edges(&ring, PATH_OP_TOLERANCE);
+        assert_eq!(winding(&edges, vec2(5.0, 5.0)), 0);
+        assert_ne!(winding(&edges, vec2(1.0, 5.0)), 0);
+
+        // nothing is left of a path inside the one it is punched by
+        assert!(hole.difference(&outer).events().next().is_none());
+        assert_eq!(area(&hole.intersection(&outer)), 16.0);
+    }
+
+    #[test]
+    fn shared_edges_merge() {
+        let a = rect(0.0, 0.0, 10.0, 10.0);
+        let b = rect(10.0, 0.0, 10.0, 10.0);
+
+        let union = a.union(&b);
+        assert_eq!(union.contours().count(), 1);
+        assert_eq!(union.contours().next().unwrap().points().len(), 4 + 1);
+        assert_eq!(area(&union), 200.0);
+
+        assert_eq!(area(&a.intersection(&b)), 0.0);
+        assert_eq!(area(&a.union(&a)), 100.0);
+        assert!(a.xor(&a).events().next().is_none());
+    }
+
+    #[test]
+    fn curves_are_flattened() {
+        let a = circle(vec2(0.0, 0.0), 10.0);
+        let far = circle(vec2(50.0, 0.0), 10.0);
+        // flattening takes about 1% off the area of the circles
+        let full = std::f32::consts::PI * 100.0;
+
+        let union = a.union(&far);
+        assert_eq!(union.contours().count(), 2);
+        assert!((area(&union) - 2.0 * full).abs() < full * 0.03);
+        assert!(a.intersection(&far).events().next().is_none());
+
+        // a circle cut in half by a rect
+        let half = a.intersection(&rect(0.0, -20.0, 20.0, 40.0));
+        assert!((area(&half) - full / 2.0).abs() < full * 0.03);
+        let bounds = half.contours().next().unwrap().bounds();
+        assert_eq!(bounds.origin.x, 0.0);
+    }
+}
@@ -1,7 +1,14 @@
-use skie_math::{vec2, Corners, Rect};
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+use skie_math::{vec2, Corners, Mat3, Rect};
+
+use crate::paint::ConicBezier;
 
 use super::{Path, PathEventsIter, PathVerb, Point, Polygon};
 
+/// Max distance in pixels between a conic and the quadratics approximating it
+pub const CONIC_TOLERANCE: f32 = 0.25;
+
 #[derive(Debug, Clone, Copy, Default, Hash, PartialEq, PartialOrd, Eq)]
 pub struct Contour(pub(crate) usize);
 
@@ -9,12 +16,21 @@ impl Contour {
     pub const INVALID: Contour = Contour(0);
 }
 
+/// A saved position in a [`PathBuilder`], see [`PathBuilder::checkpoint`]
+#[derive(Debug, Clone, Copy)]
+pub struct Marker {
+    points: usize,
+    verbs: usize,
+    first: Point,
+    validator: PathValidator,
+}
+
 #[derive(Default)]
 pub struct PathBuilder {
     pub(crate) points: Vec<Point>,
     pub(crate) verbs: Vec<PathVerb>,
     // pub crate for use in drawlist
-    pub(crate) validator: DebugPathValidator,
+    pub(crate) validator: PathValidator,
     first: Point,
 }
 
@@ -27,8 +43,54 @@ impl PathBuilder {
         }
     }
 
+    /// Applies `transform` to the points added so far
+    pub fn transform(&mut self, transform: &Mat3) {
+        for point in self.points.iter_mut() {
+            *point = *transform * *point;
+        }
+        self.first = *transform * self.first;
+    }
+
+    /// Saves the current state of the builder so that everything added after can be
+    /// discarded with [`PathBuilder::rewind_to`]
+    pub fn checkpoint(&self) -> Marker {
+        Marker {
+            points: self.points.len(),
+            verbs: self.verbs.len(),
+            first: self.first,
+            validator: self.validator,
+        }
+    }
+
+    /// Discards everything added after `marker` was taken
+    pub fn rewind_to(&mut self, marker: Marker) {
+        debug_assert!(
+            marker.points <= self.points.len() && marker.verbs <= self.verbs.len(),
+            "marker is from a different builder or the builder was rewound past it"
+        );
+
+        self.points.truncate(marker.points);
+        self.verbs.truncate(marker.verbs);
+        self.first = marker.first;
+        self.validator = marker.validator;
+    }
+
+    /// Sets how misuse of the builder is handled, see [`PathValidation`]
+    pub fn validation(mut self, policy: PathValidation) -> Self {
+        self.set_validation(policy);
+        self
+    }
+
+    #[inline]
+    pub fn set_validation(&mut self, policy: PathValidation) {
+        self.validator.policy = policy;
+    }
+
     pub fn begin(&mut self, at: Point) {
-        self.validator.begin();
+        if !self.validator.begin() {
+            // end the dangling subpath
+            self.verbs.push(PathVerb::End);
+        }
         check_is_nan(at);
 
         self.first = at;
@@ -37,7 +99,9 @@ impl PathBuilder {
     }
 
     pub fn end(&mut self, close: bool) -> Contour {
-        self.validator.end();
+        if !self.validator.end() {
+            return Contour::INVALID;
+        }
 
         if close {
             self.points.push(self.first);
@@ -59,13 +123,13 @@ impl PathBuilder {
     }
 
     #[inline]
-    pub fn path_events(&self) -> PathEventsIter {
+    pub fn path_events(&self) -> PathEventsIter<'_> {
         self.validator.build();
         PathEventsIter::new(&self.points, &self.verbs)
     }
 
     pub fn line_to(&mut self, to: Point) {
-        self.validator.edge();
+        self.ensure_subpath(to);
         check_is_nan(to);
 
         self.points.push(to);
@@ -73,7 +137,7 @@ impl PathBuilder {
     }
 
     pub fn quadratic_to(&mut self, ctrl: Point, to: Point) {
-        self.validator.edge();
+        self.ensure_subpath(ctrl);
         check_is_nan(ctrl);
         check_is_nan(to);
 
@@ -82,8 +146,41 @@ impl PathBuilder {
         self.verbs.push(PathVerb::QuadraticTo);
     }
 
+    /// Adds a rational quadratic segment, stored as quadratics within [`CONIC_TOLERANCE`] of the
+    /// curve. See [`ConicBezier`] for the meaning of `weight`
+    pub fn conic_to(&mut self, ctrl: Point, to: Point, weight: f32) {
+        self.ensure_subpath(ctrl);
+
+        let conic = ConicBezier {
+            from: self.points.last().copied().unwrap_or(ctrl),
+            ctrl,
+            to,
+            weight,
+        };
+
+        if weight.is_nan() || weight <= 0.0 {
+            self.line_to(to);
+            return;
+        }
+
+        if weight.is_infinite() {
+            self.line_to(ctrl);
+            self.line_to(to);
+            return;
+        }
+
+        if weight == 1.0 {
+            self.quadratic_to(ctrl, to);
+            return;
+        }
+
+        conic.for_each_quadratic(CONIC_TOLERANCE, |quad| {
+            self.quadratic_to(quad.ctrl, quad.to)
+        });
+    }
+
     pub fn cubic_to(&mut self, ctrl1: Point, ctrl2: Point, to: Point) {
-        self.validator.edge();
+        self.ensure_subpath(ctrl1);
         check_is_nan(ctrl1);
         check_is_nan(ctrl2);
         check_is_nan(to);
@@ -94,6 +191,161 @@ impl PathBuilder {
         self.verbs.push(PathVerb::CubicTo);
     }
 
+    /// Adds a circular arc around `center` from `start_angle` sweeping `sweep_angle` radians,
+    /// clockwise when positive. The subpath continues with a line to the start of the arc,
+    /// or begins there
+    pub fn arc(&mut self, center: Point, radius: f32, start_angle: f32, sweep_angle: f32) {
+        let radius = radius.abs();
+        let start = center + vec2(start_angle.cos(), start_angle.sin()) * radius;
+        match self.current_point() {
+            Some(current) if current != start => self.line_to(start),
+            Some(_) => {}
+            None => self.begin(start),
+        }
+
+        add_arc_cubics(
+            self,
+            center,
+            vec2(radius, radius),
+            0.0,
+            start_angle,
+            sweep_angle,
+        );
+    }
+
+    /// Adds an arc of `radius` tangent to the lines from the current point to `p1` and from
+    /// `p1` to `p2`, with a line to its start like `arcTo` of the html canvas. Adds a line to
+    /// `p1` when the points are in a line or the radius is zero
+    pub fn arc_to(&mut self, p1: Point, p2: Point, radius: f32) {
+        let Some(p0) = self.current_point() else {
+            self.begin(p1);
+            return;
+        };
+
+        let radius = radius.abs();
+        let (d0, d1) = (p0 - p1, p2 - p1);
+        let turn = (p1 - p0).cross(&d1);
+        if radius == 0.0
+            || p0 == p1
+            || p1 == p2
+            || turn.abs() <= 1e-6 * d0.magnitude() * d1.magnitude()
+        {
+            self.line_to(p1);
+            return;
+        }
+
+        let (d0, d1) = (d0.normalize(), d1.normalize());
+        // angle at p1 between the two lines
+        let half_angle = d0.dot(&d1).clamp(-1.0, 1.0).acos() / 2.0;
+        let tangent = radius / half_angle.tan();
+        let center = p1 + (d0 + d1).normalize() * (radius / half_angle.sin());
+
+        let start = p1 + d0 * tangent;
+        let end = p1 + d1 * tangent;
+        if start != p0 {
+            self.line_to(start);
+        }
+
+        let start_angle = (start - center).y.atan2((start - center).x);
+        let sweep_angle = (PI - half_angle * 2.0) * turn.signum();
+        add_arc_cubics(
+            self,
+            center,
+            vec2(radius, radius),
+            0.0,
+            start_angle,
+            sweep_angle,
+        );
+        // ends exactly on the tangent point
+        if let Some(last) = self.points.last_mut() {
+            *last = end;
+        }
+    }
+
+    /// Adds an elliptical arc from the current point to `to` like the `A` command of svg
+    /// paths. `radii` too small to reach `to` are scaled up, zero radii add a line
+    pub fn elliptical_arc_to(
+        &mut self,
+        radii: Point,
+        x_rotation: f32,
+        large_arc: bool,
+        sweep: bool,
+        to: Point,
+    ) {
+        let Some(from) = self.current_point() else {
+            self.begin(to);
+            return;
+        };
+        if from == to {
+            return;
+        }
+
+        let (mut rx, mut ry) = (radii.x.abs(), radii.y.abs());
+        if rx == 0.0 || ry == 0.0 {
+            self.line_to(to);
+            return;
+        }
+
+        // endpoint to center parameterization of the svg spec
+        let (sin, cos) = x_rotation.sin_cos();
+        let half = (from - to) * 0.5;
+        let x1 = cos * half.x + sin * half.y;
+        let y1 = -sin * half.x + cos * half.y;
+
+        // scale up radii too small to reach the end point
+        let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+        if lambda > 1.0 {
+            rx *= lambda.sqrt();
+            ry *= lambda.sqrt();
+        }
+
+        let (rx2, ry2) = (rx * rx, ry * ry);
+        let num = rx2 * ry2 - rx2 * y1 * y1 - ry2 * x1 * x1;
+        let den = rx2 * y1 * y1 + ry2 * x1 * x1;
+        let mut coef = (num / den).max(0.0).sqrt();
+        if large_arc == sweep {
+            coef = -coef;
+        }
+        let cx1 = coef * rx * y1 / ry;
+        let cy1 = -coef * ry * x1 / rx;
+        let mid = (from + to) * 0.5;
+        let center = vec2(cos * cx1 - sin * cy1 + mid.x, sin * cx1 + cos * cy1 + mid.y);
+
+        let angle = |u: Point, v: Point| (u.x * v.y - u.y * v.x).atan2(u.x * v.x + u.y * v.y);
+        let u = vec2((x1 - cx1) / rx, (y1 - cy1) / ry);
+        let v = vec2((-x1 - cx1) / rx, (-y1 - cy1) / ry);
+        let start_angle = angle(vec2(1.0, 0.0), u);
+        let mut sweep_angle = angle(u, v);
+        if !sweep && sweep_angle > 0.0 {
+            sweep_angle -= TAU;
+        } else if sweep && sweep_angle < 0.0 {
+            sweep_angle += TAU;
+        }
+
+        add_arc_cubics(
+            self,
+            center,
+            vec2(rx, ry),
+            x_rotation,
+            start_angle,
+            sweep_angle,
+        );
+        // ends exactly on the end point
+        if let Some(last) = self.points.last_mut() {
+            *last = to;
+        }
+    }
+
+    /// Appends the contours of `path`, ending the open subpath first. Their [`Contour`] ids
+    /// grow by the number of points already in the builder
+    pub fn extend(&mut self, path: &Path) {
+        if self.validator.in_subpath {
+            self.end(false);
+        }
+        self.points.extend_from_slice(&path.points);
+        self.verbs.extend_from_slice(&path.verbs);
+    }
+
     pub fn add_point(&mut self, at: Point) {
         self.begin(at);
         self.end(false);
@@ -131,6 +383,17 @@ impl PathBuilder {
         add_rounded_rectangle(self, rect, corners)
     }
 
+    /// Rounded rect with superellipse corners. `smoothing` in `0..=1` stretches the corners
+    /// along the edges for a continuous curvature, `0` gives the same shape as [`PathBuilder::round_rect`]
+    pub fn squircle(
+        &mut self,
+        rect: &Rect<f32>,
+        corners: &Corners<f32>,
+        smoothing: f32,
+    ) -> Contour {
+        add_squircle(self, rect, corners, smoothing)
+    }
+
     pub fn circle(&mut self, center: Point, radius: f32) -> Contour {
         add_circle(self, center, radius)
     }
@@ -140,9 +403,29 @@ impl PathBuilder {
         self.verbs.reserve(endpoints);
     }
 
+    /// Last point of the subpath being built
+    #[inline]
+    fn current_point(&self) -> Option<Point> {
+        if self.validator.in_subpath {
+            self.points.last().copied()
+        } else {
+            None
+        }
+    }
+
+    /// Begins a subpath at the last point when an edge is added outside of one
+    #[inline]
+    fn ensure_subpath(&mut self, fallback: Point) {
+        if !self.validator.edge() {
+            self.begin(self.points.last().copied().unwrap_or(fallback));
+        }
+    }
+
     #[must_use]
-    pub fn build(self) -> Path {
-        self.validator.build();
+    pub fn build(mut self) -> Path {
+        if !self.validator.build() {
+            self.verbs.push(PathVerb::End);
+        }
 
         Path {
             points: self.points.into_boxed_slice(),
@@ -151,6 +434,46 @@ impl PathBuilder {
     }
 }
 
+/// Adds the cubics approximating an elliptical arc, at most a quarter turn each
+fn add_arc_cubics(
+    builder: &mut PathBuilder,
+    center: Point,
+    radii: Point,
+    rotation: f32,
+    start_angle: f32,
+    sweep_angle: f32,
+) {
+    let (sin, cos) = rotation.sin_cos();
+    let (rx, ry) = (radii.x, radii.y);
+
+    let point_at = |t: f32| {
+        let (s, c) = t.sin_cos();
+        vec2(
+            center.x + rx * c * cos - ry * s * sin,
+            center.y + rx * c * sin + ry * s * cos,
+        )
+    };
+    let tangent_at = |t: f32| {
+        let (s, c) = t.sin_cos();
+        vec2(-rx * s * cos - ry * c * sin, -rx * s * sin + ry * c * cos)
+    };
+
+    // a little over a quarter turn is rounding
+    let segments = (sweep_angle.abs() / FRAC_PI_2 - 1e-3).ceil().max(1.0) as usize;
+    let delta = sweep_angle / segments as f32;
+    let k = 4.0 / 3.0 * (delta / 4.0).tan();
+
+    for i in 0..segments {
+        let t1 = start_angle + delta * i as f32;
+        let t2 = t1 + delta;
+        builder.cubic_to(
+            point_at(t1) + tangent_at(t1) * k,
+            point_at(t2) - tangent_at(t2) * k,
+            point_at(t2),
+        );
+    }
+}
+
 // Adapted from
 // https://github.com/nical/lyon/blob/main/crates/path/src/builder.rs
 fn add_circle(builder: &mut PathBuilder, center: Point, radius: f32) -> Contour {
@@ -197,8 +520,6 @@ fn add_rounded_rectangle(
     rect: &Rect<f32>,
     corners: &Corners<f32>,
 ) -> Contour {
-    let w = rect.size.width;
-    let h = rect.size.height;
     let min = rect.min();
     let max = rect.max();
 
@@ -206,33 +527,13 @@ fn add_rounded_rectangle(
     let y_min = min.y;
     let x_max = max.x;
     let y_max = max.y;
-    let min_wh = w.min(h);
-    let mut tl = corners.top_left.abs().min(min_wh);
-    let mut tr = corners.top_right.abs().min(min_wh);
-    let mut bl = corners.bottom_left.abs().min(min_wh);
-    let mut br = corners.bottom_right.abs().min(min_wh);
 
-    // clamp border radii if they don't fit in the rectangle.
-    if tl + tr > w {
-        let x = (tl + tr - w) * 0.5;
-        tl -= x;
-        tr -= x;
-    }
-    if bl + br > w {
-        let x = (bl + br - w) * 0.5;
-        bl -= x;
-        br -= x;
-    }
-    if tr + br > h {
-        let x = (tr + br - h) * 0.5;
-        tr -= x;
-        br -= x;
-    }
-    if tl + bl > h {
-        let x = (tl + bl - h) * 0.5;
-        tl -= x;
-        bl -= x;
-    }
+    let Corners {
+        top_left: tl,
+        top_right: tr,
+        bottom_left: bl,
+        bottom_right: br,
+    } = clamp_corner_radii(rect, corners);
 
     // https://spencermortensen.com/articles/bezier-circle/
     const CONSTANT_FACTOR: f32 = 0.55191505;
@@ -289,57 +590,191 @@ fn add_rounded_rectangle(
     builder.end(true)
 }
 
+fn add_squircle(
+    builder: &mut PathBuilder,
+    rect: &Rect<f32>,
+    corners: &Corners<f32>,
+    smoothing: f32,
+) -> Contour {
+    const SEGMENTS: usize = 16;
+
+    let smoothing = smoothing.clamp(0.0, 1.0);
+    // a superellipse with exponent 2 is a circle
+    let exponent = 2.0 + 3.0 * smoothing;
+
+    let radii = clamp_corner_radii(rect, corners);
+    let extents = clamp_corner_radii(
+        rect,
+        &Corners::with_each(
+            radii.top_left * (1.0 + smoothing),
+            radii.top_right * (1.0 + smoothing),
+            radii.bottom_left * (1.0 + smoothing),
+            radii.bottom_right * (1.0 + smoothing),
+        ),
+    );
+
+    let min = rect.min();
+    let max = rect.max();
+
+    let superellipse = |v: f32| v.signum() * v.abs().powf(2.0 / exponent);
+
+    // clockwise from the top left corner
+    let corner_arcs = [
+        (
+            extents.top_left,
+            vec2(min.x, min.y),
+            vec2(1.0, 1.0),
+            std::f32::consts::PI,
+        ),
+        (
+            extents.top_right,
+            vec2(max.x, min.y),
+            vec2(-1.0, 1.0),
+            std::f32::consts::PI * 1.5,
+        ),
+        (
+            extents.bottom_right,
+            vec2(max.x, max.y),
+            vec2(-1.0, -1.0),
+            0.0,
+        ),
+        (
+            extents.bottom_left,
+            vec2(min.x, max.y),
+            vec2(1.0, -1.0),
+            std::f32::consts::FRAC_PI_2,
+        ),
+    ];
+
+    let mut first = true;
+    for (extent, corner, inward, start_angle) in corner_arcs {
+        let center = corner + inward * extent;
+        let segments = if extent > 0.0 { SEGMENTS } else { 0 };
+
+        for i in 0..=segments {
+            let angle = start_angle + std::f32::consts::FRAC_PI_2 * (i as f32 / SEGMENTS as f32);
+            let point = center
+                + vec2(
+                    superellipse(angle.cos()) * extent,
+                    superellipse(angle.sin()) * extent,
+                );
+
+            if first {
+                builder.begin(point);
+                first = false;
+            } else {
+                builder.line_to(point);
+            }
+        }
+    }
+
+    builder.close()
+}
+
+/// Clamps the corner radii so that they fit in the rectangle
+pub(crate) fn clamp_corner_radii(rect: &Rect<f32>, corners: &Corners<f32>) -> Corners<f32> {
+    let w = rect.size.width;
+    let h = rect.size.height;
+    let min_wh = w.min(h);
+    let mut tl = corners.top_left.abs().min(min_wh);
+    let mut tr = corners.top_right.abs().min(min_wh);
+    let mut bl = corners.bottom_left.abs().min(min_wh);
+    let mut br = corners.bottom_right.abs().min(min_wh);
+
+    // clamp border radii if they don't fit in the rectangle.
+    if tl + tr > w {
+        let x = (tl + tr - w) * 0.5;
+        tl -= x;
+        tr -= x;
+    }
+    if bl + br > w {
+        let x = (bl + br - w) * 0.5;
+        bl -= x;
+        br -= x;
+    }
+    if tr + br > h {
+        let x = (tr + br - h) * 0.5;
+        tr -= x;
+        br -= x;
+    }
+    if tl + bl > h {
+        let x = (tl + bl - h) * 0.5;
+        tl -= x;
+        bl -= x;
+    }
+
+    Corners::with_each(tl, tr, bl, br)
+}
+
 #[inline]
 fn check_is_nan(p: Point) {
     debug_assert!(p.x.is_finite());
     debug_assert!(p.y.is_finite());
 }
 
-#[derive(Default)]
-pub(crate) struct DebugPathValidator {
-    #[cfg(debug_assertions)]
+/// What a [`PathBuilder`] does when it is misused, e.g. calling `begin()` twice without `end()`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PathValidation {
+    /// Panic with a message describing the misuse. The default in debug builds
+    #[cfg_attr(debug_assertions, default)]
+    Panic,
+    /// Log a warning and repair the path by ending or beginning subpaths as needed.
+    /// The default in release builds
+    #[cfg_attr(not(debug_assertions), default)]
+    Recover,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct PathValidator {
+    policy: PathValidation,
     in_subpath: bool,
 }
 
-impl DebugPathValidator {
+// each check returns false when the builder has to recover
+impl PathValidator {
     #[inline(always)]
-    pub fn begin(&mut self) {
-        #[cfg(debug_assertions)]
-        {
-            assert!(
-                !self.in_subpath,
-                "Please end the current subpath with `end(<close>)` or `close()` before starting a new one"
-            );
-            self.in_subpath = true;
-        }
+    pub fn begin(&mut self) -> bool {
+        let valid = self.check(
+            !self.in_subpath,
+            "Please end the current subpath with `end(<close>)` or `close()` before starting a new one",
+        );
+        self.in_subpath = true;
+        valid
     }
 
     #[inline(always)]
-    pub fn end(&mut self) {
-        #[cfg(debug_assertions)]
-        {
-            assert!(self.in_subpath, "Please start a new subpath with `begin()`");
-            self.in_subpath = false;
-        }
+    pub fn end(&mut self) -> bool {
+        let valid = self.check(self.in_subpath, "Please start a new subpath with `begin()`");
+        self.in_subpath = false;
+        valid
     }
 
     #[inline(always)]
-    pub fn edge(&self) {
-        #[cfg(debug_assertions)]
-        assert!(
+    pub fn edge(&self) -> bool {
+        self.check(
             self.in_subpath,
-            "Please begin a new subpath with begin() to continue this operation"
+            "Please begin a new subpath with begin() to continue this operation",
         )
     }
 
     #[inline(always)]
-    pub fn build(&self) {
-        #[cfg(debug_assertions)]
-        assert!(
+    pub fn build(&self) -> bool {
+        self.check(
             !self.in_subpath,
-            "Please end the current subpath with `end(<close>)` or `close()` before building"
+            "Please end the current subpath with `end(<close>)` or `close()` before building",
         )
     }
+
+    #[inline(always)]
+    fn check(&self, valid: bool, message: &str) -> bool {
+        if !valid {
+            match self.policy {
+                PathValidation::Panic => panic!("{message}"),
+                PathValidation::Recover => log::warn!("PathBuilder: {message}. Recovering"),
+            }
+        }
+        valid
+    }
 }
 
 #[cfg(test)]
@@ -581,6 +1016,43 @@ mod tests {
         path.begin(vec2(10.0, 10.0));
     }
 
+    #[test]
+    fn path_builder_validator_recover() {
+        let mut path = Path::builder().validation(PathValidation::Recover);
+
+        // edge without a subpath begins one
+        path.line_to(vec2(10.0, 0.0));
+        // begin without end ends the dangling subpath
+        path.begin(vec2(20.0, 20.0));
+        path.line_to(vec2(30.0, 20.0));
+        let contour = path.close();
+        // end without a subpath is ignored
+        assert_eq!(path.close(), Contour::INVALID);
+        // edge after a close continues from the last point
+        path.line_to(vec2(40.0, 40.0));
+
+        // build ends the open subpath
+        let path = path.build();
+
+        assert_eq!(contour, Contour(5));
+        assert_eq!(
+            &path.verbs[..],
+            &[
+                PathVerb::Begin,
+                PathVerb::LineTo,
+                PathVerb::End,
+                PathVerb::Begin,
+                PathVerb::LineTo,
+                PathVerb::Close,
+                PathVerb::Begin,
+                PathVerb::LineTo,
+                PathVerb::End,
+            ]
+        );
+        assert_eq!(path.points[5], vec2(20.0, 20.0));
+        assert_eq!(path.contours().count(), 3);
+    }
+
     #[test]
     fn path_builder_polygon() {
         // closed
@@ -651,4 +1123,197 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn path_builder_squircle() {
+        let rect = Rect::xywh(0.0, 0.0, 100.0, 100.0);
+        let area = |points: &[Point]| {
+            points
+                .windows(2)
+                .map(|w| w[0].x * w[1].y - w[1].x * w[0].y)
+                .sum::<f32>()
+                * 0.5
+        };
+
+        // without smoothing the corners are circular
+        let mut path = Path::builder();
+        path.squircle(&rect, &Corners::with_all(20.0), 0.0);
+        assert_eq!(path.points.first(), path.points.last());
+        assert_eq!(path.verbs.last(), Some(&PathVerb::Close));
+
+        for p in &path.points[..17] {
+            let d = (*p - vec2(20.0, 20.0)).magnitude();
+            assert!((d - 20.0).abs() < 1e-3);
+        }
+
+        let circular = area(&path.points);
+
+        let mut path = Path::builder();
+        path.squircle(&rect, &Corners::with_all(20.0), 1.0);
+        let smooth = area(&path.points);
+
+        // smoothing spreads the corner along the edges but stays convex and inside the rect
+        assert!(path
+            .points
+            .iter()
+            .all(|p| rect.contains_point(p) || p.x == 100.0 || p.y == 100.0));
+        assert!(smooth > 0.0 && smooth < 100.0 * 100.0);
+        assert!((smooth - circular).abs() > 1.0);
+
+        // square corners
+        let mut path = Path::builder();
+        path.squircle(&rect, &Corners::with_all(0.0), 1.0);
+        assert!((area(&path.points) - 100.0 * 100.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn path_builder_rewind() {
+        let mut path = Path::builder();
+        path.rect(&Rect::xywh(0.0, 0.0, 10.0, 10.0));
+
+        let marker = path.checkpoint();
+        let points = path.points.clone();
+        let verbs = path.verbs.clone();
+
+        // speculative open subpath
+        path.begin(vec2(20.0, 20.0));
+        path.line_to(vec2(30.0, 30.0));
+        path.cubic_to(vec2(40.0, 40.0), vec2(50.0, 40.0), vec2(60.0, 30.0));
+
+        path.rewind_to(marker);
+        assert_eq!(path.points, points);
+        assert_eq!(path.verbs, verbs);
+
+        // validator state is restored so a new subpath can begin and the path can be built
+        path.begin(vec2(5.0, 5.0));
+        path.line_to(vec2(6.0, 6.0));
+        path.close();
+
+        let path = path.build();
+        assert_eq!(path.verbs.len(), verbs.len() + 3);
+        assert_eq!(path.points.last(), Some(&vec2(5.0, 5.0)));
+    }
+
+    #[test]
+    fn path_builder_rewind_inside_subpath() {
+        let mut path = Path::builder();
+        path.begin(vec2(0.0, 0.0));
+        path.line_to(vec2(10.0, 0.0));
+
+        let marker = path.checkpoint();
+        path.line_to(vec2(10.0, 10.0));
+        path.close();
+
+        path.rewind_to(marker);
+        path.line_to(vec2(0.0, 10.0));
+        path.close();
+
+        assert_eq!(
+            &path.points,
+            &[
+                vec2(0.0, 0.0),
+                vec2(10.0, 0.0),
+                vec2(0.0, 10.0),
+                vec2(0.0, 0.0)
+            ]
+        );
+    }
+
+    fn cubic_mid(points: &[Point]) -> Point {
+        crate::paint::CubicBezier {
+            from: points[0],
+            ctrl1: points[1],
+            ctrl2: points[2],
+            to: points[3],
+        }
+        .sample(0.5)
+    }
+
+    #[test]
+    fn path_builder_arc() {
+        let mut builder = Path::builder();
+        builder.arc(vec2(0.0, 0.0), 10.0, 0.0, std::f32::consts::PI);
+        builder.arc(vec2(0.0, 0.0), 5.0, std::f32::consts::PI, 1.0);
+        builder.end(false);
+        let path = builder.build();
+        let points = path.contours().next().unwrap().points();
+
+        // a half turn is two quarter cubics, the second arc follows a line to its start
+        assert_eq!(
+            path.verbs[..4],
+            [
+                PathVerb::Begin,
+                PathVerb::CubicTo,
+                PathVerb::CubicTo,
+                PathVerb::LineTo
+            ]
+        );
+        assert!((points[0] - vec2(10.0, 0.0)).magnitude() < 1e-4);
+        assert!((points[3] - vec2(0.0, 10.0)).magnitude() < 1e-4);
+        assert!((points[7] - vec2(-5.0, 0.0)).magnitude() < 1e-4);
+        assert!((cubic_mid(&points[..4]).magnitude() - 10.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn path_builder_arc_to() {
+        let mut builder = Path::builder();
+        builder.begin(vec2(0.0, 0.0));
+        builder.arc_to(vec2(10.0, 0.0), vec2(10.0, 10.0), 5.0);
+        // in a line with the previous point
+        builder.arc_to(vec2(10.0, 20.0), vec2(10.0, 30.0), 5.0);
+        builder.end(false);
+        let path = builder.build();
+        let points = path.contours().next().unwrap().points();
+
+        assert_eq!(points[1], vec2(5.0, 0.0));
+        assert_eq!(points[4], vec2(10.0, 5.0));
+        assert!(((cubic_mid(&points[1..5]) - vec2(5.0, 5.0)).magnitude() - 5.0).abs() < 0.01);
+        assert_eq!(points[5], vec2(10.0, 20.0));
+    }
+
+    #[test]
+    fn path_builder_elliptical_arc() {
+        let mut builder = Path::builder();
+        builder.begin(vec2(0.0, 0.0));
+        builder.elliptical_arc_to(vec2(10.0, 10.0), 0.0, false, true, vec2(20.0, 0.0));
+        // too small radii reach the end point
+        builder.elliptical_arc_to(vec2(1.0, 1.0), 0.0, false, false, vec2(40.0, 0.0));
+        builder.elliptical_arc_to(vec2(0.0, 1.0), 0.0, false, false, vec2(50.0, 0.0));
+        builder.end(false);
+        let path = builder.build();
+        let points = path.contours().next().unwrap().points();
+
+        assert_eq!(points.len(), 1 + 3 * 4 + 1);
+        assert_eq!(points[6], vec2(20.0, 0.0));
+        assert_eq!(points[12], vec2(40.0, 0.0));
+        // the sweep arc bulges up, the other one down
+        assert!((points[3] - vec2(10.0, -10.0)).magnitude() < 1e-4);
+        assert!((points[9] - vec2(30.0, 10.0)).magnitude() < 1e-4);
+        assert_eq!(points[13], vec2(50.0, 0.0));
+    }
+
+    #[test]
+    fn path_builder_extend() {
+        let mut builder = Path::builder();
+        builder.rect(&Rect::xywh(0.0, 0.0, 10.0, 10.0));
+        let square = builder.build();
+
+        let mut builder = Path::builder();
+        builder.begin(vec2(0.0, 0.0));
+        builder.line_to(vec2(5.0, 5.0));
+        builder.extend(&square);
+        builder.extend(&square);
+        let path = builder.build();
+
+        let contours = path.contours().collect::<Vec<_>>();
+        assert_eq!(contours.len(), 3);
+        assert!(!contours[0].is_closed());
+        assert_eq!(contours[1].points(), contours[2].points());
+        assert_eq!(contours[1].id(), Contour(2 + 5));
+        assert_eq!(contours[2].id(), Contour(2 + 5 + 5));
+
+        let concat = square.concat(&square);
+        assert_eq!(*concat.verbs, path.verbs[3..]);
+        assert_eq!(*concat.points, path.points[2..]);
+    }
 }
@@ -0,0 +1,120 @@
+use crate::paint::DEFAULT_TOLERANCE;
+
+use super::{boolean, Path, PathEventsIter, PathGeometryBuilder, Point};
+
+/// Which points a path with overlapping or nested contours fills
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FillRule {
+    /// Points the contours wind around at least once in total
+    #[default]
+    NonZero,
+    /// Points inside an odd number of contours
+    EvenOdd,
+}
+
+impl FillRule {
+    /// Whether a point the contours wind around `winding` times is filled
+    pub fn is_inside(self, winding: i32) -> bool {
+        match self {
+            FillRule::NonZero => winding != 0,
+            FillRule::EvenOdd => winding % 2 != 0,
+        }
+    }
+}
+
+impl Path {
+    /// Whether `point` is inside the area the path fills with `fill_rule`, open contours
+    /// are closed. Curves are flattened like when the path is drawn
+    pub fn contains(&self, point: Point, fill_rule: FillRule) -> bool {
+        let edges = boolean::polygon_edges(self, DEFAULT_TOLERANCE);
+        fill_rule.is_inside(boolean::winding(&edges, point))
+    }
+
+    /// Whether `point` is within half of `stroke_width` from the outline of the path,
+    /// as if it was stroked with round joins and caps
+    pub fn stroke_contains(&self, point: Point, stroke_width: f32) -> bool {
+        let half_width = stroke_width.abs() / 2.0;
+
+        let mut outline = Vec::new();
+        <PathGeometryBuilder<PathEventsIter>>::new(self.events(), &mut outline)
+            .tolerance(DEFAULT_TOLERANCE)
+            .stroke_width(stroke_width)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .any(|(_, range)| {
+                let contour = &outline[range];
+                if contour.len() == 1 {
+                    return (point - contour[0]).magnitude() <= half_width;
+                }
+                contour
+                    .windows(2)
+                    .any(|segment| segment_distance(segment[0], segment[1], point) <= half_width)
+            })
+    }
+}
+
+fn segment_distance(a: Point, b: Point, point: Point) -> f32 {
+    let ab = b - a;
+    let length_sq = ab.dot(&ab);
+    let t = if length_sq > 0.0 {
+        ((point - a).dot(&ab) / length_sq).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    (point - (a + ab * t)).magnitude()
+}
+
+#[cfg(test)]
+mod tests {
+    use skie_math::{vec2, Rect};
+
+    use super::*;
+
+    #[test]
+    fn fill_rules() {
+        let mut builder = Path::builder();
+        builder.rect(&Rect::xywh(0.0, 0.0, 30.0, 30.0));
+        builder.rect(&Rect::xywh(10.0, 10.0, 10.0, 10.0));
+        let nested = builder.build();
+
+        // both contours wind the same way
+        assert!(nested.contains(vec2(15.0, 15.0), FillRule::NonZero));
+        assert!(!nested.contains(vec2(15.0, 15.0), FillRule::EvenOdd));
+        assert!(nested.contains(vec2(5.0, 15.0), FillRule::EvenOdd));
+        assert!(!nested.contains(vec2(35.0, 15.0), FillRule::NonZero));
+
+        let mut builder = Path::builder();
+        builder.circle(vec2(0.0, 0.0), 10.0);
+        let circle = builder.build();
+        assert!(circle.contains(vec2(6.0, 6.0), FillRule::NonZero));
+        assert!(!circle.contains(vec2(8.0, 8.0), FillRule::NonZero));
+
+        // open contours are closed
+        let mut builder = Path::builder();
+        builder.begin(vec2(0.0, 0.0));
+        builder.line_to(vec2(10.0, 0.0));
+        builder.line_to(vec2(10.0, 10.0));
+        builder.end(false);
+        let open = builder.build();
+        assert!(open.contains(vec2(8.0, 2.0), FillRule::default()));
+        assert!(!open.contains(vec2(2.0, 8.0), FillRule::default()));
+    }
+
+    #[test]
+    fn stroke_picking() {
+        let mut builder = Path::builder();
+        builder.begin(vec2(0.0, 0.0));
+        builder.line_to(vec2(10.0, 0.0));
+        builder.line_to(vec2(10.0, 10.0));
+        builder.end(false);
+        let path = builder.build();
+
+        assert!(path.stroke_contains(vec2(5.0, 1.5), 4.0));
+        assert!(!path.stroke_contains(vec2(5.0, 2.5), 4.0));
+        assert!(path.stroke_contains(vec2(12.0, 5.0), 4.0));
+        // round caps
+        assert!(path.stroke_contains(vec2(-1.0, 1.0), 4.0));
+        // the open side is not stroked
+        assert!(!path.stroke_contains(vec2(5.0, 5.0), 4.0));
+    }
+}
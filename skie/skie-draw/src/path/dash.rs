@@ -0,0 +1,195 @@
+use super::{Path, PathMeasure};
+
+impl Path {
+    /// Cuts the path into dashes alternating the dash and gap lengths of `pattern`, repeated
+    /// twice when odd like `setLineDash` of the html canvas. Each contour starts `phase` into
+    /// the pattern. Dashes are open contours, the one over the start of a closed contour is
+    /// joined with the one at its end. Curves are flattened like in [`PathMeasure::new`].
+    ///
+    /// Patterns with negative lengths, without any length or with lengths too small to
+    /// measure along the path return a copy of the path
+    #[must_use]
+    pub fn dash(&self, pattern: &[f32], phase: f32) -> Path {
+        let pattern = if pattern.len() % 2 == 1 {
+            [pattern, pattern].concat()
+        } else {
+            pattern.to_vec()
+        };
+        let period: f32 = pattern.iter().sum();
+        if !period.is_finite() || period <= 0.0 || pattern.iter().any(|length| *length < 0.0) {
+            return self.clone();
+        }
+
+        let measure = PathMeasure::new(self);
+        // intervals below the precision at the end of the longest contour cannot be cut
+        let longest = measure
+            .contours()
+            .iter()
+            .map(|contour| contour.length())
+            .fold(0.0, f32::max);
+        let step = longest * f32::EPSILON;
+        if pattern.iter().any(|length| *length > 0.0 && *length < step) {
+            return self.clone();
+        }
+
+        let mut builder = Path::builder();
+        let mut points = Vec::new();
+
+        // rem_euclid of tiny negative phases rounds up to the period
+        let phase = match phase.rem_euclid(period) {
+            phase if phase >= period => 0.0,
+            phase => phase,
+        };
+
+        for contour in measure.contours() {
+            let length = contour.length();
+
+            // where the phase falls in the pattern
+            let mut index = 0;
+            let mut offset = phase;
+            while offset >= pattern[index] {
+                offset -= pattern[index];
+                index += 1;
+            }
+
+            // interval ends are measured from the start of their period, counted in whole
+            // periods so that the walk cannot stall on rounding
+            let start = -phase;
+            let mut periods = 0u32;
+            let mut within = pattern[..index].iter().sum::<f32>();
+            let mut dashes = Vec::new();
+            let mut distance = 0.0f32;
+            while distance < length {
+                within += pattern[index];
+                let end = (start + periods as f32 * period + within).min(length);
+                if index % 2 == 0 && end > distance {
+                    dashes.push((distance, end));
+                }
+                distance = distance.max(end);
+                index = (index + 1) % pattern.len();
+                if index == 0 {
+                    periods += 1;
+                    within = 0.0;
+                }
+            }
+
+            let mut first = None;
+            if contour.closed && dashes.len() > 1 {
+                let (start, _) = dashes[0];
+                let (_, end) = dashes[dashes.len() - 1];
+                if start == 0.0 && end == length {
+                    first = Some(dashes.remove(0));
+                }
+            }
+
+            for (i, (start, end)) in dashes.iter().enumerate() {
+                points.clear();
+                contour.segment_points(*start, *end, &mut points);
+                if let (Some((start, end)), true) = (first, i + 1 == dashes.len()) {
+                    // continues over the start of the contour
+                    let joined = points.len();
+                    contour.segment_points(start, end, &mut points);
+                    points.remove(joined);
+                }
+
+                builder.begin(points[0]);
+                for point in &points[1..] {
+                    builder.line_to(*point);
+                }
+                builder.end(false);
+            }
+        }
+
+        builder.build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use skie_math::{vec2, Rect};
+
+    use crate::path::Point;
+
+    use super::*;
+
+    fn assert_points(actual: &[Point], expected: &[Point]) {
+        assert_eq!(actual.len(), expected.len(), "{actual:?}");
+        for (a, b) in actual.iter().zip(expected) {
+            assert!((*a - *b).magnitude() < 1e-4, "{actual:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn dash_lines() {
+        let mut builder = Path::builder();
+        builder.begin(vec2(0.0, 0.0));
+        builder.line_to(vec2(100.0, 0.0));
+        builder.end(false);
+        let line = builder.build();
+
+        let dashed = line.dash(&[10.0, 5.0], 0.0);
+        let contours = dashed.contours().collect::<Vec<_>>();
+        assert_eq!(contours.len(), 7);
+        assert_points(contours[1].points(), &[vec2(15.0, 0.0), vec2(25.0, 0.0)]);
+        assert_points(contours[6].points(), &[vec2(90.0, 0.0), vec2(100.0, 0.0)]);
+        assert!(contours.iter().all(|contour| !contour.is_closed()));
+
+        // starts in the middle of the first dash, odd patterns repeat
+        let shifted = line.dash(&[10.0], 5.0);
+        let first = shifted.contours().next().unwrap();
+        assert_points(first.points(), &[vec2(0.0, 0.0), vec2(5.0, 0.0)]);
+        assert_eq!(shifted.contours().count(), 6);
+
+        assert_eq!(line.dash(&[], 0.0).points, line.points);
+        assert_eq!(line.dash(&[5.0, -1.0], 0.0).points, line.points);
+        assert_eq!(line.dash(&[0.0, 0.0], 0.0).points, line.points);
+
+        // wraps around to the start of the pattern
+        let wrapped = line.dash(&[10.0, 5.0], -1e-10);
+        let first = wrapped.contours().next().unwrap();
+        assert_points(first.points(), &[vec2(0.0, 0.0), vec2(10.0, 0.0)]);
+        assert_eq!(wrapped.contours().count(), 7);
+    }
+
+    #[test]
+    fn tiny_intervals_on_long_contours() {
+        let mut builder = Path::builder();
+        builder.begin(vec2(0.0, 0.0));
+        builder.line_to(vec2(2000.0, 0.0));
+        builder.end(false);
+        let line = builder.build();
+
+        assert_eq!(line.dash(&[1e-4, 1e-4], 0.0).points, line.points);
+        assert_eq!(line.dash(&[10.0, 1e-4], 0.0).points, line.points);
+
+        // a long walk of small intervals stays on its periods
+        let dashed = line.dash(&[0.5, 0.5], 0.25);
+        let contours = dashed.contours().collect::<Vec<_>>();
+        assert_eq!(contours.len(), 2001);
+        assert_points(
+            contours[2000].points(),
+            &[vec2(1999.75, 0.0), vec2(2000.0, 0.0)],
+        );
+    }
+
+    #[test]
+    fn dashes_over_the_start_of_closed_contours_join() {
+        let mut builder = Path::builder();
+        builder.rect(&Rect::xywh(0.0, 0.0, 10.0, 10.0));
+        let square = builder.build();
+
+        let dashed = square.dash(&[10.0, 10.0], 5.0);
+        let contours = dashed.contours().collect::<Vec<_>>();
+        assert_eq!(contours.len(), 2);
+        // around the bottom right corner
+        assert_points(
+            contours[0].points(),
+            &[vec2(10.0, 5.0), vec2(10.0, 10.0), vec2(5.0, 10.0)],
+        );
+        // around the top left corner
+        assert_points(
+            contours[1].points(),
+            &[vec2(0.0, 5.0), vec2(0.0, 0.0), vec2(5.0, 0.0)],
+        );
+    }
+}
@@ -13,6 +13,8 @@ where
     output: &'a mut Vec<Point>,
     offset: usize,
     num_segments: u32,
+    tolerance: Option<f32>,
+    stroke_width: f32,
     path_iter: PathIter,
 }
 
@@ -27,10 +29,26 @@ where
             output,
             offset,
             num_segments: 16,
+            tolerance: None,
+            stroke_width: 0.0,
             path_iter: path_iter.into(),
         }
     }
 
+    /// Flattens curves adaptively so that the output stays within `tolerance` pixels of them,
+    /// instead of using a fixed number of segments per curve
+    pub fn tolerance(mut self, tolerance: f32) -> Self {
+        self.tolerance = Some(tolerance);
+        self
+    }
+
+    /// Adds segments where curves turn sharply so the edges of a stroke this wide also stay
+    /// within the tolerance, the joins would facet them otherwise. Needs [`Self::tolerance`]
+    pub fn stroke_width(mut self, width: f32) -> Self {
+        self.stroke_width = width;
+        self
+    }
+
     fn build_geometry_till_end(&mut self, start: Point) -> Contour {
         self.output.push(start);
 
@@ -43,7 +61,6 @@ where
                     ctrl2,
                     to,
                 }) => {
-                    // todo in case of 0 num_segments;
                     let bezier = CubicBezier {
                         from,
                         ctrl1,
@@ -51,24 +68,33 @@ where
                         to,
                     };
 
-                    let num_segments = self.num_segments;
+                    let num_segments = self.tolerance.map_or(self.num_segments, |tol| {
+                        bezier
+                            .flattening_segments(tol)
+                            .max(bezier.stroke_segments(self.stroke_width / 2.0, tol))
+                    });
                     let t_step = 1.0 / num_segments as f32;
                     self.output.reserve(num_segments as usize);
 
-                    for i in 1..=num_segments {
+                    for i in 1..num_segments {
                         self.output.push(bezier.sample(t_step * i as f32))
                     }
+                    self.output.push(to);
                 }
                 Some(PathEvent::Quadratic { from, ctrl, to }) => {
-                    // todo in case of 0 num_segments;
                     let bezier = QuadraticBezier { from, ctrl, to };
-                    let num_segments = self.num_segments;
+                    let num_segments = self.tolerance.map_or(self.num_segments, |tol| {
+                        bezier
+                            .flattening_segments(tol)
+                            .max(bezier.stroke_segments(self.stroke_width / 2.0, tol))
+                    });
                     let t_step = 1.0 / num_segments as f32;
                     self.output.reserve(num_segments as usize);
 
-                    for i in 1..=num_segments {
+                    for i in 1..num_segments {
                         self.output.push(bezier.sample(t_step * i as f32))
                     }
+                    self.output.push(to);
                 }
                 Some(PathEvent::Line { to, .. }) => self.output.push(to),
                 Some(PathEvent::End {
@@ -256,6 +282,106 @@ mod tests {
         );
     }
 
+    #[test]
+    fn path_geometry_conic() {
+        let mut output = <Vec<Point>>::new();
+
+        // weight 1 is a plain quadratic
+        let mut path = PathBuilder::default();
+        path.begin(vec2(0.0, 0.0));
+        path.conic_to(vec2(5.0, 5.0), vec2(10.0, 0.0), 1.0);
+        path.end(false);
+        assert_eq!(path.verbs.len(), 3);
+
+        // quarter circle
+        path.begin(vec2(100.0, 0.0));
+        path.conic_to(
+            vec2(100.0, 100.0),
+            vec2(0.0, 100.0),
+            std::f32::consts::FRAC_1_SQRT_2,
+        );
+        path.end(false);
+
+        let contours = <PathGeometryBuilder<PathEventsIter>>::new(path.path_events(), &mut output)
+            .map(|v| v.1)
+            .collect::<Vec<_>>();
+
+        assert_eq!(output[contours[0].clone()][8], vec2(5.0, 2.5));
+
+        let arc = &output[contours[1].clone()];
+        assert_eq!(arc.first(), Some(&vec2(100.0, 0.0)));
+        assert_eq!(arc.last(), Some(&vec2(0.0, 100.0)));
+        for p in arc {
+            assert!(
+                (p.magnitude() - 100.0).abs() <= 0.25,
+                "{p:?} is off the arc"
+            );
+        }
+    }
+
+    #[test]
+    fn path_geometry_tolerance() {
+        let mut path = PathBuilder::default();
+        path.begin(vec2(0.0, 0.0));
+        path.cubic_to(vec2(0.0, 100.0), vec2(100.0, 100.0), vec2(100.0, 0.0));
+        path.end(false);
+
+        let count = |tolerance: f32| {
+            let mut output = <Vec<Point>>::new();
+            let range = <PathGeometryBuilder<PathEventsIter>>::new(path.path_events(), &mut output)
+                .tolerance(tolerance)
+                .next()
+                .expect("no contours found")
+                .1;
+            assert_eq!(output[range.clone()].last(), Some(&vec2(100.0, 0.0)));
+            range.len()
+        };
+
+        assert!(count(0.05) > count(0.25));
+        assert!(count(0.25) > count(2.0));
+        // a flat curve needs a single segment
+        let mut output = <Vec<Point>>::new();
+        let mut flat = PathBuilder::default();
+        flat.begin(vec2(0.0, 0.0));
+        flat.quadratic_to(vec2(5.0, 0.0), vec2(10.0, 0.0));
+        flat.end(false);
+        let contour = <PathGeometryBuilder<PathEventsIter>>::new(flat.path_events(), &mut output)
+            .tolerance(0.25)
+            .next();
+        assert_eq!(contour.map(|c| c.1.len()), Some(2));
+    }
+
+    #[test]
+    fn path_geometry_stroke_width_splits_tight_curves() {
+        let mut path = PathBuilder::default();
+        path.begin(vec2(0.0, 0.0));
+        path.cubic_to(vec2(0.0, 10.0), vec2(10.0, 10.0), vec2(10.0, 0.0));
+        path.end(false);
+
+        let flatten = |stroke_width: f32| {
+            let mut output = <Vec<Point>>::new();
+            let range = <PathGeometryBuilder<PathEventsIter>>::new(path.path_events(), &mut output)
+                .tolerance(0.25)
+                .stroke_width(stroke_width)
+                .next()
+                .expect("no contours found")
+                .1;
+            output[range].to_vec()
+        };
+
+        let thin = flatten(1.0);
+        let thick = flatten(40.0);
+        assert_eq!(thin.len(), flatten(0.0).len());
+        assert!(thick.len() > thin.len());
+
+        // the edge 20px out deviates from its arc by `20 * turn^2 / 8`
+        let max_turn = thick
+            .windows(3)
+            .map(|w| (w[1] - w[0]).angle(&(w[2] - w[1])))
+            .fold(0.0, f32::max);
+        assert!(20.0 * max_turn * max_turn / 8.0 <= 0.3, "{max_turn}");
+    }
+
     #[test]
     fn path_geometry_cubic_bezier() {
         let mut output = <Vec<Point>>::new();
@@ -0,0 +1,220 @@
+use super::{Path, PathEventsIter, PathGeometryBuilder, Point, PATH_OP_TOLERANCE};
+
+/// Lengths along the contours of a [`Path`], for sampling positions and tangents at a
+/// distance. Contours follow each other in the order they were built, open ones are not
+/// closed
+#[derive(Debug, Clone, Default)]
+pub struct PathMeasure {
+    contours: Vec<ContourMeasure>,
+    length: f32,
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct ContourMeasure {
+    pub points: Vec<Point>,
+    /// distance along the contour at each point
+    pub lengths: Vec<f32>,
+    pub closed: bool,
+}
+
+impl ContourMeasure {
+    pub fn length(&self) -> f32 {
+        self.lengths.last().copied().unwrap_or_default()
+    }
+
+    /// Index of the segment the distance falls on and how far along it
+    pub fn segment_at(&self, distance: f32) -> (usize, f32) {
+        let distance = distance.clamp(0.0, self.length());
+        let end = self
+            .lengths
+            .partition_point(|length| *length < distance)
+            .clamp(1, self.points.len() - 1);
+        let (from, to) = (self.lengths[end - 1], self.lengths[end]);
+        let t = if to > from {
+            (distance - from) / (to - from)
+        } else {
+            0.0
+        };
+        (end - 1, t)
+    }
+
+    pub fn point_at(&self, distance: f32) -> Point {
+        let (segment, t) = self.segment_at(distance);
+        let (a, b) = (self.points[segment], self.points[segment + 1]);
+        a + (b - a) * t
+    }
+
+    /// Pushes the points of the part of the contour between the distances
+    pub fn segment_points(&self, start: f32, end: f32, output: &mut Vec<Point>) {
+        let (first, _) = self.segment_at(start);
+        let (last, _) = self.segment_at(end);
+        output.push(self.point_at(start));
+        output.extend(
+            (first + 1..=last)
+                .filter(|index| self.lengths[*index] > start && self.lengths[*index] < end)
+                .map(|index| self.points[index]),
+        );
+        output.push(self.point_at(end));
+    }
+
+    fn tangent_at(&self, distance: f32) -> Point {
+        let (segment, _) = self.segment_at(distance);
+        (self.points[segment + 1] - self.points[segment]).normalize()
+    }
+}
+
+impl PathMeasure {
+    /// Measures `path` with curves flattened like in [`Path::op`]
+    pub fn new(path: &Path) -> Self {
+        Self::with_tolerance(path, PATH_OP_TOLERANCE)
+    }
+
+    /// Measures `path` with curves flattened within `tolerance`
+    pub fn with_tolerance(path: &Path, tolerance: f32) -> Self {
+        let mut contours = Vec::new();
+        let mut points = Vec::new();
+
+        for contour in path.contours() {
+            points.clear();
+            let Some((_, range)) =
+                <PathGeometryBuilder<PathEventsIter>>::new(contour.events(), &mut points)
+                    .tolerance(tolerance)
+                    .next()
+            else {
+                continue;
+            };
+
+            let mut flattened = points[range].to_vec();
+            flattened.dedup();
+            if flattened.len() < 2 {
+                continue;
+            }
+
+            let mut length = 0.0;
+            let mut lengths = Vec::with_capacity(flattened.len());
+            lengths.push(0.0);
+            for segment in flattened.windows(2) {
+                length += (segment[1] - segment[0]).magnitude();
+                lengths.push(length);
+            }
+
+            contours.push(ContourMeasure {
+                points: flattened,
+                lengths,
+                closed: contour.is_closed(),
+            });
+        }
+
+        let length = contours.iter().map(ContourMeasure::length).sum();
+        Self { contours, length }
+    }
+
+    /// Length of all the contours
+    pub fn length(&self) -> f32 {
+        self.length
+    }
+
+    /// Number of contours with a length, in the order they were built
+    pub fn contour_count(&self) -> usize {
+        self.contours.len()
+    }
+
+    pub fn contour_length(&self, contour: usize) -> Option<f32> {
+        self.contours.get(contour).map(ContourMeasure::length)
+    }
+
+    pub fn is_contour_closed(&self, contour: usize) -> Option<bool> {
+        self.contours.get(contour).map(|contour| contour.closed)
+    }
+
+    /// Position at `distance` along the path, clamped to its ends
+    pub fn position_at(&self, distance: f32) -> Option<Point> {
+        self.sample(distance).map(|(position, _)| position)
+    }
+
+    /// Unit tangent at `distance` along the path, clamped to its ends
+    pub fn tangent_at(&self, distance: f32) -> Option<Point> {
+        self.sample(distance).map(|(_, tangent)| tangent)
+    }
+
+    /// Position and unit tangent at `distance` along the path, clamped to its ends.
+    /// Distances on a vertex take the tangent of the segment ending there
+    pub fn sample(&self, distance: f32) -> Option<(Point, Point)> {
+        let (contour, distance) = self.locate(distance)?;
+        Some((contour.point_at(distance), contour.tangent_at(distance)))
+    }
+
+    pub(super) fn contours(&self) -> &[ContourMeasure] {
+        &self.contours
+    }
+
+    /// Contour the distance along the path falls on and the distance along it
+    fn locate(&self, mut distance: f32) -> Option<(&ContourMeasure, f32)> {
+        let last = self.contours.last()?;
+        for contour in &self.contours {
+            let length = contour.length();
+            if distance <= length {
+                return Some((contour, distance));
+            }
+            distance -= length;
+        }
+        Some((last, last.length()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use skie_math::{vec2, Rect};
+
+    use super::*;
+
+    #[test]
+    fn measure_lines() {
+        let mut builder = Path::builder();
+        builder.rect(&Rect::xywh(0.0, 0.0, 10.0, 10.0));
+        builder.begin(vec2(100.0, 0.0));
+        builder.line_to(vec2(100.0, 0.0));
+        builder.end(false);
+        builder.begin(vec2(0.0, 20.0));
+        builder.line_to(vec2(30.0, 20.0));
+        builder.end(false);
+        let measure = PathMeasure::new(&builder.build());
+
+        // the zero length contour is skipped
+        assert_eq!(measure.contour_count(), 2);
+        assert_eq!(measure.length(), 70.0);
+        assert_eq!(measure.contour_length(0), Some(40.0));
+        assert_eq!(measure.is_contour_closed(0), Some(true));
+        assert_eq!(measure.is_contour_closed(1), Some(false));
+
+        assert_eq!(
+            measure.sample(15.0),
+            Some((vec2(10.0, 5.0), vec2(0.0, 1.0)))
+        );
+        // on a corner the tangent follows the segment ending there
+        assert_eq!(measure.tangent_at(10.0), Some(vec2(1.0, 0.0)));
+        assert_eq!(measure.position_at(35.0), Some(vec2(0.0, 5.0)));
+        assert_eq!(measure.position_at(50.0), Some(vec2(10.0, 20.0)));
+        // clamped to the ends
+        assert_eq!(measure.position_at(-5.0), Some(vec2(0.0, 0.0)));
+        assert_eq!(measure.position_at(100.0), Some(vec2(30.0, 20.0)));
+
+        assert_eq!(PathMeasure::new(&Path::builder().build()).sample(0.0), None);
+    }
+
+    #[test]
+    fn measure_curves() {
+        let mut builder = Path::builder();
+        builder.circle(vec2(0.0, 0.0), 50.0);
+        let measure = PathMeasure::new(&builder.build());
+
+        let circumference = std::f32::consts::TAU * 50.0;
+        assert!((measure.length() - circumference).abs() < circumference * 0.001);
+
+        // a quarter of the way around from the left, clockwise
+        let (position, tangent) = measure.sample(measure.length() / 4.0).unwrap();
+        assert!((position - vec2(0.0, -50.0)).magnitude() < 0.5);
+        // along a flattened segment
+        assert!((tangent - vec2(1.0, 0.0)).magnitude() < 0.1);
+    }
+}
@@ -1,16 +1,26 @@
+mod boolean;
 mod builder;
+mod contains;
+mod dash;
 pub mod geo;
+mod measure;
 
 pub use geo::*;
 pub mod polygon;
+mod stroke;
+pub use boolean::{PathOp, PATH_OP_TOLERANCE};
 pub use builder::*;
+pub use contains::FillRule;
+pub use measure::PathMeasure;
 pub use polygon::*;
 
 pub type Point = skie_math::Vec2<f32>;
 
 use core::f32;
 
-use skie_math::Zero;
+use skie_math::{Mat3, Rect, Zero};
+
+use crate::paint::{CubicBezier, QuadraticBezier};
 
 #[derive(Debug, Clone, Copy, Hash, PartialEq)]
 pub(crate) enum PathVerb {
@@ -33,9 +43,232 @@ impl Path {
     pub fn builder() -> PathBuilder {
         PathBuilder::default()
     }
-    pub fn events(&self) -> PathEventsIter {
+    pub fn events(&self) -> PathEventsIter<'_> {
         PathEventsIter::new(&self.points, &self.verbs)
     }
+
+    /// Applies `transform` to every point of the path, control points included
+    pub fn transform(&mut self, transform: &Mat3) {
+        for point in self.points.iter_mut() {
+            *point = *transform * *point;
+        }
+    }
+
+    /// Copy of the path with `transform` applied, see [`Path::transform`]
+    #[must_use]
+    pub fn transformed(&self, transform: &Mat3) -> Path {
+        let mut path = self.clone();
+        path.transform(transform);
+        path
+    }
+
+    /// New path with the contours of `other` after those of this path
+    #[must_use]
+    pub fn concat(&self, other: &Path) -> Path {
+        let mut builder = PathBuilder::with_capacity(
+            self.points.len() + other.points.len(),
+            self.verbs.len() + other.verbs.len(),
+        );
+        builder.extend(self);
+        builder.extend(other);
+        builder.build()
+    }
+
+    /// Copy of the path with every contour running the other way, in the same order.
+    /// Closed contours begin at their last point
+    #[must_use]
+    pub fn reversed(&self) -> Path {
+        let mut builder = PathBuilder::with_capacity(self.points.len(), self.verbs.len());
+        for contour in self.contours() {
+            add_reversed(&mut builder, &contour);
+        }
+        builder.build()
+    }
+
+    /// Tight bounds of the path, curves are bounded by their extrema instead of their
+    /// control points. Empty paths have empty bounds
+    pub fn bounds(&self) -> Rect<f32> {
+        let mut bounds: Option<Rect<f32>> = None;
+        let mut add = |rect: Rect<f32>| {
+            bounds = Some(
+                bounds
+                    .as_ref()
+                    .map_or(rect.clone(), |bounds| bounds.union(&rect)),
+            );
+        };
+
+        for event in self.events() {
+            match event {
+                PathEvent::Begin { at } => add(Rect::from_corners(at, at)),
+                PathEvent::Line { to, .. } => add(Rect::from_corners(to, to)),
+                PathEvent::Quadratic { from, ctrl, to } => {
+                    add(QuadraticBezier { from, ctrl, to }.bounds())
+                }
+                PathEvent::Cubic {
+                    from,
+                    ctrl1,
+                    ctrl2,
+                    to,
+                } => add(CubicBezier {
+                    from,
+                    ctrl1,
+                    ctrl2,
+                    to,
+                }
+                .bounds()),
+                PathEvent::End { .. } => {}
+            }
+        }
+        bounds.unwrap_or_default()
+    }
+
+    /// Iterates over the contours of the path in the order they were built
+    pub fn contours(&self) -> ContoursIter<'_> {
+        ContoursIter {
+            path: self,
+            p_index: 0,
+            v_index: 0,
+        }
+    }
+}
+
+/// A single contour of a [`Path`]
+#[derive(Debug, Clone, Copy)]
+pub struct ContourRef<'a> {
+    id: Contour,
+    closed: bool,
+    p_start: usize,
+    points: &'a [Point],
+    verbs: &'a [PathVerb],
+}
+
+impl<'a> ContourRef<'a> {
+    /// Same id returned by [`PathBuilder::end`] for this contour, usable as a [`crate::PathBrush`] key
+    #[inline]
+    pub fn id(&self) -> Contour {
+        self.id
+    }
+
+    /// Points of the contour including control points. A closed contour repeats the first point at the end
+    #[inline]
+    pub fn points(&self) -> &'a [Point] {
+        self.points
+    }
+
+    #[inline]
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Bounds of the contour points (control points included)
+    pub fn bounds(&self) -> Rect<f32> {
+        get_path_bounds(self.points)
+    }
+
+    /// The contour running the other way, see [`Path::reversed`]
+    #[must_use]
+    pub fn reversed(&self) -> Path {
+        let mut builder = Path::builder();
+        add_reversed(&mut builder, self);
+        builder.build()
+    }
+
+    pub fn events(&self) -> PathEventsIter<'a> {
+        let mut iter = PathEventsIter::new(self.points, self.verbs);
+        iter.p_index = self.p_start;
+        iter
+    }
+}
+
+fn add_reversed(builder: &mut PathBuilder, contour: &ContourRef) {
+    let mut start = None;
+    let mut close = false;
+    let mut segments = Vec::new();
+    for event in contour.events() {
+        match event {
+            PathEvent::Begin { at } => start = Some(at),
+            PathEvent::End { close: closed, .. } => {
+                close = closed;
+                break;
+            }
+            segment => segments.push(segment),
+        }
+    }
+
+    let last = match segments.last() {
+        Some(
+            PathEvent::Line { to, .. }
+            | PathEvent::Quadratic { to, .. }
+            | PathEvent::Cubic { to, .. },
+        ) => *to,
+        _ => match start {
+            Some(at) => at,
+            None => return,
+        },
+    };
+
+    builder.begin(last);
+    for segment in segments.iter().rev() {
+        match *segment {
+            PathEvent::Line { from, .. } => builder.line_to(from),
+            PathEvent::Quadratic { from, ctrl, .. } => builder.quadratic_to(ctrl, from),
+            PathEvent::Cubic {
+                from, ctrl1, ctrl2, ..
+            } => builder.cubic_to(ctrl2, ctrl1, from),
+            _ => {}
+        }
+    }
+    builder.end(close);
+}
+
+pub struct ContoursIter<'a> {
+    path: &'a Path,
+    p_index: usize,
+    v_index: usize,
+}
+
+impl<'a> Iterator for ContoursIter<'a> {
+    type Item = ContourRef<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let verbs = &self.path.verbs[self.v_index..];
+        if verbs.is_empty() {
+            return None;
+        }
+
+        let p_start = self.p_index;
+        let mut p_end = p_start;
+        let mut closed = false;
+        let mut len = verbs.len();
+
+        for (i, verb) in verbs.iter().enumerate() {
+            match verb {
+                PathVerb::Begin | PathVerb::LineTo => p_end += 1,
+                PathVerb::QuadraticTo => p_end += 2,
+                PathVerb::CubicTo => p_end += 3,
+                PathVerb::Close | PathVerb::End => {
+                    if *verb == PathVerb::Close {
+                        p_end += 1;
+                        closed = true;
+                    }
+                    len = i + 1;
+                    break;
+                }
+            }
+        }
+
+        let v_start = self.v_index;
+        self.v_index += len;
+        self.p_index = p_end;
+
+        Some(ContourRef {
+            id: Contour(p_end),
+            closed,
+            p_start,
+            points: &self.path.points[p_start..p_end],
+            verbs: &self.path.verbs[v_start..self.v_index],
+        })
+    }
 }
 
 impl<'a> IntoIterator for &'a Path {
@@ -265,4 +498,166 @@ mod tests {
 
         assert_eq!(iter.next(), None);
     }
+
+    #[test]
+    fn path_contours_iter() {
+        let mut builder = Path::builder();
+
+        builder.begin(vec2(0.0, 0.0));
+        builder.line_to(vec2(10.0, 0.0));
+        builder.line_to(vec2(10.0, 10.0));
+        let tri = builder.close();
+
+        builder.begin(vec2(20.0, 20.0));
+        builder.cubic_to(vec2(30.0, 10.0), vec2(40.0, 30.0), vec2(50.0, 20.0));
+        let curve = builder.end(false);
+
+        let path = builder.build();
+        let contours = path.contours().collect::<Vec<_>>();
+
+        assert_eq!(contours.len(), 2);
+
+        assert_eq!(contours[0].id(), tri);
+        assert!(contours[0].is_closed());
+        assert_eq!(contours[0].points().len(), 4);
+        assert_eq!(
+            contours[0].bounds(),
+            Rect::from_corners(vec2(0.0, 0.0), vec2(10.0, 10.0))
+        );
+
+        assert_eq!(contours[1].id(), curve);
+        assert!(!contours[1].is_closed());
+        assert_eq!(
+            contours[1].points(),
+            &[
+                vec2(20.0, 20.0),
+                vec2(30.0, 10.0),
+                vec2(40.0, 30.0),
+                vec2(50.0, 20.0)
+            ]
+        );
+        assert_eq!(contours[1].bounds().origin, vec2(20.0, 10.0));
+
+        // events of a contour report the same ids as the whole path
+        let last = contours[1].events().last();
+        assert_eq!(
+            last,
+            Some(PathEvent::End {
+                contour: curve,
+                last: vec2(50.0, 20.0),
+                close: false,
+                first: vec2(20.0, 20.0)
+            })
+        );
+        let path_ends = path
+            .events()
+            .filter(|e| matches!(e, PathEvent::End { .. }))
+            .count();
+        assert_eq!(path_ends, contours.len());
+    }
+
+    #[test]
+    fn path_transform() {
+        let mut builder = Path::builder();
+        builder.begin(vec2(0.0, 0.0));
+        builder.quadratic_to(vec2(10.0, 0.0), vec2(10.0, 10.0));
+        builder.close();
+        // points added before the transform are moved, later ones are not
+        builder.transform(&Mat3::from_translation(5.0, 0.0));
+        builder.begin(vec2(0.0, 0.0));
+        builder.line_to(vec2(1.0, 1.0));
+        builder.end(false);
+        let path = builder.build();
+
+        let contours = path.contours().collect::<Vec<_>>();
+        assert_eq!(
+            contours[0].points(),
+            [
+                vec2(5.0, 0.0),
+                vec2(15.0, 0.0),
+                vec2(15.0, 10.0),
+                vec2(5.0, 0.0)
+            ]
+        );
+        assert_eq!(contours[1].points(), [vec2(0.0, 0.0), vec2(1.0, 1.0)]);
+
+        let mut scale = Mat3::from_scale(2.0, 3.0);
+        scale.translate(1.0, 1.0);
+        let scaled = path.transformed(&scale);
+        assert_eq!(
+            scaled.contours().next().unwrap().bounds(),
+            Rect::from_corners(scale * vec2(5.0, 0.0), scale * vec2(15.0, 10.0))
+        );
+        assert_eq!(scaled.verbs, path.verbs);
+    }
+
+    #[test]
+    fn path_bounds() {
+        let mut builder = Path::builder();
+        builder.begin(vec2(0.0, 0.0));
+        builder.quadratic_to(vec2(10.0, 20.0), vec2(20.0, 0.0));
+        builder.cubic_to(vec2(20.0, -30.0), vec2(40.0, -30.0), vec2(40.0, 0.0));
+        builder.end(false);
+        let path = builder.build();
+
+        // the control points reach further than the curves
+        let bounds = path.bounds();
+        assert_eq!(bounds.origin.x, 0.0);
+        assert_eq!(bounds.bottom_right().x, 40.0);
+        assert!((bounds.bottom_right().y - 10.0).abs() < 1e-4);
+        assert!((bounds.origin.y + 22.5).abs() < 1e-4);
+
+        let mut builder = Path::builder();
+        builder.circle(vec2(10.0, 10.0), 5.0);
+        let circle = builder.build().bounds();
+        assert!((circle.origin - vec2(5.0, 5.0)).magnitude() < 1e-4);
+        assert!((circle.bottom_right() - vec2(15.0, 15.0)).magnitude() < 1e-4);
+
+        assert_eq!(Path::builder().build().bounds(), Rect::default());
+    }
+
+    #[test]
+    fn path_reversed() {
+        let mut builder = Path::builder();
+        builder.rect(&Rect::xywh(0.0, 0.0, 10.0, 10.0));
+        builder.begin(vec2(20.0, 20.0));
+        builder.quadratic_to(vec2(25.0, 10.0), vec2(30.0, 20.0));
+        builder.cubic_to(vec2(35.0, 30.0), vec2(40.0, 10.0), vec2(50.0, 20.0));
+        builder.end(false);
+        let path = builder.build();
+
+        let reversed = path.reversed();
+        let contours = reversed.contours().collect::<Vec<_>>();
+        assert_eq!(contours.len(), 2);
+
+        // the square begins at its last corner and winds counter-clockwise
+        assert!(contours[0].is_closed());
+        assert_eq!(
+            contours[0].points(),
+            [
+                vec2(0.0, 10.0),
+                vec2(10.0, 10.0),
+                vec2(10.0, 0.0),
+                vec2(0.0, 0.0),
+                vec2(0.0, 10.0)
+            ]
+        );
+
+        assert!(!contours[1].is_closed());
+        assert_eq!(
+            contours[1].points(),
+            [
+                vec2(50.0, 20.0),
+                vec2(40.0, 10.0),
+                vec2(35.0, 30.0),
+                vec2(30.0, 20.0),
+                vec2(25.0, 10.0),
+                vec2(20.0, 20.0)
+            ]
+        );
+
+        let curve = path.contours().nth(1).unwrap();
+        assert_eq!(curve.reversed().reversed().points, curve.points().into());
+        assert_eq!(reversed.reversed().verbs, path.verbs);
+    }
 }
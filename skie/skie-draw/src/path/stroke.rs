@@ -0,0 +1,267 @@
+use std::f32::consts::PI;
+
+use skie_math::Rect;
+
+use crate::paint::{LineCap, LineJoin, StrokeStyle};
+
+use super::{Path, PathBuilder, PathEventsIter, PathGeometryBuilder, Point, PATH_OP_TOLERANCE};
+
+/// Joins sharper than this angle between the segments are beveled instead of mitered,
+/// same as the stroke tessellator
+const MITER_MIN_ANGLE: f32 = 0.349066; // ~20 degrees
+
+impl Path {
+    /// Outline of the area covered by stroking the path with `style`, filled with the nonzero
+    /// rule. Curves are flattened like in [`Path::op`], hairlines are outlined one unit wide
+    pub fn stroke_to_path(&self, style: &StrokeStyle) -> Path {
+        let half_width = style.width() / 2.0;
+
+        let mut points = Vec::new();
+        let contours = <PathGeometryBuilder<PathEventsIter>>::new(self.events(), &mut points)
+            .tolerance(PATH_OP_TOLERANCE)
+            .stroke_width(style.width())
+            .map(|(_, range)| range)
+            .collect::<Vec<_>>();
+
+        // every piece winds clockwise, so the nonzero fill of all of them is their union
+        let mut pieces = PieceBuilder::default();
+        for range in contours {
+            let mut contour = points[range].to_vec();
+            contour.dedup();
+            add_contour_pieces(&mut pieces, &contour, style, half_width);
+        }
+
+        pieces.0.build().union(&Path::builder().build())
+    }
+}
+
+#[derive(Default)]
+struct PieceBuilder(PathBuilder);
+
+impl PieceBuilder {
+    fn polygon(&mut self, points: &[Point]) {
+        let area: f32 = points
+            .iter()
+            .zip(points.iter().cycle().skip(1))
+            .map(|(a, b)| a.cross(b))
+            .sum();
+        if area.abs() <= f32::EPSILON {
+            return;
+        }
+
+        let mut ordered = points.to_vec();
+        if area < 0.0 {
+            ordered.reverse();
+        }
+        self.0.begin(ordered[0]);
+        for &p in &ordered[1..] {
+            self.0.line_to(p);
+        }
+        self.0.close();
+    }
+
+    fn circle(&mut self, center: Point, radius: f32) {
+        self.0.circle(center, radius);
+    }
+}
+
+fn add_contour_pieces(
+    pieces: &mut PieceBuilder,
+    contour: &[Point],
+    style: &StrokeStyle,
+    half_width: f32,
+) {
+    let Some(&first) = contour.first() else {
+        return;
+    };
+
+    if contour.len() == 1 {
+        // a zero length stroke only shows its caps
+        match style.line_cap {
+            LineCap::Round => pieces.circle(first, half_width),
+            LineCap::Square => {
+                let size = half_width * 2.0;
+                let rect = Rect::xywh(first.x - half_width, first.y - half_width, size, size);
+                pieces.polygon(&rect_corners(&rect));
+            }
+            LineCap::Butt => {}
+        }
+        return;
+    }
+
+    let closed = contour.len() > 2 && contour.last() == Some(&first);
+
+    for segment in contour.windows(2) {
+        let (a, b) = (segment[0], segment[1]);
+        let n = normal(a, b) * half_width;
+        pieces.polygon(&[a + n, b + n, b - n, a - n]);
+    }
+
+    for joint in contour.windows(3) {
+        add_join(
+            pieces,
+            joint[0],
+            joint[1],
+            joint[2],
+            style.line_join,
+            half_width,
+        );
+    }
+
+    if closed {
+        let before = contour[contour.len() - 2];
+        add_join(
+            pieces,
+            before,
+            first,
+            contour[1],
+            style.line_join,
+            half_width,
+        );
+        return;
+    }
+
+    let last = contour[contour.len() - 1];
+    let ends = [(first, contour[1]), (last, contour[contour.len() - 2])];
+    for (end, next) in ends {
+        match style.line_cap {
+            LineCap::Round => pieces.circle(end, half_width),
+            LineCap::Square => {
+                let n = normal(next, end) * half_width;
+                let out = n.normal();
+                pieces.polygon(&[end + n, end + n - out, end - n - out, end - n]);
+            }
+            LineCap::Butt => {}
+        }
+    }
+}
+
+/// Fills the outer side of the turn at `at`, the inner side is covered by the segments
+fn add_join(
+    pieces: &mut PieceBuilder,
+    before: Point,
+    at: Point,
+    after: Point,
+    join: LineJoin,
+    half_width: f32,
+) {
+    let (d0, d1) = ((at - before).normalize(), (after - at).normalize());
+    let turn = d0.cross(&d1);
+    if turn.abs() <= 1e-6 {
+        return;
+    }
+
+    // normals pointing away from the turn
+    let side = if turn > 0.0 { -1.0 } else { 1.0 };
+    let n0 = normal(before, at) * (half_width * side);
+    let n1 = normal(at, after) * (half_width * side);
+
+    let angle = d0.angle(&d1);
+    match join {
+        LineJoin::Round => pieces.circle(at, half_width),
+        LineJoin::Miter if angle < PI - MITER_MIN_ANGLE => {
+            let mid = (n0 + n1).normalize();
+            let miter = at + mid * (half_width / mid.dot(&n0.normalize()));
+            pieces.polygon(&[at, at + n0, miter, at + n1]);
+        }
+        _ => pieces.polygon(&[at, at + n0, at + n1]),
+    }
+}
+
+fn normal(a: Point, b: Point) -> Point {
+    (b - a).normalize().normal()
+}
+
+fn rect_corners(rect: &Rect<f32>) -> [Point; 4] {
+    [
+        rect.top_left(),
+        rect.top_right(),
+        rect.bottom_right(),
+        rect.bottom_left(),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use skie_math::vec2;
+
+    use super::*;
+
+    fn area(path: &Path) -> f32 {
+        path.contours()
+            .flat_map(|contour| {
+                let points = contour.points();
+                points
+                    .iter()
+                    .zip(points.iter().cycle().skip(1))
+                    .map(|(a, b)| a.cross(b) / 2.0)
+                    .collect::<Vec<_>>()
+            })
+            .sum()
+    }
+
+    fn line(from: Point, to: Point) -> Path {
+        let mut builder = Path::builder();
+        builder.begin(from);
+        builder.line_to(to);
+        builder.end(false);
+        builder.build()
+    }
+
+    #[test]
+    fn line_caps() {
+        let line = line(vec2(0.0, 0.0), vec2(10.0, 0.0));
+        let style = StrokeStyle::default().line_width(4);
+
+        let butt = line.stroke_to_path(&style);
+        assert_eq!(area(&butt), 40.0);
+        assert_eq!(
+            butt.contours().next().unwrap().bounds(),
+            Rect::xywh(0.0, -2.0, 10.0, 4.0)
+        );
+
+        let square = line.stroke_to_path(&style.line_cap(LineCap::Square));
+        assert_eq!(area(&square), 56.0);
+
+        let round = line.stroke_to_path(&style.line_cap(LineCap::Round));
+        // two halves of a small circle, flattened within the tolerance
+        let caps = area(&round) - 40.0;
+        assert!((caps - PI * 4.0).abs() < 1.0, "{caps}");
+    }
+
+    #[test]
+    fn closed_contours_leave_a_hole() {
+        let mut builder = Path::builder();
+        builder.rect(&Rect::xywh(0.0, 0.0, 10.0, 10.0));
+        let square = builder.build();
+
+        let outline = square.stroke_to_path(&StrokeStyle::default().line_width(2));
+        assert_eq!(outline.contours().count(), 2);
+        // mitered corners keep the outline square
+        assert_eq!(area(&outline), 12.0 * 12.0 - 8.0 * 8.0);
+
+        let beveled = square.stroke_to_path(
+            &StrokeStyle::default()
+                .line_width(2)
+                .line_join(LineJoin::Bevel),
+        );
+        assert_eq!(area(&beveled), 12.0 * 12.0 - 8.0 * 8.0 - 4.0 * 0.5);
+    }
+
+    #[test]
+    fn overlapping_strokes_merge() {
+        let mut builder = Path::builder();
+        builder.begin(vec2(0.0, 5.0));
+        builder.line_to(vec2(10.0, 5.0));
+        builder.end(false);
+        builder.begin(vec2(5.0, 0.0));
+        builder.line_to(vec2(5.0, 10.0));
+        builder.end(false);
+
+        let cross = builder
+            .build()
+            .stroke_to_path(&StrokeStyle::default().line_width(2));
+        assert_eq!(cross.contours().count(), 1);
+        assert_eq!(area(&cross), 20.0 + 20.0 - 4.0);
+    }
+}
@@ -1,18 +1,65 @@
-use std::{borrow::Cow, cell::Cell, num::NonZeroU64, ops::Range};
+use std::{borrow::Cow, cell::Cell, num::NonZeroU64, ops::Range, sync::Arc};
 
 use crate::{
-    gpu::CommandEncoder, paint::Vertex, AtlasKey, AtlasKeySource, GpuContext, GpuTextureView, Mat3,
-    Mesh, Rect, Size, SkieAtlas, TextureAtlas, TextureId, TextureKind, TextureOptions,
+    gpu::CommandEncoder, paint::Vertex, AtlasKey, AtlasKeySource, Color, GpuContext,
+    GpuTextureView, Mat3, Mesh, Rect, Size, SkieAtlas, TextureAtlas, TextureId, TextureKind,
+    TextureOptions, Vec2,
 };
 
+use ahash::AHashMap;
+use blur::BlurRenderer;
+use layer::LayerTarget;
+use parking_lot::RwLock;
 use wgpu::util::DeviceExt;
 
+mod blur;
+mod layer;
+
 static INITIAL_VERTEX_BUFFER_SIZE: u64 = (std::mem::size_of::<Vertex>() * 1024) as u64;
 static INITIAL_INDEX_BUFFER_SIZE: u64 = (std::mem::size_of::<u32>() * 1024 * 3) as u64;
 
+/// Format of the stencil attachment the geometry pipelines are compiled with,
+/// see [`Renderer2D::stencil_view`]
+pub const STENCIL_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Stencil8;
+
+/// Work done by the renderer since the last [`Renderer2D::prepare`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RendererStats {
+    pub draw_calls: usize,
+    pub texture_binds: usize,
+    pub vertex_buffer_size: u64,
+    pub index_buffer_size: u64,
+}
+
+/// Replaces or overlays the meshes to show how they are tessellated, see
+/// [`Renderer2D::set_debug_view`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum DebugView {
+    #[default]
+    Off,
+    /// Triangle edges drawn over the meshes, in one color per draw call
+    Wireframe,
+    /// Every triangle adds to a heatmap, brighter pixels are covered more often.
+    /// The target is cleared to black
+    Overdraw,
+}
+
+/// Wireframe colors cycled through by draw call
+const WIREFRAME_COLORS: [Color; 4] = [Color::LIGHT_GREEN, Color::ORANGE, Color::KHAKI, Color::BLUE];
+/// What one triangle adds in [`DebugView::Overdraw`]
+const OVERDRAW_COLOR: wgpu::Color = wgpu::Color {
+    r: 0.2,
+    g: 0.08,
+    b: 0.04,
+    a: 1.0,
+};
+
 #[derive(Debug)]
 pub struct Renderable {
     pub clip_rect: Rect<f32>,
+    /// Convex quads the mesh is also clipped to with the stencil buffer, for clips the
+    /// scissor rect cant express
+    pub clip_quads: Vec<[Vec2<f32>; 4]>,
     pub mesh: Mesh,
 }
 
@@ -106,13 +153,17 @@ impl GlobalUniformsBuffer {
 #[derive(Debug)]
 pub struct RendererTexture {
     pub bindgroup: wgpu::BindGroup,
+    /// kept to bind the texture with other samplers, see [`crate::Mesh::sampler`]
+    pub view: GpuTextureView,
     pub kind: TextureKind,
+    pub premultiplied: bool,
 }
 
 #[derive(Debug, Clone)]
 pub struct Renderer2DSpecs {
     pub width: u32,
     pub height: u32,
+    pub format: wgpu::TextureFormat,
     pub msaa_sample_count: u32,
 }
 
@@ -121,21 +172,44 @@ pub struct Renderer2D {
     gpu: GpuContext,
 
     size: Size<u32>,
+    scale_factor: f32,
 
     global_uniforms: GlobalUniformsBuffer,
 
     textures: ahash::AHashMap<TextureId, RendererTexture>,
+    /// bind groups of textures drawn with a sampler other than their own
+    sampled_bindgroups: AHashMap<(TextureId, TextureOptions), wgpu::BindGroup>,
 
-    scene_pipes: GeometryPipes,
+    geometry_pipes: GeometryPipeCache,
+    target: PipelineKey,
 
     vertex_buffer: BatchBuffer,
 
     index_buffer: BatchBuffer,
+    /// triangles of the clip quads, sliced by vertex per renderable
+    clip_buffer: BatchBuffer,
+    /// by sample count
+    stencils: AHashMap<u32, StencilTarget>,
+    /// draw calls and binds since the last prepare
+    draw_calls: usize,
+    texture_binds: usize,
+    debug_view: DebugView,
+    /// see [`Renderer2D::set_damage`]
+    damage: Option<Rect<f32>>,
+    /// the quad [`Renderer2D::clear_damage`] draws
+    damage_buffer: wgpu::Buffer,
 
     texture_bindgroup_layout: wgpu::BindGroupLayout,
+
+    blur: Option<BlurRenderer>,
+    /// by nesting depth
+    layers: AHashMap<usize, LayerTarget>,
 }
 
 impl Renderer2D {
+    /// The offscreen scene of frames with blurs, drawn onto the target when they are done
+    pub const BLUR_SCENE_TEXTURE: TextureId = TextureId::Internal(usize::MAX);
+
     pub fn new(gpu: GpuContext, specs: &Renderer2DSpecs) -> Self {
         let proj = Mat3::ortho(0.0, 0.0, specs.height as f32, specs.width as f32);
 
@@ -166,14 +240,15 @@ impl Renderer2D {
             }),
         );
 
-        let scene_pipe = GeometryPipes::new(
+        let geometry_pipes = GeometryPipeCache::new(
             &gpu,
-            specs.msaa_sample_count,
             &[
                 &global_uniforms.bing_group_layout,
                 &texture_bindgroup_layout,
             ],
         );
+        let target = PipelineKey::new(specs.format, specs.msaa_sample_count);
+        geometry_pipes.get_or_create(target);
 
         let vertex_buffer = BatchBuffer {
             buffer: gpu.create_vertex_buffer(INITIAL_VERTEX_BUFFER_SIZE),
@@ -187,18 +262,38 @@ impl Renderer2D {
             capacity: INITIAL_INDEX_BUFFER_SIZE,
         };
 
+        let clip_buffer_size = (std::mem::size_of::<Vertex>() * 6 * 16) as u64;
+        let clip_buffer = BatchBuffer {
+            buffer: gpu.create_vertex_buffer(clip_buffer_size),
+            slices: Vec::with_capacity(64),
+            capacity: clip_buffer_size,
+        };
+        let damage_buffer = gpu.create_vertex_buffer((std::mem::size_of::<Vertex>() * 6) as u64);
+
         Self {
             gpu,
             global_uniforms,
             textures: Default::default(),
-            scene_pipes: scene_pipe,
+            geometry_pipes,
+            target,
             vertex_buffer,
             index_buffer,
+            clip_buffer,
+            stencils: AHashMap::default(),
+            draw_calls: 0,
+            texture_binds: 0,
+            debug_view: DebugView::Off,
+            damage: None,
+            damage_buffer,
+            sampled_bindgroups: AHashMap::default(),
             texture_bindgroup_layout,
             size: Size {
                 width: specs.width,
                 height: specs.height,
             },
+            scale_factor: 1.0,
+            blur: None,
+            layers: Default::default(),
         }
     }
 
@@ -210,11 +305,136 @@ impl Renderer2D {
         &self.gpu
     }
 
-    pub fn resize(&mut self, width: u32, height: u32) {
-        let proj = Mat3::ortho(0.0, 0.0, height as f32, width as f32);
+    pub fn scale_factor(&self) -> f32 {
+        self.scale_factor
+    }
+
+    /// Sets the format and sample count of the target the next passes render to,
+    /// compiling pipelines for it if they were not warmed up
+    pub fn set_target(&mut self, format: wgpu::TextureFormat, sample_count: u32) {
+        self.target = PipelineKey::new(format, sample_count);
+    }
 
+    pub fn set_debug_view(&mut self, view: DebugView) {
+        self.debug_view = view;
+    }
+
+    pub fn debug_view(&self) -> DebugView {
+        self.debug_view
+    }
+
+    /// Restricts the next renders to `damage`, in logical pixels. `None` renders everywhere
+    pub fn set_damage(&mut self, damage: Option<Rect<f32>>) {
+        self.damage = damage;
+    }
+
+    /// Fills the damaged region with `color`, ignoring what is under it
+    pub fn clear_damage(&mut self, render_pass: &mut wgpu::RenderPass<'_>, color: Color) {
+        let Some(damage) = &self.damage else {
+            return;
+        };
+
+        let [a, b, c, d] = [
+            damage.top_left(),
+            damage.top_right(),
+            damage.bottom_right(),
+            damage.bottom_left(),
+        ]
+        .map(|p| Vertex::new(p, Color::WHITE, (0.0, 0.0)));
+        self.gpu.queue.write_buffer(
+            &self.damage_buffer,
+            0,
+            bytemuck::cast_slice(&[a, b, c, a, c, d]),
+        );
+        self.global_uniforms.sync(&self.gpu);
+
+        let scissor = ScissorRect::new(damage, self.scale_factor, &self.size);
+        // nothing is sampled but the layout has a texture
+        let Some(texture) = self
+            .textures
+            .values()
+            .next()
+            .filter(|_| !scissor.is_empty())
+        else {
+            return;
+        };
+        let pipes = self.geometry_pipes.get_or_create(self.target);
+        render_pass.set_bind_group(0, &self.global_uniforms.bind_group, &[]);
+        render_pass.set_bind_group(1, &texture.bindgroup, &[]);
+        render_pass.set_scissor_rect(scissor.x, scissor.y, scissor.width, scissor.height);
+        render_pass.set_pipeline(&pipes.clear);
+        render_pass.set_blend_constant(color.into());
+        render_pass.set_vertex_buffer(0, self.damage_buffer.slice(..));
+        render_pass.draw(0..6, 0..1);
+        self.draw_calls += 1;
+        render_pass.set_scissor_rect(0, 0, self.size.width, self.size.height);
+    }
+
+    /// Whether pipelines for the given target are compiled
+    pub fn has_pipelines(&self, format: wgpu::TextureFormat, sample_count: u32) -> bool {
+        self.geometry_pipes
+            .contains(&PipelineKey::new(format, sample_count))
+    }
+
+    /// Compiles pipelines for every combination of `formats` and `sample_counts`,
+    /// so the first paint to such a target doesnt stall
+    pub fn warm_up(&self, formats: &[wgpu::TextureFormat], sample_counts: &[u32]) {
+        for key in self.geometry_pipes.supported_keys(formats, sample_counts) {
+            self.geometry_pipes.get_or_create(key);
+        }
+    }
+
+    /// Same as [`Renderer2D::warm_up`] but compiles on a background thread
+    pub fn warm_up_async(
+        &self,
+        formats: &[wgpu::TextureFormat],
+        sample_counts: &[u32],
+    ) -> std::thread::JoinHandle<()> {
+        let cache = self.geometry_pipes.clone();
+        let keys = cache.supported_keys(formats, sample_counts);
+
+        std::thread::spawn(move || {
+            for key in keys {
+                cache.get_or_create(key);
+            }
+        })
+    }
+
+    /// Stencil buffer to attach to the passes rendering renderables, matching the renderer
+    /// size. Clear it when the pass begins, clipped renderables leave it cleared
+    pub fn stencil_view(&mut self, sample_count: u32) -> wgpu::TextureView {
+        let sample_count = sample_count.max(1);
+        if let Some(stencil) = self
+            .stencils
+            .get(&sample_count)
+            .filter(|stencil| stencil.size == self.size)
+        {
+            return stencil.view.clone();
+        }
+
+        let stencil = StencilTarget::new(&self.gpu, self.size, sample_count);
+        let view = stencil.view.clone();
+        self.stencils.insert(sample_count, stencil);
+        view
+    }
+
+    /// Resizes the render target, `width` and `height` are in physical pixels
+    pub fn resize(&mut self, width: u32, height: u32) {
         self.size.width = width;
         self.size.height = height;
+        self.update_projection();
+    }
+
+    /// Sets the number of physical pixels per logical pixel. Geometry is in logical pixels
+    pub fn set_scale_factor(&mut self, scale_factor: f32) {
+        self.scale_factor = scale_factor;
+        self.update_projection();
+    }
+
+    fn update_projection(&mut self) {
+        let width = self.size.width as f32 / self.scale_factor;
+        let height = self.size.height as f32 / self.scale_factor;
+        let proj = Mat3::ortho(0.0, 0.0, height, width);
 
         self.global_uniforms.map(|data| {
             data.proj = proj.into();
@@ -276,15 +496,63 @@ impl Renderer2D {
             view,
             options,
         );
-        self.textures.insert(
+        self.insert_texture(
             texture_id.clone(),
             RendererTexture {
                 bindgroup,
+                view: view.clone(),
                 kind: options.kind,
+                premultiplied: false,
+            },
+        );
+    }
+
+    /// Like [`Renderer2D::set_texture`] for textures holding premultiplied colors, such as
+    /// layers rendered by skie
+    pub fn set_premultiplied_texture(
+        &mut self,
+        texture_id: &TextureId,
+        view: &GpuTextureView,
+        options: &TextureOptions,
+    ) {
+        let bindgroup = Self::create_texture_bind_group(
+            &self.gpu,
+            &self.texture_bindgroup_layout,
+            view,
+            options,
+        );
+        self.insert_texture(
+            texture_id.clone(),
+            RendererTexture {
+                bindgroup,
+                view: view.clone(),
+                kind: TextureKind::Color,
+                premultiplied: true,
             },
         );
     }
 
+    fn insert_texture(&mut self, texture_id: TextureId, texture: RendererTexture) {
+        // bind groups made with the old view
+        self.sampled_bindgroups
+            .retain(|(id, _), _| *id != texture_id);
+        self.textures.insert(texture_id, texture);
+    }
+
+    /// Whether the texture holds premultiplied colors, see [`Renderer2D::set_premultiplied_texture`]
+    pub fn is_premultiplied(&self, texture_id: &TextureId) -> bool {
+        self.textures
+            .get(texture_id)
+            .is_some_and(|texture| texture.premultiplied)
+    }
+
+    /// Forgets the texture, draws using it are skipped. Returns whether it was known
+    pub fn remove_texture(&mut self, texture_id: &TextureId) -> bool {
+        self.sampled_bindgroups
+            .retain(|(id, _), _| id != texture_id);
+        self.textures.remove(texture_id).is_some()
+    }
+
     pub fn set_texture_from_atlas<Key>(
         &mut self,
         atlas: &TextureAtlas<Key>,
@@ -293,28 +561,31 @@ impl Renderer2D {
     ) where
         Key: AtlasKeySource,
     {
-        let texture_in_atlas = atlas
-            .get_texture_for_key::<Option<(TextureId, TextureKind, wgpu::BindGroup)>>(
-                texture_id,
-                |texture| {
-                    let atlas_tex_id = TextureId::Atlas(texture.id());
-                    let kind = texture.kind();
-                    if self.textures.contains_key(&atlas_tex_id) {
-                        None
-                    } else {
-                        Some((
-                            atlas_tex_id,
-                            kind,
-                            Self::create_texture_bind_group(
+        let texture_in_atlas = atlas.get_texture_for_key::<Option<(TextureId, RendererTexture)>>(
+            texture_id,
+            |texture| {
+                let atlas_tex_id = TextureId::Atlas(texture.id());
+                let kind = texture.kind();
+                if self.textures.contains_key(&atlas_tex_id) {
+                    None
+                } else {
+                    Some((
+                        atlas_tex_id,
+                        RendererTexture {
+                            bindgroup: Self::create_texture_bind_group(
                                 &self.gpu,
                                 &self.texture_bindgroup_layout,
                                 texture.view(),
                                 options,
                             ),
-                        ))
-                    }
-                },
-            );
+                            view: texture.view().clone(),
+                            kind,
+                            premultiplied: false,
+                        },
+                    ))
+                }
+            },
+        );
 
         if texture_in_atlas.is_none() {
             log::error!(
@@ -326,17 +597,32 @@ impl Renderer2D {
 
         let need_to_add = texture_in_atlas.unwrap();
 
-        if let Some((atlas_tex_id, kind, bindgroup)) = need_to_add {
-            self.textures
-                .insert(atlas_tex_id, RendererTexture { bindgroup, kind });
+        if let Some((atlas_tex_id, texture)) = need_to_add {
+            self.insert_texture(atlas_tex_id, texture);
+        }
+    }
+
+    /// Counters of the last prepared frame, see [`RendererStats`]
+    pub fn stats(&self) -> RendererStats {
+        RendererStats {
+            draw_calls: self.draw_calls,
+            texture_binds: self.texture_binds,
+            vertex_buffer_size: self.vertex_buffer.capacity,
+            index_buffer_size: self.index_buffer.capacity,
         }
     }
 
     pub fn prepare(&mut self, renderables: &[Renderable]) {
+        self.draw_calls = 0;
+        self.texture_binds = 0;
         if renderables.is_empty() {
             return;
         }
 
+        for renderable in renderables {
+            self.prepare_sampler(&renderable.mesh);
+        }
+
         let (vertex_count, index_count): (usize, usize) =
             renderables.iter().fold((0, 0), |res, renderable| {
                 (
@@ -411,43 +697,251 @@ impl Renderer2D {
                 index_offset += size;
             }
         }
+
+        self.prepare_clip_quads(renderables);
+    }
+
+    /// Uploads two triangles for each clip quad
+    fn prepare_clip_quads(&mut self, renderables: &[Renderable]) {
+        let cb = &mut self.clip_buffer;
+        cb.slices.clear();
+
+        let mut vertices = Vec::new();
+        for renderable in renderables {
+            let start = vertices.len();
+            for quad in &renderable.clip_quads {
+                let [a, b, c, d] = quad.map(|p| Vertex::new(p, Color::WHITE, (0.0, 0.0)));
+                vertices.extend([a, b, c, a, c, d]);
+            }
+            cb.slices.push(start..vertices.len());
+        }
+
+        if vertices.is_empty() {
+            return;
+        }
+
+        let size = std::mem::size_of_val(vertices.as_slice()) as wgpu::BufferAddress;
+        if cb.capacity < size {
+            cb.capacity = (cb.capacity * 2).max(size);
+            cb.buffer = self.gpu.create_vertex_buffer(cb.capacity);
+        }
+        self.gpu
+            .queue
+            .write_buffer(&cb.buffer, 0, bytemuck::cast_slice(&vertices));
+    }
+    /// Creates the bind group for drawing the mesh texture with the mesh sampler
+    fn prepare_sampler(&mut self, mesh: &Mesh) {
+        let Some(sampler) = &mesh.sampler else {
+            return;
+        };
+        let key = (mesh.texture.clone(), sampler.clone());
+        if self.sampled_bindgroups.contains_key(&key) {
+            return;
+        }
+        let Some(texture) = self.textures.get(&mesh.texture) else {
+            return;
+        };
+
+        let bindgroup = Self::create_texture_bind_group(
+            &self.gpu,
+            &self.texture_bindgroup_layout,
+            &texture.view,
+            sampler,
+        );
+        self.sampled_bindgroups.insert(key, bindgroup);
+    }
+
+    /// Offscreen texture to draw a frame with blurs into, bound as
+    /// [`Renderer2D::BLUR_SCENE_TEXTURE`]. It matches the renderer size
+    pub fn blur_scene(&mut self, format: wgpu::TextureFormat) -> wgpu::TextureView {
+        let blur = match &mut self.blur {
+            Some(blur) if blur.format() == format => blur,
+            blur => blur.insert(BlurRenderer::new(&self.gpu, format)),
+        };
+
+        let created = blur.ensure_scene(&self.gpu, self.size);
+        let view = blur.scene().expect("blur scene exists").clone();
+
+        if created {
+            let bindgroup = Self::create_texture_bind_group(
+                &self.gpu,
+                &self.texture_bindgroup_layout,
+                &view,
+                &TextureOptions::default(),
+            );
+            self.insert_texture(
+                Self::BLUR_SCENE_TEXTURE,
+                RendererTexture {
+                    bindgroup,
+                    view: view.clone(),
+                    kind: TextureKind::Color,
+                    premultiplied: false,
+                },
+            );
+        }
+
+        view
+    }
+
+    /// Blurs `rect` of the scene from [`Renderer2D::blur_scene`], `rect` and `sigma` are in
+    /// logical pixels
+    pub fn blur(&self, encoder: &mut wgpu::CommandEncoder, rect: &Rect<f32>, sigma: f32) {
+        if let Some(blur) = &self.blur {
+            let region = ScissorRect::new(rect, self.scale_factor, &self.size);
+            blur.blur(&self.gpu, encoder, &region, sigma * self.scale_factor);
+        }
+    }
+
+    /// Texture of the layer nested `depth` deep, see [`Renderer2D::layer_target`]
+    pub fn layer_texture(depth: usize) -> TextureId {
+        TextureId::Internal(usize::MAX - 1 - depth)
+    }
+
+    /// Texture of the layer cached in `slot`, see [`crate::Canvas::cache_layer`]
+    pub fn cached_layer_texture(slot: usize) -> TextureId {
+        TextureId::Internal(usize::MAX / 2 + slot)
     }
+
+    /// Offscreen target for the layer nested `depth` deep, matching the renderer size.
+    /// Returns the view to render to and its resolve target when multisampled. Once drawn the
+    /// layer can be composited with [`Renderer2D::layer_texture`], its colors are premultiplied
+    pub fn layer_target(
+        &mut self,
+        depth: usize,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> (wgpu::TextureView, Option<wgpu::TextureView>) {
+        let sample_count = sample_count.max(1);
+        if let Some(layer) = self
+            .layers
+            .get(&depth)
+            .filter(|layer| layer.matches(self.size, format, sample_count))
+        {
+            return layer.attachment();
+        }
+
+        let layer = LayerTarget::new(&self.gpu, self.size, format, sample_count);
+        let bindgroup = Self::create_texture_bind_group(
+            &self.gpu,
+            &self.texture_bindgroup_layout,
+            layer.resolved(),
+            &TextureOptions::default(),
+        );
+        self.insert_texture(
+            Self::layer_texture(depth),
+            RendererTexture {
+                bindgroup,
+                view: layer.resolved().clone(),
+                kind: TextureKind::Color,
+                premultiplied: true,
+            },
+        );
+
+        let attachment = layer.attachment();
+        self.layers.insert(depth, layer);
+        attachment
+    }
+
     pub fn create_command_encoder(&self) -> CommandEncoder {
         self.gpu
             .create_command_encoder(Some("skie_command_encoder"))
     }
 
+    /// Renders the renderables given to [`Renderer2D::prepare`]. The pass needs a
+    /// [`Renderer2D::stencil_view`] attached with [`STENCIL_FORMAT`]
     pub fn render(&mut self, render_pass: &mut wgpu::RenderPass<'_>, renderables: &[Renderable]) {
-        if renderables.is_empty() {
+        self.render_range(render_pass, renderables, 0..renderables.len());
+    }
+
+    /// Renders `renderables[range]`, where `renderables` is the slice given to [`Renderer2D::prepare`]
+    pub fn render_range(
+        &mut self,
+        render_pass: &mut wgpu::RenderPass<'_>,
+        renderables: &[Renderable],
+        range: Range<usize>,
+    ) {
+        if range.is_empty() {
             return;
         }
 
         self.global_uniforms.sync(&self.gpu);
 
-        let mut vb_slices = self.vertex_buffer.slices.iter();
-        let mut ib_slices = self.index_buffer.slices.iter();
+        let mut vb_slices = self.vertex_buffer.slices[range.clone()].iter();
+        let mut ib_slices = self.index_buffer.slices[range.clone()].iter();
+        let mut clip_slices = self.clip_buffer.slices[range.clone()].iter();
+        let renderables = &renderables[range.clone()];
 
         render_pass.set_bind_group(0, &self.global_uniforms.bind_group, &[]);
 
+        let pipes = self.geometry_pipes.get_or_create(self.target);
+
         log::trace!("Rendering {} renderables", renderables.len());
 
-        for renderable in renderables {
-            let scissor = ScissorRect::new(&renderable.clip_rect, &self.size);
+        for (i, renderable) in renderables.iter().enumerate() {
+            let clip_rect = match &self.damage {
+                Some(damage) => renderable.clip_rect.intersect(damage),
+                None => renderable.clip_rect.clone(),
+            };
+            let scissor = ScissorRect::new(&clip_rect, self.scale_factor, &self.size);
+            let clip = clip_slices.next().expect("No next clip_slice").clone();
+            if scissor.is_empty() {
+                let _ = vb_slices.next().expect("No next vb_slice");
+                let _ = ib_slices.next().expect("No next ib_slice");
+                continue;
+            }
 
             render_pass.set_scissor_rect(scissor.x, scissor.y, scissor.width, scissor.height);
+            let clip_quads = renderable.clip_quads.len() as u32;
 
             let texture = &renderable.mesh.texture;
-            if let Some(RendererTexture { bindgroup, kind }) = self.textures.get(texture) {
+            if let Some(RendererTexture {
+                bindgroup,
+                kind,
+                premultiplied,
+                ..
+            }) = self.textures.get(texture)
+            {
+                let bindgroup = renderable
+                    .mesh
+                    .sampler
+                    .as_ref()
+                    .and_then(|sampler| {
+                        self.sampled_bindgroups
+                            .get(&(texture.clone(), sampler.clone()))
+                    })
+                    .unwrap_or(bindgroup);
+
                 let vb_slice = vb_slices.next().expect("No next vb_slice");
                 let ib_slice = ib_slices.next().expect("No next ib_slice");
 
-                if kind.is_color() {
-                    render_pass.set_pipeline(&self.scene_pipes.polychrome);
+                render_pass.set_bind_group(1, bindgroup, &[]);
+                self.texture_binds += 1;
+
+                // each quad increments the stencil where all the previous ones did, the mesh
+                // is drawn where every quad did
+                let clip = clip.start as u32..clip.end as u32;
+                if clip_quads > 0 {
+                    render_pass.set_pipeline(&pipes.clip_push);
+                    render_pass.set_vertex_buffer(0, self.clip_buffer.buffer.slice(..));
+                    for i in 0..clip_quads {
+                        render_pass.set_stencil_reference(i);
+                        render_pass.draw(clip.start + i * 6..clip.start + i * 6 + 6, 0..1);
+                    }
+                    self.draw_calls += clip_quads as usize;
+                }
+                render_pass.set_stencil_reference(clip_quads);
+
+                if self.debug_view == DebugView::Overdraw {
+                    render_pass.set_pipeline(&pipes.overdraw);
+                    render_pass.set_blend_constant(OVERDRAW_COLOR);
+                } else if *premultiplied {
+                    render_pass.set_pipeline(&pipes.premultiplied);
+                } else if kind.is_color() {
+                    render_pass.set_pipeline(&pipes.polychrome);
                 } else {
-                    render_pass.set_pipeline(&self.scene_pipes.monochrome);
+                    render_pass.set_pipeline(&pipes.monochrome);
                 }
 
-                render_pass.set_bind_group(1, bindgroup, &[]);
                 render_pass.set_vertex_buffer(
                     0,
                     self.vertex_buffer
@@ -461,6 +955,23 @@ impl Renderer2D {
                     wgpu::IndexFormat::Uint32,
                 );
                 render_pass.draw_indexed(0..renderable.mesh.indices.len() as u32, 0, 0..1);
+                self.draw_calls += 1;
+
+                if self.debug_view == DebugView::Wireframe {
+                    let color = WIREFRAME_COLORS[(range.start + i) % WIREFRAME_COLORS.len()];
+                    render_pass.set_pipeline(&pipes.wireframe);
+                    render_pass.set_blend_constant(color.into());
+                    render_pass.draw_indexed(0..renderable.mesh.indices.len() as u32, 0, 0..1);
+                    self.draw_calls += 1;
+                }
+
+                // the first quad covers every stencil value the others wrote
+                if clip_quads > 0 {
+                    render_pass.set_pipeline(&pipes.clip_clear);
+                    render_pass.set_vertex_buffer(0, self.clip_buffer.buffer.slice(..));
+                    render_pass.draw(clip.start..clip.start + 6, 0..1);
+                    self.draw_calls += 1;
+                }
             } else {
                 let _ = vb_slices.next().expect("No next vb_slice");
                 let _ = ib_slices.next().expect("No next ib_slice");
@@ -474,6 +985,7 @@ impl Renderer2D {
     pub fn end(&mut self) {
         self.vertex_buffer.slices.clear();
         self.index_buffer.slices.clear();
+        self.clip_buffer.slices.clear();
     }
 }
 
@@ -498,6 +1010,49 @@ pub(crate) fn create_skie_renderer(
     renderer
 }
 
+#[derive(Debug)]
+struct StencilTarget {
+    size: Size<u32>,
+    view: wgpu::TextureView,
+}
+
+impl StencilTarget {
+    fn new(gpu: &GpuContext, size: Size<u32>, sample_count: u32) -> Self {
+        let view = gpu
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some("skie_stencil"),
+                size: wgpu::Extent3d {
+                    width: size.width.max(1),
+                    height: size.height.max(1),
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count,
+                dimension: wgpu::TextureDimension::D2,
+                format: STENCIL_FORMAT,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[],
+            })
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        Self { size, view }
+    }
+}
+
+/// Attaches a view from [`Renderer2D::stencil_view`], cleared when the pass begins
+pub(crate) fn stencil_attachment(
+    view: &wgpu::TextureView,
+) -> wgpu::RenderPassDepthStencilAttachment<'_> {
+    wgpu::RenderPassDepthStencilAttachment {
+        view,
+        depth_ops: None,
+        stencil_ops: Some(wgpu::Operations {
+            load: wgpu::LoadOp::Clear(0),
+            store: wgpu::StoreOp::Discard,
+        }),
+    }
+}
+
 #[derive(Debug)]
 struct BatchBuffer {
     buffer: wgpu::Buffer,
@@ -505,38 +1060,124 @@ struct BatchBuffer {
     capacity: wgpu::BufferAddress,
 }
 
-#[derive(Debug)]
+/// Render target properties the geometry pipelines are compiled for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PipelineKey {
+    pub format: wgpu::TextureFormat,
+    pub sample_count: u32,
+}
+
+impl PipelineKey {
+    pub fn new(format: wgpu::TextureFormat, sample_count: u32) -> Self {
+        Self {
+            format,
+            sample_count: sample_count.max(1),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 struct GeometryPipes {
     polychrome: wgpu::RenderPipeline,
     monochrome: wgpu::RenderPipeline,
+    /// for textures holding premultiplied colors, like layers drawn with the other pipelines
+    premultiplied: wgpu::RenderPipeline,
+    /// increments the stencil of a clip quad without drawing color
+    clip_push: wgpu::RenderPipeline,
+    /// zeroes the stencil of a clip quad
+    clip_clear: wgpu::RenderPipeline,
+    /// see [`DebugView`]
+    wireframe: wgpu::RenderPipeline,
+    overdraw: wgpu::RenderPipeline,
+    /// replaces the color with the blend constant, see [`Renderer2D::clear_damage`]
+    clear: wgpu::RenderPipeline,
 }
 
-impl GeometryPipes {
-    pub fn new(
-        gpu: &GpuContext,
-        msaa_sample_count: u32,
-        bind_group_layouts: &[&wgpu::BindGroupLayout],
-    ) -> Self {
+/// Geometry pipelines for every [`PipelineKey`] used so far, shared with warm up threads
+#[derive(Debug, Clone)]
+struct GeometryPipeCache {
+    gpu: GpuContext,
+    shader: wgpu::ShaderModule,
+    layout: wgpu::PipelineLayout,
+    pipes: Arc<RwLock<AHashMap<PipelineKey, GeometryPipes>>>,
+}
+
+impl GeometryPipeCache {
+    fn new(gpu: &GpuContext, bind_group_layouts: &[&wgpu::BindGroupLayout]) -> Self {
         let shader =
-            gpu.create_shader_labeled(include_str!("./resources/shader.wgsl"), "Scene Shader");
+            gpu.create_shader_labeled(include_str!("./resources/shader.wgsl"), "Geometry Shader");
 
         let layout = gpu.device.create_pipeline_layout(
             &(wgpu::PipelineLayoutDescriptor {
-                label: Some("Scenepipe layout"),
+                label: Some("Geometry pipeline layout"),
                 bind_group_layouts,
                 push_constant_ranges: &[],
             }),
         );
 
+        Self {
+            gpu: gpu.clone(),
+            shader,
+            layout,
+            pipes: Default::default(),
+        }
+    }
+
+    fn contains(&self, key: &PipelineKey) -> bool {
+        self.pipes.read().contains_key(key)
+    }
+
+    fn get_or_create(&self, key: PipelineKey) -> GeometryPipes {
+        if let Some(pipes) = self.pipes.read().get(&key) {
+            return pipes.clone();
+        }
+
+        let pipes = GeometryPipes::new(&self.gpu, &self.layout, &self.shader, key);
+        self.pipes.write().entry(key).or_insert(pipes).clone()
+    }
+
+    /// Keys the adapter can render to, unsupported sample counts are skipped with a warning
+    fn supported_keys(
+        &self,
+        formats: &[wgpu::TextureFormat],
+        sample_counts: &[u32],
+    ) -> Vec<PipelineKey> {
+        let mut keys = Vec::with_capacity(formats.len() * sample_counts.len());
+
+        for &format in formats {
+            let flags = self.gpu.adapter.get_texture_format_features(format).flags;
+            for &sample_count in sample_counts {
+                let key = PipelineKey::new(format, sample_count);
+                if key.sample_count > 1 && !flags.sample_count_supported(key.sample_count) {
+                    log::warn!("warm_up: {:?} is not supported by the adapter", key);
+                    continue;
+                }
+                keys.push(key);
+            }
+        }
+
+        keys
+    }
+}
+
+impl GeometryPipes {
+    fn new(
+        gpu: &GpuContext,
+        layout: &wgpu::PipelineLayout,
+        shader: &wgpu::ShaderModule,
+        key: PipelineKey,
+    ) -> Self {
+        log::trace!("Creating geometry pipelines for {:?}", key);
+
         let vbo_layout = wgpu::VertexBufferLayout {
             array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
             step_mode: wgpu::VertexStepMode::Vertex,
             attributes: &wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Float32x4],
         };
 
-        let blend = Some(wgpu::BlendState {
+        let blend = |color_src_factor| wgpu::BlendState {
             color: wgpu::BlendComponent {
-                src_factor: wgpu::BlendFactor::SrcAlpha,
+                src_factor: color_src_factor,
                 dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
                 operation: wgpu::BlendOperation::Add,
             },
@@ -545,91 +1186,158 @@ impl GeometryPipes {
                 dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
                 operation: wgpu::BlendOperation::Add,
             },
-        });
+        };
 
-        let polychrome = gpu.device.create_render_pipeline(
-            &(wgpu::RenderPipelineDescriptor {
-                label: Some("Scene pipeline Poly"),
-                layout: Some(&layout),
-                vertex: wgpu::VertexState {
-                    module: &shader,
-                    entry_point: Some("vs"),
-                    buffers: &[vbo_layout.clone()],
-                    compilation_options: wgpu::PipelineCompilationOptions::default(),
-                },
-                fragment: Some(wgpu::FragmentState {
-                    module: &shader,
-                    entry_point: Some("fs_poly"),
-                    compilation_options: wgpu::PipelineCompilationOptions::default(),
-                    targets: &[Some(wgpu::ColorTargetState {
-                        format: wgpu::TextureFormat::Rgba8Unorm,
-                        blend,
-                        write_mask: wgpu::ColorWrites::ALL,
-                    })],
-                }),
-                primitive: wgpu::PrimitiveState {
-                    topology: wgpu::PrimitiveTopology::TriangleList,
-                    strip_index_format: None,
-                    front_face: wgpu::FrontFace::default(),
-                    cull_mode: None,
-                    polygon_mode: wgpu::PolygonMode::Fill,
-                    unclipped_depth: false,
-                    conservative: false,
-                },
-                depth_stencil: None,
-                multisample: wgpu::MultisampleState {
-                    count: msaa_sample_count,
-                    mask: !0,
-                    alpha_to_coverage_enabled: false,
-                },
-                multiview: None,
-                cache: None,
-            }),
-        );
+        let stencil = |compare, pass_op| wgpu::StencilFaceState {
+            compare,
+            fail_op: wgpu::StencilOperation::Keep,
+            depth_fail_op: wgpu::StencilOperation::Keep,
+            pass_op,
+        };
+        // meshes are drawn where the stencil equals the number of clip quads
+        let masked = stencil(wgpu::CompareFunction::Equal, wgpu::StencilOperation::Keep);
 
-        let monochrome = gpu.device.create_render_pipeline(
-            &(wgpu::RenderPipelineDescriptor {
-                label: Some("Scene pipeline Mono"),
-                layout: Some(&layout),
-                vertex: wgpu::VertexState {
-                    module: &shader,
-                    entry_point: Some("vs"),
-                    buffers: &[vbo_layout],
-                    compilation_options: wgpu::PipelineCompilationOptions::default(),
-                },
-                fragment: Some(wgpu::FragmentState {
-                    module: &shader,
-                    entry_point: Some("fs_mono"),
-                    compilation_options: wgpu::PipelineCompilationOptions::default(),
-                    targets: &[Some(wgpu::ColorTargetState {
-                        format: wgpu::TextureFormat::Rgba8Unorm,
-                        blend,
-                        write_mask: wgpu::ColorWrites::ALL,
-                    })],
+        let constant = |dst_factor| wgpu::BlendState {
+            color: wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::Constant,
+                dst_factor,
+                operation: wgpu::BlendOperation::Add,
+            },
+            alpha: wgpu::BlendComponent::OVER,
+        };
+
+        let create = |label, entry_point, blend, face, write_mask, polygon_mode| {
+            gpu.device.create_render_pipeline(
+                &(wgpu::RenderPipelineDescriptor {
+                    label: Some(label),
+                    layout: Some(layout),
+                    vertex: wgpu::VertexState {
+                        module: shader,
+                        entry_point: Some("vs"),
+                        buffers: std::slice::from_ref(&vbo_layout),
+                        compilation_options: wgpu::PipelineCompilationOptions::default(),
+                    },
+                    fragment: Some(wgpu::FragmentState {
+                        module: shader,
+                        entry_point: Some(entry_point),
+                        compilation_options: wgpu::PipelineCompilationOptions::default(),
+                        targets: &[Some(wgpu::ColorTargetState {
+                            format: key.format,
+                            blend: Some(blend),
+                            write_mask,
+                        })],
+                    }),
+                    primitive: wgpu::PrimitiveState {
+                        topology: wgpu::PrimitiveTopology::TriangleList,
+                        strip_index_format: None,
+                        front_face: wgpu::FrontFace::default(),
+                        cull_mode: None,
+                        polygon_mode,
+                        unclipped_depth: false,
+                        conservative: false,
+                    },
+                    depth_stencil: Some(wgpu::DepthStencilState {
+                        format: STENCIL_FORMAT,
+                        depth_write_enabled: false,
+                        depth_compare: wgpu::CompareFunction::Always,
+                        stencil: wgpu::StencilState {
+                            front: face,
+                            back: face,
+                            read_mask: !0,
+                            write_mask: !0,
+                        },
+                        bias: wgpu::DepthBiasState::default(),
+                    }),
+                    multisample: wgpu::MultisampleState {
+                        count: key.sample_count,
+                        mask: !0,
+                        alpha_to_coverage_enabled: false,
+                    },
+                    multiview: None,
+                    cache: None,
                 }),
-                primitive: wgpu::PrimitiveState {
-                    topology: wgpu::PrimitiveTopology::TriangleList,
-                    strip_index_format: None,
-                    front_face: wgpu::FrontFace::default(),
-                    cull_mode: None,
-                    polygon_mode: wgpu::PolygonMode::Fill,
-                    unclipped_depth: false,
-                    conservative: false,
-                },
-                depth_stencil: None,
-                multisample: wgpu::MultisampleState {
-                    count: msaa_sample_count,
-                    mask: !0,
-                    alpha_to_coverage_enabled: false,
-                },
-                multiview: None,
-                cache: None,
-            }),
-        );
+            )
+        };
 
         Self {
-            polychrome,
-            monochrome,
+            polychrome: create(
+                "Geometry pipeline Poly",
+                "fs_poly",
+                blend(wgpu::BlendFactor::SrcAlpha),
+                masked,
+                wgpu::ColorWrites::ALL,
+                wgpu::PolygonMode::Fill,
+            ),
+            monochrome: create(
+                "Geometry pipeline Mono",
+                "fs_mono",
+                blend(wgpu::BlendFactor::SrcAlpha),
+                masked,
+                wgpu::ColorWrites::ALL,
+                wgpu::PolygonMode::Fill,
+            ),
+            premultiplied: create(
+                "Geometry pipeline Premultiplied",
+                "fs_poly",
+                blend(wgpu::BlendFactor::One),
+                masked,
+                wgpu::ColorWrites::ALL,
+                wgpu::PolygonMode::Fill,
+            ),
+            clip_push: create(
+                "Geometry pipeline Clip push",
+                "fs_poly",
+                blend(wgpu::BlendFactor::SrcAlpha),
+                stencil(
+                    wgpu::CompareFunction::Equal,
+                    wgpu::StencilOperation::IncrementClamp,
+                ),
+                wgpu::ColorWrites::empty(),
+                wgpu::PolygonMode::Fill,
+            ),
+            clip_clear: create(
+                "Geometry pipeline Clip clear",
+                "fs_poly",
+                blend(wgpu::BlendFactor::SrcAlpha),
+                stencil(wgpu::CompareFunction::Always, wgpu::StencilOperation::Zero),
+                wgpu::ColorWrites::empty(),
+                wgpu::PolygonMode::Fill,
+            ),
+            wireframe: create(
+                "Geometry pipeline Wireframe",
+                "fs_debug",
+                constant(wgpu::BlendFactor::Zero),
+                masked,
+                wgpu::ColorWrites::ALL,
+                wgpu::PolygonMode::Line,
+            ),
+            overdraw: create(
+                "Geometry pipeline Overdraw",
+                "fs_debug",
+                constant(wgpu::BlendFactor::One),
+                masked,
+                wgpu::ColorWrites::ALL,
+                wgpu::PolygonMode::Fill,
+            ),
+            clear: create(
+                "Geometry pipeline Clear",
+                "fs_debug",
+                wgpu::BlendState {
+                    color: wgpu::BlendComponent {
+                        src_factor: wgpu::BlendFactor::Constant,
+                        dst_factor: wgpu::BlendFactor::Zero,
+                        operation: wgpu::BlendOperation::Add,
+                    },
+                    alpha: wgpu::BlendComponent {
+                        src_factor: wgpu::BlendFactor::Constant,
+                        dst_factor: wgpu::BlendFactor::Zero,
+                        operation: wgpu::BlendOperation::Add,
+                    },
+                },
+                stencil(wgpu::CompareFunction::Always, wgpu::StencilOperation::Keep),
+                wgpu::ColorWrites::ALL,
+                wgpu::PolygonMode::Fill,
+            ),
         }
     }
 }
@@ -642,9 +1350,13 @@ struct ScissorRect {
 }
 
 impl ScissorRect {
-    fn new(clip_rect: &Rect<f32>, screen_size: &Size<u32>) -> Self {
-        let clip_min = clip_rect.min().round().map_cloned(|v| v as u32);
-        let clip_max = clip_rect.max().round().map_cloned(|v| v as u32);
+    fn new(clip_rect: &Rect<f32>, scale_factor: f32, screen_size: &Size<u32>) -> Self {
+        let clip_min = (clip_rect.min() * scale_factor)
+            .round()
+            .map_cloned(|v| v as u32);
+        let clip_max = (clip_rect.max() * scale_factor)
+            .round()
+            .map_cloned(|v| v as u32);
 
         let clip_min_x = clip_min.x.clamp(0, screen_size.width);
         let clip_min_y = clip_min.y.clamp(0, screen_size.height);
@@ -658,4 +1370,8 @@ impl ScissorRect {
             height: clip_max_y - clip_min_y,
         }
     }
+
+    fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
 }
@@ -0,0 +1,288 @@
+use wgpu::util::DeviceExt;
+
+use crate::{GpuContext, Size};
+
+use super::ScissorRect;
+
+/// Kernels are cut at three standard deviations, and never wider than this
+const MAX_BLUR_RADIUS: f32 = 96.0;
+
+#[derive(Debug, Clone, Copy, bytemuck::Zeroable, bytemuck::Pod)]
+#[repr(C)]
+struct BlurParams {
+    step: [f32; 2],
+    sigma: f32,
+    radius: f32,
+}
+
+/// Separable gaussian blur over an offscreen scene texture
+#[derive(Debug)]
+pub(super) struct BlurRenderer {
+    format: wgpu::TextureFormat,
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    targets: Option<BlurTargets>,
+}
+
+#[derive(Debug)]
+struct BlurTargets {
+    size: Size<u32>,
+    scene: wgpu::TextureView,
+    /// holds the horizontal pass
+    scratch: wgpu::TextureView,
+}
+
+impl BlurRenderer {
+    pub fn new(gpu: &GpuContext, format: wgpu::TextureFormat) -> Self {
+        let shader =
+            gpu.create_shader_labeled(include_str!("../resources/blur.wgsl"), "Blur Shader");
+
+        let bind_group_layout = gpu.device.create_bind_group_layout(
+            &(wgpu::BindGroupLayoutDescriptor {
+                label: Some("Blur bind group layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
+            }),
+        );
+
+        let layout = gpu.device.create_pipeline_layout(
+            &(wgpu::PipelineLayoutDescriptor {
+                label: Some("Blur pipeline layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            }),
+        );
+
+        let pipeline = gpu.device.create_render_pipeline(
+            &(wgpu::RenderPipelineDescriptor {
+                label: Some("Blur pipeline"),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("vs"),
+                    buffers: &[],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some("fs"),
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            }),
+        );
+
+        let sampler = gpu.device.create_sampler(
+            &(wgpu::SamplerDescriptor {
+                label: Some("Blur sampler"),
+                address_mode_u: wgpu::AddressMode::ClampToEdge,
+                address_mode_v: wgpu::AddressMode::ClampToEdge,
+                mag_filter: wgpu::FilterMode::Linear,
+                min_filter: wgpu::FilterMode::Linear,
+                ..Default::default()
+            }),
+        );
+
+        Self {
+            format,
+            pipeline,
+            bind_group_layout,
+            sampler,
+            targets: None,
+        }
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+
+    /// The texture frames with blurs are drawn into, recreated when `size` changes.
+    /// Returns whether it was recreated
+    pub fn ensure_scene(&mut self, gpu: &GpuContext, size: Size<u32>) -> bool {
+        if self
+            .targets
+            .as_ref()
+            .is_some_and(|targets| targets.size == size)
+        {
+            return false;
+        }
+
+        let create = |label| {
+            gpu.create_texture(&wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d {
+                    width: size.width,
+                    height: size.height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: self.format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            })
+            .create_view(&wgpu::TextureViewDescriptor::default())
+        };
+
+        self.targets = Some(BlurTargets {
+            size,
+            scene: create("skie_blur_scene"),
+            scratch: create("skie_blur_scratch"),
+        });
+        true
+    }
+
+    pub fn scene(&self) -> Option<&wgpu::TextureView> {
+        self.targets.as_ref().map(|targets| &targets.scene)
+    }
+
+    /// Blurs `region` of the scene in place, `sigma` is in physical pixels
+    pub fn blur(
+        &self,
+        gpu: &GpuContext,
+        encoder: &mut wgpu::CommandEncoder,
+        region: &ScissorRect,
+        sigma: f32,
+    ) {
+        let Some(targets) = &self.targets else {
+            return;
+        };
+        if region.width == 0 || region.height == 0 || sigma <= 0.0 {
+            return;
+        }
+
+        let radius = (sigma * 3.0).ceil().min(MAX_BLUR_RADIUS);
+        let size = targets.size;
+
+        // the vertical pass reads `radius` rows above and below the region
+        let top = region.y.saturating_sub(radius as u32);
+        let bottom = (region.y + region.height + radius as u32).min(size.height);
+        let horizontal = ScissorRect {
+            x: region.x,
+            y: top,
+            width: region.width,
+            height: bottom - top,
+        };
+
+        self.pass(
+            gpu,
+            encoder,
+            (&targets.scene, &targets.scratch),
+            &horizontal,
+            BlurParams {
+                step: [1.0 / size.width as f32, 0.0],
+                sigma,
+                radius,
+            },
+        );
+        self.pass(
+            gpu,
+            encoder,
+            (&targets.scratch, &targets.scene),
+            region,
+            BlurParams {
+                step: [0.0, 1.0 / size.height as f32],
+                sigma,
+                radius,
+            },
+        );
+    }
+
+    fn pass(
+        &self,
+        gpu: &GpuContext,
+        encoder: &mut wgpu::CommandEncoder,
+        (src, dst): (&wgpu::TextureView, &wgpu::TextureView),
+        scissor: &ScissorRect,
+        params: BlurParams,
+    ) {
+        let params = gpu.device.create_buffer_init(
+            &(wgpu::util::BufferInitDescriptor {
+                label: Some("Blur params"),
+                contents: bytemuck::cast_slice(&[params]),
+                usage: wgpu::BufferUsages::UNIFORM,
+            }),
+        );
+
+        let bind_group = gpu.device.create_bind_group(
+            &(wgpu::BindGroupDescriptor {
+                label: Some("Blur bind group"),
+                layout: &self.bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(src),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&self.sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: params.as_entire_binding(),
+                    },
+                ],
+            }),
+        );
+
+        let mut pass = encoder.begin_render_pass(
+            &(wgpu::RenderPassDescriptor {
+                label: Some("Blur pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: dst,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            }),
+        );
+
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.set_scissor_rect(scissor.x, scissor.y, scissor.width, scissor.height);
+        pass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,69 @@
+use crate::{GpuContext, Size};
+
+/// Offscreen target of a layer, see [`super::Renderer2D::layer_target`]
+#[derive(Debug)]
+pub(super) struct LayerTarget {
+    size: Size<u32>,
+    format: wgpu::TextureFormat,
+    sample_count: u32,
+    /// drawn to when multisampled, resolved into `resolved`
+    multisampled: Option<wgpu::TextureView>,
+    resolved: wgpu::TextureView,
+}
+
+impl LayerTarget {
+    pub fn new(
+        gpu: &GpuContext,
+        size: Size<u32>,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> Self {
+        let create = |label, sample_count, usage| {
+            gpu.create_texture(&wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d {
+                    width: size.width,
+                    height: size.height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT | usage,
+                view_formats: &[],
+            })
+            .create_view(&wgpu::TextureViewDescriptor::default())
+        };
+
+        Self {
+            size,
+            format,
+            sample_count,
+            multisampled: (sample_count > 1).then(|| {
+                create(
+                    "skie_layer_multisampled",
+                    sample_count,
+                    wgpu::TextureUsages::empty(),
+                )
+            }),
+            resolved: create("skie_layer", 1, wgpu::TextureUsages::TEXTURE_BINDING),
+        }
+    }
+
+    pub fn matches(&self, size: Size<u32>, format: wgpu::TextureFormat, sample_count: u32) -> bool {
+        self.size == size && self.format == format && self.sample_count == sample_count
+    }
+
+    /// The view to render to and its resolve target
+    pub fn attachment(&self) -> (wgpu::TextureView, Option<wgpu::TextureView>) {
+        match &self.multisampled {
+            Some(multisampled) => (multisampled.clone(), Some(self.resolved.clone())),
+            None => (self.resolved.clone(), None),
+        }
+    }
+
+    pub fn resolved(&self) -> &wgpu::TextureView {
+        &self.resolved
+    }
+}
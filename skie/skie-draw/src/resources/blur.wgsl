@@ -0,0 +1,41 @@
+struct BlurParams {
+    // one texel along the blur direction, in uv units
+    step: vec2f,
+    sigma: f32,
+    radius: f32,
+};
+
+@group(0) @binding(0) var src: texture_2d<f32>;
+@group(0) @binding(1) var src_sampler: sampler;
+@group(0) @binding(2) var<uniform> params: BlurParams;
+
+struct VertexOut {
+    @builtin(position) position: vec4f,
+    @location(0) uv: vec2f,
+};
+
+// a single triangle covering the whole target, the scissor picks the blurred region
+@vertex fn vs(@builtin(vertex_index) index: u32) -> VertexOut {
+    let uv = vec2f(f32((index << 1u) & 2u), f32(index & 2u));
+
+    var out: VertexOut;
+    out.position = vec4f(uv * vec2f(2.0, -2.0) + vec2f(-1.0, 1.0), 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+@fragment fn fs(in: VertexOut) -> @location(0) vec4f {
+    let radius = i32(params.radius);
+    let two_sigma_sq = 2.0 * params.sigma * params.sigma;
+
+    var sum = vec4f(0.0);
+    var total = 0.0;
+    for (var i = -radius; i <= radius; i++) {
+        let x = f32(i);
+        let weight = exp(-x * x / two_sigma_sq);
+        sum += textureSampleLevel(src, src_sampler, in.uv + params.step * x, 0.0) * weight;
+        total += weight;
+    }
+
+    return sum / total;
+}
@@ -13,7 +13,9 @@ struct VertexIn {
 
 struct VertexOut {
     @builtin(position) position: vec4f,
-    @location(1) uv: vec2f,
+    // centroid keeps msaa edge pixels from sampling past the triangle's uvs,
+    // which shows as seams between the tiles of pattern fills
+    @location(1) @interpolate(perspective, centroid) uv: vec2f,
     @location(0) color: vec4f,
 };
 
@@ -41,5 +43,8 @@ fn fs_mono(in: VertexOut) -> @location(0) vec4f {
     return in.color * tex_color.r;
 }
 
-
-
+// debug views, the color comes from the blend constant
+@fragment
+fn fs_debug(in: VertexOut) -> @location(0) vec4f {
+    return vec4f(1.0);
+}
@@ -12,34 +12,56 @@ pub use path::*;
 
 pub use skie_math as math;
 
-pub use canvas::Canvas;
+pub use canvas::{Canvas, CanvasBuildError, CanvasState, ClearMode, HitId, ImageFit};
 pub use gpu::{GpuContext, GpuContextCreateError};
 
-pub use math::{mat3, vec2, Corners, Mat3, Rect, Size, Vec2};
+pub use math::{mat3, vec2, Corners, Easing, Mat3, Rect, Size, Timeline, Vec2, Viewport};
 pub use paint::color::{Color, Rgba};
 pub use paint::DrawList;
 pub use paint::{
-    circle, quad, AtlasKey, AtlasKeySource, AtlasTextureInfo, AtlasTextureInfoMap, Brush, Circle,
-    FillStyle, LineCap, LineJoin, Quad, SkieAtlas, StrokeStyle, Text, TextAlign, TextBaseline,
-    TextureAtlas,
+    arc, circle, ellipse, pie, points, polyline, quad, ring, Antialias, AtlasKey, AtlasKeySource,
+    AtlasTextureInfo, AtlasTextureInfoMap, BorderSide, BorderStyle, Borders, Brush, Circle,
+    CircleArc, ConicGradient, Ellipse, FillStyle, Gradient, GradientStop, LineCap, LineJoin,
+    LinearGradient, Pattern, PatternRepeat, PointShape, Points, Polyline, Quad, Ring, SkieAtlas,
+    StrokeStyle, Text, TextAlign, TextBaseline, TextOverflow, TextWrap, TextureAtlas,
+    TextureMapping,
 };
 
 pub use canvas::{
     backend_target::BackendRenderTarget,
+    batches::{BatchBreak, BatchInfo},
     offscreen_target::OffscreenRenderTarget,
+    picture::Picture,
     snapshot::{CanvasSnapshot, CanvasSnapshotResult, CanvasSnapshotSource},
+    stats::RenderStats,
     surface::CanvasSurface,
 };
 pub use paint::{
-    GpuTexture, GpuTextureView, GpuTextureViewDescriptor, Mesh, TextureAddressMode,
-    TextureFilterMode, TextureFormat, TextureId, TextureKind, TextureOptions,
+    GpuTexture, GpuTextureView, GpuTextureViewDescriptor, ImageAlpha, ImageColorSpace,
+    ImageDecodeOptions, Mesh, TextureAddressMode, TextureFilterMode, TextureFormat, TextureId,
+    TextureInfo, TextureKind, TextureOptions,
 };
 
-pub use renderer::{Renderer2D, Renderer2DSpecs};
+pub use renderer::{DebugView, Renderer2D, Renderer2DSpecs, RendererStats};
 
-pub use text::{Font, FontId, FontStyle, FontWeight, GlyphId, GlyphImage, TextSystem};
+pub use text::{
+    CosmicTextProvider, Digits, Font, FontFeatureTag, FontFeatures, FontId, FontProvider,
+    FontStyle, FontWeight, GlyphId, GlyphImage, GlyphKey, GlyphRun, Locale, PositionedGlyph,
+    RasterizedGlyph, ShapedGlyph, TextMeasure, TextSystem, LINE_HEIGHT_EM,
+};
 
 pub use skie_math::traits::*;
 
 #[cfg(feature = "application")]
 pub mod app;
+
+#[cfg(feature = "testing")]
+pub mod testing;
+
+#[cfg(feature = "demos")]
+pub mod demos;
+
+#[cfg(feature = "svg")]
+pub mod svg;
+#[cfg(feature = "svg")]
+pub use svg::{SvgDocument, SvgError};
@@ -0,0 +1,700 @@
+//! Svg documents
+//!
+//! Parses the subset of svg used by icons into skie paths and brushes, drawn with
+//! [`Canvas::draw_svg`]. Supports `path`, `rect`, `circle`, `ellipse`, `line`, `polyline`
+//! and `polygon` in nested `g`s with transforms, solid fills and strokes. Gradients, text,
+//! `use`, clipping and masks are skipped. Every subpath is filled on its own, so holes
+//! are not cut out, and group opacity is applied to each shape instead of the group.
+//! Unknown colors and percentage lengths are ignored like missing attributes.
+//!
+//! ```ignore
+//! let icon = SvgDocument::parse(include_str!("icon.svg"))?.current_color(Color::WHITE);
+//! canvas.draw_svg(&icon, &Rect::xywh(8.0, 8.0, 24.0, 24.0));
+//! ```
+
+mod path_data;
+
+use std::f32::consts::PI;
+
+use skie_math::{vec2, Corners, Mat3, Rect, Size};
+
+use crate::{
+    path::{Path, PathBuilder, Point},
+    Brush, Canvas, Color, LineCap, LineJoin,
+};
+
+use path_data::{add_path_data, parse_numbers};
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum SvgError {
+    #[error("invalid xml ({0})")]
+    Xml(#[from] roxmltree::Error),
+    #[error("root element is `{0}` instead of `svg`")]
+    NotSvg(String),
+    #[error("the root element has no viewBox, width or height")]
+    MissingSize,
+    #[error("invalid path data at byte {0}")]
+    InvalidPathData(usize),
+    #[error("invalid `{name}` attribute `{value}`")]
+    InvalidAttribute { name: String, value: String },
+}
+
+/// A parsed svg, see the [module docs](self) for what is supported
+#[derive(Debug, Clone)]
+pub struct SvgDocument {
+    view_box: Rect<f32>,
+    size: Size<f32>,
+    shapes: Vec<SvgShape>,
+    current_color: Color,
+}
+
+impl SvgDocument {
+    pub fn parse(source: &str) -> Result<Self, SvgError> {
+        let xml = roxmltree::Document::parse(source)?;
+        let root = xml.root_element();
+        if root.tag_name().name() != "svg" {
+            return Err(SvgError::NotSvg(root.tag_name().name().to_string()));
+        }
+
+        let width = root.attribute("width").and_then(parse_length);
+        let height = root.attribute("height").and_then(parse_length);
+        let view_box = match root.attribute("viewBox") {
+            Some(value) => match parse_numbers(value).as_deref() {
+                Some(&[x, y, width, height]) if width > 0.0 && height > 0.0 => {
+                    Rect::xywh(x, y, width, height)
+                }
+                _ => return Err(invalid("viewBox", value)),
+            },
+            None => match (width, height) {
+                (Some(width), Some(height)) => Rect::xywh(0.0, 0.0, width, height),
+                _ => return Err(SvgError::MissingSize),
+            },
+        };
+        let size = Size::new(
+            width.unwrap_or(view_box.size.width),
+            height.unwrap_or(view_box.size.height),
+        );
+
+        let mut shapes = Vec::new();
+        let style = Style::of(root, &Style::default());
+        collect_shapes(root, &style, &Mat3::IDENTITY, &mut shapes)?;
+
+        Ok(Self {
+            view_box,
+            size,
+            shapes,
+            current_color: Color::BLACK,
+        })
+    }
+
+    /// Color used for `currentColor` fills and strokes, black by default
+    pub fn current_color(mut self, color: Color) -> Self {
+        self.current_color = color;
+        self
+    }
+
+    pub fn set_current_color(&mut self, color: Color) {
+        self.current_color = color;
+    }
+
+    pub fn view_box(&self) -> &Rect<f32> {
+        &self.view_box
+    }
+
+    /// The `width` and `height` of the root, the view box size when they are missing
+    pub fn size(&self) -> Size<f32> {
+        self.size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shapes.is_empty()
+    }
+
+    /// Maps the view box into `rect` keeping the aspect ratio, centered like the default
+    /// `preserveAspectRatio`
+    fn fit(&self, rect: &Rect<f32>) -> Mat3 {
+        let view_box = &self.view_box;
+        let scale =
+            (rect.size.width / view_box.size.width).min(rect.size.height / view_box.size.height);
+        let x = rect.origin.x + (rect.size.width - view_box.size.width * scale) / 2.0;
+        let y = rect.origin.y + (rect.size.height - view_box.size.height * scale) / 2.0;
+
+        Mat3::from_translation(-view_box.origin.x, -view_box.origin.y)
+            * Mat3::from_scale(scale, scale)
+            * Mat3::from_translation(x, y)
+    }
+}
+
+impl Canvas {
+    /// Draws the svg scaled into `rect`, keeping its aspect ratio
+    pub fn draw_svg(&mut self, svg: &SvgDocument, rect: &Rect<f32>) {
+        let fit = svg.fit(rect);
+
+        for shape in &svg.shapes {
+            let transform = shape.transform * fit;
+            let mut brush = match &shape.fill {
+                Some(fill) => Brush::filled(fill.resolve(svg.current_color)),
+                None => Brush::filled(Color::TRANSPARENT),
+            };
+
+            if let Some(stroke) = &shape.stroke {
+                // strokes are whole pixels wide, scaled like the path
+                let width = stroke.width * transform.det().abs().sqrt();
+                brush = brush
+                    .stroke_color(stroke.paint.resolve(svg.current_color))
+                    .line_width(width.round().max(1.0) as u32)
+                    .line_cap(stroke.line_cap)
+                    .line_join(stroke.line_join);
+            }
+
+            self.draw_path(transform_path(&shape.path, &transform), brush);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SvgShape {
+    /// in the element's own units, mapped to the view box by `transform`
+    path: Path,
+    transform: Mat3,
+    fill: Option<Paint>,
+    stroke: Option<Stroke>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Paint {
+    /// `None` for `currentColor`
+    color: Option<Color>,
+    opacity: f32,
+}
+
+impl Paint {
+    fn resolve(&self, current_color: Color) -> Color {
+        let color = self.color.unwrap_or(current_color);
+        Color {
+            a: (color.a as f32 * self.opacity.clamp(0.0, 1.0)).round() as u8,
+            ..color
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Stroke {
+    paint: Paint,
+    width: f32,
+    line_cap: LineCap,
+    line_join: LineJoin,
+}
+
+/// Presentation attributes inherited by the children of an element
+#[derive(Debug, Clone)]
+struct Style {
+    fill: Option<Paint>,
+    stroke: Option<Paint>,
+    stroke_width: f32,
+    line_cap: LineCap,
+    line_join: LineJoin,
+    fill_opacity: f32,
+    stroke_opacity: f32,
+    /// `opacity` of the element times the ones of its groups
+    opacity: f32,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            fill: Some(Paint {
+                color: Some(Color::BLACK),
+                opacity: 1.0,
+            }),
+            stroke: None,
+            stroke_width: 1.0,
+            line_cap: LineCap::Butt,
+            line_join: LineJoin::Miter,
+            fill_opacity: 1.0,
+            stroke_opacity: 1.0,
+            opacity: 1.0,
+        }
+    }
+}
+
+impl Style {
+    /// The style of `node`, from its presentation attributes and `style` declarations
+    fn of(node: roxmltree::Node, parent: &Style) -> Self {
+        // opacity is not inherited, the element's own one applies over its parents'
+        let mut style = Style {
+            opacity: 1.0,
+            ..parent.clone()
+        };
+        let declarations = node
+            .attribute("style")
+            .into_iter()
+            .flat_map(|style| style.split(';'))
+            .filter_map(|declaration| declaration.split_once(':'))
+            .map(|(name, value)| (name.trim(), value.trim()));
+        let attributes = node
+            .attributes()
+            .filter(|attr| attr.namespace().is_none())
+            .map(|attr| (attr.name(), attr.value().trim()));
+
+        // declarations win over attributes
+        for (name, value) in attributes.chain(declarations) {
+            style.set(name, value);
+        }
+        style.opacity *= parent.opacity;
+        style
+    }
+
+    /// Invalid or unsupported values keep the inherited ones
+    fn set(&mut self, name: &str, value: &str) {
+        let number = parse_length(value);
+
+        match name {
+            "fill" => self.fill = parse_paint(value).unwrap_or(self.fill),
+            "stroke" => self.stroke = parse_paint(value).unwrap_or(self.stroke),
+            "stroke-width" => self.stroke_width = number.unwrap_or(self.stroke_width),
+            "fill-opacity" => self.fill_opacity = number.unwrap_or(self.fill_opacity),
+            "stroke-opacity" => self.stroke_opacity = number.unwrap_or(self.stroke_opacity),
+            "opacity" => self.opacity = number.unwrap_or(self.opacity),
+            "stroke-linecap" => {
+                self.line_cap = match value {
+                    "round" => LineCap::Round,
+                    "square" => LineCap::Square,
+                    _ => LineCap::Butt,
+                }
+            }
+            "stroke-linejoin" => {
+                self.line_join = match value {
+                    "round" => LineJoin::Round,
+                    "bevel" => LineJoin::Bevel,
+                    _ => LineJoin::Miter,
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn fill(&self) -> Option<Paint> {
+        self.fill.map(|paint| Paint {
+            opacity: paint.opacity * self.fill_opacity * self.opacity,
+            ..paint
+        })
+    }
+
+    fn stroke(&self) -> Option<Stroke> {
+        let paint = self.stroke?;
+        (self.stroke_width > 0.0).then_some(Stroke {
+            paint: Paint {
+                opacity: paint.opacity * self.stroke_opacity * self.opacity,
+                ..paint
+            },
+            width: self.stroke_width,
+            line_cap: self.line_cap,
+            line_join: self.line_join,
+        })
+    }
+}
+
+/// Elements whose subtree is never drawn directly
+const SKIPPED: &[&str] = &[
+    "defs",
+    "symbol",
+    "clipPath",
+    "mask",
+    "pattern",
+    "marker",
+    "linearGradient",
+    "radialGradient",
+    "style",
+    "title",
+    "desc",
+    "metadata",
+    "text",
+    "use",
+];
+
+fn collect_shapes(
+    node: roxmltree::Node,
+    parent_style: &Style,
+    parent_transform: &Mat3,
+    shapes: &mut Vec<SvgShape>,
+) -> Result<(), SvgError> {
+    for child in node.children().filter(roxmltree::Node::is_element) {
+        let name = child.tag_name().name();
+        if SKIPPED.contains(&name) || child.attribute("display") == Some("none") {
+            continue;
+        }
+
+        let style = Style::of(child, parent_style);
+        let transform = match child.attribute("transform") {
+            Some(value) => parse_transform(value)? * *parent_transform,
+            None => *parent_transform,
+        };
+
+        if name == "g" {
+            collect_shapes(child, &style, &transform, shapes)?;
+            continue;
+        }
+
+        let mut builder = Path::builder();
+        if !add_shape(&mut builder, child)? {
+            continue;
+        }
+
+        shapes.push(SvgShape {
+            path: builder.build(),
+            transform,
+            fill: style.fill(),
+            stroke: style.stroke(),
+        });
+    }
+    Ok(())
+}
+
+/// Adds the geometry of a shape element, returns false for other elements
+fn add_shape(builder: &mut PathBuilder, node: roxmltree::Node) -> Result<bool, SvgError> {
+    let number = |name: &str| match node.attribute(name) {
+        // percentages of the viewport are not supported
+        Some(value) if value.trim_end().ends_with('%') => Ok(0.0),
+        Some(value) => parse_length(value).ok_or_else(|| invalid(name, value)),
+        None => Ok(0.0),
+    };
+    let points = || {
+        let value = node.attribute("points").unwrap_or_default();
+        parse_numbers(value)
+            .filter(|numbers| numbers.len() % 2 == 0)
+            .map(|numbers| {
+                numbers
+                    .chunks_exact(2)
+                    .map(|xy| vec2(xy[0], xy[1]))
+                    .collect::<Vec<_>>()
+            })
+            .ok_or_else(|| invalid("points", value))
+    };
+
+    match node.tag_name().name() {
+        "path" => add_path_data(builder, node.attribute("d").unwrap_or_default())?,
+        "rect" => {
+            let rect = Rect::xywh(
+                number("x")?,
+                number("y")?,
+                number("width")?,
+                number("height")?,
+            );
+            // elliptical corners use the horizontal radius
+            let radius = match (node.attribute("rx"), node.attribute("ry")) {
+                (None, None) => 0.0,
+                (Some(_), _) => number("rx")?,
+                (None, Some(_)) => number("ry")?,
+            };
+            let radius = radius
+                .min(rect.size.width / 2.0)
+                .min(rect.size.height / 2.0);
+            if radius > 0.0 {
+                builder.round_rect(&rect, &Corners::with_all(radius));
+            } else {
+                builder.rect(&rect);
+            }
+        }
+        "circle" => {
+            builder.circle(vec2(number("cx")?, number("cy")?), number("r")?);
+        }
+        "ellipse" => add_ellipse(
+            builder,
+            vec2(number("cx")?, number("cy")?),
+            number("rx")?,
+            number("ry")?,
+        ),
+        "line" => {
+            builder.begin(vec2(number("x1")?, number("y1")?));
+            builder.line_to(vec2(number("x2")?, number("y2")?));
+            builder.end(false);
+        }
+        name @ ("polyline" | "polygon") => {
+            let points = points()?;
+            if let Some((first, rest)) = points.split_first() {
+                builder.begin(*first);
+                rest.iter().for_each(|point| builder.line_to(*point));
+                builder.end(name == "polygon");
+            }
+        }
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+fn add_ellipse(builder: &mut PathBuilder, center: Point, rx: f32, ry: f32) {
+    // control point distance of a quarter circle bezier
+    const KAPPA: f32 = 0.552_284_8;
+    let (kx, ky) = (rx * KAPPA, ry * KAPPA);
+    let (x, y) = (center.x, center.y);
+
+    builder.begin(vec2(x + rx, y));
+    builder.cubic_to(vec2(x + rx, y + ky), vec2(x + kx, y + ry), vec2(x, y + ry));
+    builder.cubic_to(vec2(x - kx, y + ry), vec2(x - rx, y + ky), vec2(x - rx, y));
+    builder.cubic_to(vec2(x - rx, y - ky), vec2(x - kx, y - ry), vec2(x, y - ry));
+    builder.cubic_to(vec2(x + kx, y - ry), vec2(x + rx, y - ky), vec2(x + rx, y));
+    builder.close();
+}
+
+fn transform_path(path: &Path, transform: &Mat3) -> Path {
+    Path {
+        points: path
+            .points
+            .iter()
+            .map(|point| *transform * *point)
+            .collect(),
+        verbs: path.verbs.clone(),
+    }
+}
+
+/// Parses a transform list, `translate(10) rotate(45)` rotates first
+fn parse_transform(value: &str) -> Result<Mat3, SvgError> {
+    let mut transform = Mat3::IDENTITY;
+    let mut rest = value.trim();
+
+    while !rest.is_empty() {
+        let (name, after) = rest
+            .split_once('(')
+            .ok_or_else(|| invalid("transform", value))?;
+        let (args, after) = after
+            .split_once(')')
+            .ok_or_else(|| invalid("transform", value))?;
+        let args = parse_numbers(args).ok_or_else(|| invalid("transform", value))?;
+
+        let function = match (name.trim(), args.as_slice()) {
+            ("matrix", &[a, b, c, d, e, f]) => Mat3::from_affine(a, b, c, d, e, f),
+            ("translate", &[x]) => Mat3::from_translation(x, 0.0),
+            ("translate", &[x, y]) => Mat3::from_translation(x, y),
+            ("scale", &[s]) => Mat3::from_scale(s, s),
+            ("scale", &[x, y]) => Mat3::from_scale(x, y),
+            ("rotate", &[angle]) => Mat3::from_rotation(angle * PI / 180.0),
+            ("rotate", &[angle, x, y]) => {
+                Mat3::from_translation(-x, -y)
+                    * Mat3::from_rotation(angle * PI / 180.0)
+                    * Mat3::from_translation(x, y)
+            }
+            ("skewX", &[angle]) => Mat3::from_skew((angle * PI / 180.0).tan(), 0.0),
+            ("skewY", &[angle]) => Mat3::from_skew(0.0, (angle * PI / 180.0).tan()),
+            _ => return Err(invalid("transform", value)),
+        };
+
+        // later functions apply first
+        transform = function * transform;
+        rest = after.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+    }
+    Ok(transform)
+}
+
+/// A number with an optional `px` unit
+fn parse_length(value: &str) -> Option<f32> {
+    value.trim().trim_end_matches("px").trim_end().parse().ok()
+}
+
+/// `Some(None)` for `none`, `None` when invalid
+fn parse_paint(value: &str) -> Option<Option<Paint>> {
+    let color = match value {
+        "none" | "transparent" => return Some(None),
+        "currentColor" => None,
+        // gradients and patterns are not supported
+        _ if value.starts_with("url(") => return Some(None),
+        _ => Some(parse_color(value)?),
+    };
+    Some(Some(Paint {
+        color,
+        opacity: 1.0,
+    }))
+}
+
+fn parse_color(value: &str) -> Option<Color> {
+    if let Some(hex) = value.strip_prefix('#') {
+        let digits = u32::from_str_radix(hex, 16).ok()?;
+        // expands each digit of the short forms to a byte
+        let expand = |short: u32| {
+            (0..4).rev().fold(0, |color, i| {
+                let digit = (short >> (i * 4)) & 0xf;
+                (color << 8) | (digit * 0x11)
+            })
+        };
+        return match hex.len() {
+            3 => Some(Color::from((expand(digits) << 8) | 0xff)),
+            4 => Some(Color::from(expand(digits))),
+            6 => Some(Color::from_rgb(digits)),
+            8 => Some(Color::from(digits)),
+            _ => None,
+        };
+    }
+
+    if let Some(args) = value
+        .strip_prefix("rgb(")
+        .or_else(|| value.strip_prefix("rgba("))
+        .and_then(|args| args.strip_suffix(')'))
+    {
+        let channel = |arg: &str| match arg.trim().strip_suffix('%') {
+            Some(percent) => percent.parse::<f32>().ok().map(|p| p / 100.0 * 255.0),
+            None => arg.trim().parse::<f32>().ok(),
+        };
+        let args: Vec<_> = args.split(',').collect();
+        let (r, g, b) = match args.as_slice() {
+            [r, g, b] | [r, g, b, _] => (channel(r)?, channel(g)?, channel(b)?),
+            _ => return None,
+        };
+        let a = match args.get(3) {
+            Some(alpha) => alpha.trim().parse::<f32>().ok()? * 255.0,
+            None => 255.0,
+        };
+        let byte = |v: f32| v.round().clamp(0.0, 255.0) as u8;
+        return Some(Color::from([byte(r), byte(g), byte(b), byte(a)]));
+    }
+
+    let rgb = match value.to_ascii_lowercase().as_str() {
+        "black" => 0x000000,
+        "white" => 0xffffff,
+        "red" => 0xff0000,
+        "green" => 0x008000,
+        "lime" => 0x00ff00,
+        "blue" => 0x0000ff,
+        "yellow" => 0xffff00,
+        "orange" => 0xffa500,
+        "purple" => 0x800080,
+        "gray" | "grey" => 0x808080,
+        "silver" => 0xc0c0c0,
+        "maroon" => 0x800000,
+        "navy" => 0x000080,
+        "teal" => 0x008080,
+        "olive" => 0x808000,
+        "aqua" | "cyan" => 0x00ffff,
+        "fuchsia" | "magenta" => 0xff00ff,
+        _ => return None,
+    };
+    Some(Color::from_rgb(rgb))
+}
+
+fn invalid(name: &str, value: &str) -> SvgError {
+    SvgError::InvalidAttribute {
+        name: name.to_string(),
+        value: value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canvas::tests::{pixel, test_canvas};
+
+    #[test]
+    fn groups_pass_styles_and_transforms_down() {
+        let svg = SvgDocument::parse(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="48" height="48" viewBox="0 0 24 24">
+                <defs><rect width="24" height="24"/></defs>
+                <g fill="red" opacity="0.5" transform="translate(10 0)">
+                    <path d="M0 0h5v5z" style="stroke: #00f; stroke-width: 2"/>
+                    <circle cx="2" cy="2" r="1" fill="currentColor" transform="scale(2)"/>
+                </g>
+                <rect width="4" height="4" display="none"/>
+            </svg>"##,
+        )
+        .unwrap();
+
+        assert_eq!(svg.size(), Size::new(48.0, 48.0));
+        assert_eq!(svg.view_box(), &Rect::xywh(0.0, 0.0, 24.0, 24.0));
+        assert_eq!(svg.shapes.len(), 2);
+
+        let path = &svg.shapes[0];
+        assert_eq!(path.transform * vec2(0.0, 0.0), vec2(10.0, 0.0));
+        assert_eq!(
+            path.fill.unwrap().resolve(Color::WHITE),
+            Color::from(0xff000080)
+        );
+        let stroke = path.stroke.unwrap();
+        assert_eq!(stroke.width, 2.0);
+        assert_eq!(stroke.paint.resolve(Color::WHITE), Color::from(0x0000ff80));
+
+        // the circle scales before the group translates it
+        let circle = &svg.shapes[1];
+        assert_eq!(circle.transform * vec2(2.0, 2.0), vec2(14.0, 4.0));
+        assert_eq!(circle.fill.unwrap().resolve(Color::GOLD).r, Color::GOLD.r);
+        assert!(circle.stroke.is_none());
+    }
+
+    #[test]
+    fn opacity_and_unsupported_values() {
+        let svg = SvgDocument::parse(
+            r##"<svg viewBox="0 0 24 24">
+                <g fill="red" opacity="0.5">
+                    <rect width="4" height="4" opacity="0.5" style="opacity: 0.8"/>
+                    <rect width="100%" height="4" fill="rebeccapurple" stroke="#00f" stroke-width="10%"/>
+                </g>
+            </svg>"##,
+        )
+        .unwrap();
+        assert_eq!(svg.shapes.len(), 2);
+
+        // the declaration wins over the attribute, then the group applies once
+        let fill = svg.shapes[0].fill.unwrap().resolve(Color::WHITE);
+        assert_eq!(fill, Color::from([255, 0, 0, 102]));
+
+        let ignored = &svg.shapes[1];
+        assert_eq!(ignored.fill.unwrap().resolve(Color::WHITE).r, 255);
+        assert_eq!(ignored.stroke.unwrap().width, 1.0);
+    }
+
+    #[test]
+    fn colors() {
+        assert_eq!(parse_color("#f00"), Some(Color::RED));
+        assert_eq!(parse_color("#0f08"), Some(Color::from(0x00ff0088)));
+        assert_eq!(parse_color("#0000ff"), Some(Color::from_rgb(0x0000ff)));
+        assert_eq!(
+            parse_color("rgba(255, 0, 50%, 0.5)"),
+            Some(Color::from([255, 0, 128, 128]))
+        );
+        assert_eq!(parse_color("Navy"), Some(Color::from_rgb(0x000080)));
+        assert_eq!(parse_color("#12"), None);
+        assert_eq!(parse_paint("none"), Some(None));
+        assert_eq!(parse_paint("bogus"), None);
+    }
+
+    #[test]
+    fn invalid_documents() {
+        assert!(matches!(
+            SvgDocument::parse("<html/>"),
+            Err(SvgError::NotSvg(name)) if name == "html"
+        ));
+        assert_eq!(
+            SvgDocument::parse("<svg/>").unwrap_err(),
+            SvgError::MissingSize
+        );
+        assert!(matches!(
+            SvgDocument::parse(r#"<svg viewBox="0 0 1 1"><path d="M0 0 L"/></svg>"#),
+            Err(SvgError::InvalidPathData(_))
+        ));
+        assert!(matches!(SvgDocument::parse("<svg"), Err(SvgError::Xml(_))));
+    }
+
+    #[test]
+    fn draw_svg_fits_the_rect() {
+        let Some(mut canvas) = test_canvas(64, 64) else {
+            return;
+        };
+        let mut target = canvas.create_offscreen_target();
+
+        let svg = SvgDocument::parse(
+            r##"<svg viewBox="0 0 10 10">
+                <rect width="5" height="10" fill="#f00"/>
+                <rect x="5" width="5" height="10" fill="currentColor"/>
+            </svg>"##,
+        )
+        .unwrap()
+        .current_color(Color::BLUE);
+
+        // a wide rect centers the square view box horizontally
+        canvas.draw_svg(&svg, &Rect::xywh(0.0, 16.0, 64.0, 32.0));
+        canvas.render(&mut target).unwrap();
+
+        let snapshot = canvas.snapshot_sync(&target).unwrap();
+        assert_eq!(pixel(&snapshot, 8, 32), [255, 255, 255, 255]);
+        assert_eq!(pixel(&snapshot, 24, 32), [255, 0, 0, 255]);
+        assert_eq!(pixel(&snapshot, 40, 32), [0, 0, 255, 255]);
+        assert_eq!(pixel(&snapshot, 40, 8), [255, 255, 255, 255]);
+    }
+}
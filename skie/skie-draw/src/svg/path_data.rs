@@ -0,0 +1,287 @@
+//! Parser for the `d` attribute of `<path>` elements
+
+use skie_math::vec2;
+
+use crate::path::{PathBuilder, Point};
+
+use super::SvgError;
+
+/// Adds the subpaths of `data` to `builder`
+pub(super) fn add_path_data(builder: &mut PathBuilder, data: &str) -> Result<(), SvgError> {
+    let mut tokens = Tokens::new(data);
+
+    let mut current = vec2(0.0, 0.0);
+    let mut start = current;
+    let mut open = false;
+    let mut prev_command: Option<u8> = None;
+    // the last control point of the previous segment, reflected by `S` and `T`
+    let mut prev_cubic_ctrl: Option<Point> = None;
+    let mut prev_quad_ctrl: Option<Point> = None;
+
+    loop {
+        let command = match tokens.command() {
+            Some(command) => command,
+            None if tokens.at_end() => break,
+            // numbers after a command repeat it, a moveto continues with linetos
+            None => match prev_command {
+                Some(b'M') => b'L',
+                Some(b'm') => b'l',
+                Some(b'Z' | b'z') | None => return Err(SvgError::InvalidPathData(tokens.pos)),
+                Some(command) => command,
+            },
+        };
+        let relative = command.is_ascii_lowercase();
+        let origin = if relative { current } else { vec2(0.0, 0.0) };
+
+        let mut cubic_ctrl = None;
+        let mut quad_ctrl = None;
+
+        if !open && !matches!(command, b'M' | b'm' | b'Z' | b'z') {
+            builder.begin(current);
+            open = true;
+        }
+
+        match command.to_ascii_uppercase() {
+            b'M' => {
+                let to = origin + tokens.point()?;
+                if open {
+                    builder.end(false);
+                }
+                builder.begin(to);
+                open = true;
+                current = to;
+                start = to;
+            }
+            b'L' => {
+                current = origin + tokens.point()?;
+                builder.line_to(current);
+            }
+            b'H' => {
+                current.x = origin.x + tokens.number()?;
+                builder.line_to(current);
+            }
+            b'V' => {
+                current.y = origin.y + tokens.number()?;
+                builder.line_to(current);
+            }
+            b'C' => {
+                let ctrl1 = origin + tokens.point()?;
+                let ctrl2 = origin + tokens.point()?;
+                current = origin + tokens.point()?;
+                builder.cubic_to(ctrl1, ctrl2, current);
+                cubic_ctrl = Some(ctrl2);
+            }
+            b'S' => {
+                let ctrl1 = reflect(prev_cubic_ctrl, current);
+                let ctrl2 = origin + tokens.point()?;
+                current = origin + tokens.point()?;
+                builder.cubic_to(ctrl1, ctrl2, current);
+                cubic_ctrl = Some(ctrl2);
+            }
+            b'Q' => {
+                let ctrl = origin + tokens.point()?;
+                current = origin + tokens.point()?;
+                builder.quadratic_to(ctrl, current);
+                quad_ctrl = Some(ctrl);
+            }
+            b'T' => {
+                let ctrl = reflect(prev_quad_ctrl, current);
+                current = origin + tokens.point()?;
+                builder.quadratic_to(ctrl, current);
+                quad_ctrl = Some(ctrl);
+            }
+            b'A' => {
+                let radii = tokens.point()?;
+                let rotation = tokens.number()?.to_radians();
+                let large_arc = tokens.flag()?;
+                let sweep = tokens.flag()?;
+                let to = origin + tokens.point()?;
+                builder.elliptical_arc_to(radii, rotation, large_arc, sweep, to);
+                current = to;
+            }
+            b'Z' => {
+                if open {
+                    builder.close();
+                    open = false;
+                }
+                current = start;
+            }
+            _ => return Err(SvgError::InvalidPathData(tokens.pos - 1)),
+        }
+
+        prev_command = Some(command);
+        prev_cubic_ctrl = cubic_ctrl;
+        prev_quad_ctrl = quad_ctrl;
+    }
+
+    if open {
+        builder.end(false);
+    }
+    Ok(())
+}
+
+fn reflect(ctrl: Option<Point>, around: Point) -> Point {
+    ctrl.map_or(around, |ctrl| around * 2.0 - ctrl)
+}
+
+/// Numbers separated by whitespace or commas, like the `points` of polygons
+pub(super) fn parse_numbers(data: &str) -> Option<Vec<f32>> {
+    let mut tokens = Tokens::new(data);
+    let mut numbers = Vec::new();
+    while !tokens.at_end() {
+        numbers.push(tokens.number().ok()?);
+    }
+    Some(numbers)
+}
+
+struct Tokens<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn new(data: &'a str) -> Self {
+        Self {
+            data: data.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.pos).copied()
+    }
+
+    fn skip_separators(&mut self) {
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_whitespace() || c == b',')
+        {
+            self.pos += 1;
+        }
+    }
+
+    fn at_end(&mut self) -> bool {
+        self.skip_separators();
+        self.pos >= self.data.len()
+    }
+
+    fn command(&mut self) -> Option<u8> {
+        self.skip_separators();
+        let command = self.peek().filter(u8::is_ascii_alphabetic)?;
+        self.pos += 1;
+        Some(command)
+    }
+
+    fn number(&mut self) -> Result<f32, SvgError> {
+        self.skip_separators();
+        let start = self.pos;
+        let digits = |tokens: &mut Self| {
+            let from = tokens.pos;
+            while tokens.peek().is_some_and(|c| c.is_ascii_digit()) {
+                tokens.pos += 1;
+            }
+            tokens.pos > from
+        };
+
+        if matches!(self.peek(), Some(b'-' | b'+')) {
+            self.pos += 1;
+        }
+        let mut has_digits = digits(self);
+        if self.peek() == Some(b'.') {
+            self.pos += 1;
+            has_digits |= digits(self);
+        }
+        if !has_digits {
+            return Err(SvgError::InvalidPathData(start));
+        }
+
+        if matches!(self.peek(), Some(b'e' | b'E')) {
+            let mark = self.pos;
+            self.pos += 1;
+            if matches!(self.peek(), Some(b'-' | b'+')) {
+                self.pos += 1;
+            }
+            if !digits(self) {
+                self.pos = mark;
+            }
+        }
+
+        std::str::from_utf8(&self.data[start..self.pos])
+            .ok()
+            .and_then(|number| number.parse().ok())
+            .ok_or(SvgError::InvalidPathData(start))
+    }
+
+    fn point(&mut self) -> Result<Point, SvgError> {
+        Ok(vec2(self.number()?, self.number()?))
+    }
+
+    /// Arc flags are a single digit and may be written without separators
+    fn flag(&mut self) -> Result<bool, SvgError> {
+        self.skip_separators();
+        let flag = match self.peek() {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return Err(SvgError::InvalidPathData(self.pos)),
+        };
+        self.pos += 1;
+        Ok(flag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Path;
+
+    fn parse(data: &str) -> Result<Path, SvgError> {
+        let mut builder = Path::builder();
+        add_path_data(&mut builder, data)?;
+        Ok(builder.build())
+    }
+
+    #[test]
+    fn compact_path_data() {
+        let path = parse("M10-5L20.5.5h-1.5e1v4Z m1,1 2,2").unwrap();
+        let contours: Vec<_> = path.contours().collect();
+        assert_eq!(contours.len(), 2);
+
+        assert!(contours[0].is_closed());
+        assert_eq!(
+            contours[0].points(),
+            [
+                vec2(10.0, -5.0),
+                vec2(20.5, 0.5),
+                vec2(5.5, 0.5),
+                vec2(5.5, 4.5),
+                vec2(10.0, -5.0),
+            ]
+        );
+        // relative to the start of the closed subpath, the extra pair is a lineto
+        assert_eq!(contours[1].points(), [vec2(11.0, -4.0), vec2(13.0, -2.0)]);
+    }
+
+    #[test]
+    fn arcs_end_on_the_end_point() {
+        let path = parse("M0 0A10 10 0 1 1 20 0a5 5 0 0020 0").unwrap();
+        let points = path.contours().next().unwrap().points();
+
+        // a half circle is two quarter beziers, the second arc too
+        assert_eq!(points.len(), 1 + 3 * 4);
+        assert_eq!(points[6], vec2(20.0, 0.0));
+        assert_eq!(points[12], vec2(40.0, 0.0));
+        // the sweep arc bulges up, the other one down with its radii scaled to reach the end
+        assert!((points[3] - vec2(10.0, -10.0)).magnitude() < 1e-4);
+        assert!((points[9] - vec2(30.0, 10.0)).magnitude() < 1e-4);
+    }
+
+    #[test]
+    fn invalid_path_data() {
+        assert!(matches!(parse("L10"), Err(SvgError::InvalidPathData(_))));
+        assert!(matches!(parse("M0 0 X"), Err(SvgError::InvalidPathData(_))));
+        assert!(matches!(
+            parse("M0 0Z 1 1"),
+            Err(SvgError::InvalidPathData(_))
+        ));
+    }
+}
@@ -0,0 +1,454 @@
+//! Golden image testing
+//!
+//! Renders a closure into an offscreen canvas and compares the result against a
+//! stored reference png. On failure the actual render and a diff image are written
+//! next to each other so regressions in tessellation, text or gradients can be inspected.
+//!
+//! ```ignore
+//! let gpu = GpuContext::new().block_on()?;
+//! GoldenTest::new("rounded_rect")
+//!     .size(256, 256)
+//!     .assert(&gpu, |canvas| {
+//!         canvas.draw_round_rect(&rect, &Corners::with_all(8.0), Brush::filled(Color::RED));
+//!     });
+//! ```
+//!
+//! Set `SKIE_UPDATE_GOLDEN=1` to (re)write the reference images, a missing reference
+//! fails the test otherwise.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, ensure, Result};
+
+use crate::{gpu, Canvas, CanvasSnapshot, Color, GpuContext, Size};
+
+pub const UPDATE_GOLDEN_ENV: &str = "SKIE_UPDATE_GOLDEN";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GoldenTolerance {
+    /// Perceptual color distance (0..1) after which a pixel is considered different
+    pub threshold: f32,
+    /// Ratio of pixels (0..1) allowed to differ before the test fails
+    pub max_mismatched_ratio: f32,
+}
+
+impl Default for GoldenTolerance {
+    fn default() -> Self {
+        Self {
+            threshold: 0.1,
+            max_mismatched_ratio: 0.001,
+        }
+    }
+}
+
+impl GoldenTolerance {
+    pub fn exact() -> Self {
+        Self {
+            threshold: 0.0,
+            max_mismatched_ratio: 0.0,
+        }
+    }
+
+    pub fn threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    pub fn max_mismatched_ratio(mut self, ratio: f32) -> Self {
+        self.max_mismatched_ratio = ratio;
+        self
+    }
+}
+
+#[derive(Debug)]
+pub enum GoldenOutcome {
+    /// The render matched the reference within the tolerance
+    Matched(ImageDiff),
+    /// An update was requested and the reference was written
+    Written(PathBuf),
+}
+
+#[derive(Debug, Clone)]
+pub struct GoldenTest {
+    name: String,
+    size: Size<u32>,
+    msaa_samples: u32,
+    clear_color: Color,
+    tolerance: GoldenTolerance,
+    reference_dir: PathBuf,
+    output_dir: PathBuf,
+    update: bool,
+}
+
+impl GoldenTest {
+    pub fn new(name: impl Into<String>) -> Self {
+        let root = std::env::var_os("CARGO_MANIFEST_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_default();
+
+        Self {
+            name: name.into(),
+            size: Size::new(256, 256),
+            msaa_samples: 4,
+            clear_color: Color::WHITE,
+            tolerance: Default::default(),
+            reference_dir: root.join("tests").join("golden"),
+            output_dir: root.join("target").join("golden"),
+            update: std::env::var_os(UPDATE_GOLDEN_ENV).is_some_and(|v| v != "0"),
+        }
+    }
+
+    /// Width should be a multiple of 64 (row alignment of texture copies)
+    pub fn size(mut self, width: u32, height: u32) -> Self {
+        self.size = Size::new(width, height);
+        self
+    }
+
+    pub fn msaa_samples(mut self, sample_count: u32) -> Self {
+        self.msaa_samples = sample_count;
+        self
+    }
+
+    pub fn clear_color(mut self, color: Color) -> Self {
+        self.clear_color = color;
+        self
+    }
+
+    pub fn tolerance(mut self, tolerance: GoldenTolerance) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Directory containing the reference `<name>.png` files
+    pub fn reference_dir(mut self, dir: impl AsRef<Path>) -> Self {
+        self.reference_dir = dir.as_ref().to_path_buf();
+        self
+    }
+
+    /// Directory where actual and diff images are written on failure
+    pub fn output_dir(mut self, dir: impl AsRef<Path>) -> Self {
+        self.output_dir = dir.as_ref().to_path_buf();
+        self
+    }
+
+    /// Writes the reference instead of comparing against it, defaults to whether
+    /// `SKIE_UPDATE_GOLDEN` is set
+    pub fn update(mut self, update: bool) -> Self {
+        self.update = update;
+        self
+    }
+
+    pub fn reference_path(&self) -> PathBuf {
+        self.reference_dir.join(format!("{}.png", self.name))
+    }
+
+    pub fn render(&self, gpu: &GpuContext, f: impl FnOnce(&mut Canvas)) -> Result<CanvasSnapshot> {
+        ensure!(
+            (self.size.width * 4).is_multiple_of(gpu::COPY_BYTES_PER_ROW_ALIGNMENT),
+            "golden image width must be a multiple of {} got {}",
+            gpu::COPY_BYTES_PER_ROW_ALIGNMENT / 4,
+            self.size.width
+        );
+
+        let mut canvas = Canvas::create()
+            .width(self.size.width)
+            .height(self.size.height)
+            .msaa_samples(self.msaa_samples)
+            .add_surface_usage(gpu::TextureUsages::COPY_SRC)
+            .build(gpu.clone());
+
+        let mut target = canvas.create_offscreen_target();
+
+        canvas.clear_color(self.clear_color);
+        f(&mut canvas);
+        canvas.render(&mut target)?;
+
+        canvas.snapshot_sync(&target)
+    }
+
+    /// Renders `f` and compares it against the reference image
+    pub fn run(&self, gpu: &GpuContext, f: impl FnOnce(&mut Canvas)) -> Result<GoldenOutcome> {
+        let snapshot = self.render(gpu, f)?;
+        let reference_path = self.reference_path();
+
+        if !self.update && !reference_path.exists() {
+            bail!(
+                "golden `{}`: missing reference {}, rerun with {}=1",
+                self.name,
+                reference_path.display(),
+                UPDATE_GOLDEN_ENV
+            );
+        }
+
+        if self.update {
+            std::fs::create_dir_all(&self.reference_dir)?;
+            save_png(&reference_path, &snapshot.data, snapshot.size)?;
+            log::warn!("golden: wrote reference {}", reference_path.display());
+            return Ok(GoldenOutcome::Written(reference_path));
+        }
+
+        let reference = image::open(&reference_path)?.into_rgba8();
+        let reference_size = Size::new(reference.width(), reference.height());
+
+        if reference_size != snapshot.size {
+            bail!(
+                "golden `{}`: size mismatch expected {:?} got {:?}",
+                self.name,
+                reference_size,
+                snapshot.size
+            );
+        }
+
+        let diff = compare_images(
+            reference.as_raw(),
+            &snapshot.data,
+            snapshot.size,
+            self.tolerance.threshold,
+        );
+
+        if diff.mismatched_ratio() > self.tolerance.max_mismatched_ratio {
+            std::fs::create_dir_all(&self.output_dir)?;
+
+            let actual_path = self.output_dir.join(format!("{}.actual.png", self.name));
+            let diff_path = self.output_dir.join(format!("{}.diff.png", self.name));
+
+            save_png(&actual_path, &snapshot.data, snapshot.size)?;
+            save_png(&diff_path, &diff.image, diff.size)?;
+
+            bail!(
+                "golden `{}`: {} of {} pixels differ (max delta {:.3})\n  reference: {}\n  actual: {}\n  diff: {}",
+                self.name,
+                diff.mismatched,
+                diff.total(),
+                diff.max_delta,
+                reference_path.display(),
+                actual_path.display(),
+                diff_path.display(),
+            );
+        }
+
+        Ok(GoldenOutcome::Matched(diff))
+    }
+
+    /// Same as [`GoldenTest::run`] but panics on mismatch, for use in `#[test]`s
+    pub fn assert(&self, gpu: &GpuContext, f: impl FnOnce(&mut Canvas)) {
+        if let Err(err) = self.run(gpu, f) {
+            panic!("{err}");
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ImageDiff {
+    pub size: Size<u32>,
+    /// Number of pixels over the threshold
+    pub mismatched: usize,
+    /// Largest perceptual delta found (0..1)
+    pub max_delta: f32,
+    /// Rgba8 visualization, mismatched pixels are red over a faded copy of the reference
+    pub image: Vec<u8>,
+}
+
+impl ImageDiff {
+    pub fn total(&self) -> usize {
+        (self.size.width * self.size.height) as usize
+    }
+
+    pub fn mismatched_ratio(&self) -> f32 {
+        let total = self.total();
+        if total == 0 {
+            0.0
+        } else {
+            self.mismatched as f32 / total as f32
+        }
+    }
+}
+
+/// Compares two tightly packed rgba8 images using a perceptual (YIQ) color distance
+pub fn compare_images(
+    expected: &[u8],
+    actual: &[u8],
+    size: Size<u32>,
+    threshold: f32,
+) -> ImageDiff {
+    let total = (size.width * size.height) as usize;
+    debug_assert_eq!(expected.len(), total * 4);
+    debug_assert_eq!(actual.len(), total * 4);
+
+    let mut mismatched = 0;
+    let mut max_delta: f32 = 0.0;
+    let mut image = Vec::with_capacity(total * 4);
+
+    for (a, b) in expected.chunks_exact(4).zip(actual.chunks_exact(4)) {
+        let delta = color_delta(a, b);
+        max_delta = max_delta.max(delta);
+
+        if delta > threshold {
+            mismatched += 1;
+            image.extend_from_slice(&[255, 0, 0, 255]);
+        } else {
+            let gray = (255.0 - (255.0 - luma(a)) * 0.1) as u8;
+            image.extend_from_slice(&[gray, gray, gray, 255]);
+        }
+    }
+
+    ImageDiff {
+        size,
+        mismatched,
+        max_delta,
+        image,
+    }
+}
+
+// https://github.com/mapbox/pixelmatch
+fn color_delta(a: &[u8], b: &[u8]) -> f32 {
+    if a == b {
+        return 0.0;
+    }
+
+    // max possible value of the weighted yiq distance
+    const MAX_DELTA: f32 = 35215.0;
+
+    let (r1, g1, b1) = blend_white(a);
+    let (r2, g2, b2) = blend_white(b);
+
+    let y = rgb_to_y(r1, g1, b1) - rgb_to_y(r2, g2, b2);
+    let i = rgb_to_i(r1, g1, b1) - rgb_to_i(r2, g2, b2);
+    let q = rgb_to_q(r1, g1, b1) - rgb_to_q(r2, g2, b2);
+
+    let delta = 0.5053 * y * y + 0.299 * i * i + 0.1957 * q * q;
+    (delta / MAX_DELTA).sqrt()
+}
+
+fn blend_white(c: &[u8]) -> (f32, f32, f32) {
+    let a = c[3] as f32 / 255.0;
+    let blend = |v: u8| 255.0 + (v as f32 - 255.0) * a;
+    (blend(c[0]), blend(c[1]), blend(c[2]))
+}
+
+fn luma(c: &[u8]) -> f32 {
+    let (r, g, b) = blend_white(c);
+    rgb_to_y(r, g, b)
+}
+
+fn rgb_to_y(r: f32, g: f32, b: f32) -> f32 {
+    r * 0.298_895 + g * 0.586_622 + b * 0.114_482
+}
+
+fn rgb_to_i(r: f32, g: f32, b: f32) -> f32 {
+    r * 0.595_978 - g * 0.274_176 - b * 0.321_802
+}
+
+fn rgb_to_q(r: f32, g: f32, b: f32) -> f32 {
+    r * 0.211_470 - g * 0.522_617 + b * 0.311_147
+}
+
+pub(crate) fn save_png(path: &Path, data: &[u8], size: Size<u32>) -> Result<()> {
+    image::save_buffer(
+        path,
+        data,
+        size.width,
+        size.height,
+        image::ExtendedColorType::Rgba8,
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(size: Size<u32>, color: [u8; 4]) -> Vec<u8> {
+        color.repeat((size.width * size.height) as usize)
+    }
+
+    #[test]
+    fn identical_images_match() {
+        let size = Size::new(4, 4);
+        let a = solid(size, [10, 20, 30, 255]);
+        let diff = compare_images(&a, &a, size, 0.0);
+
+        assert_eq!(diff.mismatched, 0);
+        assert_eq!(diff.max_delta, 0.0);
+        assert_eq!(diff.image.len(), a.len());
+    }
+
+    #[test]
+    fn small_differences_are_tolerated() {
+        let size = Size::new(4, 4);
+        let a = solid(size, [100, 100, 100, 255]);
+        let b = solid(size, [101, 100, 99, 255]);
+
+        let diff = compare_images(&a, &b, size, 0.1);
+        assert_eq!(diff.mismatched, 0);
+        assert!(diff.max_delta > 0.0);
+    }
+
+    #[test]
+    fn large_differences_are_reported() {
+        let size = Size::new(2, 2);
+        let a = solid(size, [255, 255, 255, 255]);
+        let mut b = a.clone();
+        b[0..4].copy_from_slice(&[0, 0, 0, 255]);
+
+        let diff = compare_images(&a, &b, size, 0.1);
+        assert_eq!(diff.mismatched, 1);
+        assert_eq!(diff.mismatched_ratio(), 0.25);
+        assert_eq!(&diff.image[0..4], &[255, 0, 0, 255]);
+        assert!(diff.max_delta > 0.9);
+    }
+
+    #[test]
+    fn transparent_pixels_blend_with_white() {
+        let size = Size::new(1, 1);
+        let a = vec![255, 255, 255, 255];
+        let b = vec![0, 0, 0, 0];
+
+        let diff = compare_images(&a, &b, size, 0.0);
+        assert_eq!(diff.mismatched, 0);
+    }
+
+    #[test]
+    fn golden_roundtrip() {
+        let Ok(gpu) = futures::executor::block_on(GpuContext::new()) else {
+            eprintln!("skipping golden_roundtrip: no gpu adapter");
+            return;
+        };
+
+        let dir = std::env::temp_dir().join(format!("skie-golden-{}", std::process::id()));
+        let test = GoldenTest::new("roundtrip")
+            .size(64, 64)
+            .reference_dir(&dir)
+            .output_dir(&dir);
+
+        let draw = |color: Color| {
+            move |canvas: &mut Canvas| {
+                canvas.draw_rect(
+                    &crate::Rect::xywh(8.0, 8.0, 48.0, 48.0),
+                    crate::Brush::filled(color),
+                )
+            }
+        };
+
+        let test = test.update(false);
+        let err = test.run(&gpu, draw(Color::RED)).unwrap_err();
+        assert!(err.to_string().contains("missing reference"), "{err}");
+        assert!(!test.reference_path().exists());
+
+        let outcome = test
+            .clone()
+            .update(true)
+            .run(&gpu, draw(Color::RED))
+            .unwrap();
+        assert!(matches!(outcome, GoldenOutcome::Written(_)));
+
+        let outcome = test.run(&gpu, draw(Color::RED)).unwrap();
+        assert!(matches!(outcome, GoldenOutcome::Matched(ref diff) if diff.mismatched == 0));
+
+        assert!(test.run(&gpu, draw(Color::BLUE)).is_err());
+        assert!(dir.join("roundtrip.diff.png").exists());
+        assert!(dir.join("roundtrip.actual.png").exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -3,18 +3,29 @@ use std::{
     hash::{Hash, Hasher},
 };
 
+mod locale;
+mod provider;
 mod system;
-mod textarea;
 
+pub use locale::*;
+pub use provider::*;
 pub use system::*;
-// pub use textarea::*;
 
-use crate::arc_string::ArcString;
+use crate::{arc_string::ArcString, TextAlign, TextWrap};
+
+/// Identifies a rasterized glyph, custom [`FontProvider`]s use [`GlyphKey::Custom`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GlyphKey {
+    CosmicText(cosmic_text::CacheKey),
+    Custom(u64),
+}
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct GlyphImage {
-    pub(crate) key: cosmic_text::CacheKey,
+    pub(crate) key: GlyphKey,
     pub(crate) is_emoji: bool,
+    /// the provider the key belongs to, see [`TextSystem::set_provider`]
+    pub(crate) provider: u32,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -30,6 +41,7 @@ pub struct Font {
     pub family: ArcString,
     pub weight: FontWeight,
     pub style: FontStyle,
+    pub features: FontFeatures,
 }
 
 impl Font {
@@ -38,9 +50,21 @@ impl Font {
             family: family.into(),
             weight: FontWeight::default(),
             style: FontStyle::default(),
+            features: FontFeatures::default(),
         }
     }
 
+    /// Turns an OpenType feature on or off, see [`FontFeatures`]
+    pub fn feature(mut self, tag: FontFeatureTag, enabled: bool) -> Self {
+        self.features.set(tag, enabled as u32);
+        self
+    }
+
+    pub fn features(mut self, features: FontFeatures) -> Self {
+        self.features = features;
+        self
+    }
+
     pub fn bold(mut self) -> Self {
         self.weight = FontWeight::BOLD;
         self
@@ -52,6 +76,51 @@ impl Font {
     }
 }
 
+/// A four byte OpenType feature tag like `liga` or `ss01`
+pub type FontFeatureTag = [u8; 4];
+
+/// OpenType feature overrides of a [`Font`], features not listed keep the font's default.
+///
+/// The features are handed to the [`FontProvider`] as is. [`CosmicTextProvider`] ignores them
+/// and shapes with the font's defaults, cosmic-text 0.12 takes no feature overrides
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct FontFeatures(Vec<(FontFeatureTag, u32)>);
+
+impl FontFeatures {
+    /// Sets the value of `tag`, 0 turns the feature off and 1 on, larger values pick an alternate
+    pub fn set(&mut self, tag: FontFeatureTag, value: u32) {
+        // kept sorted so equal sets hash the same regardless of insertion order
+        match self.0.binary_search_by_key(&tag, |(t, _)| *t) {
+            Ok(index) => self.0[index].1 = value,
+            Err(index) => self.0.insert(index, (tag, value)),
+        }
+    }
+
+    pub fn remove(&mut self, tag: FontFeatureTag) {
+        self.0.retain(|(t, _)| *t != tag);
+    }
+
+    pub fn get(&self, tag: FontFeatureTag) -> Option<u32> {
+        self.0
+            .binary_search_by_key(&tag, |(t, _)| *t)
+            .ok()
+            .map(|index| self.0[index].1)
+    }
+
+    /// Whether `tag` is on, `default` if the font decides
+    pub fn is_enabled(&self, tag: FontFeatureTag, default: bool) -> bool {
+        self.get(tag).map_or(default, |value| value != 0)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (FontFeatureTag, u32)> + '_ {
+        self.0.iter().copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
 #[derive(Clone, Copy, Eq, PartialEq, Debug, Hash, Default)]
 pub enum FontStyle {
     #[default]
@@ -106,6 +175,27 @@ impl From<FontWeight> for CosmicWeight {
     }
 }
 
+impl From<TextWrap> for cosmic_text::Wrap {
+    fn from(val: TextWrap) -> Self {
+        match val {
+            TextWrap::None => cosmic_text::Wrap::None,
+            TextWrap::Glyph => cosmic_text::Wrap::Glyph,
+            TextWrap::Word => cosmic_text::Wrap::Word,
+            TextWrap::WordOrGlyph => cosmic_text::Wrap::WordOrGlyph,
+        }
+    }
+}
+
+impl From<TextAlign> for cosmic_text::Align {
+    fn from(val: TextAlign) -> Self {
+        match val {
+            TextAlign::Left => cosmic_text::Align::Left,
+            TextAlign::Right => cosmic_text::Align::Right,
+            TextAlign::Center => cosmic_text::Align::Center,
+        }
+    }
+}
+
 impl From<FontStyle> for CosmicStyle {
     fn from(val: FontStyle) -> Self {
         match val {
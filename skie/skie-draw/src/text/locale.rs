@@ -0,0 +1,185 @@
+use std::borrow::Cow;
+
+use crate::arc_string::ArcString;
+
+/// Digits numbers are shaped with, see [`Locale::digits`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Digits {
+    /// `0123456789`, the digits are left as written
+    #[default]
+    Latin,
+    /// `٠١٢٣٤٥٦٧٨٩`
+    ArabicIndic,
+    /// `۰۱۲۳۴۵۶۷۸۹`, Persian and Pashto
+    ExtendedArabicIndic,
+    /// `०१२३४५६७८९`
+    Devanagari,
+    /// `০১২৩৪৫৬৭৮৯`
+    Bengali,
+    /// `๐๑๒๓๔๕๖๗๘๙`
+    Thai,
+}
+
+impl Digits {
+    fn zero(&self) -> char {
+        match self {
+            Digits::Latin => '0',
+            Digits::ArabicIndic => '\u{0660}',
+            Digits::ExtendedArabicIndic => '\u{06F0}',
+            Digits::Devanagari => '\u{0966}',
+            Digits::Bengali => '\u{09E6}',
+            Digits::Thai => '\u{0E50}',
+        }
+    }
+
+    /// `digit` in this numbering system, other characters are returned as is
+    pub fn map(&self, digit: char) -> char {
+        match digit.to_digit(10) {
+            Some(value) if digit.is_ascii_digit() => {
+                char::from_u32(self.zero() as u32 + value).unwrap_or(digit)
+            }
+            _ => digit,
+        }
+    }
+
+    /// `text` with its ascii digits replaced
+    pub fn substitute<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if *self == Digits::Latin || !text.bytes().any(|b| b.is_ascii_digit()) {
+            return Cow::Borrowed(text);
+        }
+        Cow::Owned(text.chars().map(|c| self.map(c)).collect())
+    }
+}
+
+/// Language hint for [`crate::Text`], a BCP 47 tag like `"ar-EG"`.
+///
+/// Picks the digits numbers are shaped with and the families tried when the text's
+/// family is not installed
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Locale {
+    tag: ArcString,
+    digits: Digits,
+}
+
+impl Locale {
+    pub fn new(tag: impl Into<ArcString>) -> Self {
+        let tag = tag.into();
+        let digits = default_digits(&tag);
+        Self { tag, digits }
+    }
+
+    /// Overrides the digits of the language, [`Digits::Latin`] keeps them as written
+    pub fn digits(mut self, digits: Digits) -> Self {
+        self.digits = digits;
+        self
+    }
+
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    pub fn get_digits(&self) -> Digits {
+        self.digits
+    }
+
+    /// Lowercase primary language subtag, `"ar"` for `"ar-EG"`
+    pub fn language(&self) -> String {
+        subtags(&self.tag).next().unwrap_or_default()
+    }
+
+    /// Families with good coverage of the language, in order of preference
+    pub fn fallback_families(&self) -> &'static [&'static str] {
+        let mut subtags = subtags(&self.tag);
+        let language = subtags.next().unwrap_or_default();
+        let traditional = subtags.any(|tag| matches!(tag.as_str(), "hant" | "tw" | "hk" | "mo"));
+
+        match language.as_str() {
+            "ar" => &["Noto Naskh Arabic", "Noto Sans Arabic", "Amiri"],
+            "fa" | "ps" | "ur" => &["Vazirmatn", "Noto Naskh Arabic", "Noto Nastaliq Urdu"],
+            "he" | "yi" => &["Noto Sans Hebrew", "Arial Hebrew"],
+            "hi" | "mr" | "ne" => &["Noto Sans Devanagari", "Mangal"],
+            "bn" => &["Noto Sans Bengali", "Vrinda"],
+            "th" => &["Noto Sans Thai", "Leelawadee UI"],
+            "ja" => &["Noto Sans CJK JP", "Hiragino Sans", "Yu Gothic"],
+            "ko" => &["Noto Sans CJK KR", "Apple SD Gothic Neo", "Malgun Gothic"],
+            "zh" if traditional => &["Noto Sans CJK TC", "PingFang TC", "Microsoft JhengHei"],
+            "zh" => &["Noto Sans CJK SC", "PingFang SC", "Microsoft YaHei"],
+            _ => &[],
+        }
+    }
+}
+
+impl From<&str> for Locale {
+    fn from(tag: &str) -> Self {
+        Self::new(ArcString::from(tag.to_owned()))
+    }
+}
+
+impl From<ArcString> for Locale {
+    fn from(tag: ArcString) -> Self {
+        Self::new(tag)
+    }
+}
+
+fn subtags(tag: &str) -> impl Iterator<Item = String> + '_ {
+    tag.split(['-', '_']).map(str::to_ascii_lowercase)
+}
+
+/// The default numbering system of the language
+fn default_digits(tag: &str) -> Digits {
+    let mut subtags = subtags(tag);
+    let language = subtags.next().unwrap_or_default();
+
+    match language.as_str() {
+        // the maghreb writes latin digits
+        "ar" if subtags
+            .any(|region| matches!(region.as_str(), "ma" | "dz" | "tn" | "ly" | "eh")) =>
+        {
+            Digits::Latin
+        }
+        "ar" => Digits::ArabicIndic,
+        "fa" | "ps" => Digits::ExtendedArabicIndic,
+        "mr" | "ne" => Digits::Devanagari,
+        "bn" => Digits::Bengali,
+        _ => Digits::Latin,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn languages_pick_their_digits() {
+        assert_eq!(Locale::from("ar-EG").get_digits(), Digits::ArabicIndic);
+        assert_eq!(Locale::from("ar-MA").get_digits(), Digits::Latin);
+        assert_eq!(Locale::from("fa").get_digits(), Digits::ExtendedArabicIndic);
+        assert_eq!(Locale::from("en-US").get_digits(), Digits::Latin);
+        assert_eq!(
+            Locale::from("th").digits(Digits::Thai).get_digits(),
+            Digits::Thai
+        );
+
+        assert_eq!(Digits::ArabicIndic.substitute("v1.20"), "v١.٢٠");
+        assert_eq!(Digits::Devanagari.substitute("42"), "४२");
+        assert!(matches!(Digits::Latin.substitute("42"), Cow::Borrowed(_)));
+        assert!(matches!(
+            Digits::Bengali.substitute("abc"),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn fallback_families_follow_the_script() {
+        assert_eq!(Locale::from("ar-EG").language(), "ar");
+        assert_eq!(
+            Locale::from("zh-Hant-TW").fallback_families()[0],
+            "Noto Sans CJK TC"
+        );
+        assert_eq!(
+            Locale::from("zh_CN").fallback_families()[0],
+            "Noto Sans CJK SC"
+        );
+        assert!(Locale::from("en").fallback_families().is_empty());
+    }
+}
@@ -0,0 +1,851 @@
+use std::{borrow::Cow, ops::Range};
+
+use cosmic_text::{
+    Attrs, Buffer, Command, FontSystem, LayoutGlyph, LayoutRun, Metrics, Shaping, SwashCache,
+    SwashContent,
+};
+
+use crate::{path::Point, FontId, Path, Size, Text, TextOverflow, TextWrap, TextureKind};
+
+use super::GlyphKey;
+
+/// A glyph positioned by [`FontProvider::layout`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShapedGlyph {
+    pub key: GlyphKey,
+    /// pen position on the baseline in canvas pixels
+    pub x: i32,
+    pub y: i32,
+}
+
+/// Distance between baselines in ems, used by [`CosmicTextProvider`]
+pub const LINE_HEIGHT_EM: f32 = 1.4;
+
+/// Size of laid out text, from [`FontProvider::measure`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextMeasure {
+    /// advance of the widest line
+    pub width: f32,
+    pub height: f32,
+    /// visual lines, wrapped lines count separately
+    pub line_count: usize,
+    /// advance of each visual line, empty lines are 0
+    pub line_advances: Vec<f32>,
+    /// whether lines or the end of the last line were cut by the max lines or the max width,
+    /// see [`TextOverflow`]
+    pub truncated: bool,
+}
+
+/// A glyph of a [`GlyphRun`], positions are in canvas pixels
+#[derive(Debug, Clone, PartialEq)]
+pub struct PositionedGlyph {
+    pub key: GlyphKey,
+    /// index of the glyph in its font, 0 when the provider does not report it
+    pub glyph_id: u16,
+    pub font_id: Option<FontId>,
+    /// pen position on the baseline, before pixel snapping
+    pub x: f32,
+    pub y: f32,
+    pub advance: f32,
+    /// byte range of the source text the glyph was shaped from,
+    /// ligatures span several characters. Empty when the provider does not report it
+    pub cluster: Range<usize>,
+}
+
+/// The glyphs of one laid out line, in visual order
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlyphRun {
+    /// index of the source line, wrapped lines share it
+    pub line: usize,
+    /// y of the baseline in canvas pixels
+    pub baseline: f32,
+    pub rtl: bool,
+    pub glyphs: Vec<PositionedGlyph>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RasterizedGlyph<'a> {
+    pub kind: TextureKind,
+    /// offset of the bitmap from the pen position (`top` grows upwards)
+    pub left: i32,
+    pub top: i32,
+    pub size: Size<i32>,
+    pub data: Cow<'a, [u8]>,
+}
+
+/// Shapes and rasterizes text for the canvas.
+///
+/// Install a custom provider with [`crate::TextSystem::new`] or [`crate::TextSystem::set_provider`]
+pub trait FontProvider: Send + Sync + 'static {
+    /// Lays out `text` inside `bounds`, glyph positions are in canvas space
+    fn layout(&mut self, text: &Text, bounds: Size<f32>) -> Vec<ShapedGlyph>;
+
+    /// Lays out `text` like [`FontProvider::layout`], keeping the shaping details of every glyph.
+    ///
+    /// The default puts the glyphs of [`FontProvider::layout`] in a single run,
+    /// advances are the distance to the next glyph and clusters are left empty
+    fn glyph_runs(&mut self, text: &Text, bounds: Size<f32>) -> Vec<GlyphRun> {
+        let shaped = self.layout(text, bounds);
+        let Some(first) = shaped.first() else {
+            return Vec::new();
+        };
+
+        let glyphs = shaped
+            .iter()
+            .enumerate()
+            .map(|(i, glyph)| PositionedGlyph {
+                key: glyph.key,
+                glyph_id: 0,
+                font_id: None,
+                x: glyph.x as f32,
+                y: glyph.y as f32,
+                advance: shaped
+                    .get(i + 1)
+                    .filter(|next| next.y == glyph.y)
+                    .map_or(0.0, |next| (next.x - glyph.x) as f32),
+                cluster: 0..0,
+            })
+            .collect();
+
+        vec![GlyphRun {
+            line: 0,
+            baseline: first.y as f32,
+            rtl: false,
+            glyphs,
+        }]
+    }
+
+    /// Measures `text` laid out inside `bounds` like [`FontProvider::layout`] would.
+    ///
+    /// The default measures the runs of [`FontProvider::glyph_runs`] with lines
+    /// [`LINE_HEIGHT_EM`] apart, lines without glyphs are not counted
+    fn measure(&mut self, text: &Text, bounds: Size<f32>) -> TextMeasure {
+        let line_advances = self
+            .glyph_runs(text, bounds)
+            .iter()
+            .map(|run| {
+                let start = run
+                    .glyphs
+                    .iter()
+                    .map(|glyph| glyph.x)
+                    .fold(f32::MAX, f32::min);
+                let end = run
+                    .glyphs
+                    .iter()
+                    .map(|glyph| glyph.x + glyph.advance)
+                    .fold(f32::MIN, f32::max);
+                (end - start).max(0.0)
+            })
+            .collect::<Vec<_>>();
+
+        TextMeasure {
+            width: line_advances.iter().copied().fold(0.0, f32::max),
+            height: line_advances.len() as f32 * text.size * LINE_HEIGHT_EM,
+            line_count: line_advances.len(),
+            line_advances,
+            truncated: false,
+        }
+    }
+
+    /// Rasterizes a glyph produced by [`FontProvider::layout`].
+    /// Mask glyphs are one byte per pixel, color glyphs rgba8
+    fn rasterize(&mut self, key: &GlyphKey) -> Option<RasterizedGlyph<'_>>;
+
+    /// Outline of a glyph relative to its pen position, y grows downwards.
+    /// `None` for glyphs without one, the default has no outlines
+    fn outline(&mut self, _key: &GlyphKey) -> Option<Path> {
+        None
+    }
+}
+
+/// The default provider backed by cosmic-text and swash
+pub struct CosmicTextProvider {
+    pub font_system: FontSystem,
+    pub swash_cache: SwashCache,
+}
+
+impl Default for CosmicTextProvider {
+    fn default() -> Self {
+        Self {
+            font_system: FontSystem::new(),
+            swash_cache: SwashCache::new(),
+        }
+    }
+}
+
+/// Text handed to cosmic-text, with the digits of the [`Text`]'s locale substituted
+struct ShapedText<'a> {
+    text: Cow<'a, str>,
+    /// source byte offset of every shaped byte offset, `None` when nothing was substituted
+    source_offsets: Option<Vec<usize>>,
+}
+
+impl<'a> ShapedText<'a> {
+    fn new(text: &'a Text) -> Self {
+        let digits = text.locale.as_ref().map(|locale| locale.get_digits());
+        let shaped = match digits {
+            Some(digits) => digits.substitute(&text.text),
+            None => Cow::Borrowed(&*text.text),
+        };
+
+        let source_offsets = matches!(shaped, Cow::Owned(_)).then(|| {
+            let mut offsets = Vec::with_capacity(shaped.len() + 1);
+            for ((source, c), shaped_c) in text.text.char_indices().zip(shaped.chars()) {
+                offsets.extend(std::iter::repeat_n(source, shaped_c.len_utf8()));
+                debug_assert!(c.is_ascii_digit() || c == shaped_c);
+            }
+            offsets.push(text.text.len());
+            offsets
+        });
+
+        Self {
+            text: shaped,
+            source_offsets,
+        }
+    }
+
+    fn source_offset(&self, offset: usize) -> usize {
+        self.source_offsets
+            .as_ref()
+            .map_or(offset, |offsets| offsets[offset])
+    }
+
+    /// The shaped text before `end` with the trailing whitespace trimmed
+    fn kept(&self, end: usize) -> &str {
+        self.text[..end].trim_end()
+    }
+
+    /// Keeps the text before the shaped offset `end` followed by [`ELLIPSIS`],
+    /// which maps to the rest of the source
+    fn ellipsize(&mut self, end: usize, source_len: usize) {
+        let kept = self.kept(end).len();
+        let mut offsets = (0..=kept)
+            .map(|offset| self.source_offset(offset))
+            .collect::<Vec<_>>();
+        let cut = offsets[kept];
+        offsets.extend(std::iter::repeat_n(cut, ELLIPSIS.len() - 1));
+        offsets.push(source_len);
+
+        self.text = Cow::Owned(format!("{}{}", &self.text[..kept], ELLIPSIS));
+        self.source_offsets = Some(offsets);
+    }
+}
+
+/// Appended by [`TextOverflow::Ellipsis`]
+const ELLIPSIS: &str = "\u{2026}";
+
+/// A [`Text`] shaped by [`CosmicTextProvider::lay_out`]
+struct LaidOutText<'a> {
+    shaped: ShapedText<'a>,
+    buffer: Buffer,
+    /// runs of `buffer` that are drawn
+    line_limit: usize,
+    truncated: bool,
+}
+
+impl CosmicTextProvider {
+    /// Shapes `text` and calls `f` with every glyph of every run, in canvas space
+    fn shape(
+        &mut self,
+        text: &Text,
+        bounds: Size<f32>,
+        mut f: impl FnMut(&ShapedText, &LayoutRun, LayoutGlyph),
+    ) {
+        let laid_out = self.lay_out(text, bounds);
+        for run in laid_out.buffer.layout_runs().take(laid_out.line_limit) {
+            for glyph in run.glyphs.iter().cloned() {
+                f(&laid_out.shaped, &run, glyph);
+            }
+        }
+    }
+
+    /// Shapes `text` cutting it to its line limit, see [`TextOverflow`]
+    fn lay_out<'a>(&mut self, text: &'a Text, bounds: Size<f32>) -> LaidOutText<'a> {
+        let mut shaped = ShapedText::new(text);
+        let mut buffer = self.buffer(text, &shaped.text, bounds);
+        let line_limit = text.line_limit(buffer.metrics().line_height);
+        let truncated = overflows(text, &buffer, line_limit);
+
+        if truncated && text.overflow == TextOverflow::Ellipsis && !shaped.text.is_empty() {
+            // the longest start of the text that fits with the ellipsis
+            let cuts = shaped
+                .text
+                .char_indices()
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>();
+            let (mut low, mut high) = (0, cuts.len() - 1);
+            while low < high {
+                let mid = (low + high).div_ceil(2);
+                let content = format!("{}{}", shaped.kept(cuts[mid]), ELLIPSIS);
+                if overflows(text, &self.buffer(text, &content, bounds), line_limit) {
+                    high = mid - 1;
+                } else {
+                    low = mid;
+                }
+            }
+
+            shaped.ellipsize(cuts[low], text.text.len());
+            buffer = self.buffer(text, &shaped.text, bounds);
+        }
+
+        LaidOutText {
+            shaped,
+            buffer,
+            line_limit: line_limit.unwrap_or(usize::MAX),
+            truncated,
+        }
+    }
+
+    /// Family of the text if it is installed, else the first installed fallback of its locale
+    fn resolve_family<'a>(&self, text: &'a Text) -> &'a str {
+        let family = &*text.font.family;
+        let Some(locale) = &text.locale else {
+            return family;
+        };
+
+        let installed = |name: &str| {
+            self.font_system.db().faces().any(|face| {
+                face.families
+                    .iter()
+                    .any(|(installed, _)| installed.eq_ignore_ascii_case(name))
+            })
+        };
+
+        if installed(family) {
+            return family;
+        }
+        locale
+            .fallback_families()
+            .iter()
+            .copied()
+            .find(|name| installed(name))
+            .unwrap_or(family)
+    }
+
+    /// `content` shaped inside `bounds` with the style of `text`
+    fn buffer(&mut self, text: &Text, content: &str, bounds: Size<f32>) -> Buffer {
+        let metrics = Metrics::new(text.size, text.size * LINE_HEIGHT_EM);
+        let mut buffer = Buffer::new(&mut self.font_system, metrics);
+        let height = match text.overflow {
+            TextOverflow::Clip => Some(text.max_height.unwrap_or(bounds.height)),
+            TextOverflow::Visible | TextOverflow::Ellipsis | TextOverflow::Fade => None,
+        };
+        buffer.set_size(
+            &mut self.font_system,
+            Some(text.max_width.unwrap_or(bounds.width)),
+            height,
+        );
+        buffer.set_wrap(&mut self.font_system, text.wrap.into());
+
+        let attrs = Attrs::new()
+            .style(text.font.style.into())
+            .weight(text.font.weight.into())
+            .family(cosmic_text::Family::Name(self.resolve_family(text)));
+
+        // cosmic-text shapes with each font's default features, see [`crate::FontFeatures`]
+        buffer.set_text(&mut self.font_system, content, attrs, Shaping::Advanced);
+        if text.max_width.is_some() {
+            for line in &mut buffer.lines {
+                line.set_align(Some(text.align.into()));
+            }
+        }
+        buffer.shape_until_scroll(&mut self.font_system, false);
+        buffer
+    }
+}
+
+/// Whether `buffer` has more runs than `line_limit`, or lines wider than the max width
+/// when they only break at newlines
+fn overflows(text: &Text, buffer: &Buffer, line_limit: Option<usize>) -> bool {
+    let max_width = text.max_width.filter(|_| text.wrap == TextWrap::None);
+    let mut lines = 0;
+    for run in buffer.layout_runs() {
+        if max_width.is_some_and(|width| run.line_w > width + 0.5) {
+            return true;
+        }
+        lines += 1;
+    }
+    line_limit.is_some_and(|limit| lines > limit)
+}
+
+/// Byte offset of every line, split the way cosmic-text splits them
+fn line_starts(text: &str) -> Vec<usize> {
+    let mut starts = vec![0];
+    let mut rest = text;
+    while let Some(i) = rest.find(['\r', '\n']) {
+        let ending = ["\r\n", "\n\r", "\n", "\r"]
+            .into_iter()
+            .find(|ending| rest[i..].starts_with(ending))
+            .map_or(1, str::len);
+        let start = text.len() - rest.len() + i + ending;
+        starts.push(start);
+        rest = &text[start..];
+    }
+    starts
+}
+
+impl FontProvider for CosmicTextProvider {
+    fn layout(&mut self, text: &Text, bounds: Size<f32>) -> Vec<ShapedGlyph> {
+        let mut glyphs = Vec::new();
+
+        self.shape(text, bounds, |_, run, glyph| {
+            let scale = 1.0;
+            let physical_glyph = glyph.physical((text.pos.x, text.pos.y), scale);
+
+            glyphs.push(ShapedGlyph {
+                key: GlyphKey::CosmicText(physical_glyph.cache_key),
+                x: physical_glyph.x,
+                y: run.line_y as i32 + physical_glyph.y,
+            });
+        });
+
+        glyphs
+    }
+
+    fn glyph_runs(&mut self, text: &Text, bounds: Size<f32>) -> Vec<GlyphRun> {
+        let mut runs: Vec<GlyphRun> = Vec::new();
+        // runs only see their own line
+        let mut line_starts = None;
+
+        self.shape(text, bounds, |shaped, run, glyph| {
+            let line_starts = line_starts.get_or_insert_with(|| self::line_starts(&shaped.text));
+            let baseline = text.pos.y + run.line_y;
+            let is_new_run = runs
+                .last()
+                .is_none_or(|last| last.line != run.line_i || last.baseline != baseline);
+            if is_new_run {
+                runs.push(GlyphRun {
+                    line: run.line_i,
+                    baseline,
+                    rtl: run.rtl,
+                    glyphs: Vec::new(),
+                });
+            }
+
+            let offset = line_starts.get(run.line_i).copied().unwrap_or_default();
+            let physical_glyph = glyph.physical((text.pos.x, text.pos.y), 1.0);
+
+            if let Some(current) = runs.last_mut() {
+                current.glyphs.push(PositionedGlyph {
+                    key: GlyphKey::CosmicText(physical_glyph.cache_key),
+                    glyph_id: glyph.glyph_id,
+                    font_id: Some(glyph.font_id),
+                    x: text.pos.x + glyph.x + glyph.x_offset * glyph.font_size,
+                    y: baseline + glyph.y - glyph.y_offset * glyph.font_size,
+                    advance: glyph.w,
+                    cluster: shaped.source_offset(offset + glyph.start)
+                        ..shaped.source_offset(offset + glyph.end),
+                });
+            }
+        });
+
+        runs
+    }
+
+    fn measure(&mut self, text: &Text, bounds: Size<f32>) -> TextMeasure {
+        let laid_out = self.lay_out(text, bounds);
+        let line_advances = laid_out
+            .buffer
+            .layout_runs()
+            .take(laid_out.line_limit)
+            .map(|run| {
+                let glyphs = run.glyphs;
+                let start = glyphs.iter().map(|glyph| glyph.x).fold(f32::MAX, f32::min);
+                let end = glyphs
+                    .iter()
+                    .map(|glyph| glyph.x + glyph.w)
+                    .fold(f32::MIN, f32::max);
+                (end - start).max(0.0)
+            })
+            .collect::<Vec<_>>();
+
+        TextMeasure {
+            width: line_advances.iter().copied().fold(0.0, f32::max),
+            height: line_advances.len() as f32 * laid_out.buffer.metrics().line_height,
+            line_count: line_advances.len(),
+            line_advances,
+            truncated: laid_out.truncated,
+        }
+    }
+
+    fn rasterize(&mut self, key: &GlyphKey) -> Option<RasterizedGlyph<'_>> {
+        let GlyphKey::CosmicText(cache_key) = key else {
+            return None;
+        };
+
+        let image = self
+            .swash_cache
+            .get_image(&mut self.font_system, *cache_key)
+            .as_ref()?;
+
+        let kind = match image.content {
+            SwashContent::Color => TextureKind::Color,
+            SwashContent::Mask => TextureKind::Mask,
+            // we dont support it for now
+            SwashContent::SubpixelMask => TextureKind::Mask,
+        };
+
+        Some(RasterizedGlyph {
+            kind,
+            left: image.placement.left,
+            top: image.placement.top,
+            size: Size::new(image.placement.width as i32, image.placement.height as i32),
+            data: Cow::Borrowed(&image.data),
+        })
+    }
+
+    fn outline(&mut self, key: &GlyphKey) -> Option<Path> {
+        let GlyphKey::CosmicText(cache_key) = key else {
+            return None;
+        };
+
+        let commands = self
+            .swash_cache
+            .get_outline_commands(&mut self.font_system, *cache_key)?;
+
+        // font outlines grow upwards from the baseline
+        let point = |x: f32, y: f32| Point::new(x, -y);
+        let mut builder = Path::builder();
+        let mut open = false;
+        for command in commands {
+            match command {
+                Command::MoveTo(to) => {
+                    if open {
+                        builder.end(false);
+                    }
+                    builder.begin(point(to.x, to.y));
+                    open = true;
+                }
+                Command::LineTo(to) => builder.line_to(point(to.x, to.y)),
+                Command::QuadTo(ctrl, to) => {
+                    builder.quadratic_to(point(ctrl.x, ctrl.y), point(to.x, to.y))
+                }
+                Command::CurveTo(ctrl1, ctrl2, to) => builder.cubic_to(
+                    point(ctrl1.x, ctrl1.y),
+                    point(ctrl2.x, ctrl2.y),
+                    point(to.x, to.y),
+                ),
+                Command::Close => {
+                    if open {
+                        builder.close();
+                    }
+                    open = false;
+                }
+            }
+        }
+        if open {
+            builder.end(false);
+        }
+
+        Some(builder.build())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Digits, FontFeatures, Locale, TextSystem, TextWrap};
+
+    // a fixed advance monospace provider
+    struct StubProvider;
+
+    impl FontProvider for StubProvider {
+        fn layout(&mut self, text: &Text, _bounds: Size<f32>) -> Vec<ShapedGlyph> {
+            text.text
+                .chars()
+                .enumerate()
+                .map(|(i, c)| ShapedGlyph {
+                    key: GlyphKey::Custom(c as u64),
+                    x: text.pos.x as i32 + i as i32 * 8,
+                    y: text.pos.y as i32,
+                })
+                .collect()
+        }
+
+        fn rasterize(&mut self, key: &GlyphKey) -> Option<RasterizedGlyph<'_>> {
+            let GlyphKey::Custom(id) = key else {
+                return None;
+            };
+
+            (*id != ' ' as u64).then(|| RasterizedGlyph {
+                kind: TextureKind::Mask,
+                left: 0,
+                top: 8,
+                size: Size::new(8, 8),
+                data: Cow::Owned(vec![255; 64]),
+            })
+        }
+    }
+
+    #[test]
+    fn custom_provider_is_used() {
+        let text_system = TextSystem::new(StubProvider);
+        let text = Text::new("a b").pos(10.0, 20.0);
+
+        let glyphs = text_system.write(|provider| provider.layout(&text, Size::new(100.0, 100.0)));
+
+        assert_eq!(
+            glyphs.iter().map(|g| (g.x, g.y)).collect::<Vec<_>>(),
+            vec![(10, 20), (18, 20), (26, 20)]
+        );
+
+        let rasterized = text_system.write(|provider| {
+            glyphs
+                .iter()
+                .filter(|g| provider.rasterize(&g.key).is_some())
+                .count()
+        });
+        assert_eq!(rasterized, 2);
+    }
+
+    #[test]
+    fn default_glyph_runs_follow_layout() {
+        let runs = StubProvider.glyph_runs(&Text::new("ab").pos(4.0, 20.0), Size::new(1.0, 1.0));
+
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].baseline, 20.0);
+        assert_eq!(
+            runs[0]
+                .glyphs
+                .iter()
+                .map(|g| (g.x, g.advance))
+                .collect::<Vec<_>>(),
+            vec![(4.0, 8.0), (12.0, 0.0)]
+        );
+    }
+
+    #[test]
+    fn glyph_runs_map_glyphs_to_source_bytes() {
+        let mut provider = CosmicTextProvider::default();
+        let text = Text::new("hé\r\nyo").size_px(20.0).pos(5.0, 0.0);
+        let runs = provider.glyph_runs(&text, Size::new(500.0, 200.0));
+        if runs.is_empty() {
+            eprintln!("skipping glyph_runs_map_glyphs_to_source_bytes: no fonts installed");
+            return;
+        }
+
+        assert_eq!(runs.len(), 2);
+        assert!(runs[1].baseline > runs[0].baseline);
+        let clusters = runs
+            .iter()
+            .flat_map(|run| run.glyphs.iter().map(|g| &text.text[g.cluster.clone()]))
+            .collect::<Vec<_>>();
+        assert_eq!(clusters, ["h", "é", "y", "o"]);
+
+        let first = &runs[0].glyphs;
+        assert!((first[0].x + first[0].advance - first[1].x).abs() < 1e-3);
+        assert_eq!(
+            provider.layout(&text, Size::new(500.0, 200.0))[0].key,
+            first[0].key
+        );
+    }
+
+    #[test]
+    fn locale_digits_map_clusters_back_to_source() {
+        let mut provider = CosmicTextProvider::default();
+        let text = Text::new("a12").size_px(20.0).locale("ar-EG");
+        let runs = provider.glyph_runs(&text, Size::new(500.0, 200.0));
+        if runs.is_empty() {
+            eprintln!("skipping locale_digits_map_clusters_back_to_source: no fonts installed");
+            return;
+        }
+
+        let mut clusters = runs
+            .iter()
+            .flat_map(|run| run.glyphs.iter().map(|g| g.cluster.clone()))
+            .collect::<Vec<_>>();
+        clusters.sort_by_key(|cluster| cluster.start);
+        assert_eq!(clusters, [0..1, 1..2, 2..3]);
+
+        let latin = Text::new("a12")
+            .size_px(20.0)
+            .locale(Locale::from("ar-EG").digits(Digits::Latin));
+        let latin_keys = provider.layout(&latin, Size::new(500.0, 200.0));
+        let arabic_keys = provider.layout(&text, Size::new(500.0, 200.0));
+        assert_ne!(latin_keys[1..], arabic_keys[1..]);
+    }
+
+    #[test]
+    fn max_width_wraps_into_lines() {
+        let mut provider = CosmicTextProvider::default();
+        let bounds = Size::new(500.0, 200.0);
+        let text = Text::new("one two three four").size_px(20.0);
+        if provider.glyph_runs(&text, bounds).is_empty() {
+            eprintln!("skipping max_width_wraps_into_lines: no fonts installed");
+            return;
+        }
+
+        assert_eq!(provider.measure(&text, bounds).line_count, 1);
+
+        let wrapped = provider.measure(&text.clone().max_width(60.0), bounds);
+        assert!(wrapped.line_count > 1);
+        assert!(wrapped.width <= 60.0);
+
+        let unwrapped = text.clone().max_width(60.0).wrap(TextWrap::None);
+        assert_eq!(provider.measure(&unwrapped, bounds).line_count, 1);
+
+        let clipped = text
+            .max_width(60.0)
+            .max_height(20.0 * LINE_HEIGHT_EM)
+            .overflow(TextOverflow::Clip);
+        assert_eq!(provider.measure(&clipped, bounds).line_count, 1);
+    }
+
+    #[test]
+    fn max_lines_truncate_with_an_ellipsis() {
+        let mut provider = CosmicTextProvider::default();
+        let bounds = Size::new(500.0, 200.0);
+        let text = Text::new("one two three four five six")
+            .size_px(20.0)
+            .max_width(80.0);
+        if provider.glyph_runs(&text, bounds).is_empty() {
+            eprintln!("skipping max_lines_truncate_with_an_ellipsis: no fonts installed");
+            return;
+        }
+
+        let full = provider.measure(&text, bounds);
+        assert!(full.line_count > 2 && !full.truncated);
+
+        let faded = provider.measure(
+            &text.clone().max_lines(2).overflow(TextOverflow::Fade),
+            bounds,
+        );
+        assert_eq!(faded.line_count, 2);
+        assert!(faded.truncated);
+
+        let ellipsis = text.clone().max_lines(2).overflow(TextOverflow::Ellipsis);
+        let measure = provider.measure(&ellipsis, bounds);
+        assert_eq!(measure.line_count, 2);
+        assert!(measure.width <= 80.0);
+
+        // the ellipsis stands for the rest of the source
+        let runs = provider.glyph_runs(&ellipsis, bounds);
+        let last = runs.last().unwrap().glyphs.last().unwrap();
+        assert_eq!(last.cluster.end, text.get_text().len());
+        assert!(last.cluster.start < text.get_text().len());
+
+        // a single line label is cut at its width
+        let label = Text::new("a label much wider than its box")
+            .size_px(20.0)
+            .max_width(100.0)
+            .wrap(TextWrap::None)
+            .overflow(TextOverflow::Ellipsis);
+        let measure = provider.measure(&label, bounds);
+        assert_eq!(measure.line_count, 1);
+        assert!(measure.truncated && measure.width <= 100.0);
+
+        let short = label.clone().text("short".into());
+        assert!(!provider.measure(&short, bounds).truncated);
+    }
+
+    #[test]
+    fn measure_counts_lines_and_advances() {
+        let mut provider = CosmicTextProvider::default();
+        let text = Text::new("wide line\n\nab").size_px(20.0).pos(5.0, 0.0);
+        let bounds = Size::new(500.0, 200.0);
+        if provider.glyph_runs(&text, bounds).is_empty() {
+            eprintln!("skipping measure_counts_lines_and_advances: no fonts installed");
+            return;
+        }
+
+        let measure = provider.measure(&text, bounds);
+        assert_eq!(measure.line_count, 3);
+        assert_eq!(measure.line_advances.len(), 3);
+        assert_eq!(measure.line_advances[1], 0.0);
+        assert!(measure.line_advances[0] > measure.line_advances[2]);
+        assert_eq!(measure.width, measure.line_advances[0]);
+        assert!((measure.height - 3.0 * 20.0 * LINE_HEIGHT_EM).abs() < 1e-3);
+
+        let runs = provider.glyph_runs(&text, bounds);
+        let last = runs[0].glyphs.last().unwrap();
+        let first = &runs[0].glyphs[0];
+        assert!((last.x + last.advance - first.x - measure.width).abs() < 1e-3);
+    }
+
+    #[test]
+    fn outlines_sit_on_the_baseline() {
+        let mut provider = CosmicTextProvider::default();
+        let text = Text::new("o").size_px(40.0);
+        let runs = provider.glyph_runs(&text, Size::new(500.0, 200.0));
+        let Some(glyph) = runs.first().and_then(|run| run.glyphs.first()) else {
+            eprintln!("skipping outlines_sit_on_the_baseline: no fonts installed");
+            return;
+        };
+
+        let outline = provider.outline(&glyph.key).unwrap();
+        // the outer and inner ring
+        assert_eq!(outline.contours().count(), 2);
+
+        let bounds = outline
+            .contours()
+            .map(|contour| contour.bounds())
+            .reduce(|a, b| a.union(&b))
+            .unwrap();
+        assert!(bounds.max().y.abs() < 2.0);
+        assert!(bounds.min().y < -10.0);
+        assert!(bounds.min().x >= 0.0 && bounds.max().x <= glyph.advance);
+
+        assert!(StubProvider.outline(&GlyphKey::Custom(1)).is_none());
+    }
+
+    #[test]
+    fn font_features_ignore_insertion_order() {
+        let mut a = FontFeatures::default();
+        a.set(*b"liga", 0);
+        a.set(*b"ss01", 1);
+
+        let mut b = FontFeatures::default();
+        b.set(*b"ss01", 1);
+        b.set(*b"liga", 1);
+        b.set(*b"liga", 0);
+
+        assert_eq!(a, b);
+        assert!(!a.is_enabled(*b"liga", true));
+        assert!(a.is_enabled(*b"kern", true));
+
+        b.remove(*b"liga");
+        assert_eq!(b.iter().collect::<Vec<_>>(), [(*b"ss01", 1)]);
+    }
+
+    #[test]
+    fn font_features_keep_font_fallback() {
+        let mut provider = CosmicTextProvider::default();
+        let plain = Text::new("a\u{4e2d}").size_px(20.0);
+        let glyphs = provider.layout(&plain, Size::new(500.0, 100.0));
+        if glyphs.is_empty() {
+            eprintln!("skipping font_features_keep_font_fallback: no fonts installed");
+            return;
+        }
+
+        let toggled = plain
+            .clone()
+            .font_feature(*b"liga", false)
+            .font_feature(*b"kern", false);
+        let keys = |glyphs: Vec<ShapedGlyph>| glyphs.into_iter().map(|g| g.key).collect::<Vec<_>>();
+        assert_eq!(
+            keys(provider.layout(&toggled, Size::new(500.0, 100.0))),
+            keys(glyphs)
+        );
+    }
+
+    #[test]
+    fn set_provider_replaces_existing() {
+        let text_system = TextSystem::default();
+        text_system.set_provider(StubProvider);
+
+        let glyphs =
+            text_system.write(|provider| provider.layout(&Text::new("ab"), Size::new(1.0, 1.0)));
+        assert_eq!(glyphs[1].key, GlyphKey::Custom('b' as u64));
+    }
+
+    #[test]
+    fn replaced_providers_dont_share_atlas_glyphs() {
+        let text_system = TextSystem::new(StubProvider);
+        let key = GlyphKey::Custom('a' as u64);
+        let before = text_system.glyph_image(key, false);
+        assert_eq!(before, text_system.glyph_image(key, false));
+
+        text_system.set_provider(StubProvider);
+        assert_ne!(before, text_system.glyph_image(key, false));
+    }
+}
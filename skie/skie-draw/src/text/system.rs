@@ -1,34 +1,106 @@
-use cosmic_text::{FontSystem as CosmisTextFontSystem, SwashCache};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU32, Ordering};
+
 use parking_lot::RwLock;
 
-#[derive(Default)]
-pub struct TextSystem(RwLock<TextSystemState>);
+use super::provider::{CosmicTextProvider, FontProvider};
+use super::{Font, GlyphImage, GlyphKey};
+use crate::{arc_string::ArcString, paint::SkieAtlas, AtlasKey, IsZero, Size, Text};
+
+// one x position in each subpixel bin the glyph cache keys glyphs by
+const SUBPIXEL_OFFSETS: [f32; 4] = [0.0, 0.25, 0.5, 0.75];
 
-impl TextSystem {}
+pub struct TextSystem {
+    provider: RwLock<Box<dyn FontProvider>>,
+    /// counts the replaced providers, glyph keys are only unique within one provider
+    generation: AtomicU32,
+}
 
-pub struct TextSystemState {
-    pub font_system: CosmisTextFontSystem,
-    pub swash_cache: SwashCache,
+impl Default for TextSystem {
+    fn default() -> Self {
+        Self::new(CosmicTextProvider::default())
+    }
 }
 
 impl TextSystem {
-    pub fn read<R>(&self, f: impl FnOnce(&TextSystemState) -> R) -> R {
-        let state = self.0.read();
-        f(&state)
+    pub fn new(provider: impl FontProvider) -> Self {
+        Self {
+            provider: RwLock::new(Box::new(provider)),
+            generation: AtomicU32::new(0),
+        }
     }
 
-    pub fn write<R>(&self, f: impl FnOnce(&mut TextSystemState) -> R) -> R {
-        let mut state = self.0.write();
-        f(&mut state)
+    /// Replaces the font provider. Glyphs already uploaded to an atlas are kept, but the
+    /// new provider's glyphs never reuse them even when their [`GlyphKey`]s are equal
+    pub fn set_provider(&self, provider: impl FontProvider) {
+        let mut state = self.provider.write();
+        *state = Box::new(provider);
+        self.generation.fetch_add(1, Ordering::Relaxed);
     }
-}
 
-impl Default for TextSystemState {
-    fn default() -> Self {
-        let font_system = CosmisTextFontSystem::new();
-        Self {
-            font_system,
-            swash_cache: SwashCache::new(),
+    pub fn read<R>(&self, f: impl FnOnce(&dyn FontProvider) -> R) -> R {
+        let state = self.provider.read();
+        f(state.as_ref())
+    }
+
+    pub fn write<R>(&self, f: impl FnOnce(&mut dyn FontProvider) -> R) -> R {
+        let mut state = self.provider.write();
+        f(state.as_mut())
+    }
+
+    /// Atlas entry of a glyph rasterized by the current provider
+    pub(crate) fn glyph_image(&self, key: GlyphKey, is_emoji: bool) -> GlyphImage {
+        GlyphImage {
+            key,
+            is_emoji,
+            provider: self.generation.load(Ordering::Relaxed),
         }
     }
+
+    /// Rasterizes each character of `charset` at each of the `sizes`, in physical pixels, and
+    /// at every horizontal subpixel offset, then uploads the glyphs to `atlas` so the first
+    /// frame drawing them doesnt stall wherever they land. Returns the number of glyph images
+    pub fn prewarm(
+        &self,
+        atlas: &SkieAtlas,
+        font: &Font,
+        sizes: &[f32],
+        charset: impl Into<ArcString>,
+    ) -> usize {
+        let charset = charset.into();
+        let mut uploaded = HashSet::new();
+
+        self.write(|provider| {
+            for &size in sizes {
+                // large enough to keep any single glyph on one line
+                let bounds = Size::new(size * 4.0, size * 4.0);
+
+                for c in charset.chars() {
+                    for offset in SUBPIXEL_OFFSETS {
+                        let text = Text::new(c.to_string())
+                            .font(font.clone())
+                            .size_px(size)
+                            .pos(offset, 0.0);
+
+                        for glyph in provider.layout(&text, bounds) {
+                            let Some(image) = provider.rasterize(&glyph.key) else {
+                                continue;
+                            };
+
+                            if image.size.is_zero() {
+                                continue;
+                            }
+
+                            let key =
+                                AtlasKey::from(self.glyph_image(glyph.key, image.kind.is_color()));
+                            atlas.get_or_insert(&key, || (image.size, image.data));
+                            uploaded.insert(key);
+                        }
+                    }
+                }
+            }
+        });
+
+        uploaded.len()
+    }
 }
@@ -54,6 +54,34 @@ where
         self.bottom_right = v;
         self
     }
+
+    /// sets both top corners
+    pub fn with_top(mut self, v: T) -> Self {
+        self.top_left = v.clone();
+        self.top_right = v;
+        self
+    }
+
+    /// sets both bottom corners
+    pub fn with_bottom(mut self, v: T) -> Self {
+        self.bottom_left = v.clone();
+        self.bottom_right = v;
+        self
+    }
+
+    /// sets both left corners
+    pub fn with_left(mut self, v: T) -> Self {
+        self.top_left = v.clone();
+        self.bottom_left = v;
+        self
+    }
+
+    /// sets both right corners
+    pub fn with_right(mut self, v: T) -> Self {
+        self.top_right = v.clone();
+        self.bottom_right = v;
+        self
+    }
 }
 
 impl<T> Corners<T>
@@ -1,4 +1,4 @@
-use super::Vec2;
+use super::{Lerp, Vec2};
 use std::ops::Mul;
 
 #[repr(C)]
@@ -74,6 +74,39 @@ impl Mat3 {
         }
     }
 
+    /// Shears x by `sx * y` and y by `sy * x`, the factors are the tangents of the skew angles
+    #[inline]
+    pub fn from_skew(sx: f32, sy: f32) -> Self {
+        Self {
+            #[rustfmt::skip]
+            data: [
+                1.0, sx, 0.0,
+                sy, 1.0, 0.0,
+                0.0, 0.0, 1.0
+            ],
+        }
+    }
+
+    /// From the `a b c d e f` of SVG and the web canvas `matrix()`, mapping a point to
+    /// `(a * x + c * y + e, b * x + d * y + f)`
+    #[inline]
+    pub fn from_affine(a: f32, b: f32, c: f32, d: f32, e: f32, f: f32) -> Self {
+        Self {
+            #[rustfmt::skip]
+            data: [
+                a, c, e,
+                b, d, f,
+                0.0, 0.0, 1.0
+            ],
+        }
+    }
+
+    /// The `[a, b, c, d, e, f]` of [`Mat3::from_affine`]
+    pub fn to_affine(&self) -> [f32; 6] {
+        let m = &self.data;
+        [m[0], m[3], m[1], m[4], m[2], m[5]]
+    }
+
     #[inline]
     pub fn translate(&mut self, dx: f32, dy: f32) -> &mut Self {
         *self = Self::from_translation(dx, dy) * *self;
@@ -102,6 +135,12 @@ impl Mat3 {
         self
     }
 
+    #[inline]
+    pub fn skew(&mut self, sx: f32, sy: f32) -> &mut Self {
+        *self = Self::from_skew(sx, sy) * *self;
+        self
+    }
+
     #[inline]
     pub fn scale_x(&mut self, sx: f32) -> &mut Self {
         self.scale(sx, 1.)
@@ -162,6 +201,12 @@ impl Mat3 {
         self == &Self::IDENTITY
     }
 
+    /// Whether axis aligned rects stay axis aligned, true without rotation or skew
+    pub fn is_axis_aligned(&self) -> bool {
+        let m = &self.data;
+        m[1] == 0.0 && m[3] == 0.0
+    }
+
     /// Constructs an orthographic projection matrix
     pub fn ortho(top: f32, left: f32, bottom: f32, right: f32) -> Self {
         let scale_x = 2.0 / (right - left);
@@ -198,6 +243,16 @@ pub fn mat3() -> Mat3 {
     Mat3::new()
 }
 
+impl Lerp for Mat3 {
+    /// Interpolates each component, fine for translations and scales.
+    /// Large rotations will shear midway
+    fn lerp(&self, to: &Self, t: f32) -> Self {
+        Self {
+            data: std::array::from_fn(|i| self.data[i].lerp(&to.data[i], t)),
+        }
+    }
+}
+
 impl Default for Mat3 {
     fn default() -> Self {
         Self::new()
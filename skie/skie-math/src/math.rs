@@ -2,15 +2,19 @@ pub mod geometry;
 pub mod mat3;
 pub mod rect;
 pub mod size;
+pub mod timeline;
 pub mod traits;
 pub mod vec2;
+pub mod viewport;
 
 pub use geometry::*;
 pub use mat3::*;
 pub use rect::*;
 pub use size::*;
+pub use timeline::*;
 pub use traits::*;
 pub use vec2::*;
+pub use viewport::*;
 
 #[cfg(test)]
 mod tests {
@@ -79,6 +83,22 @@ mod tests {
             );
         }
 
+        #[test]
+        fn skew_and_affine() {
+            let mut transform = Mat3::from_translation(5.0, 0.0);
+            transform.skew(0.5, 0.0);
+            assert_eq!(transform * vec2(0.0, 10.0), vec2(10.0, 10.0));
+
+            let affine = Mat3::from_affine(2.0, 0.0, 1.0, 3.0, 4.0, 5.0);
+            assert_eq!(affine * vec2(1.0, 1.0), vec2(7.0, 8.0));
+            assert_eq!(affine.to_affine(), [2.0, 0.0, 1.0, 3.0, 4.0, 5.0]);
+            assert!(!affine.is_axis_aligned());
+            assert!(!Mat3::from_rotation(0.1).is_axis_aligned());
+            assert!(
+                (Mat3::from_scale(2.0, 3.0) * Mat3::from_translation(1.0, 0.0)).is_axis_aligned()
+            );
+        }
+
         #[test]
         fn is_identity() {
             assert!(mat3().is_identity())
@@ -178,4 +198,92 @@ mod tests {
             assert_eq!(m * a, vec2(40.0, 200.0));
         }
     }
+
+    mod timeline {
+        use super::*;
+
+        #[test]
+        fn samples_between_keys() {
+            let timeline = Timeline::new().key(1.0, 10.0).key(0.0, 0.0).key(2.0, 0.0);
+
+            assert_eq!(timeline.sample(-1.0), Some(0.0));
+            assert_eq!(timeline.sample(0.5), Some(5.0));
+            assert_eq!(timeline.sample(1.0), Some(10.0));
+            assert_eq!(timeline.sample(1.5), Some(5.0));
+            assert_eq!(timeline.sample(3.0), Some(0.0));
+            assert!(timeline.is_finished(2.0));
+            assert_eq!(Timeline::<f32>::new().sample(0.0), None);
+        }
+
+        #[test]
+        fn looping_and_vectors() {
+            let timeline = Timeline::new()
+                .key(0.0, vec2(0.0, 0.0))
+                .key(1.0, vec2(10.0, 20.0))
+                .looping(true);
+
+            assert_eq!(timeline.sample(1.5), Some(vec2(5.0, 10.0)));
+            assert_eq!(
+                timeline.sample_at(std::time::Duration::from_millis(2250)),
+                Some(vec2(2.5, 5.0))
+            );
+            assert!(!timeline.is_finished(10.0));
+        }
+
+        #[test]
+        fn easings_keep_endpoints() {
+            for easing in [
+                Easing::Linear,
+                Easing::EaseIn,
+                Easing::EaseOut,
+                Easing::EaseInOut,
+                Easing::CubicBezier(0.25, 0.1, 0.25, 1.0),
+            ] {
+                assert!(easing.apply(0.0).abs() < 1e-4, "{:?}", easing);
+                assert!((easing.apply(1.0) - 1.0).abs() < 1e-4, "{:?}", easing);
+            }
+
+            assert!(Easing::EaseIn.apply(0.5) < 0.5);
+            assert!(Easing::EaseOut.apply(0.5) > 0.5);
+            let ease = Easing::CubicBezier(0.42, 0.0, 1.0, 1.0).apply(0.5);
+            assert!(ease < 0.5 && ease > 0.2);
+        }
+    }
+
+    mod viewport {
+        use super::*;
+
+        #[test]
+        fn maps_data_to_screen() {
+            let viewport = Viewport::new(
+                Rect::xywh(0.0, 0.0, 10.0, 100.0),
+                Rect::xywh(50.0, 50.0, 200.0, 100.0),
+            );
+
+            assert_eq!(viewport.to_screen(vec2(0.0, 0.0)), vec2(50.0, 150.0));
+            assert_eq!(viewport.to_screen(vec2(10.0, 100.0)), vec2(250.0, 50.0));
+            assert_eq!(viewport.to_screen(vec2(5.0, 50.0)), vec2(150.0, 100.0));
+            assert_eq!(viewport.to_data(vec2(150.0, 100.0)), vec2(5.0, 50.0));
+
+            let viewport = viewport.flip_y(false);
+            assert_eq!(viewport.to_screen(vec2(0.0, 0.0)), vec2(50.0, 50.0));
+            assert_eq!(viewport.to_screen(vec2(10.0, 100.0)), vec2(250.0, 150.0));
+        }
+    }
+
+    mod corners {
+        use super::*;
+
+        #[test]
+        fn side_builders() {
+            let corners = Corners::with_all(1.0).with_top(4.0).with_bottom(8.0);
+            assert_eq!(corners, Corners::with_each(4.0, 4.0, 8.0, 8.0));
+
+            let corners = Corners::default().with_left(2.0).with_right(3.0);
+            assert_eq!(corners, Corners::with_each(2.0, 3.0, 2.0, 3.0));
+
+            let corners = Corners::with_all(0.0).with_top(5.0).with_bottom_right(1.0);
+            assert_eq!(corners, Corners::with_each(5.0, 5.0, 0.0, 1.0));
+        }
+    }
 }
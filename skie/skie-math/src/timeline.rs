@@ -0,0 +1,152 @@
+use std::time::Duration;
+
+use crate::traits::Lerp;
+
+/// Maps the progress between two keyframes, `0..=1`, to the interpolation factor
+#[derive(Debug, Clone, Copy, Default)]
+pub enum Easing {
+    #[default]
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    /// Css style `cubic-bezier(x1, y1, x2, y2)`
+    CubicBezier(f32, f32, f32, f32),
+    Custom(fn(f32) -> f32),
+}
+
+impl Easing {
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match *self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+            Easing::CubicBezier(x1, y1, x2, y2) => cubic_bezier_ease(x1, y1, x2, y2, t),
+            Easing::Custom(f) => f(t),
+        }
+    }
+}
+
+/// y of the curve through (0, 0), (x1, y1), (x2, y2), (1, 1) at x = `t`
+fn cubic_bezier_ease(x1: f32, y1: f32, x2: f32, y2: f32, t: f32) -> f32 {
+    let bezier = |a: f32, b: f32, s: f32| {
+        let inv = 1.0 - s;
+        3.0 * inv * inv * s * a + 3.0 * inv * s * s * b + s * s * s
+    };
+
+    // x is monotonic for x1, x2 in 0..=1, bisect for the parameter
+    let (x1, x2) = (x1.clamp(0.0, 1.0), x2.clamp(0.0, 1.0));
+    let (mut lo, mut hi) = (0.0f32, 1.0f32);
+    let mut s = t;
+    for _ in 0..24 {
+        let x = bezier(x1, x2, s);
+        if (x - t).abs() < 1e-5 {
+            break;
+        }
+        if x < t {
+            lo = s;
+        } else {
+            hi = s;
+        }
+        s = (lo + hi) * 0.5;
+    }
+
+    bezier(y1, y2, s)
+}
+
+/// Keyframe animation of a value, keys are times in seconds.
+///
+/// ```
+/// use skie_math::{Easing, Timeline};
+///
+/// let fade = Timeline::new().key(0.0, 0.0).key(0.5, 1.0).easing(Easing::EaseOut);
+/// assert_eq!(fade.sample(1.0), Some(1.0));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Timeline<T> {
+    keys: Vec<(f32, T)>,
+    easing: Easing,
+    looping: bool,
+}
+
+impl<T> Default for Timeline<T> {
+    fn default() -> Self {
+        Self {
+            keys: Vec::new(),
+            easing: Easing::default(),
+            looping: false,
+        }
+    }
+}
+
+impl<T: Lerp + Clone> Timeline<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a keyframe, keys can be added in any order
+    pub fn key(mut self, time: f32, value: T) -> Self {
+        let index = self.keys.partition_point(|(t, _)| *t <= time);
+        self.keys.insert(index, (time, value));
+        self
+    }
+
+    /// Easing applied between each pair of keyframes
+    pub fn easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// Restart from the first key after the last one
+    pub fn looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    /// Time of the last keyframe in seconds
+    pub fn duration(&self) -> f32 {
+        self.keys.last().map(|(t, _)| *t).unwrap_or(0.0)
+    }
+
+    pub fn is_finished(&self, time: f32) -> bool {
+        !self.looping && time >= self.duration()
+    }
+
+    /// Value at `time` seconds, held at the first and last keys outside of them.
+    /// Returns `None` when there are no keys
+    pub fn sample(&self, time: f32) -> Option<T> {
+        let (first, last) = (self.keys.first()?, self.keys.last()?);
+
+        let time = if self.looping && last.0 > first.0 {
+            first.0 + (time - first.0).rem_euclid(last.0 - first.0)
+        } else {
+            time
+        };
+
+        let next = self.keys.partition_point(|(t, _)| *t <= time);
+        if next == 0 {
+            return Some(first.1.clone());
+        }
+        if next == self.keys.len() {
+            return Some(last.1.clone());
+        }
+
+        let (t0, v0) = &self.keys[next - 1];
+        let (t1, v1) = &self.keys[next];
+        let progress = (time - t0) / (t1 - t0);
+        Some(v0.lerp(v1, self.easing.apply(progress)))
+    }
+
+    /// Samples with a clock, like the time since the animation started
+    pub fn sample_at(&self, elapsed: Duration) -> Option<T> {
+        self.sample(elapsed.as_secs_f32())
+    }
+}
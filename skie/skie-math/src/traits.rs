@@ -100,3 +100,22 @@ impl_zero_and_one!(u32, 0, 1);
 impl_zero_and_one!(u16, 0, 1);
 impl_zero_and_one!(u8, 0, 1);
 impl_zero_and_one!(usize, 0, 1);
+
+/// Linear interpolation, `t` of 0 gives `self` and 1 gives `to`
+pub trait Lerp {
+    fn lerp(&self, to: &Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    #[inline]
+    fn lerp(&self, to: &Self, t: f32) -> Self {
+        self + (to - self) * t
+    }
+}
+
+impl Lerp for f64 {
+    #[inline]
+    fn lerp(&self, to: &Self, t: f32) -> Self {
+        self + (to - self) * t as f64
+    }
+}
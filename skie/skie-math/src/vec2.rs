@@ -3,7 +3,7 @@ use std::{
     ops::{self, Add, Mul, Sub},
 };
 
-use crate::traits::{Half, IsZero, One, Zero};
+use crate::traits::{Half, IsZero, Lerp, One, Zero};
 
 #[repr(C)]
 #[derive(Debug, Clone, Copy, Default, PartialEq)]
@@ -258,6 +258,16 @@ macro_rules! impl_vec2_float {
 impl_vec2_float!(f32);
 impl_vec2_float!(f64);
 
+impl<T: Lerp> Lerp for Vec2<T> {
+    #[inline]
+    fn lerp(&self, to: &Self, t: f32) -> Self {
+        Self {
+            x: self.x.lerp(&to.x, t),
+            y: self.y.lerp(&to.y, t),
+        }
+    }
+}
+
 impl<T> Vec2<T> {
     pub fn map<U>(&self, f: impl Fn(&T) -> U) -> Vec2<U> {
         Vec2 {
@@ -0,0 +1,55 @@
+use crate::{Mat3, Rect, Vec2};
+
+/// Maps a rect in data space, like a chart's value ranges, onto a rect on screen.
+/// The y axis is flipped by default so data y grows upwards
+#[derive(Debug, Clone, PartialEq)]
+pub struct Viewport {
+    pub data: Rect<f32>,
+    pub screen: Rect<f32>,
+    pub flip_y: bool,
+}
+
+impl Viewport {
+    pub fn new(data: Rect<f32>, screen: Rect<f32>) -> Self {
+        Self {
+            data,
+            screen,
+            flip_y: true,
+        }
+    }
+
+    pub fn flip_y(mut self, flip_y: bool) -> Self {
+        self.flip_y = flip_y;
+        self
+    }
+
+    /// Screen pixels per data unit on each axis, negative y when flipped
+    pub fn scale(&self) -> Vec2<f32> {
+        let sx = self.screen.size.width / self.data.size.width;
+        let sy = self.screen.size.height / self.data.size.height;
+        Vec2::new(sx, if self.flip_y { -sy } else { sy })
+    }
+
+    /// Transform from data space to screen space
+    pub fn transform(&self) -> Mat3 {
+        let scale = self.scale();
+        let origin_y = if self.flip_y {
+            self.screen.origin.y + self.screen.size.height
+        } else {
+            self.screen.origin.y
+        };
+
+        let tx = self.screen.origin.x - self.data.origin.x * scale.x;
+        let ty = origin_y - self.data.origin.y * scale.y;
+
+        Mat3::from_scale(scale.x, scale.y) * Mat3::from_translation(tx, ty)
+    }
+
+    pub fn to_screen(&self, point: Vec2<f32>) -> Vec2<f32> {
+        self.transform() * point
+    }
+
+    pub fn to_data(&self, point: Vec2<f32>) -> Vec2<f32> {
+        self.transform().inverse() * point
+    }
+}
@@ -1,21 +1,28 @@
 pub mod async_context;
+mod crash;
+mod event_sender;
 pub mod events;
+pub mod input_recording;
 pub use async_context::AsyncAppContext;
+use crash::CrashWindow;
+pub use event_sender::EventSender;
 use skie_draw::paint::SkieAtlas;
-use skie_draw::{TextSystem, Vec2};
+use skie_draw::TextSystem;
 mod handle;
 
-use crate::window::{Window, WindowId, WindowSpecification};
+use crate::window::{color_scheme::ColorScheme, Window, WindowId, WindowSpecification};
 use anyhow::Result;
 use events::AppEvents;
 use handle::AppHandle;
+use input_recording::{InputEvent, InputHandler, InputRecorder, InputRecording};
 use skie_draw::gpu::GpuContext;
 use std::cell::RefCell;
 use std::collections::VecDeque;
 use std::future::Future;
 use std::rc::{Rc, Weak};
 use std::sync::Arc;
-use winit::event::{KeyEvent, MouseScrollDelta, WindowEvent};
+use std::time::{Duration, Instant};
+use winit::event::{KeyEvent, WindowEvent};
 use winit::event_loop::ActiveEventLoop;
 use winit::keyboard::{KeyCode, PhysicalKey};
 
@@ -24,6 +31,8 @@ use crate::jobs::{Job, Jobs};
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum AppAction {
     AppUpdate,
+    /// Sent by an [`EventSender`]
+    Wake,
     Quit,
 }
 
@@ -50,7 +59,11 @@ impl App {
         Self { cx, handle }
     }
 
+    /// Runs the app until its last window closes. A panic in a callback closes the windows
+    /// and shows a window with the panic message instead of leaving them frozen
     pub fn run(mut self, on_init: impl FnOnce(&mut AppContext) + 'static) {
+        crash::install_panic_hook();
+
         let event_loop: winit::event_loop::EventLoop<AppAction> =
             winit::event_loop::EventLoop::with_user_event()
                 .build()
@@ -103,6 +116,16 @@ pub struct AppContext {
     pub(crate) windows: ahash::AHashMap<WindowId, Option<Window>>,
 
     pub(crate) gpu: GpuContext,
+
+    external_callbacks: (
+        flume::Sender<event_sender::ExternalCallback>,
+        flume::Receiver<event_sender::ExternalCallback>,
+    ),
+
+    started_at: Instant,
+    input_recorders: ahash::AHashMap<WindowId, InputRecorder>,
+    crash_window: Option<CrashWindow>,
+    color_scheme: ColorScheme,
 }
 
 impl AppContext {
@@ -131,6 +154,11 @@ impl AppContext {
                 texture_atlas: texture_system,
                 text_system: Arc::new(text_system),
                 windows: ahash::AHashMap::new(),
+                external_callbacks: flume::unbounded(),
+                started_at: Instant::now(),
+                input_recorders: Default::default(),
+                crash_window: None,
+                color_scheme: ColorScheme::default(),
             })
         });
 
@@ -168,6 +196,14 @@ impl AppContext {
                     cx.handle_on_user_event(event_loop, user_event);
                 })
             });
+
+            handle.on_panic({
+                let cx = lock.to_async();
+
+                Box::new(move |event_loop, message| {
+                    cx.handle_panic(event_loop, message);
+                })
+            });
         }
 
         cx
@@ -177,6 +213,33 @@ impl AppContext {
         &self.text_system
     }
 
+    /// Light or dark preference of the OS, updated when any window reports a change.
+    /// Windows report their own with [`Window::color_scheme`]
+    pub fn color_scheme(&self) -> ColorScheme {
+        self.color_scheme
+    }
+
+    /// Time since the app started, the clock to drive [`skie_draw::Timeline`]s with
+    pub fn clock(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+
+    /// A handle to run callbacks on the UI thread from other threads,
+    /// e.g. networking libraries pushing data into the UI
+    pub fn event_sender(&self) -> EventSender {
+        EventSender {
+            callbacks: self.external_callbacks.0.clone(),
+            proxy: self.app_events.proxy(),
+        }
+    }
+
+    fn run_external_callbacks(&mut self) {
+        let receiver = self.external_callbacks.1.clone();
+        for callback in receiver.drain() {
+            self.update(callback);
+        }
+    }
+
     pub fn to_async(&self) -> AsyncAppContext {
         AsyncAppContext {
             app: self.this.clone(),
@@ -274,11 +337,7 @@ impl AppContext {
         self.jobs.spawn(f(self.to_async()))
     }
 
-    pub fn set_timeout(
-        &mut self,
-        f: impl FnOnce(&mut AppContext) + 'static,
-        timeout: std::time::Duration,
-    ) {
+    pub fn set_timeout(&mut self, f: impl FnOnce(&mut AppContext) + 'static, timeout: Duration) {
         self.spawn(|cx| async move {
             cx.jobs.timer(timeout).await;
             let cx = cx.app.upgrade().expect("app released");
@@ -288,6 +347,39 @@ impl AppContext {
         .detach();
     }
 
+    /// Starts recording the input events of the window, restarting any recording in progress
+    pub fn start_input_recording(&mut self, window_id: &WindowId) {
+        self.input_recorders
+            .insert(*window_id, InputRecorder::new());
+    }
+
+    /// Returns `None` when the window wasnt being recorded
+    pub fn stop_input_recording(&mut self, window_id: &WindowId) -> Option<InputRecording> {
+        self.input_recorders
+            .remove(window_id)
+            .map(InputRecorder::finish)
+    }
+
+    /// Replays the recording into the window with its original timing. Fails if the window
+    /// is closed before the replay finishes
+    pub fn replay_input(
+        &mut self,
+        window_id: WindowId,
+        recording: InputRecording,
+    ) -> Job<Result<()>> {
+        self.spawn(|cx| async move {
+            let started_at = Instant::now();
+            for (at, event) in recording.events() {
+                let elapsed = started_at.elapsed();
+                if *at > elapsed {
+                    cx.jobs.timer(*at - elapsed).await;
+                }
+                cx.update_window(&window_id, |window, _| window.handle_input(event))?;
+            }
+            Ok(())
+        })
+    }
+
     pub fn update_window<R, Update>(&mut self, id: &WindowId, update: Update) -> Result<R>
     where
         Update: FnOnce(&mut Window, &mut Self) -> R,
@@ -321,13 +413,24 @@ impl AppContext {
         }
     }
 
-    fn handle_on_about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {}
+    fn handle_on_about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
+        if self.crash_window.is_some() {
+            return;
+        }
+        self.run_external_callbacks();
+
+        let now = self.clock();
+        for window in self.windows.values_mut().flatten() {
+            window.tick(now);
+        }
+    }
 
     fn handle_on_user_event(&mut self, event_loop: &ActiveEventLoop, event: AppAction) {
         self.pending_user_events.remove(&event);
 
         match event {
             AppAction::AppUpdate => self.handle_app_update_event(event_loop),
+            AppAction::Wake => self.run_external_callbacks(),
             AppAction::Quit => {
                 event_loop.exit();
                 self.app_events.dispose();
@@ -336,7 +439,11 @@ impl AppContext {
         }
     }
 
-    fn handle_on_resumed(&mut self, _event_loop: &winit::event_loop::ActiveEventLoop) {
+    fn handle_on_resumed(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
+        if let Some(theme) = event_loop.system_theme() {
+            self.color_scheme = theme.into();
+        }
+
         log::info!("Initializing App...");
         if let Some(cb) = self.init_callback.take() {
             cb(self);
@@ -344,20 +451,82 @@ impl AppContext {
         log::info!("App Initialized!");
     }
 
+    /// Drops the windows, releasing their surfaces and canvases, and shows the crash window
+    fn handle_panic(&mut self, event_loop: &ActiveEventLoop, message: String) {
+        self.windows.clear();
+        self.input_recorders.clear();
+        self.effects.clear();
+        self.pending_updates = 0;
+        self.flushing_effects = false;
+
+        match CrashWindow::new(event_loop, self.gpu.clone(), message) {
+            Ok(window) => self.crash_window = Some(window),
+            Err(error) => {
+                log::error!("Error creating the crash window\n{:#?}", error);
+                event_loop.exit();
+            }
+        }
+    }
+
+    fn handle_crash_window_event(&mut self, event_loop: &ActiveEventLoop, event: WindowEvent) {
+        let Some(window) = &mut self.crash_window else {
+            return;
+        };
+
+        match event {
+            WindowEvent::RedrawRequested => {
+                if let Err(error) = window.paint() {
+                    log::error!("Error rendering the crash window {:#?}", error);
+                }
+            }
+            WindowEvent::Resized(size) => window.resize(size.width, size.height),
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                window.set_scale_factor(scale_factor as f32)
+            }
+            WindowEvent::CloseRequested
+            | WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key: PhysicalKey::Code(KeyCode::Escape),
+                        ..
+                    },
+                ..
+            } => {
+                self.crash_window = None;
+                event_loop.exit();
+            }
+            _ => {}
+        }
+    }
+
     fn handle_window_event(
         &mut self,
-        _event_loop: &winit::event_loop::ActiveEventLoop,
+        event_loop: &winit::event_loop::ActiveEventLoop,
         window_id: winit::window::WindowId,
         event: winit::event::WindowEvent,
     ) {
-        match event {
-            WindowEvent::Resized(size) => {
-                let width = size.width;
-                let height = size.height;
-                let _ = self.update_window(&window_id, |window, _| {
-                    window.handle_resize(width, height);
-                });
+        if self.crash_window.is_some() {
+            if self.crash_window.as_ref().map(CrashWindow::id) == Some(window_id) {
+                self.handle_crash_window_event(event_loop, event);
+            }
+            return;
+        }
+
+        if let Some(input) = InputEvent::from_window_event(&event) {
+            if let Some(recorder) = self.input_recorders.get_mut(&window_id) {
+                recorder.record(input);
             }
+            let handled = self
+                .update_window(&window_id, |window, _| window.dispatch_input(&input))
+                .unwrap_or(false);
+
+            // unhandled keys go on to the window's own key handling below
+            if handled || !matches!(input, InputEvent::Key { .. }) {
+                return;
+            }
+        }
+
+        match event {
             WindowEvent::RedrawRequested => {
                 let _ = self.update_window(&window_id, |window, _| {
                     if let Err(error) = window.paint() {
@@ -365,20 +534,10 @@ impl AppContext {
                     }
                 });
             }
-            WindowEvent::CursorMoved { position, .. } => {
-                let _ = self.update_window(&window_id, |window, _| {
-                    let mut lock = window.state.write();
-                    lock.set_mouse_pos(Vec2::new(position.x as f32, position.y as f32));
-                    // FIXME:
-                    window.refresh();
-                });
-            }
-            WindowEvent::MouseWheel {
-                delta: MouseScrollDelta::LineDelta(dx, dy),
-                ..
-            } => {
+            WindowEvent::ThemeChanged(theme) => {
+                self.color_scheme = theme.into();
                 let _ = self.update_window(&window_id, |window, _| {
-                    window.handle_scroll_wheel(dx, dy);
+                    window.handle_color_scheme_changed(theme.into())
                 });
             }
             WindowEvent::CloseRequested
@@ -392,6 +551,7 @@ impl AppContext {
             } => {
                 // TODO: do this in window update
                 self.windows.remove(&window_id);
+                self.input_recorders.remove(&window_id);
 
                 if self.windows.is_empty() {
                     self.quit();
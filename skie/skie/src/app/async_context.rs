@@ -60,4 +60,10 @@ impl AsyncAppContext {
         let mut lock = cx.borrow_mut();
         lock.handle_on_user_event(event_loop, event);
     }
+
+    pub(super) fn handle_panic(&self, event_loop: &ActiveEventLoop, message: String) {
+        let cx = self.app.upgrade().expect("app released");
+        let mut lock = cx.borrow_mut();
+        lock.handle_panic(event_loop, message);
+    }
 }
@@ -0,0 +1,154 @@
+use std::{
+    any::Any,
+    cell::RefCell,
+    panic::{self, PanicHookInfo},
+    sync::{Arc, Once},
+};
+
+use anyhow::Result;
+use skie_draw::{BackendRenderTarget, Canvas, Color, FontWeight, GpuContext, Text};
+use winit::event_loop::ActiveEventLoop;
+
+use crate::window::{error::CreateWindowError, WindowId, WinitWindow};
+
+thread_local! {
+    /// Message of the last panic on this thread, taken where the unwind is caught
+    static LAST_PANIC: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Logs panics with their location before the previous hook runs, see [`crate::App::run`]
+pub(crate) fn install_panic_hook() {
+    static INSTALLED: Once = Once::new();
+
+    INSTALLED.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let message = hook_message(info);
+            log::error!("{}", message);
+            LAST_PANIC.set(Some(message));
+            previous(info);
+        }));
+    });
+}
+
+fn hook_message(info: &PanicHookInfo) -> String {
+    let message = payload_message(info.payload());
+    match info.location() {
+        Some(location) => format!("panicked at {}: {}", location, message),
+        None => format!("panicked: {}", message),
+    }
+}
+
+fn payload_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic payload".to_string()
+    }
+}
+
+/// Message of the panic that unwound with `payload`, with its location when the hook saw it
+pub(crate) fn take_panic_message(payload: &(dyn Any + Send)) -> String {
+    LAST_PANIC
+        .take()
+        .unwrap_or_else(|| payload_message(payload))
+}
+
+/// Shown in place of the app's windows after a panic so the app doesn't just freeze
+pub(crate) struct CrashWindow {
+    handle: Arc<WinitWindow>,
+    canvas: Canvas,
+    surface: BackendRenderTarget<'static>,
+    message: String,
+}
+
+impl CrashWindow {
+    pub(crate) fn new(
+        event_loop: &ActiveEventLoop,
+        gpu: GpuContext,
+        message: String,
+    ) -> Result<Self> {
+        let attr = winit::window::WindowAttributes::default()
+            .with_inner_size(winit::dpi::LogicalSize::new(560, 240))
+            .with_title("The app crashed");
+
+        let handle = Arc::new(event_loop.create_window(attr).map_err(CreateWindowError)?);
+        let size = handle.inner_size();
+        let mut canvas = Canvas::create()
+            .width(size.width)
+            .height(size.height)
+            .ui()
+            .scale_factor(handle.scale_factor() as f32)
+            .build(gpu);
+        canvas.clear_color(Color::THAMAR_BLACK);
+        let surface = canvas.create_backend_target(Arc::clone(&handle))?;
+
+        Ok(Self {
+            handle,
+            canvas,
+            surface,
+            message,
+        })
+    }
+
+    pub(crate) fn id(&self) -> WindowId {
+        self.handle.id()
+    }
+
+    pub(crate) fn resize(&mut self, width: u32, height: u32) {
+        self.canvas.resize(width, height);
+        self.handle.request_redraw();
+    }
+
+    pub(crate) fn set_scale_factor(&mut self, scale_factor: f32) {
+        self.canvas.set_scale_factor(scale_factor);
+    }
+
+    pub(crate) fn paint(&mut self) -> Result<()> {
+        let width = self.canvas.logical_size().width - 48.0;
+
+        self.canvas.fill_text(
+            &Text::new("The app crashed")
+                .pos(24.0, 24.0)
+                .size_px(24.0)
+                .font_weight(FontWeight::BOLD),
+            Color::WHITE,
+        );
+        self.canvas.fill_text(
+            &Text::new(self.message.clone())
+                .pos(24.0, 68.0)
+                .size_px(14.0)
+                .max_width(width),
+            Color::LIGHT_GRAY,
+        );
+        self.canvas.fill_text(
+            &Text::new("Press Escape or close the window to quit")
+                .pos(24.0, self.canvas.logical_size().height - 40.0)
+                .size_px(14.0),
+            Color::GRAY,
+        );
+
+        self.canvas.finish(&mut self.surface)?.present();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panic_messages_carry_the_location() {
+        install_panic_hook();
+        let payload = panic::catch_unwind(|| panic!("boom {}", 1)).unwrap_err();
+        let message = take_panic_message(payload.as_ref());
+        assert!(message.contains("boom 1"), "{}", message);
+        assert!(message.contains("crash.rs"), "{}", message);
+
+        // without the hook only the payload is known
+        assert_eq!(take_panic_message(&"plain"), "plain");
+        assert_eq!(take_panic_message(&42), "unknown panic payload");
+    }
+}
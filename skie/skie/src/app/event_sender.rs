@@ -0,0 +1,51 @@
+use std::{
+    sync::Arc,
+    task::{Wake, Waker},
+};
+
+use winit::event_loop::EventLoopProxy;
+
+use super::{AppAction, AppContext};
+
+pub(crate) type ExternalCallback = Box<dyn FnOnce(&mut AppContext) + Send + 'static>;
+
+/// A `Send` handle for scheduling work on the UI thread from other threads or async runtimes.
+/// See [`AppContext::event_sender`]
+#[derive(Clone)]
+pub struct EventSender {
+    pub(super) callbacks: flume::Sender<ExternalCallback>,
+    pub(super) proxy: Option<EventLoopProxy<AppAction>>,
+}
+
+impl EventSender {
+    /// Runs `f` on the UI thread. Returns false if the app is gone
+    pub fn send(&self, f: impl FnOnce(&mut AppContext) + Send + 'static) -> bool {
+        if self.callbacks.send(Box::new(f)).is_err() {
+            return false;
+        }
+
+        self.wake_event_loop();
+        true
+    }
+
+    pub fn wake_event_loop(&self) {
+        if let Some(proxy) = &self.proxy {
+            let _ = proxy.send_event(AppAction::Wake);
+        }
+    }
+
+    /// A [`Waker`] that wakes the event loop, for futures polled by runtimes outside the app
+    pub fn waker(&self) -> Waker {
+        Waker::from(Arc::new(self.clone()))
+    }
+}
+
+impl Wake for EventSender {
+    fn wake(self: Arc<Self>) {
+        self.wake_event_loop();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.wake_event_loop();
+    }
+}
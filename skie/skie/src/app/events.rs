@@ -21,6 +21,10 @@ impl AppEvents {
         lock.proxy = Some(proxy);
     }
 
+    pub fn proxy(&self) -> Option<EventLoopProxy<AppAction>> {
+        self.0.lock().proxy.clone()
+    }
+
     pub fn notify(&self, event: AppAction) {
         let lock = self.0.lock();
         if let Some(proxy) = &lock.proxy {
@@ -1,6 +1,8 @@
+use std::panic::{self, AssertUnwindSafe};
+
 use winit::{application::ApplicationHandler, event_loop::ActiveEventLoop};
 
-use super::AppAction;
+use super::{crash::take_panic_message, AppAction};
 
 type ResumedCallback = Box<dyn Fn(&ActiveEventLoop)>;
 type UserEventCallback = Box<dyn Fn(&ActiveEventLoop, AppAction)>;
@@ -8,6 +10,7 @@ type AboutToWaitCallback = Box<dyn Fn(&ActiveEventLoop)>;
 type WindowEventCallback = Box<
     dyn Fn(&winit::event_loop::ActiveEventLoop, winit::window::WindowId, winit::event::WindowEvent),
 >;
+type PanicCallback = Box<dyn Fn(&ActiveEventLoop, String)>;
 
 #[derive(Default)]
 pub struct AppHandleCallbacks {
@@ -15,11 +18,13 @@ pub struct AppHandleCallbacks {
     window_event: Option<WindowEventCallback>,
     about_to_wait: Option<AboutToWaitCallback>,
     user_event: Option<UserEventCallback>,
+    panic: Option<PanicCallback>,
 }
 
 #[derive(Default)]
 pub struct AppHandle {
     callbacks: AppHandleCallbacks,
+    crashed: bool,
 }
 
 impl AppHandle {
@@ -37,25 +42,62 @@ impl AppHandle {
     pub fn on_about_to_wait(&mut self, callback: AboutToWaitCallback) {
         self.callbacks.about_to_wait = Some(callback);
     }
+
+    /// Called once with the message when a callback panics
+    pub fn on_panic(&mut self, callback: PanicCallback) {
+        self.callbacks.panic = Some(callback);
+    }
+
+    /// Runs `f` catching panics. The first one is handed to the panic callback,
+    /// the event loop exits if the app panics again after that
+    fn guard(&mut self, event_loop: &ActiveEventLoop, f: impl FnOnce(&AppHandleCallbacks)) {
+        let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| f(&self.callbacks))) else {
+            return;
+        };
+        let message = take_panic_message(payload.as_ref());
+
+        if self.crashed {
+            log::error!("Panicked again after a crash, exiting: {}", message);
+            event_loop.exit();
+            return;
+        }
+        self.crashed = true;
+
+        let handled = panic::catch_unwind(AssertUnwindSafe(|| {
+            if let Some(callback) = &self.callbacks.panic {
+                callback(event_loop, message);
+            }
+        }));
+        if handled.is_err() {
+            log::error!("Panicked while handling a crash, exiting");
+            event_loop.exit();
+        }
+    }
 }
 
 impl ApplicationHandler<AppAction> for AppHandle {
     fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
-        if let Some(callback) = &self.callbacks.about_to_wait {
-            callback(event_loop)
-        }
+        self.guard(event_loop, |callbacks| {
+            if let Some(callback) = &callbacks.about_to_wait {
+                callback(event_loop)
+            }
+        });
     }
 
     fn user_event(&mut self, event_loop: &ActiveEventLoop, event: AppAction) {
-        if let Some(callback) = &self.callbacks.user_event {
-            callback(event_loop, event)
-        }
+        self.guard(event_loop, |callbacks| {
+            if let Some(callback) = &callbacks.user_event {
+                callback(event_loop, event)
+            }
+        });
     }
 
     fn resumed(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
-        if let Some(callback) = &self.callbacks.resumed {
-            callback(event_loop);
-        }
+        self.guard(event_loop, |callbacks| {
+            if let Some(callback) = &callbacks.resumed {
+                callback(event_loop);
+            }
+        });
     }
 
     fn window_event(
@@ -64,8 +106,10 @@ impl ApplicationHandler<AppAction> for AppHandle {
         window_id: winit::window::WindowId,
         event: winit::event::WindowEvent,
     ) {
-        if let Some(callback) = &self.callbacks.window_event {
-            callback(event_loop, window_id, event)
-        }
+        self.guard(event_loop, |callbacks| {
+            if let Some(callback) = &callbacks.window_event {
+                callback(event_loop, window_id, event)
+            }
+        });
     }
 }
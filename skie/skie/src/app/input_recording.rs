@@ -0,0 +1,284 @@
+use std::{
+    fmt,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Context, Result};
+use skie_draw::Vec2;
+use winit::{
+    event::{KeyEvent, MouseButton, MouseScrollDelta, WindowEvent},
+    keyboard::{KeyCode, ModifiersState, PhysicalKey},
+};
+
+use crate::window::shortcuts::{key_from_name, key_name};
+
+/// The window events a [`crate::window::Window`] reacts to, positions are in physical pixels.
+/// Only keys with a [`key_name`] are reported
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputEvent {
+    PointerMoved(Vec2<f32>),
+    PointerButton { pressed: bool },
+    PointerLeft,
+    ScrollWheel { dx: f32, dy: f32 },
+    Resized { width: u32, height: u32 },
+    ScaleFactorChanged(f32),
+    Key { code: KeyCode, pressed: bool },
+    ModifiersChanged(ModifiersState),
+}
+
+impl InputEvent {
+    pub fn from_window_event(event: &WindowEvent) -> Option<Self> {
+        let input = match event {
+            WindowEvent::CursorMoved { position, .. } => {
+                Self::PointerMoved(Vec2::new(position.x as f32, position.y as f32))
+            }
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Left,
+                ..
+            } => Self::PointerButton {
+                pressed: state.is_pressed(),
+            },
+            WindowEvent::CursorLeft { .. } => Self::PointerLeft,
+            WindowEvent::MouseWheel {
+                delta: MouseScrollDelta::LineDelta(dx, dy),
+                ..
+            } => Self::ScrollWheel { dx: *dx, dy: *dy },
+            WindowEvent::Resized(size) => Self::Resized {
+                width: size.width,
+                height: size.height,
+            },
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                Self::ScaleFactorChanged(*scale_factor as f32)
+            }
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key: PhysicalKey::Code(code),
+                        state,
+                        ..
+                    },
+                ..
+            } if key_name(*code).is_some() => Self::Key {
+                code: *code,
+                pressed: state.is_pressed(),
+            },
+            WindowEvent::ModifiersChanged(modifiers) => Self::ModifiersChanged(modifiers.state()),
+            _ => return None,
+        };
+
+        Some(input)
+    }
+}
+
+/// Receives replayed input, implemented by [`crate::window::Window`] and by test doubles
+/// to replay without a real window
+pub trait InputHandler {
+    fn handle_input(&mut self, event: &InputEvent);
+}
+
+/// Input events of a window with the time they arrived since the recording started
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InputRecording {
+    events: Vec<(Duration, InputEvent)>,
+}
+
+impl InputRecording {
+    pub fn events(&self) -> &[(Duration, InputEvent)] {
+        &self.events
+    }
+
+    pub fn push(&mut self, at: Duration, event: InputEvent) {
+        self.events.push((at, event));
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.events.last().map(|(at, _)| *at).unwrap_or_default()
+    }
+
+    /// Feeds every event to `handler` at once, ignoring the timestamps
+    pub fn replay(&self, handler: &mut impl InputHandler) {
+        for (_, event) in &self.events {
+            handler.handle_input(event);
+        }
+    }
+
+    /// Feeds the events recorded in `from..to` to `handler`, for replaying in step with a clock
+    pub fn replay_between(&self, from: Duration, to: Duration, handler: &mut impl InputHandler) {
+        self.events
+            .iter()
+            .filter(|(at, _)| *at >= from && *at < to)
+            .for_each(|(_, event)| handler.handle_input(event));
+    }
+}
+
+/// Records the input of a window while it is active, see [`crate::app::AppContext::start_input_recording`]
+#[derive(Debug)]
+pub(crate) struct InputRecorder {
+    started_at: Instant,
+    recording: InputRecording,
+}
+
+impl InputRecorder {
+    pub fn new() -> Self {
+        Self {
+            started_at: Instant::now(),
+            recording: InputRecording::default(),
+        }
+    }
+
+    pub fn record(&mut self, event: InputEvent) {
+        self.recording.push(self.started_at.elapsed(), event);
+    }
+
+    pub fn finish(self) -> InputRecording {
+        self.recording
+    }
+}
+
+/// One event a line, `<micros> <kind> <args..>`
+impl fmt::Display for InputRecording {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (at, event) in &self.events {
+            write!(f, "{} ", at.as_micros())?;
+            match event {
+                InputEvent::PointerMoved(pos) => writeln!(f, "move {} {}", pos.x, pos.y)?,
+                InputEvent::PointerButton { pressed } => writeln!(f, "button {}", pressed)?,
+                InputEvent::PointerLeft => writeln!(f, "leave")?,
+                InputEvent::ScrollWheel { dx, dy } => writeln!(f, "wheel {} {}", dx, dy)?,
+                InputEvent::Resized { width, height } => {
+                    writeln!(f, "resize {} {}", width, height)?
+                }
+                InputEvent::ScaleFactorChanged(scale) => writeln!(f, "scale {}", scale)?,
+                InputEvent::Key { code, pressed } => {
+                    let name = key_name(*code).unwrap_or("Unidentified");
+                    writeln!(f, "key {} {}", name, pressed)?
+                }
+                InputEvent::ModifiersChanged(modifiers) => {
+                    writeln!(f, "modifiers {}", modifiers.bits())?
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for InputRecording {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut recording = Self::default();
+
+        for (number, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let event = parse_line(line).with_context(|| format!("line {}", number + 1))?;
+            recording.events.push(event);
+        }
+
+        Ok(recording)
+    }
+}
+
+fn parse_line(line: &str) -> Result<(Duration, InputEvent)> {
+    let mut parts = line.split_whitespace();
+    let mut next = |what: &str| parts.next().ok_or_else(|| anyhow!("missing {}", what));
+
+    let at = Duration::from_micros(next("timestamp")?.parse()?);
+    let event = match next("event")? {
+        "move" => InputEvent::PointerMoved(Vec2::new(next("x")?.parse()?, next("y")?.parse()?)),
+        "button" => InputEvent::PointerButton {
+            pressed: next("pressed")?.parse()?,
+        },
+        "leave" => InputEvent::PointerLeft,
+        "wheel" => InputEvent::ScrollWheel {
+            dx: next("dx")?.parse()?,
+            dy: next("dy")?.parse()?,
+        },
+        "resize" => InputEvent::Resized {
+            width: next("width")?.parse()?,
+            height: next("height")?.parse()?,
+        },
+        "scale" => InputEvent::ScaleFactorChanged(next("scale")?.parse()?),
+        "key" => {
+            let name = next("key")?;
+            InputEvent::Key {
+                code: key_from_name(name).ok_or_else(|| anyhow!("unknown key {:?}", name))?,
+                pressed: next("pressed")?.parse()?,
+            }
+        }
+        "modifiers" => {
+            InputEvent::ModifiersChanged(ModifiersState::from_bits_truncate(next("bits")?.parse()?))
+        }
+        kind => return Err(anyhow!("unknown event {:?}", kind)),
+    };
+
+    Ok((at, event))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Recorded(Vec<InputEvent>);
+
+    impl InputHandler for Recorded {
+        fn handle_input(&mut self, event: &InputEvent) {
+            self.0.push(*event);
+        }
+    }
+
+    #[test]
+    fn recording_round_trips_and_replays() {
+        let mut recording = InputRecording::default();
+        let ms = Duration::from_millis;
+        recording.push(
+            ms(0),
+            InputEvent::Resized {
+                width: 800,
+                height: 600,
+            },
+        );
+        recording.push(ms(16), InputEvent::PointerMoved(Vec2::new(10.5, 20.0)));
+        recording.push(ms(20), InputEvent::PointerButton { pressed: true });
+        recording.push(ms(40), InputEvent::ScrollWheel { dx: 0.0, dy: -1.5 });
+        recording.push(ms(50), InputEvent::PointerLeft);
+        recording.push(ms(60), InputEvent::ScaleFactorChanged(2.0));
+        recording.push(
+            ms(70),
+            InputEvent::ModifiersChanged(ModifiersState::CONTROL),
+        );
+        recording.push(
+            ms(80),
+            InputEvent::Key {
+                code: KeyCode::KeyS,
+                pressed: true,
+            },
+        );
+
+        let parsed: InputRecording = recording.to_string().parse().unwrap();
+        assert_eq!(parsed, recording);
+        assert_eq!(parsed.duration(), ms(80));
+
+        let mut handler = Recorded::default();
+        parsed.replay_between(ms(16), ms(40), &mut handler);
+        assert_eq!(
+            handler.0,
+            [
+                InputEvent::PointerMoved(Vec2::new(10.5, 20.0)),
+                InputEvent::PointerButton { pressed: true },
+            ]
+        );
+
+        let mut handler = Recorded::default();
+        parsed.replay(&mut handler);
+        assert_eq!(handler.0.len(), 8);
+
+        assert!("12 teleport 1 2".parse::<InputRecording>().is_err());
+    }
+}
@@ -1,10 +1,13 @@
+#[cfg(feature = "windowing")]
 pub mod app;
 pub mod jobs;
 
 pub mod arena;
 pub mod unit;
+#[cfg(feature = "windowing")]
 pub mod window;
 
+#[cfg(feature = "windowing")]
 pub use app::App;
 pub use unit::{px, DevicePixels, Pixels, ScaledPixels};
 
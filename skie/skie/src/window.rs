@@ -1,26 +1,53 @@
+pub mod color_scheme;
 pub mod error;
+pub mod frame_timings;
+pub mod paint_phase;
+pub mod pointer_capture;
+pub mod shortcuts;
+pub mod texture_upload;
+pub mod tick;
 use derive_more::derive::{Deref, DerefMut};
 use parking_lot::RwLock;
 
 use core::f32;
-use std::{borrow::Cow, future::Future, io::Read, sync::Arc};
+use std::{
+    borrow::Cow,
+    collections::HashSet,
+    future::Future,
+    io::Read,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use crate::{
-    app::{AppContext, AsyncAppContext},
+    app::{
+        input_recording::{InputEvent, InputHandler},
+        AppContext, AsyncAppContext,
+    },
     jobs::Job,
     Pixels,
 };
 use anyhow::{anyhow, Result};
+use color_scheme::{ColorScheme, ColorSchemeCallbackId, ColorSchemeState};
 use error::CreateWindowError;
+use frame_timings::FrameTimings;
 use image::{ImageBuffer, RgbaImage};
+use paint_phase::{PaintCallbackId, PaintPhase, PaintPhases};
+pub use pointer_capture::PointerCaptureTarget;
+use pointer_capture::{
+    CapturedPointerEvent, PointerCapture, PointerCaptureCallbackId, SCROLLER_CAPTURE,
+};
+use shortcuts::{Shortcut, ShortcutError, ShortcutId, Shortcuts};
+use texture_upload::{TextureUpload, TextureUploadQueue};
+use tick::{TickCallbackId, Ticks};
+use winit::keyboard::{KeyCode, ModifiersState};
 pub(crate) use winit::window::Window as WinitWindow;
 
 use skie_draw::{
-    gpu,
     paint::{AtlasImage, AtlasKey, Brush, PathBuilderBrushExt, SkieAtlas},
-    quad, vec2, BackendRenderTarget, Canvas, Color, Corners, FontWeight, GpuContext, Half, LineCap,
-    LineJoin, Path, Rect, Size, Text, TextSystem, TextureFilterMode, TextureId, TextureOptions,
-    Vec2,
+    quad, vec2, BackendRenderTarget, Canvas, ClearMode, Color, Corners, FontWeight, GpuContext,
+    Half, ImageDecodeOptions, LineCap, LineJoin, Path, Rect, Size, Text, TextSystem,
+    TextureFilterMode, TextureId, TextureOptions, Vec2,
 };
 
 #[derive(Debug, Clone)]
@@ -96,11 +123,14 @@ impl Object {
 pub(crate) struct State {
     // TODO: active
     mouse_pos: Option<Vec2<f32>>,
+    modifiers: ModifiersState,
+    pressed_keys: HashSet<KeyCode>,
 }
 
 impl State {
-    pub fn set_mouse_pos(&mut self, pos: Vec2<f32>) {
-        self.mouse_pos = Some(pos)
+    /// Returns the previous position
+    pub fn set_mouse_pos(&mut self, pos: Vec2<f32>) -> Option<Vec2<f32>> {
+        self.mouse_pos.replace(pos)
     }
 
     pub fn mouse_pos(&self) -> Option<&Vec2<f32>> {
@@ -110,15 +140,21 @@ impl State {
 
 pub struct Window {
     objects: Vec<Object>,
-    clear_color: Color,
 
     yellow_thing_texture_id: TextureId,
     checker_texture_id: TextureId,
 
     scroller: Scroller,
+    pointer_capture: PointerCapture,
 
     pub(crate) texture_atlas: Arc<SkieAtlas>,
     next_texture_id: usize,
+    texture_uploads: TextureUploadQueue,
+    paint_phases: PaintPhases,
+    ticks: Ticks,
+    shortcuts: Shortcuts,
+    frame_timings: FrameTimings,
+    color_scheme: ColorSchemeState,
 
     pub(crate) canvas: Canvas,
     pub(crate) state: RwLock<State>,
@@ -145,12 +181,18 @@ impl Window {
 
         let winit_window = event_loop.create_window(attr).map_err(CreateWindowError)?;
         let handle = Arc::new(winit_window);
+        let color_scheme = handle
+            .theme()
+            .or_else(|| event_loop.system_theme())
+            .map(ColorScheme::from)
+            .unwrap_or_default();
 
+        let physical_size = handle.inner_size();
         let mut canvas = Canvas::create()
-            .width(width)
-            .height(height)
-            .msaa_samples(4)
-            .surface_format(gpu::TextureFormat::Rgba8Unorm)
+            .width(physical_size.width)
+            .height(physical_size.height)
+            .ui()
+            .scale_factor(handle.scale_factor() as f32)
             .with_text_system(text_system.clone())
             .with_texture_atlas(texture_atlas.clone())
             .build(gpu);
@@ -196,11 +238,7 @@ impl Window {
             .set_texture_from_atlas(&texture_atlas, &yellow_thing_texture_key, &opts);
 
         let scroller = {
-            let size = handle.inner_size();
-            let size = Size {
-                width: size.width as f32,
-                height: size.height as f32,
-            };
+            let size = canvas.logical_size();
 
             let mut dims = Rect::xywh(size.width.half(), size.height.half(), 500.0, 500.0);
             dims.origin.x -= dims.size.width.half();
@@ -218,16 +256,27 @@ impl Window {
             yellow_thing_texture_id: yellow_thing_texture_key.into(),
             checker_texture_id: checker_texture_key.into(),
             objects: Vec::new(),
-            clear_color: Color::WHITE,
             scroller,
+            pointer_capture: PointerCapture::default(),
 
             // FIXME: this is bad
             next_texture_id: 10000,
+            texture_uploads: TextureUploadQueue::default(),
+            paint_phases: PaintPhases::default(),
+            ticks: Ticks::default(),
+            shortcuts: Shortcuts::default(),
+            frame_timings: FrameTimings::default(),
+            color_scheme: ColorSchemeState::new(color_scheme),
         })
     }
 
     pub fn set_bg_color(&mut self, color: Color) {
-        self.clear_color = color;
+        self.set_clear_mode(color);
+    }
+
+    /// See [`ClearMode`], the mode is kept by the window canvas across frames
+    pub fn set_clear_mode(&mut self, mode: impl Into<ClearMode>) {
+        self.canvas.set_clear_mode(mode);
         self.refresh();
     }
 
@@ -236,10 +285,21 @@ impl Window {
         self.handle.id()
     }
 
+    /// `width` and `height` are in physical pixels
     pub(crate) fn handle_resize(&mut self, width: u32, height: u32) {
         self.canvas.resize(width, height);
     }
 
+    pub(crate) fn handle_scale_factor_changed(&mut self, scale_factor: f32) {
+        self.canvas.set_scale_factor(scale_factor);
+        self.refresh();
+    }
+
+    /// Physical pixels per logical pixel
+    pub fn scale_factor(&self) -> f32 {
+        self.canvas.scale_factor()
+    }
+
     pub fn winit_handle(&self) -> &Arc<WinitWindow> {
         &self.handle
     }
@@ -278,9 +338,9 @@ impl Window {
     }
 
     pub fn _add_basic_scene(&mut self) {
-        let size = self.winit_handle().inner_size();
-        let width = size.width as f32;
-        let height = size.height as f32;
+        let size = self.canvas.logical_size();
+        let width = size.width;
+        let height = size.height;
         let cx = &mut self.canvas;
 
         cx.draw_image(
@@ -407,43 +467,159 @@ impl Window {
         );
     }
 
-    fn add_image_from_data(
+    /// Queues the image for upload to the atlas, large images are uploaded over several
+    /// frames. The returned [`TextureUpload`] resolves once the texture can be drawn
+    pub fn upload_image(&mut self, image: RgbaImage) -> TextureUpload {
+        self.upload_image_with(image, ImageDecodeOptions::default())
+    }
+
+    /// Like [`Window::upload_image`] for images that are linear or premultiplied, they are
+    /// converted to sRGB with straight alpha before the upload
+    pub fn upload_image_with(
         &mut self,
-        image: &[u8],
-        natutal_size: Size<u32>,
-        bounds: Rect<Pixels>,
-    ) -> usize {
-        let width = natutal_size.width;
-        let height = natutal_size.height;
-        let key = AtlasKey::from(AtlasImage::new(self.get_next_tex_id()));
-        self.texture_atlas.get_or_insert(&key, || {
-            (
-                Size {
-                    width: width as _,
-                    height: height as _,
-                },
-                Cow::Borrowed(image),
-            )
-        });
+        mut image: RgbaImage,
+        options: ImageDecodeOptions,
+    ) -> TextureUpload {
+        options.convert(&mut image);
 
-        self.canvas.renderer.set_texture_from_atlas(
-            &self.texture_atlas,
+        let key = AtlasKey::from(AtlasImage::new(self.get_next_tex_id()));
+        let bytes_per_row = image.width() as usize * 4;
+        let tile = self.texture_atlas.create_texture(
             &key,
-            &TextureOptions::default()
-                .min_filter(TextureFilterMode::Linear)
-                .mag_filter(TextureFilterMode::Linear),
+            Size {
+                width: image.width() as _,
+                height: image.height() as _,
+            },
         );
 
+        let upload = self
+            .texture_uploads
+            .push(key, tile, image.into_raw(), bytes_per_row);
+        self.refresh();
+        upload
+    }
+
+    /// Max bytes of queued images uploaded each frame, see [`Window::upload_image`]
+    pub fn set_texture_upload_budget(&mut self, bytes_per_frame: usize) {
+        self.texture_uploads.set_budget(bytes_per_frame);
+    }
+
+    fn process_texture_uploads(&mut self) {
+        if self.texture_uploads.is_empty() {
+            return;
+        }
+
+        let filter = self.canvas.image_filter();
+        let renderer = &mut self.canvas.renderer;
+        let atlas = &self.texture_atlas;
+        self.texture_uploads.process(atlas, |key| {
+            renderer.set_texture_from_atlas(
+                atlas,
+                key,
+                &TextureOptions::default()
+                    .min_filter(filter)
+                    .mag_filter(filter),
+            );
+        });
+
+        // keep frames coming until the queue drains
+        if !self.texture_uploads.is_empty() {
+            self.refresh();
+        }
+    }
+
+    fn add_image_object(
+        &mut self,
+        key: AtlasKey,
+        natutal_size: Size<u32>,
+        bounds: Rect<Pixels>,
+    ) -> usize {
         let idx = self.objects.len();
         self.objects.push(Object::Image(ImageObject {
             bbox: bounds,
-            natural_width: width as f32,
-            natural_height: height as f32,
+            natural_width: natutal_size.width as f32,
+            natural_height: natutal_size.height as f32,
             texture: key.into(),
         }));
         idx
     }
 
+    /// Routes pointer moves and the release to the callbacks of `target` added with
+    /// [`Window::on_captured_pointer`] until [`Window::release_pointer_capture`] is called or
+    /// the button is released, even when the cursor leaves the target or the window.
+    /// Returns false if another target holds the capture or `target` is reserved
+    pub fn capture_pointer(&mut self, target: PointerCaptureTarget) -> bool {
+        target != SCROLLER_CAPTURE && self.pointer_capture.capture(target)
+    }
+
+    pub fn release_pointer_capture(&mut self, target: PointerCaptureTarget) {
+        if target != SCROLLER_CAPTURE {
+            self.pointer_capture.release(target);
+        }
+    }
+
+    pub fn pointer_capture(&self) -> Option<PointerCaptureTarget> {
+        self.pointer_capture.holder()
+    }
+
+    /// Calls `f` with the pointer events `target` receives while it holds the capture
+    pub fn on_captured_pointer(
+        &mut self,
+        target: PointerCaptureTarget,
+        f: impl FnMut(CapturedPointerEvent) + 'static,
+    ) -> PointerCaptureCallbackId {
+        self.pointer_capture.add(target, f)
+    }
+
+    pub fn remove_captured_pointer_callback(&mut self, id: PointerCaptureCallbackId) -> bool {
+        self.pointer_capture.remove(id)
+    }
+
+    pub fn has_pointer_capture(&self, target: PointerCaptureTarget) -> bool {
+        self.pointer_capture() == Some(target)
+    }
+
+    /// `pos` is in physical pixels
+    pub(crate) fn handle_pointer_move(&mut self, pos: Vec2<f32>) {
+        let pos = pos / self.scale_factor();
+        let last = self.state.write().set_mouse_pos(pos);
+        let holder = self
+            .pointer_capture
+            .dispatch(CapturedPointerEvent::Moved(pos));
+
+        if let Some(last) = last.filter(|_| holder == Some(SCROLLER_CAPTURE)) {
+            self.scroller.scroll_x -= pos.x - last.x;
+        }
+
+        // FIXME:
+        self.refresh();
+    }
+
+    pub(crate) fn handle_pointer_button(&mut self, pressed: bool) {
+        if pressed {
+            let hit_scroller = self
+                .state
+                .read()
+                .mouse_pos()
+                .is_some_and(|pos| self.scroller.dims.contains_point(pos));
+
+            if hit_scroller {
+                self.pointer_capture.capture(SCROLLER_CAPTURE);
+            }
+        } else {
+            // a capture lasts for a single drag
+            self.pointer_capture
+                .dispatch(CapturedPointerEvent::Released);
+        }
+    }
+
+    pub(crate) fn handle_pointer_left(&mut self) {
+        if self.pointer_capture.holder().is_none() {
+            self.state.write().mouse_pos = None;
+            self.refresh();
+        }
+    }
+
     pub(crate) fn handle_scroll_wheel(&mut self, _dx: f32, dy: f32) {
         {
             let state = self.state.read();
@@ -459,15 +635,153 @@ impl Window {
         }
     }
 
+    /// Runs `f` when the chord in `shortcut` is pressed, e.g. `"Ctrl+S"`, see [`Shortcut`].
+    /// Shortcuts run before the key reaches the window's own key handling, binding a chord
+    /// that is already bound fails
+    pub fn bind_shortcut(
+        &mut self,
+        shortcut: &str,
+        f: impl FnMut() + 'static,
+    ) -> Result<ShortcutId, ShortcutError> {
+        let shortcut: Shortcut = shortcut.parse()?;
+        self.shortcuts.bind(shortcut, f)
+    }
+
+    pub fn unbind_shortcut(&mut self, id: ShortcutId) -> bool {
+        self.shortcuts.unbind(id)
+    }
+
+    pub fn modifiers(&self) -> ModifiersState {
+        self.state.read().modifiers
+    }
+
+    pub fn is_key_pressed(&self, key: KeyCode) -> bool {
+        self.state.read().pressed_keys.contains(&key)
+    }
+
+    /// Returns whether a shortcut handled the key
+    pub(crate) fn handle_key(&mut self, code: KeyCode, pressed: bool) -> bool {
+        let modifiers = {
+            let mut state = self.state.write();
+            if pressed {
+                state.pressed_keys.insert(code);
+            } else {
+                state.pressed_keys.remove(&code);
+            }
+            state.modifiers
+        };
+
+        pressed && self.shortcuts.dispatch(modifiers, code)
+    }
+
+    pub(crate) fn handle_modifiers_changed(&mut self, modifiers: ModifiersState) {
+        self.state.write().modifiers = modifiers;
+    }
+
+    /// Handles `event`, returns whether it was consumed by a shortcut
+    pub(crate) fn dispatch_input(&mut self, event: &InputEvent) -> bool {
+        match *event {
+            InputEvent::PointerMoved(pos) => self.handle_pointer_move(pos),
+            InputEvent::PointerButton { pressed } => self.handle_pointer_button(pressed),
+            InputEvent::PointerLeft => self.handle_pointer_left(),
+            InputEvent::ScrollWheel { dx, dy } => self.handle_scroll_wheel(dx, dy),
+            InputEvent::Resized { width, height } => self.handle_resize(width, height),
+            InputEvent::ScaleFactorChanged(scale_factor) => {
+                self.handle_scale_factor_changed(scale_factor)
+            }
+            InputEvent::Key { code, pressed } => return self.handle_key(code, pressed),
+            InputEvent::ModifiersChanged(modifiers) => self.handle_modifiers_changed(modifiers),
+        }
+        false
+    }
+
+    /// Paints with `f` every frame in the given phase, callbacks of the same phase are
+    /// painted in the order they were added
+    pub fn on_paint(
+        &mut self,
+        phase: PaintPhase,
+        f: impl FnMut(&mut Canvas) + 'static,
+    ) -> PaintCallbackId {
+        let id = self.paint_phases.add(phase, f);
+        self.refresh();
+        id
+    }
+
+    pub fn remove_paint_callback(&mut self, id: PaintCallbackId) -> bool {
+        let removed = self.paint_phases.remove(id);
+        if removed {
+            self.refresh();
+        }
+        removed
+    }
+
+    /// Calls `f` every `step` of the app clock with `step` as the delta, whether or not the
+    /// window redraws. Simulations advanced here move the same on any display, the window
+    /// is refreshed after the steps run
+    pub fn on_tick(&mut self, step: Duration, f: impl FnMut(Duration) + 'static) -> TickCallbackId {
+        self.ticks.add(step, f)
+    }
+
+    pub fn remove_tick_callback(&mut self, id: TickCallbackId) -> bool {
+        self.ticks.remove(id)
+    }
+
+    /// Light or dark preference of the OS for this window, [`ColorScheme::Light`] when the
+    /// platform doesn't report one
+    pub fn color_scheme(&self) -> ColorScheme {
+        self.color_scheme.current()
+    }
+
+    /// Calls `f` with the new scheme when the OS preference changes, the window is refreshed
+    /// afterwards so paint callbacks can pick their colors from [`Window::color_scheme`]
+    pub fn on_color_scheme_change(
+        &mut self,
+        f: impl FnMut(ColorScheme) + 'static,
+    ) -> ColorSchemeCallbackId {
+        self.color_scheme.add(f)
+    }
+
+    pub fn remove_color_scheme_callback(&mut self, id: ColorSchemeCallbackId) -> bool {
+        self.color_scheme.remove(id)
+    }
+
+    pub(crate) fn handle_color_scheme_changed(&mut self, scheme: ColorScheme) {
+        if self.color_scheme.update(scheme) {
+            self.refresh();
+        }
+    }
+
+    /// `now` is the app clock, see [`AppContext::clock`]
+    pub(crate) fn tick(&mut self, now: Duration) {
+        if self.ticks.update(now) {
+            self.refresh();
+        }
+    }
+
+    /// Smoothed frame rate, the cost of the last frame and recent frame times,
+    /// for perf overlays or for dropping effects when frames get slow
+    pub fn frame_timings(&self) -> &FrameTimings {
+        &self.frame_timings
+    }
+
     pub(crate) fn paint(&mut self) -> Result<()> {
-        self.canvas.clear();
-        self.canvas.clear_color(self.clear_color);
-        // TODO: remove
+        let frame_start = Instant::now();
+        self.process_texture_uploads();
 
-        self._add_basic_scene();
-        self.canvas.render(&mut self.surface)?.present();
+        for phase in PaintPhase::ALL {
+            if phase == PaintPhase::Content {
+                // TODO: remove
+                self._add_basic_scene();
+            }
+            self.paint_phases.paint(phase, &mut self.canvas);
+        }
+
+        self.canvas.finish(&mut self.surface)?.present();
         self.canvas.restore();
 
+        self.frame_timings
+            .record_frame(frame_start, Instant::now(), self.canvas.last_gpu_time());
+
         Ok(())
     }
 
@@ -490,6 +804,12 @@ impl Window {
     }
 }
 
+impl InputHandler for Window {
+    fn handle_input(&mut self, event: &InputEvent) {
+        self.dispatch_input(event);
+    }
+}
+
 #[derive(Deref, DerefMut)]
 pub struct AsyncWindowContext {
     #[deref]
@@ -529,21 +849,35 @@ impl AsyncWindowContext {
         &self,
         bounds: Rect<Pixels>,
         file_path: String,
+    ) -> Result<usize> {
+        self.load_image_from_file_with(bounds, file_path, ImageDecodeOptions::default())
+            .await
+    }
+
+    /// Loads the image described by `options`, converting it off the main thread
+    pub async fn load_image_from_file_with(
+        &self,
+        bounds: Rect<Pixels>,
+        file_path: String,
+        options: ImageDecodeOptions,
     ) -> Result<usize> {
         let img_job: Job<Result<_>> =
-            self.spawn_blocking(load_image_from_file_async(file_path.clone()));
+            self.spawn_blocking(load_image_from_file_async(file_path.clone(), options));
 
         self.spawn(|cx| async move {
             let img = img_job.await?;
+            let size = Size::new(img.width(), img.height());
+            let upload = cx.update_window(|window, _| window.upload_image(img))?;
+            let key = upload.finished().await?;
+
             cx.update_window(|window, _| {
-                let idx =
-                    window.add_image_from_data(&img, Size::new(img.width(), img.height()), bounds);
+                let idx = window.add_image_object(key, size, bounds);
                 // FIXME: mark window as dirty and notify app to redraw instead
                 window.refresh();
-                Ok(idx)
+                idx
             })
         })
-        .await?
+        .await
     }
 }
 
@@ -554,7 +888,7 @@ fn create_checker_texture(width: usize, height: usize, tile_size: usize) -> Vec<
         for x in 0..width {
             let tile_x = x / tile_size;
             let tile_y = y / tile_size;
-            let is_black = (tile_x + tile_y) % 2 == 0;
+            let is_black = (tile_x + tile_y).is_multiple_of(2);
 
             let offset = (y * width + x) * 4;
             if is_black {
@@ -643,24 +977,26 @@ impl Scroller {
         ];
 
         // paint children overflow hidden
-        canvas.save();
-        canvas.clip(&clip);
-        for _ in 0..4 {
-            for i in 0..10 {
-                canvas.draw_rect(
-                    &Rect::from_origin_size(cursor + vec2(-self.scroll_x, 0.0), size),
-                    Brush::filled(colors[i % colors.len()]),
-                );
-                cursor.x += margin + size.width;
+        canvas.with_clip_rect(&clip, |canvas| {
+            for _ in 0..4 {
+                for i in 0..10 {
+                    canvas.draw_rect(
+                        &Rect::from_origin_size(cursor + vec2(-self.scroll_x, 0.0), size),
+                        Brush::filled(colors[i % colors.len()]),
+                    );
+                    cursor.x += margin + size.width;
+                }
+                cursor.y += 30.0 + margin + size.height;
+                cursor.x = container.origin.x + 10.0;
             }
-            cursor.y += 30.0 + margin + size.height;
-            cursor.x = container.origin.x + 10.0;
-        }
-        canvas.restore();
+        });
     }
 }
 
-async fn load_image_from_file_async<P: AsRef<std::path::Path>>(file_path: P) -> Result<RgbaImage> {
+async fn load_image_from_file_async<P: AsRef<std::path::Path>>(
+    file_path: P,
+    options: ImageDecodeOptions,
+) -> Result<RgbaImage> {
     let mut file = std::fs::File::open(file_path).map_err(|_| anyhow!("Error opening file"))?;
 
     let mut data = Vec::<u8>::new();
@@ -670,5 +1006,7 @@ async fn load_image_from_file_async<P: AsRef<std::path::Path>>(file_path: P) ->
     let loaded_image =
         image::load_from_memory(&data).map_err(|_| anyhow!("Error parsing image"))?;
 
-    Ok(loaded_image.to_rgba8())
+    let mut image = loaded_image.to_rgba8();
+    options.convert(&mut image);
+    Ok(image)
 }
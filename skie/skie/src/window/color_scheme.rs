@@ -0,0 +1,104 @@
+use winit::window::Theme;
+
+/// Light or dark preference of the OS, see [`crate::window::Window::color_scheme`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ColorScheme {
+    #[default]
+    Light,
+    Dark,
+}
+
+impl ColorScheme {
+    pub fn is_dark(&self) -> bool {
+        *self == ColorScheme::Dark
+    }
+}
+
+impl From<Theme> for ColorScheme {
+    fn from(theme: Theme) -> Self {
+        match theme {
+            Theme::Light => ColorScheme::Light,
+            Theme::Dark => ColorScheme::Dark,
+        }
+    }
+}
+
+/// Returned by [`crate::window::Window::on_color_scheme_change`] to remove the callback later
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ColorSchemeCallbackId(usize);
+
+type ColorSchemeCallback = Box<dyn FnMut(ColorScheme) + 'static>;
+
+/// The window's color scheme and the callbacks told when it changes
+#[derive(Default)]
+pub(crate) struct ColorSchemeState {
+    current: ColorScheme,
+    callbacks: Vec<(ColorSchemeCallbackId, ColorSchemeCallback)>,
+    next_id: usize,
+}
+
+impl ColorSchemeState {
+    pub fn new(current: ColorScheme) -> Self {
+        Self {
+            current,
+            ..Default::default()
+        }
+    }
+
+    pub fn current(&self) -> ColorScheme {
+        self.current
+    }
+
+    pub fn add(&mut self, callback: impl FnMut(ColorScheme) + 'static) -> ColorSchemeCallbackId {
+        let id = ColorSchemeCallbackId(self.next_id);
+        self.next_id += 1;
+        self.callbacks.push((id, Box::new(callback)));
+        id
+    }
+
+    /// Returns false if the callback was already removed
+    pub fn remove(&mut self, id: ColorSchemeCallbackId) -> bool {
+        let len = self.callbacks.len();
+        self.callbacks.retain(|(callback_id, _)| *callback_id != id);
+        self.callbacks.len() != len
+    }
+
+    /// Stores `scheme` and runs the callbacks, returns false when it didn't change
+    pub fn update(&mut self, scheme: ColorScheme) -> bool {
+        if scheme == self.current {
+            return false;
+        }
+
+        self.current = scheme;
+        for (_, callback) in &mut self.callbacks {
+            callback(scheme);
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::*;
+
+    #[test]
+    fn callbacks_run_on_changes_only() {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let mut state = ColorSchemeState::new(ColorScheme::Light);
+        let id = state.add({
+            let seen = seen.clone();
+            move |scheme| seen.borrow_mut().push(scheme)
+        });
+
+        assert!(!state.update(ColorScheme::Light));
+        assert!(state.update(ColorScheme::Dark));
+        assert!(state.current().is_dark());
+
+        assert!(state.remove(id));
+        assert!(!state.remove(id));
+        state.update(ColorScheme::Light);
+        assert_eq!(*seen.borrow(), [ColorScheme::Dark]);
+    }
+}
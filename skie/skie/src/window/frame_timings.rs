@@ -0,0 +1,107 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// Number of frame intervals kept in [`FrameTimings::history`]
+pub const FRAME_HISTORY: usize = 120;
+
+/// Weight of the newest frame in the smoothed frame time
+const SMOOTHING: f32 = 0.1;
+
+/// Frame rate and frame cost of a window, see [`crate::window::Window::frame_timings`]
+#[derive(Debug, Clone, Default)]
+pub struct FrameTimings {
+    smoothed_frame_time: Option<Duration>,
+    cpu_time: Duration,
+    gpu_time: Option<Duration>,
+    history: VecDeque<Duration>,
+    last_frame_start: Option<Instant>,
+}
+
+impl FrameTimings {
+    /// Frames per second from the smoothed frame time, 0 before the second frame
+    pub fn fps(&self) -> f32 {
+        self.smoothed_frame_time
+            .filter(|time| !time.is_zero())
+            .map(|time| 1.0 / time.as_secs_f32())
+            .unwrap_or_default()
+    }
+
+    /// Exponential moving average of the time between frames
+    pub fn frame_time(&self) -> Duration {
+        self.smoothed_frame_time.unwrap_or_default()
+    }
+
+    /// Time the last frame spent painting on the cpu
+    pub fn cpu_time(&self) -> Duration {
+        self.cpu_time
+    }
+
+    /// See [`skie_draw::Canvas::last_gpu_time`]
+    pub fn gpu_time(&self) -> Option<Duration> {
+        self.gpu_time
+    }
+
+    /// Time between the last frames, oldest first
+    pub fn history(&self) -> impl ExactSizeIterator<Item = Duration> + '_ {
+        self.history.iter().copied()
+    }
+
+    pub(crate) fn record_frame(
+        &mut self,
+        start: Instant,
+        end: Instant,
+        gpu_time: Option<Duration>,
+    ) {
+        if let Some(last) = self.last_frame_start.replace(start) {
+            let interval = start.saturating_duration_since(last);
+
+            if self.history.len() == FRAME_HISTORY {
+                self.history.pop_front();
+            }
+            self.history.push_back(interval);
+
+            self.smoothed_frame_time = Some(match self.smoothed_frame_time {
+                Some(smoothed) => smoothed.mul_f32(1.0 - SMOOTHING) + interval.mul_f32(SMOOTHING),
+                None => interval,
+            });
+        }
+
+        self.cpu_time = end.saturating_duration_since(start);
+        self.gpu_time = gpu_time.or(self.gpu_time);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_timings_smooth_and_keep_history() {
+        let mut timings = FrameTimings::default();
+        let start = Instant::now();
+        let ms = Duration::from_millis;
+
+        timings.record_frame(start, start + ms(2), None);
+        assert_eq!(timings.fps(), 0.0);
+        assert_eq!(timings.cpu_time(), ms(2));
+
+        for frame in 1..=FRAME_HISTORY as u32 + 10 {
+            let at = start + ms(10) * frame;
+            timings.record_frame(at, at + ms(3), Some(ms(4)));
+        }
+
+        assert!((timings.fps() - 100.0).abs() < 0.5);
+        assert_eq!(timings.cpu_time(), ms(3));
+        assert_eq!(timings.gpu_time(), Some(ms(4)));
+        assert_eq!(timings.history().len(), FRAME_HISTORY);
+        assert!(timings.history().all(|interval| interval == ms(10)));
+
+        // a slow frame moves the average without jumping to it
+        let at = start + ms(10) * (FRAME_HISTORY as u32 + 10) + ms(100);
+        timings.record_frame(at, at, None);
+        assert!(timings.frame_time() > ms(10) && timings.frame_time() < ms(100));
+        assert_eq!(timings.gpu_time(), Some(ms(4)));
+    }
+}
@@ -0,0 +1,103 @@
+use skie_draw::Canvas;
+
+/// Paint order of a window, each phase is painted above the ones before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PaintPhase {
+    Background,
+    /// App content, the window's own scene is painted first in this phase
+    Content,
+    Overlay,
+    Debug,
+}
+
+impl PaintPhase {
+    pub const ALL: [Self; 4] = [Self::Background, Self::Content, Self::Overlay, Self::Debug];
+}
+
+/// Returned by [`crate::window::Window::on_paint`] to remove the callback later
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PaintCallbackId(usize);
+
+type PaintCallback = Box<dyn FnMut(&mut Canvas) + 'static>;
+
+/// Paint callbacks ordered by phase, then by the order they were added in
+#[derive(Default)]
+pub(crate) struct PaintPhases {
+    callbacks: Vec<(PaintPhase, PaintCallbackId, PaintCallback)>,
+    next_id: usize,
+}
+
+impl PaintPhases {
+    pub fn add(
+        &mut self,
+        phase: PaintPhase,
+        callback: impl FnMut(&mut Canvas) + 'static,
+    ) -> PaintCallbackId {
+        let id = PaintCallbackId(self.next_id);
+        self.next_id += 1;
+
+        let index = self.callbacks.partition_point(|(p, ..)| *p <= phase);
+        self.callbacks
+            .insert(index, (phase, id, Box::new(callback)));
+        id
+    }
+
+    /// Returns false if the callback was already removed
+    pub fn remove(&mut self, id: PaintCallbackId) -> bool {
+        let len = self.callbacks.len();
+        self.callbacks
+            .retain(|(_, callback_id, _)| *callback_id != id);
+        self.callbacks.len() != len
+    }
+
+    /// Runs the callbacks of `phase`, each with its own canvas state so transforms
+    /// and clips dont leak into later callbacks
+    pub fn paint(&mut self, phase: PaintPhase, canvas: &mut Canvas) {
+        for (_, _, callback) in self.callbacks.iter_mut().filter(|(p, ..)| *p == phase) {
+            canvas.save();
+            callback(canvas);
+            canvas.restore();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use skie_draw::GpuContext;
+    use std::{cell::RefCell, rc::Rc};
+
+    #[test]
+    fn callbacks_run_in_phase_order() {
+        let Ok(gpu) = pollster::block_on(GpuContext::new()) else {
+            eprintln!("skipping callbacks_run_in_phase_order: no gpu adapter");
+            return;
+        };
+
+        let mut canvas = Canvas::create().width(16).height(16).build(gpu);
+        let order = Rc::new(RefCell::new(Vec::new()));
+        let mut phases = PaintPhases::default();
+
+        let mut record = |phase, name: &'static str| {
+            let order = order.clone();
+            phases.add(phase, move |_| order.borrow_mut().push(name))
+        };
+        record(PaintPhase::Debug, "debug");
+        record(PaintPhase::Overlay, "overlay");
+        let removed = record(PaintPhase::Content, "removed");
+        record(PaintPhase::Content, "content");
+        record(PaintPhase::Background, "background");
+
+        assert!(phases.remove(removed));
+        assert!(!phases.remove(removed));
+
+        for phase in PaintPhase::ALL {
+            phases.paint(phase, &mut canvas);
+        }
+
+        assert_eq!(
+            *order.borrow(),
+            ["background", "content", "overlay", "debug"]
+        );
+    }
+}
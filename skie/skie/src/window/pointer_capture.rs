@@ -0,0 +1,155 @@
+use skie_draw::Vec2;
+
+/// Owner of the pointer capture of a window, either an object index or an app defined token.
+/// `Token(u64::MAX)` is reserved by the window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PointerCaptureTarget {
+    Object(usize),
+    Token(u64),
+}
+
+// used by the scroller for drag scrolling
+pub(crate) const SCROLLER_CAPTURE: PointerCaptureTarget = PointerCaptureTarget::Token(u64::MAX);
+
+/// Pointer input routed to the holder of the capture, positions are in logical pixels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CapturedPointerEvent {
+    Moved(Vec2<f32>),
+    /// The button was released, the capture ends after this event
+    Released,
+}
+
+/// Returned by [`crate::window::Window::on_captured_pointer`] to remove the callback later
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PointerCaptureCallbackId(usize);
+
+type PointerCaptureCallback = Box<dyn FnMut(CapturedPointerEvent) + 'static>;
+
+/// The holder of the pointer capture and the callbacks receiving its events
+#[derive(Default)]
+pub(crate) struct PointerCapture {
+    holder: Option<PointerCaptureTarget>,
+    callbacks: Vec<(
+        PointerCaptureCallbackId,
+        PointerCaptureTarget,
+        PointerCaptureCallback,
+    )>,
+    next_id: usize,
+}
+
+impl PointerCapture {
+    pub fn holder(&self) -> Option<PointerCaptureTarget> {
+        self.holder
+    }
+
+    /// Returns false if another target holds the capture
+    pub fn capture(&mut self, target: PointerCaptureTarget) -> bool {
+        match self.holder {
+            Some(current) if current != target => false,
+            _ => {
+                self.holder = Some(target);
+                true
+            }
+        }
+    }
+
+    pub fn release(&mut self, target: PointerCaptureTarget) {
+        if self.holder == Some(target) {
+            self.holder = None;
+        }
+    }
+
+    pub fn add(
+        &mut self,
+        target: PointerCaptureTarget,
+        callback: impl FnMut(CapturedPointerEvent) + 'static,
+    ) -> PointerCaptureCallbackId {
+        let id = PointerCaptureCallbackId(self.next_id);
+        self.next_id += 1;
+        self.callbacks.push((id, target, Box::new(callback)));
+        id
+    }
+
+    /// Returns false if the callback was already removed
+    pub fn remove(&mut self, id: PointerCaptureCallbackId) -> bool {
+        let len = self.callbacks.len();
+        self.callbacks
+            .retain(|(callback_id, ..)| *callback_id != id);
+        self.callbacks.len() != len
+    }
+
+    /// Runs the callbacks of the holder with `event`, a release ends the capture.
+    /// Returns the holder the event went to
+    pub fn dispatch(&mut self, event: CapturedPointerEvent) -> Option<PointerCaptureTarget> {
+        let holder = self.holder?;
+        for (_, target, callback) in &mut self.callbacks {
+            if *target == holder {
+                callback(event);
+            }
+        }
+
+        if event == CapturedPointerEvent::Released {
+            self.holder = None;
+        }
+        Some(holder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use skie_draw::vec2;
+
+    use super::*;
+
+    #[test]
+    fn holder_receives_events_until_released() {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let mut capture = PointerCapture::default();
+        let dragged = PointerCaptureTarget::Token(1);
+        let other = PointerCaptureTarget::Object(0);
+
+        capture.add(dragged, {
+            let seen = seen.clone();
+            move |event| seen.borrow_mut().push(event)
+        });
+        capture.add(other, |_| panic!("only the holder receives events"));
+
+        assert_eq!(capture.dispatch(CapturedPointerEvent::Released), None);
+        assert!(capture.capture(dragged));
+        assert!(!capture.capture(other));
+
+        // far outside of anything the token was drawn at
+        let outside = vec2(-500.0, 10_000.0);
+        assert_eq!(
+            capture.dispatch(CapturedPointerEvent::Moved(outside)),
+            Some(dragged)
+        );
+        capture.dispatch(CapturedPointerEvent::Released);
+        assert_eq!(capture.holder(), None);
+
+        assert_eq!(
+            *seen.borrow(),
+            [
+                CapturedPointerEvent::Moved(outside),
+                CapturedPointerEvent::Released
+            ]
+        );
+    }
+
+    #[test]
+    fn only_the_holder_releases() {
+        let mut capture = PointerCapture::default();
+        let id = capture.add(PointerCaptureTarget::Token(1), |_| {});
+
+        assert!(capture.capture(PointerCaptureTarget::Token(1)));
+        capture.release(PointerCaptureTarget::Token(2));
+        assert_eq!(capture.holder(), Some(PointerCaptureTarget::Token(1)));
+        capture.release(PointerCaptureTarget::Token(1));
+        assert_eq!(capture.holder(), None);
+
+        assert!(capture.remove(id));
+        assert!(!capture.remove(id));
+    }
+}
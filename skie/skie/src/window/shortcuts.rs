@@ -0,0 +1,319 @@
+use std::{fmt, str::FromStr};
+
+use winit::keyboard::{KeyCode, ModifiersState};
+
+/// Names accepted for keys in shortcuts, matched case insensitively
+const KEY_NAMES: &[(&str, KeyCode)] = &[
+    ("A", KeyCode::KeyA),
+    ("B", KeyCode::KeyB),
+    ("C", KeyCode::KeyC),
+    ("D", KeyCode::KeyD),
+    ("E", KeyCode::KeyE),
+    ("F", KeyCode::KeyF),
+    ("G", KeyCode::KeyG),
+    ("H", KeyCode::KeyH),
+    ("I", KeyCode::KeyI),
+    ("J", KeyCode::KeyJ),
+    ("K", KeyCode::KeyK),
+    ("L", KeyCode::KeyL),
+    ("M", KeyCode::KeyM),
+    ("N", KeyCode::KeyN),
+    ("O", KeyCode::KeyO),
+    ("P", KeyCode::KeyP),
+    ("Q", KeyCode::KeyQ),
+    ("R", KeyCode::KeyR),
+    ("S", KeyCode::KeyS),
+    ("T", KeyCode::KeyT),
+    ("U", KeyCode::KeyU),
+    ("V", KeyCode::KeyV),
+    ("W", KeyCode::KeyW),
+    ("X", KeyCode::KeyX),
+    ("Y", KeyCode::KeyY),
+    ("Z", KeyCode::KeyZ),
+    ("0", KeyCode::Digit0),
+    ("1", KeyCode::Digit1),
+    ("2", KeyCode::Digit2),
+    ("3", KeyCode::Digit3),
+    ("4", KeyCode::Digit4),
+    ("5", KeyCode::Digit5),
+    ("6", KeyCode::Digit6),
+    ("7", KeyCode::Digit7),
+    ("8", KeyCode::Digit8),
+    ("9", KeyCode::Digit9),
+    ("F1", KeyCode::F1),
+    ("F2", KeyCode::F2),
+    ("F3", KeyCode::F3),
+    ("F4", KeyCode::F4),
+    ("F5", KeyCode::F5),
+    ("F6", KeyCode::F6),
+    ("F7", KeyCode::F7),
+    ("F8", KeyCode::F8),
+    ("F9", KeyCode::F9),
+    ("F10", KeyCode::F10),
+    ("F11", KeyCode::F11),
+    ("F12", KeyCode::F12),
+    ("Enter", KeyCode::Enter),
+    ("Escape", KeyCode::Escape),
+    ("Space", KeyCode::Space),
+    ("Tab", KeyCode::Tab),
+    ("Backspace", KeyCode::Backspace),
+    ("Delete", KeyCode::Delete),
+    ("Insert", KeyCode::Insert),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+    ("PageUp", KeyCode::PageUp),
+    ("PageDown", KeyCode::PageDown),
+    ("Up", KeyCode::ArrowUp),
+    ("Down", KeyCode::ArrowDown),
+    ("Left", KeyCode::ArrowLeft),
+    ("Right", KeyCode::ArrowRight),
+    ("Minus", KeyCode::Minus),
+    ("Equal", KeyCode::Equal),
+    ("Comma", KeyCode::Comma),
+    ("Period", KeyCode::Period),
+    ("Slash", KeyCode::Slash),
+    ("Backslash", KeyCode::Backslash),
+    ("Semicolon", KeyCode::Semicolon),
+    ("Quote", KeyCode::Quote),
+    ("Backquote", KeyCode::Backquote),
+    ("BracketLeft", KeyCode::BracketLeft),
+    ("BracketRight", KeyCode::BracketRight),
+];
+
+/// Name of a key usable in shortcuts and input recordings, `None` for keys without one
+pub fn key_name(code: KeyCode) -> Option<&'static str> {
+    KEY_NAMES
+        .iter()
+        .find(|(_, key)| *key == code)
+        .map(|(name, _)| *name)
+}
+
+/// The key named `name`, see [`key_name`]
+pub fn key_from_name(name: &str) -> Option<KeyCode> {
+    KEY_NAMES
+        .iter()
+        .find(|(key_name, _)| key_name.eq_ignore_ascii_case(name))
+        .map(|(_, key)| *key)
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum ShortcutError {
+    #[error("shortcut {0:?} has no key")]
+    MissingKey(String),
+    #[error("unknown key {0:?}")]
+    UnknownKey(String),
+    #[error("shortcut {0:?} has more than one key")]
+    MultipleKeys(String),
+    #[error("{0} is already bound")]
+    Conflict(Shortcut),
+}
+
+/// A key pressed with an exact set of modifiers, parsed from strings like `"Ctrl+Shift+S"`.
+///
+/// Modifiers are `Ctrl`, `Shift`, `Alt` and `Super` (or `Cmd`, `Meta`), `CmdOrCtrl` is
+/// `Super` on macOS and `Ctrl` elsewhere. Keys are matched by their physical position
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Shortcut {
+    pub modifiers: ModifiersState,
+    pub key: KeyCode,
+}
+
+impl Shortcut {
+    pub fn new(modifiers: ModifiersState, key: KeyCode) -> Self {
+        Self { modifiers, key }
+    }
+
+    pub fn matches(&self, modifiers: ModifiersState, key: KeyCode) -> bool {
+        self.key == key && self.modifiers == modifiers
+    }
+}
+
+fn modifier_from_name(name: &str) -> Option<ModifiersState> {
+    let modifier = match name.to_ascii_lowercase().as_str() {
+        "ctrl" | "control" => ModifiersState::CONTROL,
+        "shift" => ModifiersState::SHIFT,
+        "alt" | "option" => ModifiersState::ALT,
+        "super" | "cmd" | "command" | "meta" => ModifiersState::SUPER,
+        "cmdorctrl" if cfg!(target_os = "macos") => ModifiersState::SUPER,
+        "cmdorctrl" => ModifiersState::CONTROL,
+        _ => return None,
+    };
+    Some(modifier)
+}
+
+impl FromStr for Shortcut {
+    type Err = ShortcutError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = ModifiersState::empty();
+        let mut key = None;
+
+        for part in s.split('+').map(str::trim) {
+            if let Some(modifier) = modifier_from_name(part) {
+                modifiers |= modifier;
+                continue;
+            }
+
+            let code = key_from_name(part).ok_or_else(|| ShortcutError::UnknownKey(part.into()))?;
+            if key.replace(code).is_some() {
+                return Err(ShortcutError::MultipleKeys(s.into()));
+            }
+        }
+
+        let key = key.ok_or_else(|| ShortcutError::MissingKey(s.into()))?;
+        Ok(Self { modifiers, key })
+    }
+}
+
+impl fmt::Display for Shortcut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [
+            (ModifiersState::CONTROL, "Ctrl"),
+            (ModifiersState::ALT, "Alt"),
+            (ModifiersState::SHIFT, "Shift"),
+            (ModifiersState::SUPER, "Super"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{}+", name)?;
+            }
+        }
+
+        match key_name(self.key) {
+            Some(name) => write!(f, "{}", name),
+            None => write!(f, "{:?}", self.key),
+        }
+    }
+}
+
+/// Returned by [`crate::window::Window::bind_shortcut`] to unbind it later
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ShortcutId(usize);
+
+type ShortcutCallback = Box<dyn FnMut() + 'static>;
+
+struct Binding {
+    id: ShortcutId,
+    shortcut: Shortcut,
+    callback: ShortcutCallback,
+}
+
+/// Shortcuts of a window, each chord bound at most once
+#[derive(Default)]
+pub(crate) struct Shortcuts {
+    bindings: Vec<Binding>,
+    next_id: usize,
+}
+
+impl Shortcuts {
+    pub fn bind(
+        &mut self,
+        shortcut: Shortcut,
+        callback: impl FnMut() + 'static,
+    ) -> Result<ShortcutId, ShortcutError> {
+        if self
+            .bindings
+            .iter()
+            .any(|binding| binding.shortcut == shortcut)
+        {
+            return Err(ShortcutError::Conflict(shortcut));
+        }
+
+        let id = ShortcutId(self.next_id);
+        self.next_id += 1;
+        self.bindings.push(Binding {
+            id,
+            shortcut,
+            callback: Box::new(callback),
+        });
+        Ok(id)
+    }
+
+    /// Returns false if the shortcut was already unbound
+    pub fn unbind(&mut self, id: ShortcutId) -> bool {
+        let len = self.bindings.len();
+        self.bindings.retain(|binding| binding.id != id);
+        self.bindings.len() != len
+    }
+
+    /// Runs the shortcut bound to the pressed chord, returns whether there was one
+    pub fn dispatch(&mut self, modifiers: ModifiersState, key: KeyCode) -> bool {
+        let Some(binding) = self
+            .bindings
+            .iter_mut()
+            .find(|binding| binding.shortcut.matches(modifiers, key))
+        else {
+            return false;
+        };
+
+        (binding.callback)();
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use super::*;
+
+    #[test]
+    fn parses_and_displays_chords() {
+        let shortcut: Shortcut = "ctrl + shift+s".parse().unwrap();
+        assert_eq!(
+            shortcut,
+            Shortcut::new(
+                ModifiersState::CONTROL | ModifiersState::SHIFT,
+                KeyCode::KeyS
+            )
+        );
+        assert_eq!(shortcut.to_string(), "Ctrl+Shift+S");
+        assert_eq!(shortcut.to_string().parse::<Shortcut>(), Ok(shortcut));
+
+        assert_eq!(
+            "F5".parse::<Shortcut>(),
+            Ok(Shortcut::new(ModifiersState::empty(), KeyCode::F5))
+        );
+        assert_eq!(
+            "Ctrl+Shift".parse::<Shortcut>(),
+            Err(ShortcutError::MissingKey("Ctrl+Shift".into()))
+        );
+        assert_eq!(
+            "Ctrl+Hyper+S".parse::<Shortcut>(),
+            Err(ShortcutError::UnknownKey("Hyper".into()))
+        );
+        assert_eq!(
+            "Ctrl+A+B".parse::<Shortcut>(),
+            Err(ShortcutError::MultipleKeys("Ctrl+A+B".into()))
+        );
+    }
+
+    #[test]
+    fn dispatches_exact_chords_and_rejects_conflicts() {
+        let saved = Rc::new(Cell::new(0));
+        let mut shortcuts = Shortcuts::default();
+
+        let counter = saved.clone();
+        let id = shortcuts
+            .bind("Ctrl+S".parse().unwrap(), move || {
+                counter.set(counter.get() + 1)
+            })
+            .unwrap();
+
+        assert_eq!(
+            shortcuts.bind("control+s".parse().unwrap(), || {}).err(),
+            Some(ShortcutError::Conflict("Ctrl+S".parse().unwrap()))
+        );
+
+        assert!(!shortcuts.dispatch(ModifiersState::empty(), KeyCode::KeyS));
+        assert!(!shortcuts.dispatch(
+            ModifiersState::CONTROL | ModifiersState::SHIFT,
+            KeyCode::KeyS
+        ));
+        assert!(shortcuts.dispatch(ModifiersState::CONTROL, KeyCode::KeyS));
+        assert_eq!(saved.get(), 1);
+
+        assert!(shortcuts.unbind(id));
+        assert!(!shortcuts.dispatch(ModifiersState::CONTROL, KeyCode::KeyS));
+        assert!(shortcuts.bind("Ctrl+S".parse().unwrap(), || {}).is_ok());
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::VecDeque;
+
+use anyhow::{anyhow, Result};
+use skie_draw::paint::{AtlasKey, AtlasTile, SkieAtlas};
+
+/// Bytes uploaded per frame by default, about a 512x512 rgba image
+pub const DEFAULT_UPLOAD_BUDGET: usize = 1024 * 1024;
+
+/// Handle to an image queued with [`crate::window::Window::upload_image`]
+#[derive(Debug)]
+pub struct TextureUpload {
+    key: AtlasKey,
+    done: flume::Receiver<()>,
+}
+
+impl TextureUpload {
+    pub fn key(&self) -> &AtlasKey {
+        &self.key
+    }
+
+    pub fn is_finished(&self) -> bool {
+        !self.done.is_empty()
+    }
+
+    /// Resolves with the atlas key once every row is on the gpu
+    pub async fn finished(self) -> Result<AtlasKey> {
+        self.done
+            .recv_async()
+            .await
+            .map_err(|_| anyhow!("Texture upload was cancelled"))?;
+        Ok(self.key)
+    }
+}
+
+#[derive(Debug)]
+struct PendingUpload {
+    key: AtlasKey,
+    tile: AtlasTile,
+    data: Vec<u8>,
+    bytes_per_row: usize,
+    next_row: usize,
+    done: flume::Sender<()>,
+}
+
+impl PendingUpload {
+    fn rows(&self) -> usize {
+        self.data.len() / self.bytes_per_row
+    }
+}
+
+/// Uploads queued images to the atlas a few rows at a time so large images
+/// are spread over several frames
+#[derive(Debug)]
+pub(crate) struct TextureUploadQueue {
+    pending: VecDeque<PendingUpload>,
+    budget: usize,
+}
+
+impl Default for TextureUploadQueue {
+    fn default() -> Self {
+        Self {
+            pending: Default::default(),
+            budget: DEFAULT_UPLOAD_BUDGET,
+        }
+    }
+}
+
+impl TextureUploadQueue {
+    pub fn set_budget(&mut self, bytes_per_frame: usize) {
+        self.budget = bytes_per_frame;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// `data` holds `bytes_per_row` sized rows for the tile
+    pub fn push(
+        &mut self,
+        key: AtlasKey,
+        tile: AtlasTile,
+        data: Vec<u8>,
+        bytes_per_row: usize,
+    ) -> TextureUpload {
+        let (done, rx) = flume::bounded(1);
+
+        self.pending.push_back(PendingUpload {
+            key: key.clone(),
+            tile,
+            data,
+            bytes_per_row: bytes_per_row.max(1),
+            next_row: 0,
+            done,
+        });
+
+        TextureUpload { key, done: rx }
+    }
+
+    /// Uploads up to the budget, at least one row, and calls `on_finished` for the completed
+    /// uploads before their [`TextureUpload`] resolves
+    pub fn process(&mut self, atlas: &SkieAtlas, mut on_finished: impl FnMut(&AtlasKey)) {
+        let mut budget = self.budget;
+
+        while let Some(upload) = self.pending.front_mut() {
+            let remaining = upload.rows() - upload.next_row;
+            let rows = (budget / upload.bytes_per_row).max(1).min(remaining);
+
+            if rows > 0 {
+                let start = upload.next_row * upload.bytes_per_row;
+                let end = start + rows * upload.bytes_per_row;
+                atlas.upload_texture_rows(
+                    &upload.tile,
+                    upload.next_row as u32,
+                    &upload.data[start..end],
+                );
+                upload.next_row += rows;
+            }
+
+            budget = budget.saturating_sub(rows * upload.bytes_per_row);
+
+            if upload.next_row < upload.rows() {
+                break;
+            }
+
+            let upload = self.pending.pop_front().expect("front upload");
+            on_finished(&upload.key);
+            let _ = upload.done.send(());
+
+            if budget == 0 {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use skie_draw::{paint::AtlasImage, GpuContext, Size};
+
+    #[test]
+    fn uploads_are_spread_over_frames() {
+        let Ok(gpu) = pollster::block_on(GpuContext::new()) else {
+            eprintln!("skipping uploads_are_spread_over_frames: no gpu adapter");
+            return;
+        };
+
+        let atlas = SkieAtlas::new(gpu);
+        let key = AtlasKey::from(AtlasImage::new(1));
+        let tile = atlas.create_texture(&key, Size::new(16, 16));
+
+        let mut queue = TextureUploadQueue::default();
+        // four rows a frame
+        queue.set_budget(16 * 4 * 4);
+        let upload = queue.push(key.clone(), tile, vec![255; 16 * 16 * 4], 16 * 4);
+
+        let mut finished = Vec::new();
+        for _ in 0..3 {
+            queue.process(&atlas, |key| finished.push(key.clone()));
+        }
+        assert!(finished.is_empty());
+        assert!(!upload.is_finished());
+
+        queue.process(&atlas, |key| finished.push(key.clone()));
+        assert_eq!(finished.len(), 1);
+        assert!(queue.is_empty());
+        assert_eq!(pollster::block_on(upload.finished()).unwrap(), key);
+    }
+}
@@ -0,0 +1,111 @@
+use std::time::Duration;
+
+/// A ticker more than this many steps behind skips the missed steps instead of
+/// running them all at once
+const MAX_CATCH_UP_STEPS: u32 = 8;
+
+/// Returned by [`crate::window::Window::on_tick`] to remove the callback later
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TickCallbackId(usize);
+
+type TickCallback = Box<dyn FnMut(Duration) + 'static>;
+
+struct Ticker {
+    id: TickCallbackId,
+    step: Duration,
+    /// app clock time of the next step, set on the first update
+    next: Option<Duration>,
+    callback: TickCallback,
+}
+
+/// Fixed timestep callbacks driven by the app clock
+#[derive(Default)]
+pub(crate) struct Ticks {
+    tickers: Vec<Ticker>,
+    next_id: usize,
+}
+
+impl Ticks {
+    pub fn add(
+        &mut self,
+        step: Duration,
+        callback: impl FnMut(Duration) + 'static,
+    ) -> TickCallbackId {
+        let id = TickCallbackId(self.next_id);
+        self.next_id += 1;
+
+        self.tickers.push(Ticker {
+            id,
+            // a zero step would never catch up with the clock
+            step: step.max(Duration::from_micros(100)),
+            next: None,
+            callback: Box::new(callback),
+        });
+        id
+    }
+
+    /// Returns false if the callback was already removed
+    pub fn remove(&mut self, id: TickCallbackId) -> bool {
+        let len = self.tickers.len();
+        self.tickers.retain(|ticker| ticker.id != id);
+        self.tickers.len() != len
+    }
+
+    /// Runs every step that is due at `now` on the app clock, returns whether any ran
+    pub fn update(&mut self, now: Duration) -> bool {
+        let mut ran = false;
+
+        for ticker in &mut self.tickers {
+            let next = ticker.next.get_or_insert(now + ticker.step);
+            let mut steps = 0;
+
+            while *next <= now && steps < MAX_CATCH_UP_STEPS {
+                (ticker.callback)(ticker.step);
+                *next += ticker.step;
+                steps += 1;
+            }
+
+            if *next <= now {
+                *next = now + ticker.step;
+            }
+            ran |= steps > 0;
+        }
+
+        ran
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::RefCell, rc::Rc};
+
+    #[test]
+    fn ticks_run_at_a_fixed_step() {
+        let ms = Duration::from_millis;
+        let mut ticks = Ticks::default();
+        let steps = Rc::new(RefCell::new(Vec::new()));
+
+        let id = ticks.add(ms(10), {
+            let steps = steps.clone();
+            move |dt| steps.borrow_mut().push(dt)
+        });
+
+        assert!(!ticks.update(ms(0)));
+        assert!(!ticks.update(ms(9)));
+        assert!(ticks.update(ms(25)));
+        assert_eq!(*steps.borrow(), [ms(10), ms(10)]);
+
+        // the 5ms left over from the last update carry into the next step
+        assert!(ticks.update(ms(30)));
+        assert_eq!(steps.borrow().len(), 3);
+
+        // a long stall runs a bounded number of steps and drops the rest
+        ticks.update(ms(1030));
+        assert_eq!(steps.borrow().len(), 3 + MAX_CATCH_UP_STEPS as usize);
+        assert!(!ticks.update(ms(1035)));
+
+        assert!(ticks.remove(id));
+        assert!(!ticks.update(ms(5000)));
+    }
+}